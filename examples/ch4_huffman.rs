@@ -2,7 +2,7 @@ use my_algo::ch4::avlt::AVLTreeMap;
 use my_algo::ch4::bst::TreeMap;
 use my_algo::ch4::bt::BTreeMap as MyBTreeMap;
 use my_algo::ch4::coding_tree::HuffmanCodingTree;
-use my_algo::ch4::complete_heap::CompleteMaxHeap;
+use my_algo::ch4::complete_heap::CompleteMinHeap;
 use my_algo::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
 use my_algo::ch4::left_heap::LeftHeap;
 use my_algo::ch4::linked_binary_tree::LinkedBinaryTree;
@@ -10,6 +10,7 @@ use my_algo::ch4::llrbt;
 use my_algo::ch4::rbt::RBTreeMap;
 use my_algo::ch4::st::SplayTreeMap;
 use my_algo::ch4::vec_binary_tree::VecBinaryTree;
+use my_algo::ch4::MinFirst;
 use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::prelude::*;
@@ -76,7 +77,7 @@ fn main() -> std::io::Result<()> {
     match (opt.tree, opt.pq, opt.map) {
         (Tree::Lbt, Heap::Ch, Map::Btm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 BTreeMap<_, _>,
                 BTreeMap<_, _>,
             >(&contents)
@@ -88,7 +89,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Ch, Map::Hm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 HashMap<_, _>,
                 HashMap<_, _>,
             >(&contents)
@@ -100,7 +101,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Ch, Map::Tm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -112,7 +113,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Ch, Map::Avl) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -124,7 +125,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Ch, Map::Stm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -136,7 +137,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Ch, Map::Mbtm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 MyBTreeMap<_, _, MBTM_ARG>,
                 MyBTreeMap<_, _, MBTM_ARG>,
             >(&contents)
@@ -148,7 +149,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Ch, Map::Rbtm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -160,7 +161,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Ch, Map::Lrbm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 llrbt::RBTreeMap<_, _>,
                 llrbt::RBTreeMap<_, _>,
             >(&contents)
@@ -172,7 +173,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Lh, Map::Btm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 BTreeMap<_, _>,
                 BTreeMap<_, _>,
             >(&contents)
@@ -184,7 +185,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Lh, Map::Hm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 HashMap<_, _>,
                 HashMap<_, _>,
             >(&contents)
@@ -196,7 +197,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Lh, Map::Tm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -208,7 +209,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Lh, Map::Avl) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -220,7 +221,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Lh, Map::Stm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -232,7 +233,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Lh, Map::Mbtm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 MyBTreeMap<_, _, MBTM_ARG>,
                 MyBTreeMap<_, _, MBTM_ARG>,
             >(&contents)
@@ -244,7 +245,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Lh, Map::Rbtm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -256,7 +257,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Lbt, Heap::Lh, Map::Lrbm) => {
             let tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 llrbt::RBTreeMap<_, _>,
                 llrbt::RBTreeMap<_, _>,
             >(&contents)
@@ -268,7 +269,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Ch, Map::Btm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 BTreeMap<_, _>,
                 BTreeMap<_, _>,
             >(&contents)
@@ -280,7 +281,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Ch, Map::Hm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 HashMap<_, _>,
                 HashMap<_, _>,
             >(&contents)
@@ -292,7 +293,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Ch, Map::Tm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -304,7 +305,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Ch, Map::Avl) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -316,7 +317,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Ch, Map::Stm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -328,7 +329,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Ch, Map::Mbtm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 MyBTreeMap<_, _, MBTM_ARG>,
                 MyBTreeMap<_, _, MBTM_ARG>,
             >(&contents)
@@ -340,7 +341,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Ch, Map::Rbtm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -352,7 +353,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Ch, Map::Lrbm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 llrbt::RBTreeMap<_, _>,
                 llrbt::RBTreeMap<_, _>,
             >(&contents)
@@ -364,7 +365,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Lh, Map::Btm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 BTreeMap<_, _>,
                 BTreeMap<_, _>,
             >(&contents)
@@ -376,7 +377,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Lh, Map::Hm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 HashMap<_, _>,
                 HashMap<_, _>,
             >(&contents)
@@ -388,7 +389,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Lh, Map::Tm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -400,7 +401,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Lh, Map::Avl) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -412,7 +413,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Lh, Map::Stm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -424,7 +425,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Lh, Map::Mbtm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 MyBTreeMap<_, _, MBTM_ARG>,
                 MyBTreeMap<_, _, MBTM_ARG>,
             >(&contents)
@@ -436,7 +437,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Lh, Map::Rbtm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -448,7 +449,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Vbt, Heap::Lh, Map::Lrbm) => {
             let tree = HuffmanCodingTree::<VecBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 llrbt::RBTreeMap<_, _>,
                 llrbt::RBTreeMap<_, _>,
             >(&contents)
@@ -460,7 +461,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Ch, Map::Btm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 BTreeMap<_, _>,
                 BTreeMap<_, _>,
             >(&contents)
@@ -472,7 +473,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Ch, Map::Hm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 HashMap<_, _>,
                 HashMap<_, _>,
             >(&contents)
@@ -484,7 +485,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Ch, Map::Tm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -496,7 +497,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Ch, Map::Avl) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -508,7 +509,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Ch, Map::Stm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -520,7 +521,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Ch, Map::Mbtm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 MyBTreeMap<_, _, MBTM_ARG>,
                 MyBTreeMap<_, _, MBTM_ARG>,
             >(&contents)
@@ -532,7 +533,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Ch, Map::Rbtm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -544,7 +545,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Ch, Map::Lrbm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                CompleteMaxHeap<_>,
+                CompleteMinHeap<_>,
                 llrbt::RBTreeMap<_, _>,
                 llrbt::RBTreeMap<_, _>,
             >(&contents)
@@ -556,7 +557,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Lh, Map::Btm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 BTreeMap<_, _>,
                 BTreeMap<_, _>,
             >(&contents)
@@ -568,7 +569,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Lh, Map::Hm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 HashMap<_, _>,
                 HashMap<_, _>,
             >(&contents)
@@ -580,7 +581,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Lh, Map::Tm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
                 TreeMap<LinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -592,7 +593,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Lh, Map::Avl) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -604,7 +605,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Lh, Map::Stm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -616,7 +617,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Lh, Map::Mbtm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 MyBTreeMap<_, _, MBTM_ARG>,
                 MyBTreeMap<_, _, MBTM_ARG>,
             >(&contents)
@@ -628,7 +629,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Lh, Map::Rbtm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
                 RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             >(&contents)
@@ -640,7 +641,7 @@ fn main() -> std::io::Result<()> {
         }
         (Tree::Dlbt, Heap::Lh, Map::Lrbm) => {
             let tree = HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<
-                LeftHeap<_>,
+                LeftHeap<_, MinFirst>,
                 llrbt::RBTreeMap<_, _>,
                 llrbt::RBTreeMap<_, _>,
             >(&contents)
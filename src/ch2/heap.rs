@@ -0,0 +1,103 @@
+use super::List;
+
+impl<T, U> HeapListExt<U> for T
+where
+    T: List<U>,
+    U: PartialOrd,
+{
+}
+
+/// `List` trait的一个扩展trait, 提供了原地的(大顶)堆算法.
+///
+/// 堆是存放在表中的完全`D`叉树: 结点`n`的孩子为`D * n + 1`至`D * n + D`, 每个结点都不小于它的孩子.
+/// 所有方法都只通过`get`与`swap`访问元素, 因此`Vec`、`MyVec`以及`SliceMut`(只堆化表的一段)都可以直接使用, 不需要复制.
+/// `D`为2时即为二叉堆; 更大的`D`使树更矮, 上滤更快, 下滤时则每层要多比较几个孩子.
+/// # Panics
+/// `D`为0时报错.
+pub trait HeapListExt<Item: PartialOrd>: List<Item> {
+    /// 判断表是否为`D`叉堆.
+    fn is_heap<const D: usize>(&self) -> bool {
+        (1..self.len()).all(|n| *self.get((n - 1) / D).unwrap() >= *self.get(n).unwrap())
+    }
+
+    /// 原地建堆.
+    /// # 算法
+    /// 自最后一个有孩子的结点起, 自下而上地逐个下滤. 时间复杂度为`O(n)`.
+    fn make_heap<const D: usize>(&mut self) {
+        let len = self.len();
+        if len > 1 {
+            for n in (0..=(len - 2) / D).rev() {
+                sift_down::<_, _, D>(self, n, len);
+            }
+        }
+    }
+
+    /// 将表的最后一个元素加入堆中.
+    /// # Correctness
+    /// 此方法要求除最后一个元素外的部分为`D`叉堆. 时间复杂度为`O(log_D n)`.
+    fn push_heap<const D: usize>(&mut self) {
+        if !self.is_empty() {
+            sift_up::<_, _, D>(self, self.len() - 1);
+        }
+    }
+
+    /// 将堆顶(最大元素)移到表的末尾, 其余部分仍为`D`叉堆. 之后可以用`delete`取出该元素.
+    /// # Correctness
+    /// 此方法要求表为`D`叉堆. 时间复杂度为`O(D log_D n)`.
+    fn pop_heap<const D: usize>(&mut self) {
+        let len = self.len();
+        if len > 1 {
+            self.swap(0, len - 1).unwrap();
+            sift_down::<_, _, D>(self, 0, len - 1);
+        }
+    }
+
+    /// 堆排序, 结果为升序.
+    /// # Correctness
+    /// 此方法要求表为`D`叉堆. 反复把堆顶换到堆的末尾, 时间复杂度为`O(D n log_D n)`, 空间复杂度为`O(1)`.
+    fn sort_heap<const D: usize>(&mut self) {
+        for end in (1..self.len()).rev() {
+            self.swap(0, end).unwrap();
+            sift_down::<_, _, D>(self, 0, end);
+        }
+    }
+}
+
+/// 上滤: 若父母小于`n`处的元素则交换, 并继续向上比较.
+fn sift_up<L, Item, const D: usize>(list: &mut L, mut n: usize)
+where
+    L: List<Item> + ?Sized,
+    Item: PartialOrd,
+{
+    while n != 0 {
+        let parent = (n - 1) / D;
+        if *list.get(parent).unwrap() < *list.get(n).unwrap() {
+            list.swap(parent, n).unwrap();
+            n = parent;
+        } else {
+            break;
+        }
+    }
+}
+
+/// 在前`limit`个元素中下滤: 若最大的孩子大于`n`处的元素则交换, 并继续向下比较.
+fn sift_down<L, Item, const D: usize>(list: &mut L, mut n: usize, limit: usize)
+where
+    L: List<Item> + ?Sized,
+    Item: PartialOrd,
+{
+    loop {
+        let first = D * n + 1;
+        let mut max = n;
+        for child in first..limit.min(first + D) {
+            if *list.get(max).unwrap() < *list.get(child).unwrap() {
+                max = child;
+            }
+        }
+        if max == n {
+            break;
+        }
+        list.swap(max, n).unwrap();
+        n = max;
+    }
+}
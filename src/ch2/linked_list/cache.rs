@@ -0,0 +1,664 @@
+//! 基于循环双链表与字典的缓存.
+//!
+//! 链表维护淘汰顺序, 缓存自己的句柄表记录每个词条在链表中的结点句柄.
+//! 字典`M`只把键映射到句柄表的下标, 可以是任何`UnorderedMap`的实现(散列表或有序的`Map`).
+//! 因此各操作只需一次字典操作与`O(1)`的链表操作.
+
+use super::cdll::{self, Handle, LinkedList};
+use super::SinglyLinkedList;
+use crate::ch4::UnorderedMap;
+use std::mem;
+
+/// `put`的结果.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Put<K, V> {
+    /// 键原本不在缓存中, 且缓存未满.
+    Inserted,
+    /// 键原本就在缓存中, 其值被替换. 包含旧值.
+    Replaced(V),
+    /// 键原本不在缓存中, 为此淘汰了另一个词条. 包含被淘汰的键值对.
+    Evicted(K, V),
+}
+
+/// 缓存中的词条. `slot`是它在句柄表中的下标.
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    slot: usize,
+}
+
+/// 缓存维护的句柄表.
+///
+/// 不变式: 表中的句柄总是指向缓存中尚未删除的结点. 结点被移动或删除时, 缓存同步更新或清除它的表项.
+/// 字典给出的下标都要经过检查, 因此即使字典的实现有误(例如返回已清除或属于别的键的下标),
+/// 也只会查不到词条, 而不会产生悬垂的句柄.
+struct Slots<H> {
+    handles: Vec<Option<H>>,
+    /// 已清除的表项的下标.
+    free: Vec<usize>,
+}
+
+impl<H: Copy> Slots<H> {
+    fn new() -> Self {
+        Self {
+            handles: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// 占用一个表项, 其中的句柄由`f`根据下标创建. 返回该下标.
+    fn insert<F: FnOnce(usize) -> H>(&mut self, f: F) -> usize {
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                self.handles.push(None);
+                self.handles.len() - 1
+            }
+        };
+        self.handles[slot] = Some(f(slot));
+        slot
+    }
+
+    /// 下标所对应的句柄. 下标越界或表项已清除时返回`None`.
+    fn get(&self, slot: usize) -> Option<H> {
+        self.handles.get(slot).copied().flatten()
+    }
+
+    fn set(&mut self, slot: usize, handle: H) {
+        self.handles[slot] = Some(handle);
+    }
+
+    fn remove(&mut self, slot: usize) {
+        self.handles[slot] = None;
+        self.free.push(slot);
+    }
+}
+
+/// LRU缓存: 容量已满时淘汰最久未使用的词条.
+///
+/// 链表按最近使用的时间从新到旧排列词条.
+/// 经由句柄的链表操作只使用句柄表中的句柄, 并核对所指词条的键, 字典中的下标只用于查找表项.
+/// 因此对任意`M`(即使它的实现有误)都不会访问已删除的结点.
+pub struct LruCache<M: UnorderedMap<K, usize>, K: Eq + Clone, V> {
+    index: M,
+    slots: Slots<Handle<Entry<K, V>>>,
+    list: LinkedList<Entry<K, V>>,
+    capacity: usize,
+}
+
+impl<M: UnorderedMap<K, usize>, K: Eq + Clone, V> LruCache<M, K, V> {
+    /// 创建容量为`capacity`的空缓存.
+    ///
+    /// # Panics
+    /// 容量必须大于0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "缓存容量必须大于0.");
+        Self {
+            index: M::default(),
+            slots: Slots::new(),
+            list: LinkedList::new(),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// 键所对应的结点句柄. 字典给出的下标须对应句柄表中的表项, 且所指词条的键等于`key`.
+    fn handle(&self, key: &K) -> Option<Handle<Entry<K, V>>> {
+        let handle = self.slots.get(*self.index.get(key)?)?;
+        // Safety: 句柄表中的句柄总是指向本表中尚未删除的结点.
+        if unsafe { self.list.get_by_handle(handle) }.key == *key {
+            Some(handle)
+        } else {
+            None
+        }
+    }
+
+    /// 返回键所对应的值, 并将其标记为最近使用.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// 返回键所对应的值的可变引用, 并将其标记为最近使用.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let handle = self.handle(key)?;
+        // Safety: 句柄取自句柄表, 见`handle`.
+        unsafe {
+            self.list.move_to_front(handle);
+            Some(&mut self.list.get_mut_by_handle(handle).value)
+        }
+    }
+
+    /// 返回键所对应的值, 不改变使用顺序.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let handle = self.handle(key)?;
+        Some(unsafe { &self.list.get_by_handle(handle).value })
+    }
+
+    /// 插入键值对, 并将其标记为最近使用.
+    /// 若键已存在则替换它的值; 否则若缓存已满, 则淘汰最久未使用的词条. 结果见`Put`.
+    pub fn put(&mut self, key: K, value: V) -> Put<K, V> {
+        if let Some(handle) = self.handle(&key) {
+            let old = unsafe {
+                self.list.move_to_front(handle);
+                mem::replace(&mut self.list.get_mut_by_handle(handle).value, value)
+            };
+            return Put::Replaced(old);
+        }
+        let evicted = if self.list.len() == self.capacity {
+            self.list.pop_back().map(|entry| {
+                self.slots.remove(entry.slot);
+                self.index.remove(&entry.key);
+                (entry.key, entry.value)
+            })
+        } else {
+            None
+        };
+        let (list, cloned) = (&mut self.list, key.clone());
+        let slot = self.slots.insert(|slot| {
+            list.push_front_handle(Entry {
+                key: cloned,
+                value,
+                slot,
+            })
+        });
+        self.index.insert(key, slot);
+        match evicted {
+            Some((key, value)) => Put::Evicted(key, value),
+            None => Put::Inserted,
+        }
+    }
+
+    /// 移除键值对, 并返回它的值.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let handle = self.handle(key)?;
+        self.index.remove(key);
+        let entry = unsafe { self.list.remove_by_handle(handle) };
+        self.slots.remove(entry.slot);
+        Some(entry.value)
+    }
+
+    /// 按最近使用的时间从新到旧返回所有键值对的迭代器, 不改变使用顺序.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.list.iter(),
+        }
+    }
+}
+
+/// LRU缓存的迭代器.
+pub struct Iter<'a, K, V> {
+    inner: cdll::Iter<'a, Entry<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+/// LFU缓存中访问频次相同的词条, 按最近使用的时间从新到旧排列.
+struct Bucket<K, V> {
+    frequency: usize,
+    entries: LinkedList<Entry<K, V>>,
+}
+
+impl<K, V> Bucket<K, V> {
+    fn new(frequency: usize) -> Self {
+        Self {
+            frequency,
+            entries: LinkedList::new(),
+        }
+    }
+}
+
+/// LFU缓存中词条的位置: 所在的桶, 以及在桶中的结点.
+struct Slot<K, V> {
+    bucket: Handle<Bucket<K, V>>,
+    entry: Handle<Entry<K, V>>,
+}
+
+impl<K, V> Clone for Slot<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for Slot<K, V> {}
+
+/// LFU缓存: 容量已满时淘汰访问频次最低的词条, 频次相同时淘汰其中最久未使用的.
+///
+/// 非空的桶按频次升序排列成链表, 访问一个词条时把它移入频次加一的桶(必要时在其后新建该桶),
+/// 因此各操作的复杂度与LRU缓存相同.
+/// 句柄表记录每个词条所在的桶与结点, 与`LruCache`一样, 字典有误时也不会访问已删除的桶或结点.
+pub struct LfuCache<M: UnorderedMap<K, usize>, K: Eq + Clone, V> {
+    index: M,
+    slots: Slots<Slot<K, V>>,
+    buckets: LinkedList<Bucket<K, V>>,
+    len: usize,
+    capacity: usize,
+}
+
+impl<M: UnorderedMap<K, usize>, K: Eq + Clone, V> LfuCache<M, K, V> {
+    /// 创建容量为`capacity`的空缓存.
+    ///
+    /// # Panics
+    /// 容量必须大于0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "缓存容量必须大于0.");
+        Self {
+            index: M::default(),
+            slots: Slots::new(),
+            buckets: LinkedList::new(),
+            len: 0,
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 键所对应的词条的位置, 检查同`LruCache::handle`.
+    fn slot(&self, key: &K) -> Option<Slot<K, V>> {
+        let slot = self.slots.get(*self.index.get(key)?)?;
+        // Safety: 句柄表中的位置总是指向尚未删除的桶与词条.
+        let entry = unsafe {
+            self.buckets
+                .get_by_handle(slot.bucket)
+                .entries
+                .get_by_handle(slot.entry)
+        };
+        if entry.key == *key {
+            Some(slot)
+        } else {
+            None
+        }
+    }
+
+    /// 把词条移入频次加一的桶并更新句柄表, 返回它的新位置. 原来的桶若因此变空则删除.
+    ///
+    /// # Safety
+    /// `slot`须指向`buckets`中的词条.
+    unsafe fn promote(
+        buckets: &mut LinkedList<Bucket<K, V>>,
+        slots: &mut Slots<Slot<K, V>>,
+        slot: Slot<K, V>,
+    ) -> Slot<K, V> {
+        let bucket = buckets.get_mut_by_handle(slot.bucket);
+        let frequency = bucket.frequency + 1;
+        let entry = bucket.entries.remove_by_handle(slot.entry);
+        let next = match buckets.next_handle(slot.bucket) {
+            Some(next) if buckets.get_by_handle(next).frequency == frequency => next,
+            _ => buckets.insert_after_handle(slot.bucket, Bucket::new(frequency)),
+        };
+        if buckets.get_by_handle(slot.bucket).entries.is_empty() {
+            buckets.remove_by_handle(slot.bucket);
+        }
+        let index = entry.slot;
+        let slot = Slot {
+            bucket: next,
+            entry: buckets
+                .get_mut_by_handle(next)
+                .entries
+                .push_front_handle(entry),
+        };
+        slots.set(index, slot);
+        slot
+    }
+
+    /// 返回键所对应的值, 并将其访问频次加一.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// 返回键所对应的值的可变引用, 并将其访问频次加一.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let slot = self.slot(key)?;
+        // Safety: 位置取自句柄表, 见`slot`.
+        unsafe {
+            let slot = Self::promote(&mut self.buckets, &mut self.slots, slot);
+            let bucket = self.buckets.get_mut_by_handle(slot.bucket);
+            Some(&mut bucket.entries.get_mut_by_handle(slot.entry).value)
+        }
+    }
+
+    /// 返回键所对应的值, 不改变访问频次.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let slot = self.slot(key)?;
+        unsafe {
+            let bucket = self.buckets.get_by_handle(slot.bucket);
+            Some(&bucket.entries.get_by_handle(slot.entry).value)
+        }
+    }
+
+    /// 返回键的访问频次. 插入计一次, 此后每次`get`或`put`加一.
+    pub fn frequency(&self, key: &K) -> Option<usize> {
+        let slot = self.slot(key)?;
+        Some(unsafe { self.buckets.get_by_handle(slot.bucket) }.frequency)
+    }
+
+    /// 插入键值对, 并将其访问频次加一.
+    /// 若键已存在则替换它的值; 否则若缓存已满, 则淘汰一个词条. 结果见`Put`.
+    pub fn put(&mut self, key: K, value: V) -> Put<K, V> {
+        if let Some(slot) = self.slot(&key) {
+            let old = unsafe {
+                let slot = Self::promote(&mut self.buckets, &mut self.slots, slot);
+                let bucket = self.buckets.get_mut_by_handle(slot.bucket);
+                mem::replace(
+                    &mut bucket.entries.get_mut_by_handle(slot.entry).value,
+                    value,
+                )
+            };
+            return Put::Replaced(old);
+        }
+        let evicted = if self.len == self.capacity {
+            self.evict()
+        } else {
+            None
+        };
+        let bucket = match self.buckets.front_handle() {
+            Some(front) if unsafe { self.buckets.get_by_handle(front) }.frequency == 1 => front,
+            _ => self.buckets.push_front_handle(Bucket::new(1)),
+        };
+        let entries = &mut unsafe { self.buckets.get_mut_by_handle(bucket) }.entries;
+        let cloned = key.clone();
+        let slot = self.slots.insert(|slot| Slot {
+            bucket,
+            entry: entries.push_front_handle(Entry {
+                key: cloned,
+                value,
+                slot,
+            }),
+        });
+        self.index.insert(key, slot);
+        self.len += 1;
+        match evicted {
+            Some((key, value)) => Put::Evicted(key, value),
+            None => Put::Inserted,
+        }
+    }
+
+    /// 淘汰频次最低的桶中最久未使用的词条.
+    fn evict(&mut self) -> Option<(K, V)> {
+        let front = self.buckets.front_handle()?;
+        let bucket = unsafe { self.buckets.get_mut_by_handle(front) };
+        let entry = bucket.entries.pop_back()?;
+        if bucket.entries.is_empty() {
+            unsafe { self.buckets.remove_by_handle(front) };
+        }
+        self.slots.remove(entry.slot);
+        self.index.remove(&entry.key);
+        self.len -= 1;
+        Some((entry.key, entry.value))
+    }
+
+    /// 移除键值对, 并返回它的值.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.slot(key)?;
+        self.index.remove(key);
+        let entry = unsafe {
+            let bucket = self.buckets.get_mut_by_handle(slot.bucket);
+            let entry = bucket.entries.remove_by_handle(slot.entry);
+            if bucket.entries.is_empty() {
+                self.buckets.remove_by_handle(slot.bucket);
+            }
+            entry
+        };
+        self.slots.remove(entry.slot);
+        self.len -= 1;
+        Some(entry.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ch4::avlt::AVLTreeMap;
+    use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_lru() {
+        let mut cache = LruCache::<HashMap<_, _>, _, _>::new(2);
+        assert_eq!(cache.put(1, "a"), Put::Inserted);
+        assert_eq!(cache.put(2, "b"), Put::Inserted);
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.put(3, "c"), Put::Evicted(2, "b"));
+        assert_eq!(cache.peek(&1), Some(&"a"));
+        assert_eq!(cache.put(4, "d"), Put::Evicted(1, "a"));
+        assert_eq!(cache.put(3, "e"), Put::Replaced("c"));
+        assert_eq!(
+            cache.iter().collect::<Vec<_>>(),
+            vec![(&3, &"e"), (&4, &"d")]
+        );
+        assert_eq!(cache.remove(&4), Some("d"));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_lfu() {
+        let mut cache = LfuCache::<AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>, _, _>::new(2);
+        assert_eq!(cache.put(1, "a"), Put::Inserted);
+        assert_eq!(cache.put(2, "b"), Put::Inserted);
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.frequency(&1), Some(2));
+        assert_eq!(cache.put(3, "c"), Put::Evicted(2, "b"));
+        assert_eq!(cache.get(&3), Some(&"c"));
+        // 频次相同时淘汰最久未使用的.
+        assert_eq!(cache.put(4, "d"), Put::Evicted(1, "a"));
+        assert_eq!(cache.peek(&3), Some(&"c"));
+        assert_eq!(cache.frequency(&3), Some(2));
+        assert_eq!(cache.put(3, "e"), Put::Replaced("c"));
+        assert_eq!(cache.frequency(&3), Some(3));
+        assert_eq!(cache.remove(&3), Some("e"));
+        assert_eq!(cache.len(), 1);
+    }
+
+    /// 移除键时并不删除它的字典, 此后仍会给出已经失效的下标.
+    #[derive(Default)]
+    struct StaleMap(HashMap<u8, usize>);
+
+    impl UnorderedMap<u8, usize> for StaleMap {
+        fn get(&self, key: &u8) -> Option<&usize> {
+            self.0.get(key)
+        }
+
+        fn get_mut(&mut self, key: &u8) -> Option<&mut usize> {
+            self.0.get_mut(key)
+        }
+
+        fn insert(&mut self, key: u8, value: usize) -> Option<usize> {
+            self.0.insert(key, value)
+        }
+
+        fn get_mut_or_insert(&mut self, key: u8, default: usize) -> &mut usize {
+            self.0.entry(key).or_insert(default)
+        }
+
+        fn remove(&mut self, key: &u8) -> Option<usize> {
+            self.0.get(key).copied()
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&u8, &usize)>> {
+            Box::new(self.0.iter())
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_capacity() {
+        LruCache::<BTreeMap<u8, _>, u8, u8>::new(0);
+    }
+
+    /// 缓存操作: 读取、不改变顺序的读取、写入、移除.
+    #[derive(Debug, Clone)]
+    enum Op {
+        Get(u8),
+        Peek(u8),
+        Put(u8, u32),
+        Remove(u8),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            2 => (0..12u8).prop_map(Op::Get),
+            1 => (0..12u8).prop_map(Op::Peek),
+            3 => (0..12u8, any::<u32>()).prop_map(|(key, value)| Op::Put(key, value)),
+            1 => (0..12u8).prop_map(Op::Remove),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_lru_cache(capacity in 1..8usize, ops in prop::collection::vec(op(), 0..200)) {
+            let mut cache = LruCache::<HashMap<_, _>, _, _>::new(capacity);
+            // 按最近使用的时间从新到旧排列.
+            let mut expected: Vec<(u8, u32)> = Vec::new();
+            for op in ops {
+                match op {
+                    Op::Get(key) => {
+                        let value = expected.iter().position(|&(k, _)| k == key).map(|i| {
+                            let entry = expected.remove(i);
+                            expected.insert(0, entry);
+                            entry.1
+                        });
+                        prop_assert_eq!(cache.get(&key).copied(), value);
+                    }
+                    Op::Peek(key) => {
+                        let value = expected.iter().find(|&&(k, _)| k == key).map(|&(_, v)| v);
+                        prop_assert_eq!(cache.peek(&key).copied(), value);
+                    }
+                    Op::Put(key, value) => {
+                        let out = match expected.iter().position(|&(k, _)| k == key) {
+                            Some(i) => Put::Replaced(expected.remove(i).1),
+                            None if expected.len() == capacity => {
+                                let (k, v) = expected.pop().unwrap();
+                                Put::Evicted(k, v)
+                            }
+                            None => Put::Inserted,
+                        };
+                        expected.insert(0, (key, value));
+                        prop_assert_eq!(cache.put(key, value), out);
+                    }
+                    Op::Remove(key) => {
+                        let value = expected.iter().position(|&(k, _)| k == key).map(|i| expected.remove(i).1);
+                        prop_assert_eq!(cache.remove(&key), value);
+                    }
+                }
+                prop_assert_eq!(cache.len(), expected.len());
+                prop_assert!(cache.iter().eq(expected.iter().map(|(k, v)| (k, v))));
+            }
+        }
+
+        #[test]
+        fn test_stale_index(capacity in 1..8usize, ops in prop::collection::vec(op(), 0..200)) {
+            let mut lru = (LruCache::<HashMap<_, _>, _, _>::new(capacity), LruCache::<StaleMap, _, _>::new(capacity));
+            let mut lfu = (LfuCache::<HashMap<_, _>, _, _>::new(capacity), LfuCache::<StaleMap, _, _>::new(capacity));
+            for op in ops {
+                match op {
+                    Op::Get(key) => {
+                        prop_assert_eq!(lru.0.get(&key), lru.1.get(&key));
+                        prop_assert_eq!(lfu.0.get(&key), lfu.1.get(&key));
+                    }
+                    Op::Peek(key) => {
+                        prop_assert_eq!(lru.0.peek(&key), lru.1.peek(&key));
+                        prop_assert_eq!(lfu.0.frequency(&key), lfu.1.frequency(&key));
+                    }
+                    Op::Put(key, value) => {
+                        prop_assert_eq!(lru.0.put(key, value), lru.1.put(key, value));
+                        prop_assert_eq!(lfu.0.put(key, value), lfu.1.put(key, value));
+                    }
+                    Op::Remove(key) => {
+                        prop_assert_eq!(lru.0.remove(&key), lru.1.remove(&key));
+                        prop_assert_eq!(lfu.0.remove(&key), lfu.1.remove(&key));
+                    }
+                }
+            }
+            prop_assert!(lru.0.iter().eq(lru.1.iter()));
+            prop_assert_eq!(lfu.0.len(), lfu.1.len());
+        }
+
+        #[test]
+        fn test_lfu_cache(capacity in 1..8usize, ops in prop::collection::vec(op(), 0..200)) {
+            let mut cache = LfuCache::<BTreeMap<_, _>, _, _>::new(capacity);
+            // (键, 值, 频次, 最近使用的时刻).
+            let mut expected: Vec<(u8, u32, usize, usize)> = Vec::new();
+            for (tick, op) in ops.into_iter().enumerate() {
+                match op {
+                    Op::Get(key) => {
+                        let value = expected.iter_mut().find(|entry| entry.0 == key).map(|entry| {
+                            entry.2 += 1;
+                            entry.3 = tick;
+                            entry.1
+                        });
+                        prop_assert_eq!(cache.get(&key).copied(), value);
+                    }
+                    Op::Peek(key) => {
+                        let value = expected.iter().find(|entry| entry.0 == key).map(|entry| entry.1);
+                        prop_assert_eq!(cache.peek(&key).copied(), value);
+                    }
+                    Op::Put(key, value) => {
+                        let out = match expected.iter_mut().find(|entry| entry.0 == key) {
+                            Some(entry) => {
+                                entry.2 += 1;
+                                entry.3 = tick;
+                                Put::Replaced(mem::replace(&mut entry.1, value))
+                            }
+                            None => {
+                                let out = if expected.len() == capacity {
+                                    let (i, _) = expected
+                                        .iter()
+                                        .enumerate()
+                                        .min_by_key(|(_, entry)| (entry.2, entry.3))
+                                        .unwrap();
+                                    let (k, v, _, _) = expected.remove(i);
+                                    Put::Evicted(k, v)
+                                } else {
+                                    Put::Inserted
+                                };
+                                expected.push((key, value, 1, tick));
+                                out
+                            }
+                        };
+                        prop_assert_eq!(cache.put(key, value), out);
+                    }
+                    Op::Remove(key) => {
+                        let value = expected.iter().position(|entry| entry.0 == key).map(|i| expected.remove(i).1);
+                        prop_assert_eq!(cache.remove(&key), value);
+                    }
+                }
+                prop_assert_eq!(cache.len(), expected.len());
+                for &(key, value, frequency, _) in &expected {
+                    prop_assert_eq!(cache.peek(&key), Some(&value));
+                    prop_assert_eq!(cache.frequency(&key), Some(frequency));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use super::*;
+
+/// 结点句柄.
+/// 在结点被删除之前始终指向它, 用于`O(1)`地访问、删除与移动该结点, 而不必从首结点开始遍历.
+///
+/// 句柄不持有链表的借用, 因此经由句柄的操作都是`unsafe`的:
+/// 调用者须保证句柄指向本表中尚未删除的结点.
+/// 移动结点不会使其句柄失效.
+#[derive(Debug)]
+pub struct Handle<T>(Link<T>);
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> LinkedList<T> {
+    /// 从链表中摘下一个结点, 但不释放它.
+    ///
+    /// # Safety
+    /// `node`须是本表中的结点.
+    unsafe fn unlink_node(&mut self, node: Link<T>) -> Box<Node<T>> {
+        let prev = node.as_ref().prev;
+        let next = node.as_ref().next;
+        if next == node {
+            // 根据循环链表性质, 说明这是表的唯一结点.
+            self.head = None;
+        } else {
+            // 这里保持了链表不变式1、2.
+            (*prev.as_ptr()).next = next;
+            (*next.as_ptr()).prev = prev;
+            if self.head == Some(node) {
+                self.head = Some(next);
+            }
+        }
+        self.len -= 1;
+        // Safety: 根据不变式2、3, 这里是安全的.
+        Box::from_raw(node.as_ptr())
+    }
+
+    /// 首结点的句柄. 若表空则返回`None`.
+    pub(crate) fn front_handle(&self) -> Option<Handle<T>> {
+        self.head.map(Handle)
+    }
+
+    /// 把新值作为新的首结点插入, 并返回它的句柄.
+    pub(crate) fn push_front_handle(&mut self, elem: T) -> Handle<T> {
+        self.push_front_node(Box::new(Node::new(elem)));
+        Handle(self.head.unwrap())
+    }
+
+    /// 返回句柄所指结点的后继的句柄. 若它是尾结点则返回`None`.
+    ///
+    /// # Safety
+    /// `handle`须指向本表中尚未删除的结点.
+    pub(crate) unsafe fn next_handle(&self, handle: Handle<T>) -> Option<Handle<T>> {
+        let next = handle.0.as_ref().next;
+        if Some(next) == self.head {
+            None
+        } else {
+            Some(Handle(next))
+        }
+    }
+
+    /// 获得句柄所指结点的元素的只读引用.
+    ///
+    /// # Safety
+    /// `handle`须指向本表中尚未删除的结点.
+    pub(crate) unsafe fn get_by_handle(&self, handle: Handle<T>) -> &T {
+        &(*handle.0.as_ptr()).elem
+    }
+
+    /// 获得句柄所指结点的元素的可变引用.
+    ///
+    /// # Safety
+    /// `handle`须指向本表中尚未删除的结点.
+    pub(crate) unsafe fn get_mut_by_handle(&mut self, handle: Handle<T>) -> &mut T {
+        &mut (*handle.0.as_ptr()).elem
+    }
+
+    /// 删除句柄所指结点并返回其内容. 此后该句柄失效.
+    ///
+    /// # Safety
+    /// `handle`须指向本表中尚未删除的结点.
+    pub(crate) unsafe fn remove_by_handle(&mut self, handle: Handle<T>) -> T {
+        self.unlink_node(handle.0).into_elem()
+    }
+
+    /// 把句柄所指结点移为首结点. 结点不会重新分配, 因此句柄依然有效.
+    ///
+    /// # Safety
+    /// `handle`须指向本表中尚未删除的结点.
+    pub(crate) unsafe fn move_to_front(&mut self, handle: Handle<T>) {
+        if self.head != Some(handle.0) {
+            let node = self.unlink_node(handle.0);
+            // `Box::leak`会重新得到同一个指针.
+            self.push_front_node(node);
+        }
+    }
+
+    /// 在句柄所指结点后插入新值, 并返回新结点的句柄.
+    ///
+    /// # Safety
+    /// `handle`须指向本表中尚未删除的结点.
+    pub(crate) unsafe fn insert_after_handle(&mut self, handle: Handle<T>, elem: T) -> Handle<T> {
+        let node: Link<T> = Box::leak(Box::new(Node::new(elem))).into();
+        // 与`CursorMut::insert_after_node`相同, `node`共享了它的两个指针给当前结点(作为后继)和当前结点的后继(作为前驱),
+        // 因此保持链表不变式. 若当前结点为尾结点, 则新结点成为尾结点.
+        let current = handle.0;
+        let next = current.as_ref().next;
+        (*node.as_ptr()).next = next;
+        (*node.as_ptr()).prev = current;
+        (*current.as_ptr()).next = node;
+        (*next.as_ptr()).prev = node;
+        self.len += 1;
+        Handle(node)
+    }
+}
@@ -1,9 +1,11 @@
 pub mod algos;
 pub mod cursor;
+pub mod handle;
 pub mod iter;
 
 use super::*;
 pub use cursor::*;
+pub use handle::*;
 pub use iter::*;
 use std::cmp::PartialEq;
 use std::marker::PhantomData;
@@ -150,6 +150,44 @@ proptest! {
         prop_assert!(list.is_empty());
         prop_assert!(data.is_empty());
     }
+
+    #[test]
+    fn test_handles(ops: Vec<(u8, usize)>) {
+        let mut list = LinkedList::default();
+        // 与链表中结点顺序一致的(元素, 句柄).
+        let mut expected: Vec<(u8, Handle<u8>)> = Vec::new();
+        for (op, i) in ops {
+            match op % 4 {
+                0 => {
+                    let handle = list.push_front_handle(op);
+                    expected.insert(0, (op, handle));
+                }
+                _ if expected.is_empty() => {}
+                1 => {
+                    let (elem, handle) = expected.remove(i % expected.len());
+                    prop_assert_eq!(unsafe { list.remove_by_handle(handle) }, elem);
+                }
+                2 => {
+                    let entry = expected.remove(i % expected.len());
+                    unsafe { list.move_to_front(entry.1) };
+                    expected.insert(0, entry);
+                }
+                _ => {
+                    let i = i % expected.len();
+                    let handle = unsafe { list.insert_after_handle(expected[i].1, op) };
+                    expected.insert(i + 1, (op, handle));
+                }
+            }
+            prop_assert_eq!(list.len(), expected.len());
+            prop_assert!(list.iter().eq(expected.iter().map(|(elem, _)| elem)));
+            prop_assert_eq!(list.front_handle(), expected.first().map(|&(_, handle)| handle));
+            for (i, &(elem, handle)) in expected.iter().enumerate() {
+                prop_assert_eq!(unsafe { *list.get_by_handle(handle) }, elem);
+                let next = expected.get(i + 1).map(|&(_, handle)| handle);
+                prop_assert_eq!(unsafe { list.next_handle(handle) }, next);
+            }
+        }
+    }
 }
 
 proptest! {
@@ -1,3 +1,4 @@
+pub mod cache;
 pub mod cdll;
 pub mod shll;
 pub mod singly;
@@ -1,5 +1,6 @@
 //! 实现了线性表相关算法.
 
+pub mod heap;
 pub mod integer;
 pub mod linked_list;
 pub mod list;
@@ -7,6 +8,7 @@ pub mod partial_eq;
 pub mod partial_ord;
 pub mod slice;
 
+pub use heap::*;
 pub use integer::*;
 pub use list::*;
 pub use partial_eq::*;
@@ -1,6 +1,7 @@
-use super::{IndexError, List, ListExt, PartialEqListExt, PartialOrdListExt};
+use super::{HeapListExt, IndexError, List, ListExt, PartialEqListExt, PartialOrdListExt};
 use crate::vec::MyVec;
 use proptest::prelude::*;
+use std::collections::BinaryHeap;
 
 proptest! {
     #[test]
@@ -546,3 +547,72 @@ fn test_merge_mid_debug() {
     let merged_mid = merged.mid().copied();
     assert_eq!(mid, merged_mid);
 }
+
+/// 以`BinaryHeap`为模型检查`D`叉堆算法: 建堆、排序, 以及交替的`push_heap`与`pop_heap`.
+fn check_heap<const D: usize>(data: Vec<i64>, ops: Vec<Option<i64>>) -> Result<(), TestCaseError> {
+    let mut sorted = data.clone();
+    sorted.sort_unstable();
+    let mut list = MyVec::from(data.clone());
+    list.make_heap::<D>();
+    prop_assert!(list.is_heap::<D>());
+    list.sort_heap::<D>();
+    prop_assert_eq!(&list[..], &sorted[..]);
+
+    let mut list = data.clone();
+    list.make_heap::<D>();
+    let mut model = BinaryHeap::from(data);
+    for op in ops {
+        match op {
+            Some(x) => {
+                list.push(x);
+                list.push_heap::<D>();
+                model.push(x);
+            }
+            None => {
+                list.pop_heap::<D>();
+                prop_assert_eq!(list.pop(), model.pop());
+            }
+        }
+        prop_assert!(list.is_heap::<D>());
+        prop_assert_eq!(list.first(), model.peek());
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn test_heap(data: Vec<i64>, ops: Vec<Option<i64>>) {
+        check_heap::<2>(data.clone(), ops.clone())?;
+        check_heap::<3>(data.clone(), ops.clone())?;
+        check_heap::<4>(data, ops)?;
+    }
+
+    #[test]
+    fn test_heap_slice(data: Vec<i64>, start: usize, end: usize) {
+        let (start, end) = if data.is_empty() {
+            (0, 0)
+        } else {
+            let (start, end) = (start % data.len(), end % (data.len() + 1));
+            (start.min(end), start.max(end))
+        };
+        let mut list = MyVec::from(data.clone());
+        let mut expected = data;
+        expected[start..end].sort_unstable();
+        if start < end {
+            let mut slice = list.slice_mut(start, end).unwrap();
+            slice.make_heap::<4>();
+            prop_assert!(slice.is_heap::<4>());
+            slice.sort_heap::<4>();
+        }
+        prop_assert_eq!(&list[..], &expected[..]);
+    }
+}
+
+#[test]
+fn test_is_heap() {
+    let list = vec![9, 3, 8, 7, 1, 2];
+    // 二叉堆中3的孩子为7与1; 四叉堆中9的孩子为3、8、7、1, 3的孩子为2.
+    assert!(!list.is_heap::<2>());
+    assert!(list.is_heap::<4>());
+    assert!(Vec::<i32>::new().is_heap::<2>());
+}
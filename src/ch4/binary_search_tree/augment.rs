@@ -0,0 +1,159 @@
+/// 幺半群: 带有单位元的结合运算.
+pub trait Monoid {
+    /// 单位元，即空子树的附加信息.
+    fn identity() -> Self;
+
+    /// 结合运算. 要求满足结合律，但不要求满足交换律: `self`总是位于`other`的左侧.
+    fn combine(&self, other: &Self) -> Self;
+}
+
+/// 结点的附加信息.
+///
+/// 每个结点保存其子树的附加信息，即按中序将子树中每个词条的`from_entry`依次结合的结果.
+/// 平衡树在每次插入、删除与旋转之后，由孩子的附加信息自底向上重新计算.
+///
+/// 注意: 通过`get_mut`或`iter_mut`直接修改值时不会重新计算附加信息.
+/// 若附加信息依赖于值，应使用`insert`更新值.
+pub trait Augment<K, V>: Monoid {
+    /// 单个词条的附加信息.
+    fn from_entry(key: &K, value: &V) -> Self;
+}
+
+impl Monoid for () {
+    fn identity() -> Self {}
+
+    fn combine(&self, _: &Self) -> Self {}
+}
+
+/// 不维护任何附加信息.
+impl<K, V> Augment<K, V> for () {
+    fn from_entry(_: &K, _: &V) -> Self {}
+}
+
+/// 子树的大小.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Size(pub usize);
+
+impl Monoid for Size {
+    fn identity() -> Self {
+        Size(0)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Size(self.0 + other.0)
+    }
+}
+
+impl<K, V> Augment<K, V> for Size {
+    fn from_entry(_: &K, _: &V) -> Self {
+        Size(1)
+    }
+}
+
+/// 由左子树、当前词条与右子树的附加信息计算子树的附加信息.
+pub(crate) fn summarize<K, V, A: Augment<K, V>>(
+    left: Option<&A>,
+    key: &K,
+    value: &V,
+    right: Option<&A>,
+) -> A {
+    let mut augment = A::from_entry(key, value);
+    if let Some(left) = left {
+        augment = left.combine(&augment);
+    }
+    if let Some(right) = right {
+        augment = augment.combine(right);
+    }
+    augment
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::avlt::{AVLEntry, AVLTreeMap};
+    use super::super::rbt::{RBEntry, RBTreeMap};
+    use super::super::UnorderedMap;
+    use super::*;
+    use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
+    use crate::ch4::{BinTree, BinTreeCursor};
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
+    /// 子树中值的和与键的个数.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Sum(u64, Size);
+
+    impl Monoid for Sum {
+        fn identity() -> Self {
+            Sum(0, Size::identity())
+        }
+
+        fn combine(&self, other: &Self) -> Self {
+            Sum(self.0 + other.0, self.1.combine(&other.1))
+        }
+    }
+
+    impl Augment<u8, u32> for Sum {
+        fn from_entry(key: &u8, value: &u32) -> Self {
+            Sum(*value as u64, Size::from_entry(key, value))
+        }
+    }
+
+    fn check_avl<'a, C: BinTreeCursor<'a, Elem = AVLEntry<u8, u32, Sum>> + Clone>(
+        cursor: C,
+    ) -> Sum {
+        match cursor.as_ref() {
+            None => Sum::identity(),
+            Some(entry) => {
+                let (left, right) = cursor.split();
+                let sum = left
+                    .map_or(Sum::identity(), check_avl)
+                    .combine(&Sum::from_entry(&entry.key, &*entry.value))
+                    .combine(&right.map_or(Sum::identity(), check_avl));
+                assert_eq!(*entry.value.augment(), sum);
+                sum
+            }
+        }
+    }
+
+    fn check_rb<'a, C: BinTreeCursor<'a, Elem = RBEntry<u8, u32, Sum>> + Clone>(cursor: C) -> Sum {
+        match cursor.as_ref() {
+            None => Sum::identity(),
+            Some(entry) => {
+                let (left, right) = cursor.split();
+                let sum = left
+                    .map_or(Sum::identity(), check_rb)
+                    .combine(&Sum::from_entry(&entry.key, &*entry.value))
+                    .combine(&right.map_or(Sum::identity(), check_rb));
+                assert_eq!(*entry.value.augment(), sum);
+                sum
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_augment(entrys: Vec<(u8, u32)>, removes: Vec<u8>) {
+            let mut avl = AVLTreeMap::<DoublyLinkedBinaryTree<_>, _, _, Sum>::default();
+            let mut rb = RBTreeMap::<DoublyLinkedBinaryTree<_>, _, _, Sum>::default();
+            let mut expected = BTreeMap::new();
+            for (key, value) in entrys.iter() {
+                avl.insert(*key, *value);
+                rb.insert(*key, *value);
+                expected.insert(*key, *value);
+            }
+            check_avl(avl.bst.tree.cursor());
+            check_rb(rb.bst.tree.cursor());
+            for key in removes.iter() {
+                avl.remove(key);
+                rb.remove(key);
+                expected.remove(key);
+                *avl.get_mut_or_insert(key.wrapping_mul(3), 7) += 0;
+                *rb.get_mut_or_insert(key.wrapping_mul(3), 7) += 0;
+                expected.entry(key.wrapping_mul(3)).or_insert(7);
+            }
+            let total = Sum(expected.values().map(|value| *value as u64).sum(), Size(expected.len()));
+            prop_assert_eq!(check_avl(avl.bst.tree.cursor()), total);
+            prop_assert_eq!(check_rb(rb.bst.tree.cursor()), total);
+        }
+    }
+}
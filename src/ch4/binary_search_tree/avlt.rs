@@ -1,19 +1,33 @@
-use super::bst::TreeMap;
-use super::{Entry, Map};
+use super::augment::{self, Augment};
+use super::bst::{self, TreeMap};
+use super::join::{self, Exposed, Join, Ranked};
+use super::{Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
 use crate::ch4::{
     BinTreeCursor, BinTreeCursorMut, BinTreeMut, MoveParentBinTreeMut, MoveParentCursor,
 };
 use std::cmp::Ordering;
+use std::iter::FromIterator;
 use std::mem;
 use std::ops::{Deref, DerefMut};
 
 #[derive(Debug)]
-pub struct AVLNode<T> {
+pub struct AVLNode<T, A = ()> {
     elem: T,
     height: isize,
+    size: usize,
+    augment: A,
 }
 
-impl<T> Deref for AVLNode<T> {
+impl<T, A> AVLNode<T, A> {
+    /// 以该结点为根的子树的附加信息.
+    pub fn augment(&self) -> &A {
+        &self.augment
+    }
+}
+
+impl<T, A> Deref for AVLNode<T, A> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -21,21 +35,24 @@ impl<T> Deref for AVLNode<T> {
     }
 }
 
-impl<T> DerefMut for AVLNode<T> {
+impl<T, A> DerefMut for AVLNode<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.elem
     }
 }
 
-pub type AVLEntry<K, V> = Entry<K, AVLNode<V>>;
+pub type AVLEntry<K, V, A = ()> = Entry<K, AVLNode<V, A>>;
 
-pub struct AVLTreeMap<Tree: BinTreeMut<Elem = Entry<K, AVLNode<V>>>, K: Ord, V> {
-    bst: TreeMap<Tree, K, AVLNode<V>>,
+/// 基于AVL树的字典(Map).
+///
+/// `A`是结点的附加信息，默认不维护任何附加信息.
+pub struct AVLTreeMap<Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A = ()> {
+    pub(crate) bst: TreeMap<Tree, K, AVLNode<V, A>>,
 }
 
-impl<Tree, K: Ord, V> Default for AVLTreeMap<Tree, K, V>
+impl<Tree, K: Ord, V, A> Default for AVLTreeMap<Tree, K, V, A>
 where
-    Tree: Default + BinTreeMut<Elem = Entry<K, AVLNode<V>>>,
+    Tree: Default + BinTreeMut<Elem = AVLEntry<K, V, A>>,
 {
     fn default() -> Self {
         Self {
@@ -44,9 +61,9 @@ where
     }
 }
 
-impl<Tree, K: Ord, V> AVLTreeMap<Tree, K, V>
+impl<Tree, K: Ord, V, A: Augment<K, V>> AVLTreeMap<Tree, K, V, A>
 where
-    Tree: Default + BinTreeMut<Elem = Entry<K, AVLNode<V>>>,
+    Tree: Default + BinTreeMut<Elem = AVLEntry<K, V, A>>,
 {
     fn balance_factor<'a, C>(cursor: &C) -> isize
     where
@@ -65,22 +82,50 @@ where
         -2 < bf && bf < 2
     }
 
+    /// 由孩子重新计算高度、规模与附加信息.
     fn update_height<'a, C>(cursor: &mut C)
     where
         C: BinTreeCursorMut<'a, Elem = Tree::Elem>,
     {
         let lhs = cursor.left().map_or(-1, |node| node.value.height);
         let rhs = cursor.right().map_or(-1, |node| node.value.height);
-        cursor.as_mut().unwrap().value.height = lhs.max(rhs) + 1;
+        let size = cursor.left().map_or(0, |node| node.value.size)
+            + cursor.right().map_or(0, |node| node.value.size)
+            + 1;
+        let augment = {
+            let entry = cursor.as_ref().unwrap();
+            augment::summarize(
+                cursor.left().map(|node| &node.value.augment),
+                &entry.key,
+                &entry.value.elem,
+                cursor.right().map(|node| &node.value.augment),
+            )
+        };
+        let node = &mut cursor.as_mut().unwrap().value;
+        node.height = lhs.max(rhs) + 1;
+        node.size = size;
+        node.augment = augment;
     }
 
-    fn move_to_taller_child<'a, C>(cursor: &mut C) -> bool
+    /// 更新所有祖先的高度与附加信息.
+    fn update_ancestors<'a, C>(cursor: &mut C)
+    where
+        C: MoveParentCursor<'a, Elem = Tree::Elem> + BinTreeCursorMut<'a, Elem = Tree::Elem>,
+    {
+        while cursor.parent().is_some() {
+            cursor.move_parent();
+            Self::update_height(cursor);
+        }
+    }
+
+    /// 移动至较高的孩子，并返回是否为左孩子. 两者等高时按`prefer_left`选择.
+    fn move_to_taller_child<'a, C>(cursor: &mut C, prefer_left: bool) -> bool
     where
         C: BinTreeCursor<'a, Elem = Tree::Elem>,
     {
         let lhs = cursor.left().map_or(-1, |node| node.value.height);
         let rhs = cursor.right().map_or(-1, |node| node.value.height);
-        if lhs > rhs {
+        if lhs > rhs || (lhs == rhs && prefer_left) {
             cursor.move_left();
             true
         } else {
@@ -100,6 +145,7 @@ where
             + BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
         Tree: MoveParentBinTreeMut,
     {
+        stats::record(|stats| stats.rotations += if p_flag == v_flag { 1 } else { 2 });
         if p_flag && v_flag {
             // T T: 1 v 2 p 3 g 4
             let t1 = cursor.take_left();
@@ -222,13 +268,82 @@ where
             Ordering::Equal
         }
     }
+
+    /// 3 + 4重构前，修正`track`所记录的路径. `cursor`指向`v`.
+    /// 重构等价于先在`p`处旋转(`p_flag != v_flag`时)，再在`g`处旋转.
+    fn track_restructure<'a, C>(
+        cursor: &C,
+        p_flag: bool,
+        v_flag: bool,
+        track: &mut Option<Vec<bool>>,
+    ) where
+        C: MoveParentCursor<'a>,
+    {
+        if track.is_some() {
+            let mut at = bst::path_of(cursor);
+            at.pop();
+            if p_flag != v_flag {
+                bst::track_rotation(track, &at, v_flag);
+            }
+            at.pop();
+            bst::track_rotation(track, &at, p_flag);
+        }
+    }
+
+    /// 插入新结点后，自其父母开始使树重新平衡，并更新所有祖先. 之后游标指向根.
+    ///
+    /// `track`记录某个结点自根的路径，重新平衡时随之修正.
+    fn rebalance_after_insert<'a, C>(parent: &mut C, track: &mut Option<Vec<bool>>)
+    where
+        C: MoveParentCursor<'a, Elem = Tree::Elem>
+            + BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+        Tree: MoveParentBinTreeMut,
+    {
+        Self::update_height(parent);
+        while parent.parent().is_some() {
+            parent.move_parent();
+            if Self::is_avl_balanced(parent) {
+                Self::update_height(parent);
+            } else {
+                let p_flag = Self::move_to_taller_child(parent, true);
+                let v_flag = Self::move_to_taller_child(parent, p_flag);
+                Self::track_restructure(parent, p_flag, v_flag, track);
+                Self::rebalance_at(parent, p_flag, v_flag);
+                break;
+            }
+        }
+        // 旋转后子树高度复原，但祖先的附加信息仍需更新.
+        Self::update_ancestors(parent);
+    }
+
+    /// 摘除结点后，自被摘除的位置开始使树重新平衡. 之后游标指向根.
+    ///
+    /// `track`同`rebalance_after_insert`.
+    fn rebalance_after_remove<'a, C>(cursor: &mut C, track: &mut Option<Vec<bool>>)
+    where
+        C: MoveParentCursor<'a, Elem = Tree::Elem>
+            + BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+        Tree: MoveParentBinTreeMut,
+    {
+        while cursor.parent().is_some() {
+            cursor.move_parent();
+            if !Self::is_avl_balanced(cursor) {
+                let p_flag = Self::move_to_taller_child(cursor, true);
+                // 删除后孙辈可能等高，此时必须选择与`p`同侧者，使单旋即可恢复平衡.
+                let v_flag = Self::move_to_taller_child(cursor, p_flag);
+                Self::track_restructure(cursor, p_flag, v_flag, track);
+                Self::rebalance_at(cursor, p_flag, v_flag);
+            }
+            Self::update_height(cursor);
+        }
+    }
 }
 
-impl<Tree, K: Ord, V> Map<K, V> for AVLTreeMap<Tree, K, V>
+impl<Tree, K: Ord, V, A: Augment<K, V>> UnorderedMap<K, V> for AVLTreeMap<Tree, K, V, A>
 where
     Tree: Default
-        + MoveParentBinTreeMut<Elem = Entry<K, AVLNode<V>>>
-        + BinTreeMut<Elem = Entry<K, AVLNode<V>>>,
+        + MoveParentBinTreeMut<Elem = AVLEntry<K, V, A>>
+        + BinTreeMut<Elem = AVLEntry<K, V, A>>,
 {
     fn get(&self, key: &K) -> Option<&V> {
         self.bst.get(key).map(|node| &**node)
@@ -243,10 +358,13 @@ where
     }
 
     fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let _stats = self.bst.stats.enter();
         let mut parent = MoveParentBinTreeMut::move_parent_cursor_mut(&mut self.bst.tree);
         let value = AVLNode {
+            augment: A::from_entry(&key, &default),
             elem: default,
             height: 0,
+            size: 1,
         };
         let mut stack = Vec::new();
         match TreeMap::<Tree, _, _>::move_to_target(&mut parent, &key) {
@@ -255,16 +373,19 @@ where
             }
             Some(Ordering::Less) => {
                 parent.insert_as_left(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 stack.push(true);
             }
             Some(Ordering::Greater) => {
                 parent.insert_as_right(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 stack.push(false);
             }
             None => {
                 parent.insert_as_root(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 // 根是树中唯一结点，因此无需平衡.
                 return &mut parent.into_mut().unwrap().value;
@@ -294,11 +415,23 @@ where
                 match Self::rebalance_at(&mut parent, p_flag, v_flag) {
                     Ordering::Less => stack.push(true),
                     Ordering::Greater => stack.push(false),
-                    _ => (),
+                    _ => {
+                        // v成为子树的根，其原左子树成为新左孩子的右子树，原右子树成为新右孩子的左子树.
+                        if let Some(flag) = stack.pop() {
+                            stack.push(!flag);
+                            stack.push(flag);
+                        }
+                    }
                 }
                 break;
             }
         }
+        // 旋转后子树高度复原，但祖先的附加信息仍需更新.
+        while parent.parent().is_some() {
+            stack.push(parent.is_left_child());
+            parent.move_parent();
+            Self::update_height(&mut parent);
+        }
         while let Some(flag) = stack.pop() {
             if flag {
                 parent.move_left();
@@ -310,51 +443,46 @@ where
     }
 
     fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let _stats = self.bst.stats.enter();
         let mut parent = MoveParentBinTreeMut::move_parent_cursor_mut(&mut self.bst.tree);
         let mut value = AVLNode {
+            augment: A::from_entry(&key, &value),
             elem: value,
             height: 0,
+            size: 1,
         };
         match TreeMap::<Tree, _, _>::move_to_target(&mut parent, &key) {
             Some(Ordering::Equal) => {
-                mem::swap(&mut parent.as_mut().unwrap().value, &mut value);
-                parent.as_mut().unwrap().value.height = value.height;
-                // 没有发生实质的结点插入，因此无需重平衡.
+                mem::swap(&mut parent.as_mut().unwrap().value.elem, &mut value.elem);
+                // 没有发生实质的结点插入，因此无需重平衡，但值的改变可能影响附加信息.
+                Self::update_height(&mut parent);
+                Self::update_ancestors(&mut parent);
                 return Some(value.elem);
             }
             Some(Ordering::Less) => {
                 parent.insert_as_left(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
             }
             Some(Ordering::Greater) => {
                 parent.insert_as_right(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
             }
             None => {
                 parent.insert_as_root(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 // 根是树中唯一结点，因此无需平衡.
                 return None;
             }
         }
-        Self::update_height(&mut parent);
-        // 使树重新平衡.
-        while parent.parent().is_some() {
-            parent.move_parent();
-            if Self::is_avl_balanced(&parent) {
-                Self::update_height(&mut parent);
-            } else {
-                let p_flag = Self::move_to_taller_child(&mut parent);
-                let v_flag = Self::move_to_taller_child(&mut parent);
-                Self::rebalance_at(&mut parent, p_flag, v_flag);
-                break;
-            }
-        }
-
+        Self::rebalance_after_insert(&mut parent, &mut None);
         None
     }
 
     fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.bst.stats.enter();
         let mut cursor = MoveParentBinTreeMut::move_parent_cursor_mut(&mut self.bst.tree);
         if let Some(Ordering::Equal) = TreeMap::<Tree, _, _>::move_to_target(&mut cursor, key) {
             self.bst.len -= 1;
@@ -362,16 +490,7 @@ where
                 mem::swap(x, y);
             })
             .elem;
-            // 使树重新平衡.
-            while cursor.parent().is_some() {
-                cursor.move_parent();
-                if !Self::is_avl_balanced(&cursor) {
-                    let p_flag = Self::move_to_taller_child(&mut cursor);
-                    let v_flag = Self::move_to_taller_child(&mut cursor);
-                    Self::rebalance_at(&mut cursor, p_flag, v_flag);
-                }
-                Self::update_height(&mut cursor);
-            }
+            Self::rebalance_after_remove(&mut cursor, &mut None);
             Some(elem)
         } else {
             None
@@ -379,17 +498,628 @@ where
     }
 
     fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
-        Box::new(self.bst.iter().map(|(k, node)| (k, &node.elem)))
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.bst.stats.get()
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> Map<K, V> for AVLTreeMap<Tree, K, V, A> where
+    Tree: Default
+        + MoveParentBinTreeMut<Elem = AVLEntry<K, V, A>>
+        + BinTreeMut<Elem = AVLEntry<K, V, A>>
+{
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> AVLTreeMap<Tree, K, V, A>
+where
+    Tree: Default + BinTreeMut<Elem = AVLEntry<K, V, A>>,
+{
+    fn height(tree: &Tree) -> isize {
+        tree.cursor()
+            .as_ref()
+            .map_or(-1, |entry| entry.value.height)
+    }
+
+    /// 以`mid`为根接入两棵子树，并重新计算根的高度.
+    fn node(left: Tree, mid: Tree, right: Tree) -> Tree {
+        let mut tree = join::attach(left, mid, right);
+        Self::update_height(&mut tree.cursor_mut());
+        tree
+    }
+
+    /// `right`时`outer`为左子树，否则为右子树.
+    fn node_at(outer: Tree, mid: Tree, inner: Tree, right: bool) -> Tree {
+        if right {
+            Self::node(outer, mid, inner)
+        } else {
+            Self::node(inner, mid, outer)
+        }
+    }
+
+    /// 旋转整棵树. `promote_right`时提升右孩子，否则提升左孩子.
+    fn rotate(tree: &mut Tree, promote_right: bool) {
+        {
+            let mut cursor = tree.cursor_mut();
+            if promote_right {
+                cursor.zag();
+                cursor.move_left();
+            } else {
+                cursor.zig();
+                cursor.move_right();
+            }
+            Self::update_height(&mut cursor);
+        }
+        Self::update_height(&mut tree.cursor_mut());
+    }
+
+    /// 沿较高的`big`靠近`small`一侧的链下降，直至高度与`small`相差不超过1处接入.
+    /// `right`时`big`位于左侧.
+    fn join_side(big: Tree, mid: Tree, small: Tree, right: bool) -> Tree {
+        let (left, node, rest) = join::take_children(big);
+        let (outer, inner) = if right { (left, rest) } else { (rest, left) };
+        if Self::height(&inner) <= Self::height(&small) + 1 {
+            let mut inner = Self::node_at(inner, mid, small, right);
+            if Self::height(&inner) <= Self::height(&outer) + 1 {
+                return Self::node_at(outer, node, inner, right);
+            }
+            Self::rotate(&mut inner, !right);
+            let mut tree = Self::node_at(outer, node, inner, right);
+            Self::rotate(&mut tree, right);
+            tree
+        } else {
+            let inner = Self::join_side(inner, mid, small, right);
+            let unbalanced = Self::height(&inner) > Self::height(&outer) + 1;
+            let mut tree = Self::node_at(outer, node, inner, right);
+            if unbalanced {
+                Self::rotate(&mut tree, right);
+            }
+            tree
+        }
+    }
+
+    fn into_ranked(self) -> Ranked<Tree> {
+        let rank = (Self::height(&self.bst.tree) + 1) as usize;
+        (self.bst.tree, rank)
+    }
+
+    fn from_ranked((tree, _): Ranked<Tree>) -> Self {
+        let len = tree.cursor().as_ref().map_or(0, |entry| entry.value.size);
+        Self {
+            bst: TreeMap::from_tree(tree, len),
+        }
+    }
+
+    /// 按`key`将字典拆分为三部分: 所有小于`key`的键、`key`对应的值与所有大于`key`的键.
+    ///
+    /// 复杂度为`O(log n)`.
+    pub fn split(self, key: &K) -> (Self, Option<V>, Self) {
+        let (left, found, right) = join::split::<K, V, Self>(self.into_ranked(), key);
+        (
+            Self::from_ranked(left),
+            found.map(|tree| join::into_entry(tree).value.elem),
+            Self::from_ranked(right),
+        )
+    }
+
+    /// 连接两个字典.
+    ///
+    /// 复杂度为`O(log n)`.
+    /// # Panics
+    /// `left`所有的键都必须小于`right`中的键.
+    pub fn join(left: Self, right: Self) -> Self {
+        if let (Some(max), Some(min)) = (
+            join::last_key(&left.bst.tree),
+            join::first_key(&right.bst.tree),
+        ) {
+            assert!(max < min, "`join`要求左侧所有的键都小于右侧的键.");
+        }
+        Self::from_ranked(join::join2::<K, V, Self>(
+            left.into_ranked(),
+            right.into_ranked(),
+        ))
+    }
+
+    /// 并集. 两者都有的键，其值为`resolve(key, 自身的值, other的值)`.
+    ///
+    /// 复杂度为`O(m log(n / m + 1))`，其中`m <= n`分别为两者的规模.
+    pub fn union<F: FnMut(&K, V, V) -> V>(self, other: Self, mut resolve: F) -> Self {
+        Self::from_ranked(join::union::<K, V, Self, F>(
+            self.into_ranked(),
+            other.into_ranked(),
+            &mut resolve,
+        ))
+    }
+
+    /// 交集. 值为`resolve(key, 自身的值, other的值)`.
+    ///
+    /// 复杂度为`O(m log(n / m + 1))`，其中`m <= n`分别为两者的规模.
+    pub fn intersection<F: FnMut(&K, V, V) -> V>(self, other: Self, mut resolve: F) -> Self {
+        Self::from_ranked(join::intersection::<K, V, Self, F>(
+            self.into_ranked(),
+            other.into_ranked(),
+            &mut resolve,
+        ))
+    }
+
+    /// 差集，即自身中不在`other`中的键.
+    ///
+    /// 复杂度为`O(m log(n / m + 1))`，其中`m <= n`分别为两者的规模.
+    pub fn difference(self, other: Self) -> Self {
+        Self::from_ranked(join::difference::<K, V, Self>(
+            self.into_ranked(),
+            other.into_ranked(),
+        ))
+    }
+
+    /// 由按键严格递增的键值对序列构造完全平衡的树，复杂度为`O(n)`.
+    /// # Panics
+    /// 键必须严格递增.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::build(super::sorted_entries(iter, false))
+    }
+
+    fn build(entries: Vec<(K, V)>) -> Self {
+        let len = entries.len();
+        Self::from_ranked(join::build::<K, V, Self, _>(&mut entries.into_iter(), len))
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> FromIterator<(K, V)> for AVLTreeMap<Tree, K, V, A>
+where
+    Tree: Default + BinTreeMut<Elem = AVLEntry<K, V, A>>,
+{
+    /// 输入的键已经严格递增时复杂度为`O(n)`，否则先排序. 相等的键只保留最后一个值.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::build(super::sorted_entries(iter, true))
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> Extend<(K, V)> for AVLTreeMap<Tree, K, V, A>
+where
+    Tree: Default + BinTreeMut<Elem = AVLEntry<K, V, A>>,
+{
+    /// 将新的键值对构造为一棵树后与自身求并集，已有的键取新的值.
+    ///
+    /// 输入有序时复杂度为`O(m log(n / m + 1))`，其中`m <= n`分别为两者的规模.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let other = Self::from_iter(iter);
+        let stats = mem::take(&mut self.bst.stats);
+        *self = mem::take(self).union(other, |_, _, value| value);
+        self.bst.stats = stats;
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> Join<K, V> for AVLTreeMap<Tree, K, V, A>
+where
+    Tree: Default + BinTreeMut<Elem = AVLEntry<K, V, A>>,
+{
+    type Node = AVLNode<V, A>;
+    type Tree = Tree;
+
+    fn entry(key: K, value: V) -> AVLEntry<K, V, A> {
+        let value = AVLNode {
+            augment: A::from_entry(&key, &value),
+            elem: value,
+            height: 0,
+            size: 1,
+        };
+        Entry { key, value }
+    }
+
+    fn into_value(node: Self::Node) -> V {
+        node.elem
+    }
+
+    fn expose((tree, _): Ranked<Tree>) -> Exposed<Tree> {
+        let (left, mid, right) = join::take_children(tree);
+        let left_rank = (Self::height(&left) + 1) as usize;
+        let right_rank = (Self::height(&right) + 1) as usize;
+        ((left, left_rank), mid, (right, right_rank))
+    }
+
+    fn join((left, _): Ranked<Tree>, mid: Tree, (right, _): Ranked<Tree>) -> Ranked<Tree> {
+        let (lhs, rhs) = (Self::height(&left), Self::height(&right));
+        let tree = if lhs > rhs + 1 {
+            Self::join_side(left, mid, right, true)
+        } else if rhs > lhs + 1 {
+            Self::join_side(right, mid, left, false)
+        } else {
+            Self::node(left, mid, right)
+        };
+        let rank = (Self::height(&tree) + 1) as usize;
+        (tree, rank)
+    }
+}
+
+impl<Tree, K: Ord, V, A> AVLTreeMap<Tree, K, V, A>
+where
+    Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>,
+{
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, Tree, K, V, A> {
+        Iter {
+            inner: self.bst.iter(),
+        }
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, Tree, K, V, A> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, Tree, K, V, A> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, Tree, K, V, A>> {
+        self.bst.cursor_at(key).map(|inner| Cursor { inner })
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, Tree, K, V, A> {
+        Cursor {
+            inner: self.bst.lower_bound_cursor(key),
+        }
+    }
+
+    /// 检查二叉查找树的不变式，以及每个结点记录的高度、规模与平衡因子.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        self.bst.validate_with(
+            &mut violations,
+            |position, entry, left: Option<&(isize, usize)>, right, violations| {
+                let (lh, ls) = left.copied().unwrap_or((-1, 0));
+                let (rh, rs) = right.copied().unwrap_or((-1, 0));
+                let (height, size) = (lh.max(rh) + 1, ls + rs + 1);
+                if entry.value.height != height {
+                    violations.push(Violation::Height {
+                        position,
+                        recorded: entry.value.height,
+                        actual: height,
+                    });
+                }
+                if entry.value.size != size {
+                    violations.push(Violation::Size {
+                        position,
+                        recorded: entry.value.size,
+                        actual: size,
+                    });
+                }
+                if (lh - rh).abs() > 1 {
+                    violations.push(Violation::BalanceFactor {
+                        position,
+                        factor: lh - rh,
+                    });
+                }
+                (height, size)
+            },
+        );
+        validate::into_validation(violations)
+    }
+}
+
+impl<Tree, K: Ord, V, A> AVLTreeMap<Tree, K, V, A>
+where
+    Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>,
+{
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Tree, K, V, A> {
+        IterMut {
+            inner: self.bst.iter_mut(),
+        }
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> AVLTreeMap<Tree, K, V, A>
+where
+    Tree: Default + MoveParentBinTreeMut<Elem = AVLEntry<K, V, A>>,
+{
+    /// 返回指向`key`的可变游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at_mut(&mut self, key: &K) -> Option<CursorMut<'_, Tree, K, V, A>> {
+        self.bst.cursor_at_mut(key).map(|inner| CursorMut { inner })
+    }
+
+    /// 返回指向第一个不小于`key`的词条的可变游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor_mut(&mut self, key: &K) -> CursorMut<'_, Tree, K, V, A> {
+        CursorMut {
+            inner: self.bst.lower_bound_cursor_mut(key),
+        }
+    }
+}
+
+/// AVL树上的只读游标，参见`bst::Cursor`.
+pub struct Cursor<
+    'a,
+    Tree: 'a + BinTreeMut<Elem = AVLEntry<K, V, A>>,
+    K: 'a + Ord,
+    V: 'a,
+    A: 'a = (),
+> {
+    inner: bst::Cursor<'a, Tree, K, AVLNode<V, A>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> Clone
+    for Cursor<'a, Tree, K, V, A>
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> Cursor<'a, Tree, K, V, A> {
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.inner.move_next()
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev()
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.key_value().map(|(key, node)| (key, &node.elem))
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.inner.key()
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.inner.value().map(|node| &node.elem)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_next().map(|(key, node)| (key, &node.elem))
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_prev().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+/// AVL树上的可变游标，参见`bst::CursorMut`.
+///
+/// 插入与删除前记下目标词条自根的路径，自底向上重新平衡时随每次重构修正，之后沿路径回到目标词条.
+pub struct CursorMut<
+    'a,
+    Tree: 'a + MoveParentBinTreeMut<Elem = AVLEntry<K, V, A>>,
+    K: 'a + Ord,
+    V: 'a,
+    A: 'a = (),
+> {
+    inner: bst::CursorMut<'a, Tree, K, AVLNode<V, A>>,
+}
+
+impl<'a, Tree, K: Ord, V, A: Augment<K, V>> CursorMut<'a, Tree, K, V, A>
+where
+    Tree: Default + MoveParentBinTreeMut<Elem = AVLEntry<K, V, A>>,
+{
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.inner.move_next()
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev()
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&K, &V)> {
+        self.inner.key_value().map(|(key, node)| (key, &node.elem))
+    }
+
+    /// 当前词条，其中值是可变的. 与`get_mut`相同，修改值不会重新计算附加信息.
+    pub fn key_value_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.inner
+            .key_value_mut()
+            .map(|(key, node)| (key, &mut node.elem))
+    }
+
+    pub fn key(&self) -> Option<&K> {
+        self.inner.key()
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        self.key_value_mut().map(|(_, value)| value)
+    }
+
+    /// 在当前词条之后插入. 位于幽灵位置时插入为第一个词条. 游标位置不变.
+    /// # Panics
+    /// `key`必须严格位于当前词条与其后继之间.
+    pub fn insert_after(&mut self, key: K, value: V) {
+        self.insert_neighbour(key, value, true)
+    }
+
+    /// 在当前词条之前插入. 位于幽灵位置时插入为最后一个词条. 游标位置不变.
+    /// # Panics
+    /// `key`必须严格位于当前词条与其前驱之间.
+    pub fn insert_before(&mut self, key: K, value: V) {
+        self.insert_neighbour(key, value, false)
+    }
+
+    fn insert_neighbour(&mut self, key: K, value: V, forward: bool) {
+        self.inner.check_neighbour(&key, forward);
+        let mut track = self.inner.path();
+        let entry = <AVLTreeMap<Tree, K, V, A> as Join<K, V>>::entry(key, value);
+        if self.inner.insert_leaf(entry, forward).is_some() {
+            AVLTreeMap::<Tree, K, V, A>::rebalance_after_insert(&mut self.inner.cursor, &mut track);
+        }
+        self.inner.follow(track);
+    }
+
+    /// 删除当前词条并返回，之后游标指向其后继. 位于幽灵位置时返回`None`.
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        if self.inner.ghost {
+            return None;
+        }
+        let mut track = self.inner.succ_path();
+        let Entry { key, value } = self.inner.remove_node(mem::swap);
+        AVLTreeMap::<Tree, K, V, A>::rebalance_after_remove(&mut self.inner.cursor, &mut track);
+        self.inner.follow(track);
+        Some((key, value.elem))
+    }
+}
+
+/// AVL树的中序迭代器.
+pub struct Iter<'a, Tree: 'a + BinTreeMut<Elem = AVLEntry<K, V, A>>, K: 'a + Ord, V: 'a, A: 'a = ()>
+{
+    inner: bst::Iter<'a, Tree, K, AVLNode<V, A>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> Iterator
+    for Iter<'a, Tree, K, V, A>
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> DoubleEndedIterator
+    for Iter<'a, Tree, K, V, A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> ExactSizeIterator
+    for Iter<'a, Tree, K, V, A>
+{
+}
+
+/// AVL树的可变中序迭代器.
+pub struct IterMut<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A = ()> {
+    inner: bst::IterMut<'a, Tree, K, AVLNode<V, A>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: 'a + Ord, V: 'a, A: 'a> Iterator
+    for IterMut<'a, Tree, K, V, A>
+{
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &mut node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: 'a + Ord, V: 'a, A: 'a> DoubleEndedIterator
+    for IterMut<'a, Tree, K, V, A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(key, node)| (key, &mut node.elem))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: 'a + Ord, V: 'a, A: 'a> ExactSizeIterator
+    for IterMut<'a, Tree, K, V, A>
+{
+}
+
+/// AVL树的所有权中序迭代器.
+pub struct IntoIter<Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A = ()> {
+    inner: bst::IntoIter<Tree, K, AVLNode<V, A>>,
+}
+
+impl<Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> Iterator
+    for IntoIter<Tree, K, V, A>
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> DoubleEndedIterator
+    for IntoIter<Tree, K, V, A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, node.elem))
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> ExactSizeIterator
+    for IntoIter<Tree, K, V, A>
+{
+}
+
+/// AVL树的键迭代器.
+pub type Keys<'a, Tree, K, V, A = ()> = super::Keys<Iter<'a, Tree, K, V, A>>;
+
+/// AVL树的值迭代器.
+pub type Values<'a, Tree, K, V, A = ()> = super::Values<Iter<'a, Tree, K, V, A>>;
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> IntoIterator
+    for &'a AVLTreeMap<Tree, K, V, A>
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, Tree, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> IntoIterator
+    for &'a mut AVLTreeMap<Tree, K, V, A>
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, Tree, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = AVLEntry<K, V, A>>, K: Ord, V, A> IntoIterator
+    for AVLTreeMap<Tree, K, V, A>
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<Tree, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.bst.into_iter(),
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::super::test::check_iter;
     use super::*;
     use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
     use crate::ch4::vec_binary_tree::VecBinaryTree;
     use proptest::prelude::*;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn test_map_basic() {
@@ -467,6 +1197,7 @@ mod test {
             // insert
             for (k, v) in data.clone() {
                 assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -491,6 +1222,7 @@ mod test {
             for k in data.keys().cloned() {
                 let elem = data.get(&k).copied().unwrap();
                 assert_eq!(map.insert(k, elem), Some(elem + 1));
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -498,9 +1230,168 @@ mod test {
             // remove
             for (idx, k) in data.keys().enumerate() {
                 assert_eq!(map.remove(k), data.get(k).copied());
+                prop_assert_eq!(map.validate(), Ok(()));
                 assert_eq!(map.len(), data.len() - idx - 1);
             }
             assert!(map.is_empty());
         }
+
+        #[test]
+        fn test_iter(data: HashMap<String, i64>) {
+            check_iter::<AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>, _>(data, AVLTreeMap::validate)?;
+        }
+    }
+
+    type JoinMap = AVLTreeMap<DoublyLinkedBinaryTree<AVLEntry<u8, u32>>, u8, u32>;
+
+    fn join_map(entrys: &BTreeMap<u8, u32>) -> JoinMap {
+        let mut map = JoinMap::default();
+        for (k, v) in entrys {
+            map.insert(*k, *v);
+        }
+        map
+    }
+
+    fn assert_join_map(map: &JoinMap, expected: &BTreeMap<u8, u32>) {
+        assert_eq!(map.validate(), Ok(()));
+        assert_eq!(map.len(), expected.len());
+        assert!(map.iter().eq(expected.iter()));
+    }
+
+    proptest! {
+        #[test]
+        fn test_join(lhs: BTreeMap<u8, u32>, rhs: BTreeMap<u8, u32>, key: u8) {
+            // split & join
+            let (left, found, right) = join_map(&lhs).split(&key);
+            assert_eq!(found, lhs.get(&key).copied());
+            let mut less = lhs.clone();
+            let mut greater = less.split_off(&key);
+            greater.remove(&key);
+            assert_join_map(&left, &less);
+            assert_join_map(&right, &greater);
+            less.append(&mut greater);
+            assert_join_map(&AVLTreeMap::join(left, right), &less);
+
+            // union
+            let map = join_map(&lhs).union(join_map(&rhs), |_, x, y| x.wrapping_sub(y));
+            let mut expected = rhs.clone();
+            for (k, v) in lhs.iter() {
+                let value = rhs.get(k).map_or(*v, |y| v.wrapping_sub(*y));
+                expected.insert(*k, value);
+            }
+            assert_join_map(&map, &expected);
+
+            // intersection
+            let map = join_map(&lhs).intersection(join_map(&rhs), |_, x, y| x.wrapping_sub(y));
+            let expected = lhs
+                .iter()
+                .filter_map(|(k, x)| rhs.get(k).map(|y| (*k, x.wrapping_sub(*y))))
+                .collect();
+            assert_join_map(&map, &expected);
+
+            // difference
+            let map = join_map(&lhs).difference(join_map(&rhs));
+            let expected = lhs
+                .iter()
+                .filter(|(k, _)| !rhs.contains_key(k))
+                .map(|(k, v)| (*k, *v))
+                .collect();
+            assert_join_map(&map, &expected);
+        }
+
+        #[test]
+        fn test_cursor(mut data: BTreeMap<u8, u32>, ops: Vec<(u8, u8, u32)>) {
+            let mut map = join_map(&data);
+            for (probe, op, value) in ops {
+                let current = data.range(probe..).next().map(|(k, _)| *k);
+                let cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(cursor.key_value(), data.range(probe..).next());
+                assert_eq!(cursor.peek_prev(), data.range(..probe).next_back());
+                assert_eq!(map.cursor_at(&probe).is_some(), data.contains_key(&probe));
+                let mut cursor = map.lower_bound_cursor_mut(&probe);
+                match op % 3 {
+                    0 => {
+                        let next = data.range(probe..).nth(1).map(|(k, _)| *k);
+                        let removed = current.map(|k| (k, data.remove(&k).unwrap()));
+                        assert_eq!(cursor.remove_current(), removed);
+                        assert_eq!(cursor.key().copied(), next);
+                    }
+                    1 => {
+                        let prev = data.range(..probe).next_back().map(|(k, _)| *k);
+                        if let Some(key) = probe.checked_sub(1).filter(|key| prev < Some(*key)) {
+                            cursor.insert_before(key, value);
+                            data.insert(key, value);
+                        }
+                        assert_eq!(cursor.key().copied(), current);
+                    }
+                    _ => {
+                        let key = current.map_or(Some(0), |key| key.checked_add(1));
+                        if let Some(key) = key.filter(|key| !data.contains_key(key)) {
+                            cursor.insert_after(key, value);
+                            data.insert(key, value);
+                        }
+                        assert_eq!(cursor.key().copied(), current);
+                    }
+                }
+                assert_join_map(&map, &data);
+            }
+        }
+
+        #[test]
+        fn test_from_iter(data: Vec<(u8, u32)>, more: Vec<(u8, u32)>) {
+            let mut expected: BTreeMap<_, _> = data.iter().copied().collect();
+            assert_join_map(&JoinMap::from_sorted_iter(expected.clone()), &expected);
+            let mut map: JoinMap = data.into_iter().collect();
+            assert_join_map(&map, &expected);
+            map.extend(more.iter().copied());
+            expected.extend(more.iter().copied());
+            assert_join_map(&map, &expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_unsorted_iter() {
+        JoinMap::from_sorted_iter(vec![(1, 0), (0, 0)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_join_overlap() {
+        let lhs = join_map(&(0..10).map(|k| (k, 0)).collect());
+        let rhs = join_map(&(9..20).map(|k| (k, 0)).collect());
+        AVLTreeMap::join(lhs, rhs);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut map = join_map(&(1..=3).map(|k| (k, 0)).collect());
+        assert_eq!(map.validate(), Ok(()));
+        map.bst.tree.cursor_mut().as_mut().unwrap().value.height = 3;
+        assert_eq!(
+            map.validate(),
+            Err(vec![Violation::Height {
+                position: 1,
+                recorded: 3,
+                actual: 1
+            }])
+        );
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = AVLTreeMap::<DoublyLinkedBinaryTree<_>, _, _>::default();
+        for key in 1..=3 {
+            map.insert(key, ());
+        }
+        let stats = map.stats();
+        assert_eq!(stats.comparisons, 3);
+        assert_eq!(stats.allocations, 3);
+        assert_eq!(stats.rotations, 1);
+        for key in 4..100 {
+            map.insert(key, ());
+        }
+        assert!(map.stats().rotations > stats.rotations);
     }
 }
@@ -0,0 +1,1323 @@
+use super::{chunk_sizes, Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr::NonNull;
+
+type LeafPosi<K, V> = NonNull<Leaf<K, V>>;
+
+/// 结点分裂的结果: `(分隔键, 新的右兄弟)`.
+type Split<K, V> = (K, Node<K, V>);
+
+/// 叶子链上的位置: `(叶子, 词条下标)`，`None`表示最后一个词条之后.
+type Position<K, V> = Option<(LeafPosi<K, V>, usize)>;
+
+struct Leaf<K: Ord, V> {
+    entrys: Vec<Entry<K, V>>,
+    prev: Option<LeafPosi<K, V>>,
+    next: Option<LeafPosi<K, V>>,
+}
+
+impl<K: Ord, V> Leaf<K, V> {
+    fn leak(leaf: Self) -> LeafPosi<K, V> {
+        stats::record(|stats| stats.allocations += 1);
+        NonNull::new(Box::leak(Box::new(leaf))).unwrap()
+    }
+}
+
+struct Internal<K: Ord, V> {
+    /// `keys[i]`大于`childrens[i]`中所有的键，且不大于`childrens[i + 1]`中所有的键.
+    keys: Vec<K>,
+    childrens: Vec<Node<K, V>>,
+}
+
+/// 叶子被所有叶子链共享，因此以指针的形式保存，由`BPlusTreeMap`负责释放.
+enum Node<K: Ord, V> {
+    Leaf(LeafPosi<K, V>),
+    Internal(Internal<K, V>),
+}
+
+impl<K: Ord, V> Node<K, V> {
+    /// 结点的规模: 叶子的词条数或内部结点的孩子数.
+    fn size(&self) -> usize {
+        match self {
+            // Safety: 叶子指针来自`Leaf::leak`，且在树中始终合法.
+            Self::Leaf(posi) => unsafe { posi.as_ref().entrys.len() },
+            Self::Internal(internal) => internal.childrens.len(),
+        }
+    }
+}
+
+/// 在内部结点中查找`key`所在孩子的秩.
+fn child_index<K: Ord>(keys: &[K], key: &K) -> usize {
+    match keys.binary_search_by(|probe| {
+        stats::record(|stats| stats.comparisons += 1);
+        probe.cmp(key)
+    }) {
+        Ok(idx) => idx + 1,
+        Err(idx) => idx,
+    }
+}
+
+/// 在叶子的词条中二分查找`key`.
+fn search<K: Ord, V>(entrys: &[Entry<K, V>], key: &K) -> Result<usize, usize> {
+    entrys.binary_search_by(|entry| {
+        stats::record(|stats| stats.comparisons += 1);
+        entry.key.cmp(key)
+    })
+}
+
+/// B+树.
+///
+/// 所有词条都保存在叶子中，内部结点只保存分隔键(因此要求插入时`K: Clone`).
+/// 叶子之间按键的顺序双向链接，区间查询与全序遍历都只需沿叶子链顺序扫描.
+/// 每个结点(除根外)的规模在`⌈M/2⌉`与`M`之间.
+pub struct BPlusTreeMap<K: Ord, V, const M: usize> {
+    root: Option<Node<K, V>>,
+    len: usize,
+    stats: Counter,
+}
+
+impl<K: Ord, V, const M: usize> BPlusTreeMap<K, V, M> {
+    /// # Panics
+    /// 阶数`M`不能小于`3`.
+    pub fn new() -> Self {
+        assert!(M >= 3, "B+树的阶数不能小于`3`.");
+        Self {
+            root: None,
+            len: 0,
+            stats: Counter::default(),
+        }
+    }
+
+    /// 非根结点的最小规模`⌈M/2⌉`.
+    fn min_size() -> usize {
+        M - M / 2
+    }
+
+    /// 最左(`first`)或最右的叶子.
+    fn end_leaf(&self, first: bool) -> Option<LeafPosi<K, V>> {
+        let mut node = self.root.as_ref()?;
+        loop {
+            match node {
+                Node::Leaf(posi) => return Some(*posi),
+                Node::Internal(internal) => {
+                    node = if first {
+                        internal.childrens.first()
+                    } else {
+                        internal.childrens.last()
+                    }
+                    .unwrap();
+                }
+            }
+        }
+    }
+
+    /// `key`所在(或应当所在)的叶子.
+    fn leaf_of(&self, key: &K) -> Option<LeafPosi<K, V>> {
+        let mut node = self.root.as_ref()?;
+        loop {
+            match node {
+                Node::Leaf(posi) => return Some(*posi),
+                Node::Internal(internal) => {
+                    node = &internal.childrens[child_index(&internal.keys, key)];
+                }
+            }
+        }
+    }
+
+    /// 第一个不小于(`inclusive`时)或大于`key`的词条的位置.
+    fn lower_bound(&self, key: &K, inclusive: bool) -> Position<K, V> {
+        let posi = self.leaf_of(key)?;
+        // Safety: 叶子指针在树中始终合法.
+        let leaf = unsafe { posi.as_ref() };
+        let idx = match search(&leaf.entrys, key) {
+            Ok(idx) if inclusive => idx,
+            Ok(idx) => idx + 1,
+            Err(idx) => idx,
+        };
+        if idx < leaf.entrys.len() {
+            Some((posi, idx))
+        } else {
+            leaf.next.map(|next| (next, 0))
+        }
+    }
+
+    fn raw_range<R: RangeBounds<K>>(&self, range: R) -> RawRange<K, V> {
+        let mut front = match range.start_bound() {
+            Bound::Included(key) => self.lower_bound(key, true),
+            Bound::Excluded(key) => self.lower_bound(key, false),
+            Bound::Unbounded => self.end_leaf(true).map(|posi| (posi, 0)),
+        };
+        let mut back = match range.end_bound() {
+            Bound::Included(key) => self.lower_bound(key, false),
+            Bound::Excluded(key) => self.lower_bound(key, true),
+            Bound::Unbounded => None,
+        };
+        // 区间为空(包括起点在终点之后)时，令两端重合.
+        let is_empty = match front {
+            Some((posi, idx)) => unsafe { !range.contains(&posi.as_ref().entrys[idx].key) },
+            None => true,
+        };
+        if is_empty {
+            front = None;
+            back = None;
+        }
+        RawRange {
+            front,
+            back,
+            last: self.end_leaf(false),
+        }
+    }
+
+    /// 返回最小的键及其值.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        // Safety: 叶子指针在树中始终合法，且非空树的叶子都非空.
+        let leaf = unsafe { &*self.end_leaf(true)?.as_ptr() };
+        leaf.entrys.first().map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// 返回最大的键及其值.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let leaf = unsafe { &*self.end_leaf(false)?.as_ptr() };
+        leaf.entrys.last().map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// 按键的升序返回键落在`range`中的所有键值对的迭代器. 起点在终点之后时迭代器为空.
+    ///
+    /// 只需两次从根下降确定两端，之后沿叶子链顺序扫描.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, M> {
+        Range {
+            raw: self.raw_range(range),
+            marker: PhantomData::default(),
+        }
+    }
+
+    /// 按键的升序返回键落在`range`中的所有键值对的迭代器，其中值是可变的.
+    pub fn range_mut<R: RangeBounds<K>>(&mut self, range: R) -> RangeMut<'_, K, V, M> {
+        RangeMut {
+            raw: self.raw_range(range),
+            marker: PhantomData::default(),
+        }
+    }
+
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, K, V, M> {
+        Iter {
+            inner: self.range(..),
+            len: self.len,
+        }
+    }
+
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V, M> {
+        let len = self.len;
+        IterMut {
+            inner: self.range_mut(..),
+            len,
+        }
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, K, V, M> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, K, V, M> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, K, V, M>> {
+        let cursor = self.lower_bound_cursor(key);
+        match cursor.key() {
+            Some(found) if found == key => Some(cursor),
+            _ => None,
+        }
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, K, V, M> {
+        Cursor {
+            current: self.lower_bound(key, true),
+            map: self,
+        }
+    }
+
+    /// 返回指向`key`的可变游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at_mut(&mut self, key: &K) -> Option<CursorMut<'_, K, V, M>> {
+        let cursor = self.lower_bound_cursor_mut(key);
+        match cursor.key() {
+            Some(found) if found == key => Some(cursor),
+            _ => None,
+        }
+    }
+
+    /// 返回指向第一个不小于`key`的词条的可变游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor_mut(&mut self, key: &K) -> CursorMut<'_, K, V, M> {
+        CursorMut {
+            current: self.lower_bound(key, true),
+            map: self,
+        }
+    }
+
+    /// `forward`时返回后继的位置，否则返回前驱的位置.
+    /// 最后一个词条的后继与第一个词条的前驱都是幽灵位置，幽灵位置的后继与前驱分别是第一个与最后一个词条.
+    fn step(&self, current: Position<K, V>, forward: bool) -> Position<K, V> {
+        // Safety: 叶子指针在树中始终合法.
+        unsafe {
+            let (posi, idx) = match current {
+                Some(current) => current,
+                None => {
+                    let posi = self.end_leaf(forward)?;
+                    let len = posi.as_ref().entrys.len();
+                    return match len {
+                        0 => None,
+                        _ if forward => Some((posi, 0)),
+                        _ => Some((posi, len - 1)),
+                    };
+                }
+            };
+            let leaf = posi.as_ref();
+            if forward && idx + 1 < leaf.entrys.len() {
+                Some((posi, idx + 1))
+            } else if forward {
+                leaf.next.map(|next| (next, 0))
+            } else if idx > 0 {
+                Some((posi, idx - 1))
+            } else {
+                leaf.prev.map(|prev| (prev, prev.as_ref().entrys.len() - 1))
+            }
+        }
+    }
+
+    /// 检查结点规模、树高、分隔键、键的顺序、叶子链与长度.
+    pub fn validate(&self) -> Validation {
+        let mut validator = Validator {
+            min: Self::min_size(),
+            max: M,
+            position: 0,
+            leaf_depth: None,
+            last: None,
+            last_leaf: None,
+            violations: Vec::new(),
+        };
+        if let Some(root) = self.root.as_ref() {
+            validator.visit(root, (None, None), 0);
+        }
+        if let Some((posi, position)) = validator.last_leaf {
+            // Safety: 叶子指针在树中始终合法.
+            if unsafe { posi.as_ref() }.next.is_some() {
+                validator
+                    .violations
+                    .push(Violation::SiblingLink { position });
+            }
+        }
+        if validator.position != self.len {
+            validator.violations.push(Violation::Length {
+                recorded: self.len,
+                actual: validator.position,
+            });
+        }
+        validate::into_validation(validator.violations)
+    }
+}
+
+impl<K: Ord + Clone, V, const M: usize> BPlusTreeMap<K, V, M> {
+    /// 由按键严格递增的键值对序列自底向上地批量构造，复杂度为`O(n)`.
+    /// # Panics
+    /// 键必须严格递增.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        let entrys = iter
+            .into_iter()
+            .map(|(key, value)| Entry { key, value })
+            .collect::<Vec<_>>();
+        assert!(
+            entrys.windows(2).all(|pair| pair[0].key < pair[1].key),
+            "`from_sorted_iter`要求键严格递增."
+        );
+        map.len = entrys.len();
+        if map.len == 0 {
+            return map;
+        }
+
+        // 每层记录各结点子树中的最小键，用作上一层的分隔键.
+        let mut level = Vec::new();
+        let mut entrys = entrys.into_iter();
+        let mut prev: Option<LeafPosi<K, V>> = None;
+        for size in chunk_sizes(map.len, M) {
+            let entrys = entrys.by_ref().take(size).collect::<Vec<_>>();
+            let min = entrys[0].key.clone();
+            let posi = Leaf::leak(Leaf {
+                entrys,
+                prev,
+                next: None,
+            });
+            if let Some(prev) = prev {
+                // Safety: `prev`是刚刚创建的叶子.
+                unsafe {
+                    (*prev.as_ptr()).next = Some(posi);
+                }
+            }
+            prev = Some(posi);
+            level.push((min, Node::Leaf(posi)));
+        }
+        while level.len() > 1 {
+            let len = level.len();
+            let mut nodes = level.into_iter();
+            level = Vec::new();
+            for size in chunk_sizes(len, M) {
+                let mut nodes = nodes.by_ref().take(size);
+                let (min, first) = nodes.next().unwrap();
+                let mut internal = Internal {
+                    keys: Vec::new(),
+                    childrens: vec![first],
+                };
+                for (key, node) in nodes {
+                    internal.keys.push(key);
+                    internal.childrens.push(node);
+                }
+                level.push((min, Node::Internal(internal)));
+            }
+        }
+        map.root = level.pop().map(|(_, node)| node);
+        map
+    }
+
+    /// 分裂上溢的叶子，返回分隔键与新的右兄弟.
+    fn split_leaf(posi: LeafPosi<K, V>) -> Split<K, V> {
+        stats::record(|stats| stats.splits += 1);
+        // Safety: 叶子指针在树中始终合法，且此时没有其它引用.
+        unsafe {
+            let leaf = &mut *posi.as_ptr();
+            let entrys = leaf.entrys.split_off(leaf.entrys.len() / 2);
+            let key = entrys[0].key.clone();
+            let right = Leaf::leak(Leaf {
+                entrys,
+                prev: Some(posi),
+                next: leaf.next,
+            });
+            if let Some(next) = leaf.next {
+                (*next.as_ptr()).prev = Some(right);
+            }
+            leaf.next = Some(right);
+            (key, Node::Leaf(right))
+        }
+    }
+
+    /// 分裂上溢的内部结点，返回分隔键与新的右兄弟.
+    fn split_internal(internal: &mut Internal<K, V>) -> Split<K, V> {
+        stats::record(|stats| stats.splits += 1);
+        let mid = internal.childrens.len() / 2;
+        let childrens = internal.childrens.split_off(mid);
+        let mut keys = internal.keys.split_off(mid - 1);
+        let key = keys.remove(0);
+        (key, Node::Internal(Internal { keys, childrens }))
+    }
+
+    /// 向以`node`为根的子树插入词条，返回旧值. 若`node`因上溢而分裂，还返回分隔键与新的右兄弟.
+    fn insert_at(node: &mut Node<K, V>, key: K, value: V) -> (Option<V>, Option<Split<K, V>>) {
+        match node {
+            Node::Leaf(posi) => {
+                // Safety: 叶子指针在树中始终合法.
+                let leaf = unsafe { &mut *posi.as_ptr() };
+                match search(&leaf.entrys, &key) {
+                    Ok(idx) => (Some(mem::replace(&mut leaf.entrys[idx].value, value)), None),
+                    Err(idx) => {
+                        leaf.entrys.insert(idx, Entry { key, value });
+                        if leaf.entrys.len() > M {
+                            (None, Some(Self::split_leaf(*posi)))
+                        } else {
+                            (None, None)
+                        }
+                    }
+                }
+            }
+            Node::Internal(internal) => {
+                let idx = child_index(&internal.keys, &key);
+                let (old, split) = Self::insert_at(&mut internal.childrens[idx], key, value);
+                if let Some((key, right)) = split {
+                    internal.keys.insert(idx, key);
+                    internal.childrens.insert(idx + 1, right);
+                }
+                if internal.childrens.len() > M {
+                    (old, Some(Self::split_internal(internal)))
+                } else {
+                    (old, None)
+                }
+            }
+        }
+    }
+
+    /// 左兄弟借出最后一个词条(孩子)，`key`为两者之间的分隔键.
+    fn borrow_from_left(left: &mut Node<K, V>, node: &mut Node<K, V>, key: &mut K) {
+        match (left, node) {
+            // Safety: 两个叶子互不相同，且都在树中.
+            (Node::Leaf(left), Node::Leaf(node)) => unsafe {
+                let entry = (*left.as_ptr()).entrys.pop().unwrap();
+                *key = entry.key.clone();
+                (*node.as_ptr()).entrys.insert(0, entry);
+            },
+            (Node::Internal(left), Node::Internal(node)) => {
+                let last = left.keys.pop().unwrap();
+                node.keys.insert(0, mem::replace(key, last));
+                node.childrens.insert(0, left.childrens.pop().unwrap());
+            }
+            _ => unreachable!("B+树的所有叶子都在同一层."),
+        }
+    }
+
+    /// 右兄弟借出第一个词条(孩子)，`key`为两者之间的分隔键.
+    fn borrow_from_right(node: &mut Node<K, V>, right: &mut Node<K, V>, key: &mut K) {
+        match (node, right) {
+            // Safety: 两个叶子互不相同，且都在树中.
+            (Node::Leaf(node), Node::Leaf(right)) => unsafe {
+                let right = &mut *right.as_ptr();
+                (*node.as_ptr()).entrys.push(right.entrys.remove(0));
+                *key = right.entrys[0].key.clone();
+            },
+            (Node::Internal(node), Node::Internal(right)) => {
+                let first = right.keys.remove(0);
+                node.keys.push(mem::replace(key, first));
+                node.childrens.push(right.childrens.remove(0));
+            }
+            _ => unreachable!("B+树的所有叶子都在同一层."),
+        }
+    }
+
+    /// 将`right`并入其左兄弟`left`，`key`为两者之间的分隔键.
+    fn merge(left: &mut Node<K, V>, key: K, right: Node<K, V>) {
+        stats::record(|stats| stats.merges += 1);
+        match (left, right) {
+            // Safety: `right`已从树中摘下，此后只会通过叶子链被访问，因此要先修复链接.
+            (Node::Leaf(left), Node::Leaf(right)) => unsafe {
+                let Leaf { entrys, next, .. } = *Box::from_raw(right.as_ptr());
+                let leaf = &mut *left.as_ptr();
+                leaf.entrys.extend(entrys);
+                leaf.next = next;
+                if let Some(next) = next {
+                    (*next.as_ptr()).prev = Some(*left);
+                }
+            },
+            (Node::Internal(left), Node::Internal(right)) => {
+                left.keys.push(key);
+                left.keys.extend(right.keys);
+                left.childrens.extend(right.childrens);
+            }
+            _ => unreachable!("B+树的所有叶子都在同一层."),
+        }
+    }
+
+    /// 修复下溢的第`idx`个孩子: 先尝试向兄弟借，否则与兄弟合并.
+    fn solve_underflow(internal: &mut Internal<K, V>, idx: usize) {
+        let min = Self::min_size();
+        if idx > 0 && internal.childrens[idx - 1].size() > min {
+            let (lhs, rhs) = internal.childrens.split_at_mut(idx);
+            Self::borrow_from_left(&mut lhs[idx - 1], &mut rhs[0], &mut internal.keys[idx - 1]);
+        } else if idx + 1 < internal.childrens.len() && internal.childrens[idx + 1].size() > min {
+            let (lhs, rhs) = internal.childrens.split_at_mut(idx + 1);
+            Self::borrow_from_right(&mut lhs[idx], &mut rhs[0], &mut internal.keys[idx]);
+        } else {
+            let idx = if idx > 0 { idx - 1 } else { idx };
+            let key = internal.keys.remove(idx);
+            let right = internal.childrens.remove(idx + 1);
+            Self::merge(&mut internal.childrens[idx], key, right);
+        }
+    }
+
+    /// 从以`node`为根的子树中删除`key`，返回它的值.
+    fn remove_at(node: &mut Node<K, V>, key: &K) -> Option<V> {
+        match node {
+            Node::Leaf(posi) => {
+                // Safety: 叶子指针在树中始终合法.
+                let leaf = unsafe { &mut *posi.as_ptr() };
+                let idx = search(&leaf.entrys, key).ok()?;
+                Some(leaf.entrys.remove(idx).value)
+            }
+            Node::Internal(internal) => {
+                let idx = child_index(&internal.keys, key);
+                let value = Self::remove_at(&mut internal.childrens[idx], key)?;
+                if internal.childrens[idx].size() < Self::min_size() {
+                    Self::solve_underflow(internal, idx);
+                }
+                Some(value)
+            }
+        }
+    }
+}
+
+/// `validate`的遍历状态.
+struct Validator<'a, K: Ord, V> {
+    /// 非根结点的最小规模.
+    min: usize,
+    /// 结点的最大规模.
+    max: usize,
+    /// 下一个词条的次序.
+    position: usize,
+    /// 第一个叶子的深度.
+    leaf_depth: Option<usize>,
+    /// 上一个词条的键.
+    last: Option<&'a K>,
+    /// 上一个叶子及其第一个词条的次序.
+    last_leaf: Option<(LeafPosi<K, V>, usize)>,
+    violations: Vec<Violation>,
+}
+
+impl<'a, K: Ord, V> Validator<'a, K, V> {
+    /// 按键的顺序遍历以`node`为根的子树，其中的键应当落在`[bounds.0, bounds.1)`中. 根结点的深度为0.
+    fn visit(
+        &mut self,
+        node: &'a Node<K, V>,
+        bounds: (Option<&'a K>, Option<&'a K>),
+        depth: usize,
+    ) {
+        let position = self.position;
+        let size = node.size();
+        let min = match node {
+            _ if depth > 0 => self.min,
+            Node::Leaf(_) => 1,
+            Node::Internal(_) => 2,
+        };
+        if size < min || size > self.max {
+            self.violations.push(Violation::Occupancy {
+                position,
+                entrys: size,
+                min,
+                max: self.max,
+            });
+        }
+        match node {
+            Node::Leaf(posi) => {
+                // Safety: 叶子指针在树中始终合法.
+                let leaf: &'a Leaf<K, V> = unsafe { &*posi.as_ptr() };
+                match self.leaf_depth {
+                    None => self.leaf_depth = Some(depth),
+                    Some(expected) if expected != depth => {
+                        self.violations.push(Violation::LeafDepth {
+                            position,
+                            depth,
+                            expected,
+                        })
+                    }
+                    _ => (),
+                }
+                let last_leaf = self.last_leaf.map(|(posi, _)| posi);
+                // Safety: 同上.
+                let unlinked =
+                    matches!(last_leaf, Some(last) if unsafe { last.as_ref() }.next != Some(*posi));
+                if leaf.prev != last_leaf || unlinked {
+                    self.violations.push(Violation::SiblingLink { position });
+                }
+                self.last_leaf = Some((*posi, position));
+                for entry in leaf.entrys.iter() {
+                    let out_of_bounds = matches!(bounds.0, Some(lower) if *lower > entry.key)
+                        || matches!(bounds.1, Some(upper) if entry.key >= *upper);
+                    if out_of_bounds || matches!(self.last, Some(last) if *last >= entry.key) {
+                        self.violations.push(Violation::Unordered {
+                            position: self.position,
+                        });
+                    }
+                    self.last = Some(&entry.key);
+                    self.position += 1;
+                }
+            }
+            Node::Internal(internal) => {
+                if internal.keys.len() + 1 != internal.childrens.len() {
+                    self.violations.push(Violation::Childrens {
+                        position,
+                        entrys: internal.keys.len(),
+                        childrens: internal.childrens.len(),
+                    });
+                }
+                for (idx, child) in internal.childrens.iter().enumerate() {
+                    let lower = if idx > 0 {
+                        internal.keys.get(idx - 1)
+                    } else {
+                        bounds.0
+                    };
+                    let upper = internal.keys.get(idx).or(bounds.1);
+                    self.visit(child, (lower, upper), depth + 1);
+                }
+            }
+        }
+    }
+}
+
+impl<K: Ord, V, const M: usize> Drop for BPlusTreeMap<K, V, M> {
+    /// 沿叶子链释放所有叶子，内部结点则随`root`自动释放.
+    fn drop(&mut self) {
+        let mut link = self.end_leaf(true);
+        while let Some(posi) = link {
+            // Safety: 每个叶子恰好在链上出现一次.
+            let leaf = unsafe { Box::from_raw(posi.as_ptr()) };
+            link = leaf.next;
+        }
+    }
+}
+
+impl<K: Ord, V, const M: usize> Default for BPlusTreeMap<K, V, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V, const M: usize> UnorderedMap<K, V> for BPlusTreeMap<K, V, M> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
+        // Safety: 叶子指针在树中始终合法，且在`&self`期间不会被修改.
+        let leaf = unsafe { &*self.leaf_of(key)?.as_ptr() };
+        let idx = search(&leaf.entrys, key).ok()?;
+        Some(&leaf.entrys[idx].value)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let _stats = self.stats.enter();
+        // Safety: 叶子由树独占，`&mut self`保证了引用的唯一性.
+        let leaf = unsafe { &mut *self.leaf_of(key)?.as_ptr() };
+        let idx = search(&leaf.entrys, key).ok()?;
+        Some(&mut leaf.entrys[idx].value)
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        if self.get(&key).is_none() {
+            self.insert(key.clone(), default);
+        }
+        self.get_mut(&key).unwrap()
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let _stats = self.stats.enter();
+        if let Some(root) = self.root.as_mut() {
+            let (old, split) = Self::insert_at(root, key, value);
+            if let Some((key, right)) = split {
+                let left = self.root.take().unwrap();
+                self.root = Some(Node::Internal(Internal {
+                    keys: vec![key],
+                    childrens: vec![left, right],
+                }));
+            }
+            if old.is_none() {
+                self.len += 1;
+            }
+            old
+        } else {
+            let posi = Leaf::leak(Leaf {
+                entrys: vec![Entry { key, value }],
+                prev: None,
+                next: None,
+            });
+            self.root = Some(Node::Leaf(posi));
+            self.len = 1;
+            None
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.stats.enter();
+        let value = Self::remove_at(self.root.as_mut()?, key)?;
+        self.len -= 1;
+        self.root = match self.root.take().unwrap() {
+            Node::Internal(mut internal) if internal.childrens.len() == 1 => {
+                internal.childrens.pop()
+            }
+            Node::Leaf(posi) if self.len == 0 => {
+                // Safety: 唯一的叶子已经为空，且不再被任何结点引用.
+                unsafe {
+                    drop(Box::from_raw(posi.as_ptr()));
+                }
+                None
+            }
+            root => Some(root),
+        };
+        Some(value)
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<K: Ord + Clone, V, const M: usize> Map<K, V> for BPlusTreeMap<K, V, M> {}
+
+/// 叶子链上的双端游标对，`[front, back)`为尚未访问的词条.
+struct RawRange<K: Ord, V> {
+    front: Position<K, V>,
+    back: Position<K, V>,
+    last: Option<LeafPosi<K, V>>,
+}
+
+impl<K: Ord, V> RawRange<K, V> {
+    /// # Safety
+    /// 所有叶子在迭代期间必须合法.
+    unsafe fn next(&mut self) -> Option<*mut Entry<K, V>> {
+        if self.front == self.back {
+            return None;
+        }
+        let (posi, idx) = self.front?;
+        let leaf = posi.as_ref();
+        self.front = if idx + 1 < leaf.entrys.len() {
+            Some((posi, idx + 1))
+        } else {
+            leaf.next.map(|next| (next, 0))
+        };
+        Some(leaf.entrys.as_ptr().add(idx) as *mut _)
+    }
+
+    /// # Safety
+    /// 所有叶子在迭代期间必须合法.
+    unsafe fn next_back(&mut self) -> Option<*mut Entry<K, V>> {
+        if self.front == self.back {
+            return None;
+        }
+        let (posi, idx) = match self.back {
+            None => {
+                let last = self.last?;
+                (last, last.as_ref().entrys.len() - 1)
+            }
+            Some((posi, 0)) => {
+                let prev = posi.as_ref().prev.unwrap();
+                (prev, prev.as_ref().entrys.len() - 1)
+            }
+            Some((posi, idx)) => (posi, idx - 1),
+        };
+        self.back = Some((posi, idx));
+        Some(posi.as_ref().entrys.as_ptr().add(idx) as *mut _)
+    }
+}
+
+/// B+树上的只读游标.
+///
+/// 游标指向某个词条，或者指向最后一个词条之后、第一个词条之前的幽灵位置. 沿叶子链移动，复杂度为`O(1)`.
+pub struct Cursor<'a, K: Ord, V, const M: usize> {
+    map: &'a BPlusTreeMap<K, V, M>,
+    current: Position<K, V>,
+}
+
+impl<'a, K: Ord, V, const M: usize> Clone for Cursor<'a, K, V, M> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map,
+            current: self.current,
+        }
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> Cursor<'a, K, V, M> {
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.current = self.map.step(self.current, true);
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.current = self.map.step(self.current, false);
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        // Safety: 游标存活期间树被不可变借用，叶子始终合法.
+        self.current.map(|(posi, idx)| unsafe {
+            let entry = &posi.as_ref().entrys[idx];
+            (&entry.key, &entry.value)
+        })
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.key_value().map(|(_, value)| value)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_next();
+        cursor.key_value()
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_prev();
+        cursor.key_value()
+    }
+}
+
+/// B+树上的可变游标.
+///
+/// 只能修改值. 插入与删除会分裂或合并叶子并修改祖先中的分隔键，仍需通过`Map`的方法.
+pub struct CursorMut<'a, K: Ord, V, const M: usize> {
+    map: &'a mut BPlusTreeMap<K, V, M>,
+    current: Position<K, V>,
+}
+
+impl<'a, K: Ord, V, const M: usize> CursorMut<'a, K, V, M> {
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.current = self.map.step(self.current, true);
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.current = self.map.step(self.current, false);
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&K, &V)> {
+        // Safety: 游标存活期间树被可变借用，叶子始终合法.
+        self.current.map(|(posi, idx)| unsafe {
+            let entry = &posi.as_ref().entrys[idx];
+            (&entry.key, &entry.value)
+        })
+    }
+
+    /// 当前词条，其中值是可变的. 位于幽灵位置时返回`None`.
+    pub fn key_value_mut(&mut self) -> Option<(&K, &mut V)> {
+        // Safety: 同`key_value`，且返回的引用借用了游标，不会同时存在其它引用.
+        self.current.map(|(mut posi, idx)| unsafe {
+            let entry = &mut posi.as_mut().entrys[idx];
+            (&entry.key, &mut entry.value)
+        })
+    }
+
+    pub fn key(&self) -> Option<&K> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        self.key_value_mut().map(|(_, value)| value)
+    }
+}
+
+/// B+树的区间迭代器.
+pub struct Range<'a, K: Ord, V, const M: usize> {
+    raw: RawRange<K, V>,
+    marker: PhantomData<&'a BPlusTreeMap<K, V, M>>,
+}
+
+impl<'a, K: Ord, V, const M: usize> Iterator for Range<'a, K, V, M> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Safety: 在`'a`期间树不会被修改.
+        unsafe {
+            self.raw
+                .next()
+                .map(|entry| (&(*entry).key, &(*entry).value))
+        }
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> DoubleEndedIterator for Range<'a, K, V, M> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.raw
+                .next_back()
+                .map(|entry| (&(*entry).key, &(*entry).value))
+        }
+    }
+}
+
+/// B+树的可变区间迭代器.
+pub struct RangeMut<'a, K: Ord, V, const M: usize> {
+    raw: RawRange<K, V>,
+    marker: PhantomData<&'a mut BPlusTreeMap<K, V, M>>,
+}
+
+impl<'a, K: Ord, V, const M: usize> Iterator for RangeMut<'a, K, V, M> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Safety: 每个词条至多被访问一次，因此不会产生重叠的可变引用.
+        unsafe {
+            self.raw
+                .next()
+                .map(|entry| (&(*entry).key, &mut (*entry).value))
+        }
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> DoubleEndedIterator for RangeMut<'a, K, V, M> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.raw
+                .next_back()
+                .map(|entry| (&(*entry).key, &mut (*entry).value))
+        }
+    }
+}
+
+/// B+树的中序迭代器.
+pub struct Iter<'a, K: Ord, V, const M: usize> {
+    inner: Range<'a, K, V, M>,
+    len: usize,
+}
+
+impl<'a, K: Ord, V, const M: usize> Iterator for Iter<'a, K, V, M> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> DoubleEndedIterator for Iter<'a, K, V, M> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next_back()?;
+        self.len -= 1;
+        Some(item)
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> ExactSizeIterator for Iter<'a, K, V, M> {}
+
+/// B+树的可变中序迭代器.
+pub struct IterMut<'a, K: Ord, V, const M: usize> {
+    inner: RangeMut<'a, K, V, M>,
+    len: usize,
+}
+
+impl<'a, K: Ord, V, const M: usize> Iterator for IterMut<'a, K, V, M> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> DoubleEndedIterator for IterMut<'a, K, V, M> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next_back()?;
+        self.len -= 1;
+        Some(item)
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> ExactSizeIterator for IterMut<'a, K, V, M> {}
+
+/// B+树的所有权中序迭代器.
+pub struct IntoIter<K: Ord, V, const M: usize> {
+    entrys: std::vec::IntoIter<Entry<K, V>>,
+}
+
+impl<K: Ord, V, const M: usize> IntoIter<K, V, M> {
+    /// 沿叶子链依次取出所有词条.
+    pub fn new(map: BPlusTreeMap<K, V, M>) -> Self {
+        let mut entrys = Vec::with_capacity(map.len);
+        let mut link = map.end_leaf(true);
+        while let Some(posi) = link {
+            // Safety: `map`被独占，叶子都是合法的；取空的叶子之后随`map`一起释放.
+            let leaf = unsafe { &mut *posi.as_ptr() };
+            entrys.append(&mut leaf.entrys);
+            link = leaf.next;
+        }
+        Self {
+            entrys: entrys.into_iter(),
+        }
+    }
+}
+
+impl<K: Ord, V, const M: usize> Iterator for IntoIter<K, V, M> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entrys.next().map(|entry| (entry.key, entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entrys.size_hint()
+    }
+}
+
+impl<K: Ord, V, const M: usize> DoubleEndedIterator for IntoIter<K, V, M> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.entrys
+            .next_back()
+            .map(|entry| (entry.key, entry.value))
+    }
+}
+
+impl<K: Ord, V, const M: usize> ExactSizeIterator for IntoIter<K, V, M> {}
+
+pub type Keys<'a, K, V, const M: usize> = super::Keys<Iter<'a, K, V, M>>;
+
+pub type Values<'a, K, V, const M: usize> = super::Values<Iter<'a, K, V, M>>;
+
+impl<'a, K: Ord, V, const M: usize> IntoIterator for &'a BPlusTreeMap<K, V, M> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> IntoIterator for &'a mut BPlusTreeMap<K, V, M> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Ord, V, const M: usize> IntoIterator for BPlusTreeMap<K, V, M> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_map_basic() {
+        let mut map = BPlusTreeMap::<_, _, 3>::new();
+        for idx in 0..100 {
+            assert!(map.insert(idx, idx).is_none());
+            assert_eq!(map.validate(), Ok(()));
+        }
+        assert_eq!(map.first_key_value(), Some((&0, &0)));
+        assert_eq!(map.last_key_value(), Some((&99, &99)));
+        for idx in (0..100).step_by(3) {
+            assert_eq!(map.remove(&idx), Some(idx));
+            assert_eq!(map.validate(), Ok(()));
+        }
+        for idx in 0..100 {
+            assert_eq!(map.get(&idx).is_some(), idx % 3 != 0);
+        }
+        for idx in 0..100 {
+            map.remove(&idx);
+            assert_eq!(map.validate(), Ok(()));
+        }
+        assert!(map.is_empty());
+        assert_eq!(map.first_key_value(), None);
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        for len in 0..200 {
+            let map = BPlusTreeMap::<_, _, 4>::from_sorted_iter((0..len).map(|idx| (idx, idx)));
+            assert_eq!(map.validate(), Ok(()));
+            assert_eq!(map.len(), len);
+            assert!(map.keys().copied().eq(0..len));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_unsorted_iter() {
+        BPlusTreeMap::<_, _, 4>::from_sorted_iter(vec![(1, 1), (0, 0)]);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = BPlusTreeMap::<_, _, 3>::default();
+        for key in 0..100 {
+            map.insert(key, ());
+        }
+        let stats = map.stats();
+        assert!(stats.splits > 0);
+        assert!(stats.comparisons > 0);
+        assert_eq!(stats.merges, 0);
+        for key in 0..100 {
+            map.remove(&key);
+        }
+        assert!(map.stats().merges > 0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_bplus_tree_map(mut data: HashMap<i64, i64>, random: i64) {
+            let mut map = BPlusTreeMap::<_, _, 3>::default();
+            assert!(map.is_empty());
+            // insert
+            for (k, v) in data.clone() {
+                assert!(map.insert(k, v).is_none());
+            }
+            prop_assert_eq!(map.validate(), Ok(()));
+
+            assert_eq!(map.len(), data.len());
+
+            // get
+            for k in data.keys() {
+                assert_eq!(map.get(k), data.get(k));
+            }
+
+            // random get
+            assert_eq!(map.get(&random), data.get(&random));
+
+            // get_mut
+            for k in data.keys() {
+                if let Some(elem) = map.get_mut(k) { *elem += 1 };
+                assert_eq!(map.get(k).copied(), data.get(k).map(|elem| elem + 1));
+            }
+
+            // replace by insert
+            for k in data.keys().cloned() {
+                let elem = data.get(&k).copied().unwrap();
+                assert_eq!(map.insert(k, elem), Some(elem + 1));
+            }
+
+            assert_eq!(map.len(), data.len());
+
+            // remove
+            for (idx, k) in data.keys().enumerate() {
+                assert_eq!(map.remove(k), data.get(k).copied());
+                assert_eq!(map.len(), data.len() - idx - 1);
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            assert!(map.is_empty());
+        }
+
+        #[test]
+        fn test_get_mut_or_insert(data: Vec<(u8, i64)>) {
+            let mut map = BPlusTreeMap::<_, _, 5>::default();
+            let mut expected = HashMap::new();
+            for (k, v) in data {
+                *map.get_mut_or_insert(k, v) += 1;
+                *expected.entry(k).or_insert(v) += 1;
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            assert_eq!(map.len(), expected.len());
+            for (k, v) in expected.iter() {
+                assert_eq!(map.get(k), Some(v));
+            }
+        }
+
+        #[test]
+        fn test_range(data: HashMap<i16, i64>, start: i16, end: i16) {
+            let map = BPlusTreeMap::<_, _, 4>::from_sorted_iter({
+                let mut sorted = data.clone().into_iter().collect::<Vec<_>>();
+                sorted.sort();
+                sorted
+            });
+            prop_assert_eq!(map.validate(), Ok(()));
+            let expected = data.into_iter().collect::<BTreeMap<_, _>>();
+            let (start, end) = (start.min(end), start.max(end));
+            assert!(map.range(start..end).eq(expected.range(start..end)));
+            assert!(map.range(start..=end).rev().eq(expected.range(start..=end).rev()));
+            assert!(map.range(..end).eq(expected.range(..end)));
+            assert!(map.range((Bound::Excluded(start), Bound::Unbounded)).eq(expected.range((Bound::Excluded(start), Bound::Unbounded))));
+            assert_eq!(map.range(end..start).count(), 0);
+        }
+
+        #[test]
+        fn test_cursor(data: BTreeMap<u8, u8>, removes: Vec<u8>, probes: Vec<u8>) {
+            let mut map = BPlusTreeMap::<_, _, 3>::default();
+            let mut expected = data.clone();
+            for (k, v) in data {
+                map.insert(k, v);
+            }
+            for k in removes {
+                map.remove(&k);
+                expected.remove(&k);
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(map.cursor_at(&probe).is_some(), expected.contains_key(&probe));
+                assert_eq!(cursor.peek_prev(), expected.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                let mut walk = expected.range(probe..).chain(expected.range(..probe));
+                for _ in 0..=expected.len() {
+                    match cursor.key_value() {
+                        Some(entry) => assert_eq!(Some(entry), walk.next()),
+                        None => assert_eq!(cursor.peek_next(), expected.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                assert_eq!(cursor.key_value(), expected.range(probe..).next());
+
+                // 向前修改所有小于`probe`的值.
+                let mut cursor = map.lower_bound_cursor_mut(&probe);
+                cursor.move_prev();
+                while let Some(value) = cursor.value_mut() {
+                    *value = value.wrapping_add(1);
+                    cursor.move_prev();
+                }
+                for (_, value) in expected.range_mut(..probe) {
+                    *value = value.wrapping_add(1);
+                }
+                assert!(map.iter().eq(expected.iter()));
+            }
+        }
+
+        #[test]
+        fn test_iter(data: HashMap<i64, i64>) {
+            let mut map = BPlusTreeMap::<_, _, 3>::default();
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+            }
+            let mut expected = data.into_iter().collect::<Vec<_>>();
+            expected.sort();
+
+            // iter
+            assert_eq!(map.iter().len(), expected.len());
+            assert!(map.iter().map(|(k, v)| (*k, *v)).eq(expected.iter().cloned()));
+            assert!(map.iter().rev().map(|(k, v)| (*k, *v)).eq(expected.iter().rev().cloned()));
+            assert!(map.keys().eq(expected.iter().map(|(k, _)| k)));
+            assert!(map.values().eq(expected.iter().map(|(_, v)| v)));
+
+            // 交替从两端迭代
+            let mut iter = map.iter();
+            let mut expected_iter = expected.iter();
+            for idx in 0..=expected.len() {
+                assert_eq!(iter.len(), expected_iter.len());
+                if idx % 2 == 0 {
+                    assert_eq!(iter.next().map(|(k, v)| (*k, *v)), expected_iter.next().cloned());
+                } else {
+                    assert_eq!(iter.next_back().map(|(k, v)| (*k, *v)), expected_iter.next_back().cloned());
+                }
+            }
+
+            // iter_mut
+            for (_, v) in &mut map {
+                *v = v.wrapping_add(1);
+            }
+            assert!(map.values().copied().eq(expected.iter().map(|(_, v)| v.wrapping_add(1))));
+            for (_, v) in map.iter_mut().rev() {
+                *v = v.wrapping_sub(1);
+            }
+            assert!((&map).into_iter().map(|(k, v)| (*k, *v)).eq(expected.iter().cloned()));
+
+            // into_iter
+            let mut into_iter = map.into_iter();
+            assert_eq!(into_iter.len(), expected.len());
+            assert_eq!(into_iter.next_back(), expected.pop());
+            assert!(into_iter.eq(expected.into_iter()));
+        }
+    }
+}
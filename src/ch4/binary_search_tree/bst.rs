@@ -1,12 +1,50 @@
-use super::{Entry, Map};
-use crate::ch4::{BinTreeCursor, BinTreeCursorExt, BinTreeCursorMut, BinTreeMut};
+use super::{Entry, InOrderPieces, Map, Split, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use crate::ch4::{
+    BinTree, BinTreeCursor, BinTreeCursorMut, BinTreeMut, MoveParentBinTree, MoveParentBinTreeMut,
+    MoveParentCursor, SplitCursorMut,
+};
 use std::cmp::Ordering;
 use std::mem;
 
+/// 结点自根的路径，`true`表示向左.
+pub(crate) fn path_of<'a, C: MoveParentCursor<'a>>(cursor: &C) -> Vec<bool> {
+    let mut view = cursor.move_parent_cursor();
+    let mut path = Vec::new();
+    while view.parent().is_some() {
+        path.push(view.is_left_child());
+        view.move_parent();
+    }
+    path.reverse();
+    path
+}
+
+/// 在路径为`at`的结点处旋转后，修正`track`所记录的路径. `zig`时左孩子上升，否则右孩子上升.
+pub(crate) fn track_rotation(track: &mut Option<Vec<bool>>, at: &[bool], zig: bool) {
+    let path = match track {
+        Some(path) if path.starts_with(at) => path,
+        _ => return,
+    };
+    // 上升的孩子`c`取代原结点`x`，`c`外侧的子树不变，内侧的子树成为`x`的孩子.
+    let mut suffix = path.split_off(at.len()).into_iter();
+    match suffix.next() {
+        None => path.push(!zig),
+        Some(dir) if dir != zig => path.extend(&[!zig, !zig]),
+        Some(_) => match suffix.next() {
+            None => (),
+            Some(dir) if dir == zig => path.push(zig),
+            Some(_) => path.extend(&[!zig, zig]),
+        },
+    }
+    path.extend(suffix);
+}
+
 /// 二叉查找树.
 pub struct TreeMap<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> {
     pub(crate) tree: Tree,
     pub(crate) len: usize,
+    pub(crate) stats: Counter,
 }
 
 impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Default for TreeMap<Tree, K, V> {
@@ -14,6 +52,7 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Default for Tree
         Self {
             tree: Tree::default(),
             len: 0,
+            stats: Counter::default(),
         }
     }
 }
@@ -58,6 +97,7 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> TreeMap<Tree, K,
         C: BinTreeCursor<'a, Elem = Tree::Elem>,
     {
         while let Some(entry) = cursor.as_ref() {
+            stats::record(|stats| stats.comparisons += 1);
             match target.cmp(&entry.key) {
                 Ordering::Equal => return Some(Ordering::Equal),
                 Ordering::Less => {
@@ -80,8 +120,11 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> TreeMap<Tree, K,
     }
 }
 
-impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for TreeMap<Tree, K, V> {
+impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> UnorderedMap<K, V>
+    for TreeMap<Tree, K, V>
+{
     fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
         let mut cursor = self.tree.cursor();
         if let Some(Ordering::Equal) = Self::move_to_target(&mut cursor, key) {
             cursor.into_ref().map(|entry| &entry.value)
@@ -91,6 +134,7 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for Tr
     }
 
     fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let _stats = self.stats.enter();
         let mut cursor = self.tree.cursor_mut();
         if let Some(Ordering::Equal) = Self::move_to_target(&mut cursor, key) {
             cursor.into_mut().map(|entry| &mut entry.value)
@@ -104,6 +148,7 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for Tr
     }
 
     fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let _stats = self.stats.enter();
         let mut parent = self.tree.cursor_mut();
         match Self::move_to_target(&mut parent, &key) {
             Some(Ordering::Equal) => (),
@@ -112,6 +157,7 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for Tr
                     key,
                     value: default,
                 });
+                stats::record(|stats| stats.allocations += 1);
                 self.len += 1;
                 parent.move_left();
             }
@@ -120,6 +166,7 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for Tr
                     key,
                     value: default,
                 });
+                stats::record(|stats| stats.allocations += 1);
                 self.len += 1;
                 parent.move_right();
             }
@@ -128,6 +175,7 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for Tr
                     key,
                     value: default,
                 });
+                stats::record(|stats| stats.allocations += 1);
                 self.len += 1;
             }
         }
@@ -135,6 +183,7 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for Tr
     }
 
     fn insert(&mut self, key: K, mut value: V) -> Option<V> {
+        let _stats = self.stats.enter();
         let mut parent = self.tree.cursor_mut();
         match Self::move_to_target(&mut parent, &key) {
             Some(Ordering::Equal) => {
@@ -143,16 +192,19 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for Tr
             }
             Some(Ordering::Less) => {
                 parent.insert_as_left(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.len += 1;
                 None
             }
             Some(Ordering::Greater) => {
                 parent.insert_as_right(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.len += 1;
                 None
             }
             None => {
                 parent.insert_as_root(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.len += 1;
                 None
             }
@@ -160,6 +212,7 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for Tr
     }
 
     fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.stats.enter();
         let mut cursor = self.tree.cursor_mut();
         if let Some(Ordering::Equal) = Self::move_to_target(&mut cursor, key) {
             self.len -= 1;
@@ -172,22 +225,778 @@ impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for Tr
     }
 
     fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
-        Box::new(
-            self.tree
-                .cursor()
-                .into_mid_order_iter()
-                .map(|entry| (&entry.key, &entry.value)),
-        )
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<K: Ord, V, Tree: Default + BinTreeMut<Elem = Entry<K, V>>> Map<K, V> for TreeMap<Tree, K, V> {}
+
+impl<K: Ord, V, Tree: BinTreeMut<Elem = Entry<K, V>>> TreeMap<Tree, K, V> {
+    /// 由一棵含有`len`个词条的二叉查找树构造.
+    pub(crate) fn from_tree(tree: Tree, len: usize) -> Self {
+        Self {
+            tree,
+            len,
+            stats: Counter::default(),
+        }
+    }
+
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, Tree, K, V> {
+        Iter::new(self)
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, Tree, K, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, Tree, K, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 检查键的顺序与长度.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        self.validate_with(&mut violations, |_, _, _, _, _| ());
+        validate::into_validation(violations)
+    }
+
+    /// 后序遍历，检查键的顺序与长度.
+    ///
+    /// 对每个结点调用`visit(次序, 词条, 左子树的结果, 右子树的结果, 违反之处)`以检查其它不变式，
+    /// 并返回根的结果. 若为空树则返回`None`.
+    pub(crate) fn validate_with<R, F>(
+        &self,
+        violations: &mut Vec<Violation>,
+        mut visit: F,
+    ) -> Option<R>
+    where
+        F: FnMut(usize, &Entry<K, V>, Option<&R>, Option<&R>, &mut Vec<Violation>) -> R,
+    {
+        let mut position = 0;
+        let root = Self::validate_at(self.tree.cursor(), &mut position, violations, &mut visit);
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        root.map(|(result, _, _)| result)
+    }
+
+    /// 返回`(visit的结果, 子树中最小的键, 子树中最大的键)`.
+    fn validate_at<'a, R, F>(
+        cursor: EntryCursor<'a, Tree, K, V>,
+        position: &mut usize,
+        violations: &mut Vec<Violation>,
+        visit: &mut F,
+    ) -> Option<(R, &'a K, &'a K)>
+    where
+        F: FnMut(usize, &Entry<K, V>, Option<&R>, Option<&R>, &mut Vec<Violation>) -> R,
+    {
+        let (left, right) = cursor.split();
+        let entry = cursor.into_ref()?;
+        let left = left.and_then(|left| Self::validate_at(left, position, violations, visit));
+        let current = *position;
+        *position += 1;
+        let right = right.and_then(|right| Self::validate_at(right, position, violations, visit));
+        let unordered = matches!(&left, Some((_, _, max)) if *max >= &entry.key)
+            || matches!(&right, Some((_, min, _)) if *min <= &entry.key);
+        if unordered {
+            violations.push(Violation::Unordered { position: current });
+        }
+        let result = visit(
+            current,
+            entry,
+            left.as_ref().map(|(result, _, _)| result),
+            right.as_ref().map(|(result, _, _)| result),
+            violations,
+        );
+        let min = left.map_or(&entry.key, |(_, min, _)| min);
+        let max = right.map_or(&entry.key, |(_, _, max)| max);
+        Some((result, min, max))
+    }
+}
+
+impl<K: Ord, V, Tree: BinTreeMut<Elem = Entry<K, V>>> TreeMap<Tree, K, V> {
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Tree, K, V> {
+        IterMut::new(self)
+    }
+}
+
+type EntryCursor<'a, Tree, K, V> = <Tree as BinTree>::Cursor<'a, Entry<K, V>>;
+
+/// 二叉查找树的中序迭代器.
+pub struct Iter<'a, Tree: 'a + BinTreeMut<Elem = Entry<K, V>>, K: 'a + Ord, V: 'a> {
+    pieces: InOrderPieces<EntryCursor<'a, Tree, K, V>, &'a Entry<K, V>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Iter<'a, Tree, K, V> {
+    pub fn new(map: &'a TreeMap<Tree, K, V>) -> Self {
+        let cursor = map.tree.cursor();
+        let root = if cursor.is_empty_subtree() {
+            None
+        } else {
+            Some(cursor)
+        };
+        Self {
+            pieces: InOrderPieces::new(root, map.len),
+        }
+    }
+
+    fn split(
+        cursor: EntryCursor<'a, Tree, K, V>,
+    ) -> Split<EntryCursor<'a, Tree, K, V>, &'a Entry<K, V>> {
+        let (left, right) = cursor.split();
+        (left, cursor.into_ref().unwrap(), right)
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Iterator for Iter<'a, Tree, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next(Self::split)
+            .map(|entry| (&entry.key, &entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pieces.len(), Some(self.pieces.len()))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> DoubleEndedIterator
+    for Iter<'a, Tree, K, V>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next_back(Self::split)
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> ExactSizeIterator
+    for Iter<'a, Tree, K, V>
+{
+}
+
+/// 二叉查找树的可变中序迭代器.
+pub struct IterMut<'a, Tree: 'a + BinTreeMut<Elem = Entry<K, V>>, K: 'a + Ord, V: 'a> {
+    pieces: InOrderPieces<Tree::CursorMut<'a>, &'a mut Entry<K, V>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IterMut<'a, Tree, K, V> {
+    pub fn new(map: &'a mut TreeMap<Tree, K, V>) -> Self {
+        let len = map.len;
+        let cursor = map.tree.cursor_mut();
+        let root = if cursor.is_empty_subtree() {
+            None
+        } else {
+            Some(cursor)
+        };
+        Self {
+            pieces: InOrderPieces::new(root, len),
+        }
+    }
+
+    fn split(cursor: Tree::CursorMut<'a>) -> Split<Tree::CursorMut<'a>, &'a mut Entry<K, V>> {
+        // Safety: 迭代器只拆分游标并交出词条，迭代期间树被可变借用，结构不会改变.
+        unsafe { cursor.split_mut() }.unwrap()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Iterator for IterMut<'a, Tree, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next(Self::split)
+            .map(|entry| (&entry.key, &mut entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pieces.len(), Some(self.pieces.len()))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> DoubleEndedIterator
+    for IterMut<'a, Tree, K, V>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next_back(Self::split)
+            .map(|entry| (&entry.key, &mut entry.value))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> ExactSizeIterator
+    for IterMut<'a, Tree, K, V>
+{
+}
+
+/// 二叉查找树的所有权中序迭代器.
+pub struct IntoIter<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> {
+    pieces: InOrderPieces<Tree, Entry<K, V>>,
+}
+
+impl<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIter<Tree, K, V> {
+    pub fn new(map: TreeMap<Tree, K, V>) -> Self {
+        let root = if map.tree.is_empty() {
+            None
+        } else {
+            Some(map.tree)
+        };
+        Self {
+            pieces: InOrderPieces::new(root, map.len),
+        }
+    }
+
+    fn split(mut tree: Tree) -> Split<Tree, Entry<K, V>> {
+        let mut cursor = tree.cursor_mut();
+        let left = cursor.take_left().filter(|tree| !tree.is_empty());
+        let right = cursor.take_right().filter(|tree| !tree.is_empty());
+        (left, cursor.into_inner().unwrap(), right)
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Iterator for IntoIter<Tree, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next(Self::split)
+            .map(|entry| (entry.key, entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pieces.len(), Some(self.pieces.len()))
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> DoubleEndedIterator for IntoIter<Tree, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next_back(Self::split)
+            .map(|entry| (entry.key, entry.value))
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> ExactSizeIterator for IntoIter<Tree, K, V> {}
+
+/// 二叉查找树的键迭代器.
+pub type Keys<'a, Tree, K, V> = super::Keys<Iter<'a, Tree, K, V>>;
+
+/// 二叉查找树的值迭代器.
+pub type Values<'a, Tree, K, V> = super::Values<Iter<'a, Tree, K, V>>;
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIterator for &'a TreeMap<Tree, K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIterator
+    for &'a mut TreeMap<Tree, K, V>
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIterator for TreeMap<Tree, K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<K: Ord, V, Tree: BinTreeMut<Elem = Entry<K, V>>> TreeMap<Tree, K, V> {
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, Tree, K, V>> {
+        let cursor = self.lower_bound_cursor(key);
+        match cursor.key() {
+            Some(found) if found == key => Some(cursor),
+            _ => None,
+        }
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, Tree, K, V> {
+        let mut cursor = Cursor {
+            root: self.tree.cursor(),
+            path: Vec::new(),
+            current: None,
+        };
+        let mut current = self.tree.cursor();
+        if current.is_empty_subtree() {
+            return cursor;
+        }
+        loop {
+            let ordering = key.cmp(&current.as_ref().unwrap().key);
+            let has_child = match ordering {
+                Ordering::Equal => false,
+                Ordering::Less => current.left().is_some(),
+                Ordering::Greater => current.right().is_some(),
+            };
+            if !has_child {
+                cursor.current = Some(current);
+                if ordering == Ordering::Greater {
+                    cursor.move_next();
+                }
+                return cursor;
+            }
+            let mut child = current.clone();
+            if ordering == Ordering::Less {
+                child.move_left();
+            } else {
+                child.move_right();
+            }
+            cursor.path.push((current, ordering == Ordering::Less));
+            current = child;
+        }
+    }
+}
+
+impl<K: Ord, V, Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>> TreeMap<Tree, K, V> {
+    /// 返回指向`key`的可变游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at_mut(&mut self, key: &K) -> Option<CursorMut<'_, Tree, K, V>> {
+        let cursor = self.lower_bound_cursor_mut(key);
+        match cursor.key() {
+            Some(found) if found == key => Some(cursor),
+            _ => None,
+        }
+    }
+
+    /// 返回指向第一个不小于`key`的词条的可变游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor_mut(&mut self, key: &K) -> CursorMut<'_, Tree, K, V> {
+        let mut cursor = CursorMut {
+            cursor: self.tree.move_parent_cursor_mut(),
+            len: &mut self.len,
+            ghost: false,
+        };
+        match Self::move_to_target(&mut cursor.cursor, key) {
+            Some(Ordering::Equal) | Some(Ordering::Less) => (),
+            Some(Ordering::Greater) => cursor.move_next(),
+            None => cursor.ghost = true,
+        }
+        cursor
+    }
+}
+
+/// 二叉查找树上的只读游标.
+///
+/// 游标指向某个词条，或者指向最后一个词条之后、第一个词条之前的幽灵位置.
+/// 游标记下自根至当前结点的路径，因此移动的均摊复杂度为`O(1)`.
+pub struct Cursor<'a, Tree: 'a + BinTreeMut<Elem = Entry<K, V>>, K: 'a + Ord, V: 'a> {
+    root: EntryCursor<'a, Tree, K, V>,
+    /// 当前结点的所有祖先，以及路径在该祖先处是否向左.
+    path: Vec<(EntryCursor<'a, Tree, K, V>, bool)>,
+    /// 为`None`时指向幽灵位置.
+    current: Option<EntryCursor<'a, Tree, K, V>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Clone for Cursor<'a, Tree, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            path: self.path.clone(),
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Cursor<'a, Tree, K, V> {
+    /// 自`current`下降至子树中的第一个(`forward`)或最后一个结点.
+    fn descend(&mut self, mut current: EntryCursor<'a, Tree, K, V>, forward: bool) {
+        loop {
+            let mut child = current.clone();
+            if forward && current.left().is_some() {
+                child.move_left();
+            } else if !forward && current.right().is_some() {
+                child.move_right();
+            } else {
+                break;
+            }
+            self.path.push((current, forward));
+            current = child;
+        }
+        self.current = Some(current);
+    }
+
+    /// `forward`时移动至后继，否则移动至前驱.
+    fn step(&mut self, forward: bool) {
+        match self.current.take() {
+            None => {
+                if !self.root.is_empty_subtree() {
+                    self.descend(self.root.clone(), forward);
+                }
+            }
+            Some(current) => {
+                let mut child = current.clone();
+                if forward && current.right().is_some() {
+                    child.move_right();
+                } else if !forward && current.left().is_some() {
+                    child.move_left();
+                } else {
+                    // 回溯至第一个从另一侧下降而来的祖先，若不存在则到达幽灵位置.
+                    while let Some((ancestor, is_left)) = self.path.pop() {
+                        if is_left == forward {
+                            self.current = Some(ancestor);
+                            break;
+                        }
+                    }
+                    return;
+                }
+                self.path.push((current, !forward));
+                self.descend(child, forward);
+            }
+        }
+    }
+
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.step(true)
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.step(false)
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        self.current
+            .clone()
+            .and_then(|current| current.into_ref())
+            .map(|entry| (&entry.key, &entry.value))
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.key_value().map(|(_, value)| value)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_next();
+        cursor.key_value()
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_prev();
+        cursor.key_value()
+    }
+}
+
+/// 二叉查找树上的可变游标.
+///
+/// 与`Cursor`相同，游标指向某个词条或幽灵位置. 可以在当前词条的前后插入词条或删除当前词条，
+/// 这些操作都从当前位置出发，无需再从根开始查找.
+pub struct CursorMut<'a, Tree: 'a + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: 'a + Ord, V: 'a> {
+    pub(crate) cursor: Tree::MoveParentCursorMut<'a>,
+    pub(crate) len: &'a mut usize,
+    /// 位于幽灵位置时，`cursor`指向根.
+    pub(crate) ghost: bool,
+}
+
+impl<'a, Tree: MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> CursorMut<'a, Tree, K, V> {
+    fn has_child(&self, right: bool) -> bool {
+        if right {
+            self.cursor.right().is_some()
+        } else {
+            self.cursor.left().is_some()
+        }
+    }
+
+    fn move_child(&mut self, right: bool) {
+        if right {
+            self.cursor.move_right()
+        } else {
+            self.cursor.move_left()
+        }
+    }
+
+    /// 下降至子树中的第一个(`forward`)或最后一个结点，返回下降的步数.
+    fn descend(&mut self, forward: bool) -> usize {
+        let mut steps = 0;
+        while self.has_child(!forward) {
+            self.move_child(!forward);
+            steps += 1;
+        }
+        steps
+    }
+
+    /// 回溯至第一个从`forward`一侧下降而来的祖先. 若不存在则到达幽灵位置.
+    fn ascend(&mut self, forward: bool) {
+        loop {
+            if self.cursor.parent().is_none() {
+                self.ghost = true;
+                return;
+            }
+            let is_left = self.cursor.is_left_child();
+            self.cursor.move_parent();
+            if is_left == forward {
+                return;
+            }
+        }
+    }
+
+    /// `forward`时移动至后继，否则移动至前驱.
+    fn step(&mut self, forward: bool) {
+        if self.ghost {
+            if !self.cursor.is_empty_subtree() {
+                self.ghost = false;
+                self.descend(forward);
+            }
+        } else if self.has_child(forward) {
+            self.move_child(forward);
+            self.descend(forward);
+        } else {
+            self.ascend(forward);
+        }
+    }
+
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.step(true)
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.step(false)
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&K, &V)> {
+        if self.ghost {
+            None
+        } else {
+            self.cursor.as_ref().map(|entry| (&entry.key, &entry.value))
+        }
+    }
+
+    /// 当前词条，其中值是可变的. 位于幽灵位置时返回`None`.
+    pub fn key_value_mut(&mut self) -> Option<(&K, &mut V)> {
+        if self.ghost {
+            None
+        } else {
+            self.cursor
+                .as_mut()
+                .map(|entry| (&entry.key, &mut entry.value))
+        }
+    }
+
+    pub fn key(&self) -> Option<&K> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        self.key_value_mut().map(|(_, value)| value)
+    }
+
+    fn move_to_root(&mut self) {
+        while self.cursor.parent().is_some() {
+            self.cursor.move_parent();
+        }
+    }
+
+    /// 当前结点自根的路径. 位于幽灵位置时返回`None`.
+    pub(crate) fn path(&self) -> Option<Vec<bool>> {
+        if self.ghost {
+            None
+        } else {
+            Some(path_of(&self.cursor))
+        }
+    }
+
+    /// 按`remove_node`摘除当前结点后，其后继自根的路径. 后继不存在时返回`None`.
+    /// # Panics
+    /// 游标不能位于幽灵位置.
+    pub(crate) fn succ_path(&self) -> Option<Vec<bool>> {
+        assert!(!self.ghost);
+        let mut path = path_of(&self.cursor);
+        if self.cursor.right().is_some() {
+            // 有两个孩子时后继的词条移入当前结点；否则右子树接替当前结点，后继是其中的第一个结点.
+            if self.cursor.left().is_none() {
+                let mut view = self.cursor.move_parent_cursor();
+                view.move_right();
+                while view.left().is_some() {
+                    view.move_left();
+                    path.push(true);
+                }
+            }
+            return Some(path);
+        }
+        // 后继是第一个从左侧下降而来的祖先.
+        while let Some(is_left) = path.pop() {
+            if is_left {
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    /// 自根沿`path`下降. `path`为`None`时到达幽灵位置.
+    pub(crate) fn follow(&mut self, path: Option<Vec<bool>>) {
+        self.move_to_root();
+        self.ghost = path.is_none();
+        for is_left in path.into_iter().flatten() {
+            self.move_child(!is_left);
+        }
+    }
+
+    /// 检查`key`能否插入到当前位置之后(`forward`)或之前.
+    /// # Panics
+    /// `key`必须严格位于当前词条与相应的相邻词条之间.
+    pub(crate) fn check_neighbour(&mut self, key: &K, forward: bool) {
+        // 沿`forward`的方向，当前词条、`key`与相邻词条必须严格递增.
+        let ordered = |lhs: &K, rhs: &K| if forward { lhs < rhs } else { rhs < lhs };
+        let before = !matches!(self.key(), Some(current) if !ordered(current, key));
+        self.step(forward);
+        let after = !matches!(self.key(), Some(neighbour) if !ordered(key, neighbour));
+        self.step(!forward);
+        assert!(
+            before && after,
+            "插入的键必须严格位于当前词条与其相邻词条之间."
+        );
+    }
+
+    /// 把词条作为叶子插入到当前词条之后(`forward`)或之前；位于幽灵位置时插入为第一个(`forward`)或最后一个词条.
+    ///
+    /// 若原为空树，则新结点成为根并返回`None`. 否则游标指向新结点的父母，
+    /// 返回新结点是否为左孩子以及游标自原位置下降的步数.
+    pub(crate) fn insert_leaf(
+        &mut self,
+        entry: Entry<K, V>,
+        forward: bool,
+    ) -> Option<(bool, usize)> {
+        *self.len += 1;
+        if self.cursor.is_empty_subtree() {
+            self.cursor.insert_as_root(entry);
+            return None;
+        }
+        let mut steps = 0;
+        let is_left = if self.ghost {
+            steps += self.descend(forward);
+            forward
+        } else if self.has_child(forward) {
+            self.move_child(forward);
+            steps += 1 + self.descend(forward);
+            forward
+        } else {
+            !forward
+        };
+        if is_left {
+            self.cursor.insert_as_left(entry);
+        } else {
+            self.cursor.insert_as_right(entry);
+        }
+        Some((is_left, steps))
+    }
+
+    /// 在当前词条之后插入. 位于幽灵位置时插入为第一个词条. 游标位置不变.
+    /// # Panics
+    /// `key`必须严格位于当前词条与其后继之间.
+    pub fn insert_after(&mut self, key: K, value: V) {
+        self.insert_neighbour(Entry { key, value }, true)
+    }
+
+    /// 在当前词条之前插入. 位于幽灵位置时插入为最后一个词条. 游标位置不变.
+    /// # Panics
+    /// `key`必须严格位于当前词条与其前驱之间.
+    pub fn insert_before(&mut self, key: K, value: V) {
+        self.insert_neighbour(Entry { key, value }, false)
+    }
+
+    fn insert_neighbour(&mut self, entry: Entry<K, V>, forward: bool) {
+        self.check_neighbour(&entry.key, forward);
+        if let Some((_, steps)) = self.insert_leaf(entry, forward) {
+            for _ in 0..steps {
+                self.cursor.move_parent();
+            }
+        }
+    }
+
+    /// 摘除当前结点，返回其词条. 若当前结点有两个孩子，则先用`swap`与后继交换词条，转而摘除后继所在的结点.
+    ///
+    /// 之后游标指向被摘除结点的位置，该位置由其唯一的孩子接替.
+    /// # Panics
+    /// 游标不能位于幽灵位置.
+    pub(crate) fn remove_node<F>(&mut self, mut swap: F) -> Entry<K, V>
+    where
+        F: FnMut(&mut Entry<K, V>, &mut Entry<K, V>),
+    {
+        assert!(!self.ghost);
+        *self.len -= 1;
+        if self.cursor.left().is_some() && self.cursor.right().is_some() {
+            let (current, succ) = self.cursor.move_succ_and_split_mut();
+            swap(current.unwrap(), succ.unwrap());
+        }
+        let child = if self.cursor.left().is_none() {
+            self.cursor.take_right()
+        } else {
+            self.cursor.take_left()
+        }
+        .unwrap();
+        let entry = self.cursor.take().cursor_mut().into_inner().unwrap();
+        self.cursor.append(child);
+        entry
+    }
+
+    /// 删除当前词条并返回，之后游标指向其后继. 位于幽灵位置时返回`None`.
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        if self.ghost {
+            return None;
+        }
+        let has_two = self.cursor.left().is_some() && self.cursor.right().is_some();
+        let has_right = self.cursor.right().is_some();
+        let entry = self.remove_node(mem::swap);
+        if has_two {
+            // 被摘除的是后继所在的结点，回到原结点，它现在保存着后继.
+            while self.cursor.is_left_child() {
+                self.cursor.move_parent();
+            }
+            self.cursor.move_parent();
+        } else if has_right {
+            self.descend(true);
+        } else {
+            self.ascend(true);
+        }
+        Some((entry.key, entry.value))
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::super::test::check_iter;
     use super::*;
+    use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
     use crate::ch4::linked_binary_tree::LinkedBinaryTree;
     use crate::ch4::vec_binary_tree::VecBinaryTree;
     use proptest::prelude::*;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn test_map_basic() {
@@ -224,6 +1033,7 @@ mod test {
             // insert
             for (k, v) in data.clone() {
                 assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -248,6 +1058,7 @@ mod test {
             for k in data.keys().cloned() {
                 let elem = data.get(&k).copied().unwrap();
                 assert_eq!(map.insert(k, elem), Some(elem + 1));
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -255,6 +1066,7 @@ mod test {
             // remove
             for (idx, k) in data.keys().enumerate() {
                 assert_eq!(map.remove(k), data.get(k).copied());
+                prop_assert_eq!(map.validate(), Ok(()));
                 assert_eq!(map.len(), data.len() - idx - 1);
             }
             assert!(map.is_empty());
@@ -267,6 +1079,7 @@ mod test {
             // insert
             for (k, v) in data.clone() {
                 assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -291,6 +1104,7 @@ mod test {
             for k in data.keys().cloned() {
                 let elem = data.get(&k).copied().unwrap();
                 assert_eq!(map.insert(k, elem), Some(elem + 1));
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -298,9 +1112,145 @@ mod test {
             // remove
             for (idx, k) in data.keys().enumerate() {
                 assert_eq!(map.remove(k), data.get(k).copied());
+                prop_assert_eq!(map.validate(), Ok(()));
                 assert_eq!(map.len(), data.len() - idx - 1);
             }
             assert!(map.is_empty());
         }
+
+        #[test]
+        fn test_iter(data: HashMap<String, i64>) {
+            check_iter::<TreeMap<VecBinaryTree<_>, _, _>, _>(data, TreeMap::validate)?;
+        }
+
+        #[test]
+        fn test_cursor(data: BTreeMap<u8, u8>, probes: Vec<u8>) {
+            let mut map = TreeMap::<LinkedBinaryTree<_>, _, _>::default();
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                let mut expected = data.range(probe..).chain(data.range(..probe));
+                assert_eq!(map.cursor_at(&probe).is_some(), data.contains_key(&probe));
+                assert_eq!(cursor.peek_prev(), data.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                for _ in 0..=data.len() {
+                    match cursor.key_value() {
+                        Some(entry) => assert_eq!(Some(entry), expected.next()),
+                        None => assert_eq!(cursor.peek_next(), data.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                assert_eq!(cursor.key_value(), data.range(probe..).next());
+                cursor.move_prev();
+                assert_eq!(cursor.peek_next(), data.range(probe..).next());
+            }
+        }
+
+        #[test]
+        fn test_cursor_mut(data: BTreeMap<u8, u8>, ops: Vec<(u8, u8, u8)>) {
+            let mut map = TreeMap::<DoublyLinkedBinaryTree<_>, _, _>::default();
+            let mut data = data;
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for (probe, op, value) in ops {
+                let mut cursor = map.lower_bound_cursor_mut(&probe);
+                let current = data.range(probe..).next().map(|(k, _)| *k);
+                assert_eq!(cursor.key().copied(), current);
+                match op % 4 {
+                    0 => {
+                        let next = data.range(probe..).nth(1).map(|(k, _)| *k);
+                        assert_eq!(cursor.remove_current(), current.map(|k| (k, data.remove(&k).unwrap())));
+                        assert_eq!(cursor.key().copied(), next);
+                    }
+                    1 => {
+                        // 在当前词条之前插入`probe - 1`.
+                        let prev = data.range(..probe).next_back().map(|(k, _)| *k);
+                        if let Some(key) = probe.checked_sub(1).filter(|key| prev.map_or(true, |prev| prev < *key)) {
+                            cursor.insert_before(key, value);
+                            data.insert(key, value);
+                            assert_eq!(cursor.key().copied(), current);
+                        }
+                    }
+                    2 => {
+                        // 在当前词条之后插入`current + 1`.
+                        let key = current.map_or(Some(0), |key| key.checked_add(1));
+                        let next = data.range(probe..).nth(1).map(|(k, _)| *k);
+                        let first = data.keys().next().copied();
+                        if let Some(key) = key.filter(|key| {
+                            if current.is_some() {
+                                next.map_or(true, |next| *key < next)
+                            } else {
+                                first.map_or(true, |first| *key < first)
+                            }
+                        }) {
+                            cursor.insert_after(key, value);
+                            data.insert(key, value);
+                            assert_eq!(cursor.key().copied(), current);
+                        }
+                    }
+                    _ => {
+                        if let Some(v) = cursor.value_mut() {
+                            *v = value;
+                            data.insert(current.unwrap(), value);
+                        }
+                        cursor.move_prev();
+                        assert_eq!(cursor.key(), data.range(..probe).next_back().map(|(k, _)| k));
+                    }
+                }
+                assert_eq!(map.len(), data.len());
+                assert!(map.iter().eq(data.iter()));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut map = TreeMap::<LinkedBinaryTree<_>, _, _>::default();
+        for k in [2, 1, 3].iter() {
+            map.insert(*k, ());
+        }
+        assert_eq!(map.validate(), Ok(()));
+        map.tree.cursor_mut().left_mut().unwrap().key = 4;
+        map.len = 2;
+        assert_eq!(
+            map.validate(),
+            Err(vec![
+                Violation::Unordered { position: 1 },
+                Violation::Length {
+                    recorded: 2,
+                    actual: 3
+                }
+            ])
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cursor_mut_unordered() {
+        let mut map = TreeMap::<DoublyLinkedBinaryTree<_>, _, _>::default();
+        for key in [1, 3, 5].iter() {
+            map.insert(*key, ());
+        }
+        map.lower_bound_cursor_mut(&2).insert_after(6, ());
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = TreeMap::<LinkedBinaryTree<_>, _, _>::default();
+        for key in 1..=3 {
+            map.insert(key, ());
+        }
+        map.get(&3);
+        let stats = map.stats();
+        assert_eq!(stats.comparisons, 6);
+        assert_eq!(stats.allocations, 3);
+        assert_eq!(stats.rotations, 0);
     }
 }
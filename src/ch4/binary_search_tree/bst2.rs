@@ -1,4 +1,6 @@
-use super::Map;
+use super::{InOrderPieces, Map, Split, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
 use std::cmp::Ordering;
 
 type Link<K, V> = Option<Box<Node<K, V>>>;
@@ -14,6 +16,7 @@ pub(super) struct Node<K, V> {
 
 impl<K, V> Node<K, V> {
     pub fn new(key: K, value: V) -> Self {
+        stats::record(|stats| stats.allocations += 1);
         Self {
             left: None,
             right: None,
@@ -34,6 +37,7 @@ impl<K, V> Node<K, V> {
 impl<K: Ord, V> Node<K, V> {
     fn get<'a>(link: &'a Link<K, V>, key: &K) -> Option<&'a V> {
         if let Some(node) = link {
+            stats::record(|stats| stats.comparisons += 1);
             match key.cmp(&node.key) {
                 Ordering::Less => Self::get(&node.left, key),
                 Ordering::Greater => Self::get(&node.right, key),
@@ -46,6 +50,7 @@ impl<K: Ord, V> Node<K, V> {
 
     fn get_mut<'a>(link: &'a mut Link<K, V>, key: &K) -> Option<&'a mut V> {
         if let Some(node) = link {
+            stats::record(|stats| stats.comparisons += 1);
             match key.cmp(&node.key) {
                 Ordering::Less => Self::get_mut(&mut node.left, key),
                 Ordering::Greater => Self::get_mut(&mut node.right, key),
@@ -57,6 +62,7 @@ impl<K: Ord, V> Node<K, V> {
     }
 
     fn search_mut<'a>(&'a mut self, key: &K) -> (&'a mut Self, Ordering) {
+        stats::record(|stats| stats.comparisons += 1);
         match key.cmp(&self.key) {
             Ordering::Less => {
                 if self.left.is_some() {
@@ -88,6 +94,7 @@ impl<K: Ord, V> Node<K, V> {
 
     fn insert(link: Link<K, V>, key: K, value: V) -> (Box<Self>, Option<V>) {
         if let Some(mut node) = link {
+            stats::record(|stats| stats.comparisons += 1);
             let value = match key.cmp(&node.key) {
                 Ordering::Less => {
                     let (child, value) = Self::insert(node.left, key, value);
@@ -124,6 +131,7 @@ impl<K: Ord, V> Node<K, V> {
     }
 
     fn delete(mut self: Box<Self>, key: &K) -> (Link<K, V>, Link<K, V>) {
+        stats::record(|stats| stats.comparisons += 1);
         match key.cmp(&self.key) {
             Ordering::Less => {
                 if let Some(left) = self.left {
@@ -171,24 +179,31 @@ impl<K: Ord, V> Node<K, V> {
 
 pub struct TreeMap<K: Ord, V> {
     pub(super) root: Link<K, V>,
+    pub(super) stats: Counter,
 }
 
 impl<K: Ord, V> Default for TreeMap<K, V> {
     fn default() -> Self {
-        Self { root: None }
+        Self {
+            root: None,
+            stats: Counter::default(),
+        }
     }
 }
 
-impl<K: Ord, V> Map<K, V> for TreeMap<K, V> {
+impl<K: Ord, V> UnorderedMap<K, V> for TreeMap<K, V> {
     fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
         Node::get(&self.root, key)
     }
 
     fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let _stats = self.stats.enter();
         Node::get_mut(&mut self.root, key)
     }
 
     fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let _stats = self.stats.enter();
         if self.root.is_some() {
             let (node, ordering) = Node::search_mut(self.root.as_mut().unwrap(), &key);
             match ordering {
@@ -212,12 +227,14 @@ impl<K: Ord, V> Map<K, V> for TreeMap<K, V> {
     }
 
     fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let _stats = self.stats.enter();
         let (node, value) = Node::insert(self.root.take(), key, value);
         self.root = Some(node);
         value
     }
 
     fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.stats.enter();
         if let Some(root) = self.root.take() {
             let (root, deleted) = root.delete(key);
             self.root = root;
@@ -232,31 +249,270 @@ impl<K: Ord, V> Map<K, V> for TreeMap<K, V> {
     }
 
     fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
-        Box::new(Iter::new(self))
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
     }
 }
 
-pub struct Iter<'a, K, V> {
+impl<K: Ord, V> Map<K, V> for TreeMap<K, V> {}
+
+impl<K: Ord, V> TreeMap<K, V> {
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self)
+    }
+
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(self)
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, K, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, K, V>> {
+        let cursor = self.lower_bound_cursor(key);
+        match cursor.key() {
+            Some(found) if found == key => Some(cursor),
+            _ => None,
+        }
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, K, V> {
+        let mut cursor = Cursor {
+            root: self.root.as_deref(),
+            path: Vec::new(),
+            current: None,
+        };
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            let is_left = match key.cmp(&node.key) {
+                Ordering::Equal => {
+                    cursor.current = Some(node);
+                    return cursor;
+                }
+                Ordering::Less => true,
+                Ordering::Greater => false,
+            };
+            cursor.path.push((node, is_left));
+            link = if is_left {
+                node.left.as_deref()
+            } else {
+                node.right.as_deref()
+            };
+        }
+        // 查找止于空链接，此时后继是最后一个从左侧下降而来的祖先.
+        while let Some((node, is_left)) = cursor.path.pop() {
+            if is_left {
+                cursor.current = Some(node);
+                break;
+            }
+        }
+        cursor
+    }
+
+    /// 检查键的顺序与每个结点记录的规模.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        self.validate_with(&mut violations, |_, _, _, _, _| ());
+        validate::into_validation(violations)
+    }
+
+    /// 后序遍历，检查键的顺序与每个结点记录的规模.
+    ///
+    /// 对每个结点调用`visit(次序, 结点, 左子树的结果, 右子树的结果, 违反之处)`以检查其它不变式，
+    /// 并返回根的结果. 若为空树则返回`None`.
+    pub(super) fn validate_with<R, F>(
+        &self,
+        violations: &mut Vec<Violation>,
+        mut visit: F,
+    ) -> Option<R>
+    where
+        F: FnMut(usize, &Node<K, V>, Option<&R>, Option<&R>, &mut Vec<Violation>) -> R,
+    {
+        let mut position = 0;
+        Self::validate_at(&self.root, &mut position, violations, &mut visit)
+            .map(|(result, _, _, _)| result)
+    }
+
+    /// 返回`(visit的结果, 子树规模, 子树中最小的键, 子树中最大的键)`.
+    fn validate_at<'a, R, F>(
+        link: &'a Link<K, V>,
+        position: &mut usize,
+        violations: &mut Vec<Violation>,
+        visit: &mut F,
+    ) -> Option<(R, usize, &'a K, &'a K)>
+    where
+        F: FnMut(usize, &Node<K, V>, Option<&R>, Option<&R>, &mut Vec<Violation>) -> R,
+    {
+        let node = link.as_ref()?;
+        let left = Self::validate_at(&node.left, position, violations, visit);
+        let current = *position;
+        *position += 1;
+        let right = Self::validate_at(&node.right, position, violations, visit);
+        let unordered = matches!(&left, Some((_, _, _, max)) if *max >= &node.key)
+            || matches!(&right, Some((_, _, min, _)) if *min <= &node.key);
+        if unordered {
+            violations.push(Violation::Unordered { position: current });
+        }
+        let size = left.as_ref().map_or(0, |(_, size, _, _)| *size)
+            + right.as_ref().map_or(0, |(_, size, _, _)| *size)
+            + 1;
+        if node.size != size {
+            violations.push(Violation::Size {
+                position: current,
+                recorded: node.size,
+                actual: size,
+            });
+        }
+        let result = visit(
+            current,
+            node,
+            left.as_ref().map(|(result, _, _, _)| result),
+            right.as_ref().map(|(result, _, _, _)| result),
+            violations,
+        );
+        let min = left.map_or(&node.key, |(_, _, min, _)| min);
+        let max = right.map_or(&node.key, |(_, _, _, max)| max);
+        Some((result, size, min, max))
+    }
+}
+
+/// 二叉查找树上的只读游标.
+///
+/// 游标指向某个词条，或者指向最后一个词条之后、第一个词条之前的幽灵位置.
+/// 游标记下自根至当前结点的路径，因此移动的均摊复杂度为`O(1)`.
+pub struct Cursor<'a, K, V> {
+    root: Option<&'a Node<K, V>>,
+    /// 当前结点的所有祖先，以及路径在该祖先处是否向左.
+    path: Vec<(&'a Node<K, V>, bool)>,
+    /// 为`None`时指向幽灵位置.
     current: Option<&'a Node<K, V>>,
-    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Clone for Cursor<'a, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root,
+            path: self.path.clone(),
+            current: self.current,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Cursor<'a, K, V> {
+    /// 自`current`下降至子树中的第一个(`forward`)或最后一个结点.
+    fn descend(&mut self, mut current: &'a Node<K, V>, forward: bool) {
+        loop {
+            let child = if forward {
+                current.left.as_deref()
+            } else {
+                current.right.as_deref()
+            };
+            match child {
+                Some(child) => {
+                    self.path.push((current, forward));
+                    current = child;
+                }
+                None => break,
+            }
+        }
+        self.current = Some(current);
+    }
+
+    /// `forward`时移动至后继，否则移动至前驱.
+    fn step(&mut self, forward: bool) {
+        match self.current.take() {
+            None => {
+                if let Some(root) = self.root {
+                    self.descend(root, forward);
+                }
+            }
+            Some(current) => {
+                let child = if forward {
+                    current.right.as_deref()
+                } else {
+                    current.left.as_deref()
+                };
+                if let Some(child) = child {
+                    self.path.push((current, !forward));
+                    self.descend(child, forward);
+                } else {
+                    // 回溯至第一个从另一侧下降而来的祖先，若不存在则到达幽灵位置.
+                    while let Some((ancestor, is_left)) = self.path.pop() {
+                        if is_left == forward {
+                            self.current = Some(ancestor);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.step(true)
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.step(false)
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        self.current.map(|node| (&node.key, &node.value))
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.key_value().map(|(_, value)| value)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_next();
+        cursor.key_value()
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_prev();
+        cursor.key_value()
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    pieces: InOrderPieces<&'a Node<K, V>, &'a Node<K, V>>,
 }
 
 impl<'a, K: Ord, V> Iter<'a, K, V> {
     pub fn new(tree: &'a TreeMap<K, V>) -> Self {
-        let mut iter = Self {
-            current: None,
-            stack: Vec::new(),
-        };
-        iter.push_left_chain(tree.root.as_deref());
-        iter.current = iter.stack.pop();
-        iter
+        Self {
+            pieces: InOrderPieces::new(tree.root.as_deref(), Node::size(&tree.root)),
+        }
     }
 
-    fn push_left_chain(&mut self, mut current: Option<&'a Node<K, V>>) {
-        while let Some(node) = current {
-            self.stack.push(node);
-            current = node.left.as_deref();
-        }
+    fn split(node: &'a Node<K, V>) -> Split<&'a Node<K, V>, &'a Node<K, V>> {
+        (node.left.as_deref(), node, node.right.as_deref())
     }
 }
 
@@ -264,23 +520,155 @@ impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(node) = self.current.take() {
-            let key = &node.key;
-            let value = &node.value;
-            self.push_left_chain(node.right.as_deref());
-            self.current = self.stack.pop();
-            Some((key, value))
-        } else {
-            None
+        self.pieces
+            .next(Self::split)
+            .map(|node| (&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pieces.len(), Some(self.pieces.len()))
+    }
+}
+
+impl<'a, K: Ord, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next_back(Self::split)
+            .map(|node| (&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+pub struct IterMut<'a, K, V> {
+    pieces: InOrderPieces<&'a mut Node<K, V>, (&'a K, &'a mut V)>,
+}
+
+impl<'a, K: Ord, V> IterMut<'a, K, V> {
+    pub fn new(tree: &'a mut TreeMap<K, V>) -> Self {
+        let len = Node::size(&tree.root);
+        Self {
+            pieces: InOrderPieces::new(tree.root.as_deref_mut(), len),
+        }
+    }
+
+    fn split(node: &'a mut Node<K, V>) -> Split<&'a mut Node<K, V>, (&'a K, &'a mut V)> {
+        let Node {
+            left,
+            right,
+            key,
+            value,
+            ..
+        } = node;
+        (left.as_deref_mut(), (key, value), right.as_deref_mut())
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pieces.next(Self::split)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pieces.len(), Some(self.pieces.len()))
+    }
+}
+
+impl<'a, K: Ord, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces.next_back(Self::split)
+    }
+}
+
+impl<'a, K: Ord, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+pub struct IntoIter<K, V> {
+    pieces: InOrderPieces<Node<K, V>, (K, V)>,
+}
+
+impl<K: Ord, V> IntoIter<K, V> {
+    pub fn new(tree: TreeMap<K, V>) -> Self {
+        let len = Node::size(&tree.root);
+        Self {
+            pieces: InOrderPieces::new(tree.root.map(|node| *node), len),
         }
     }
+
+    fn split(node: Node<K, V>) -> Split<Node<K, V>, (K, V)> {
+        let Node {
+            left,
+            right,
+            key,
+            value,
+            ..
+        } = node;
+        (
+            left.map(|node| *node),
+            (key, value),
+            right.map(|node| *node),
+        )
+    }
+}
+
+impl<K: Ord, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pieces.next(Self::split)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pieces.len(), Some(self.pieces.len()))
+    }
+}
+
+impl<K: Ord, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces.next_back(Self::split)
+    }
+}
+
+impl<K: Ord, V> ExactSizeIterator for IntoIter<K, V> {}
+
+pub type Keys<'a, K, V> = super::Keys<Iter<'a, K, V>>;
+
+pub type Values<'a, K, V> = super::Values<Iter<'a, K, V>>;
+
+impl<'a, K: Ord, V> IntoIterator for &'a TreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a mut TreeMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Ord, V> IntoIterator for TreeMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use super::super::test::check_iter;
     use super::*;
     use proptest::prelude::*;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn test_map_basic() {
@@ -317,6 +705,7 @@ mod test {
             // insert
             for (k, v) in data.clone() {
                 assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -341,6 +730,7 @@ mod test {
             for k in data.keys().cloned() {
                 let elem = data.get(&k).copied().unwrap();
                 assert_eq!(map.insert(k, elem), Some(elem + 1));
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -348,9 +738,46 @@ mod test {
             // remove
             for (idx, k) in data.keys().enumerate() {
                 assert_eq!(map.remove(k), data.get(k).copied());
+                prop_assert_eq!(map.validate(), Ok(()));
                 assert_eq!(map.len(), data.len() - idx - 1);
             }
             assert!(map.is_empty());
         }
+
+        #[test]
+        fn test_iter(data: HashMap<String, i64>) {
+            check_iter::<TreeMap<_, _>, _>(data, TreeMap::validate)?;
+        }
+
+        #[test]
+        fn test_cursor(data: BTreeMap<u8, u8>, probes: Vec<u8>) {
+            let mut map = TreeMap::<_, _>::default();
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(map.cursor_at(&probe).is_some(), data.contains_key(&probe));
+                assert_eq!(cursor.peek_prev(), data.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                let mut walk = data.range(probe..).chain(data.range(..probe));
+                for _ in 0..=data.len() {
+                    match cursor.key_value() {
+                        Some(entry) => assert_eq!(Some(entry), walk.next()),
+                        None => assert_eq!(cursor.peek_next(), data.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                assert_eq!(cursor.key_value(), data.range(probe..).next());
+                // 向前走至幽灵位置.
+                let mut walk = data.range(..probe).rev();
+                cursor.move_prev();
+                while let Some(entry) = cursor.key_value() {
+                    assert_eq!(Some(entry), walk.next());
+                    cursor.move_prev();
+                }
+                assert_eq!(walk.next(), None);
+            }
+        }
     }
 }
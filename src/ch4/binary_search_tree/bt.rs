@@ -1,5 +1,9 @@
-use super::{Entry, Map};
+use super::set::MapSet;
+use super::{chunk_sizes, Entry, InOrderPieces, Map, Piece, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
 use std::collections::VecDeque;
+use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::mem;
 use std::ptr::NonNull;
@@ -28,6 +32,7 @@ struct Node<K: Ord, V> {
 
 impl<K: Ord, V> Node<K, V> {
     fn new() -> Box<Self> {
+        stats::record(|stats| stats.allocations += 1);
         Box::new(Self {
             parent: None,
             elems: Elems::default(),
@@ -45,6 +50,7 @@ impl<K: Ord, V> Node<K, V> {
     fn search(&self, key: &K) -> Option<usize> {
         let mut founded = None;
         for (idx, entry) in self.elems.entrys.iter().enumerate() {
+            stats::record(|stats| stats.comparisons += 1);
             if entry.key <= *key {
                 founded = Some(idx);
             } else {
@@ -59,6 +65,7 @@ impl<K: Ord, V> Node<K, V> {
         if len < 3 {
             panic!("不能对词条数小于`3`的B树结点进行分裂.");
         } else {
+            stats::record(|stats| stats.splits += 1);
             let mid = len / 2;
             let is_leaf = boxed.elems.childrens.is_empty();
             let mut left = Self::new();
@@ -86,6 +93,7 @@ impl<K: Ord, V> Node<K, V> {
     }
 
     fn merge(mut left: Box<Self>, mid: Entry<K, V>, mut right: Self) -> Box<Self> {
+        stats::record(|stats| stats.merges += 1);
         left.elems.entrys.push_back(mid);
         left.elems.entrys.append(&mut right.elems.entrys);
         for mut posi in right.elems.childrens {
@@ -101,6 +109,7 @@ impl<K: Ord, V> Node<K, V> {
 pub struct BTreeMap<K: Ord, V, const M: usize> {
     root: NodePosi<K, V>,
     len: usize,
+    stats: Counter,
     marker: PhantomData<Box<Node<K, V>>>,
 }
 
@@ -158,59 +167,217 @@ impl<K: Ord, V> UnsafeCursor<K, V> {
     }
 }
 
+/// 结点展开所得的片段序列.
+type Expanded<T, E> = std::vec::IntoIter<Piece<T, E>>;
+
+/// 把结点的词条与孩子按中序交错排列为`孩子, 词条, 孩子, ..., 词条, 孩子`，叶结点则只有词条.
+fn interleave<T, E>(
+    entrys: impl Iterator<Item = E>,
+    childrens: impl Iterator<Item = T>,
+) -> Expanded<T, E> {
+    let mut childrens = childrens.map(Piece::Tree);
+    let mut pieces: Vec<_> = childrens.next().into_iter().collect();
+    for entry in entrys {
+        pieces.push(Piece::Entry(entry));
+        pieces.extend(childrens.next());
+    }
+    pieces.into_iter()
+}
+
 pub struct Iter<'a, K: Ord, V, const M: usize> {
-    current: Link<K, V>,
-    rank: usize,
-    stack: Vec<(NodePosi<K, V>, usize)>,
+    pieces: InOrderPieces<&'a Node<K, V>, &'a Entry<K, V>>,
     marker: PhantomData<&'a BTreeMap<K, V, M>>,
 }
 
 impl<'a, K: Ord, V, const M: usize> Iter<'a, K, V, M> {
-    fn push_left_most_chain(&mut self) {
-        unsafe {
-            while let Some(current) = self.current {
-                if self.rank < current.as_ref().elems.entrys.len() {
-                    self.stack.push((current, self.rank));
-                }
-                self.current = current.as_ref().elems.childrens.get(self.rank).copied();
-                self.rank = 0;
-            }
-            if let Some((next, rank)) = self.stack.pop() {
-                self.current = Some(next);
-                self.rank = rank;
-            }
+    pub fn new(map: &'a BTreeMap<K, V, M>) -> Self {
+        // Safety: 根结点(若存在)是合法的，且在`'a`期间不会被修改.
+        let root = unsafe {
+            map.unsafe_cursor()
+                .current_link()
+                .map(|posi| &*posi.as_ptr())
+        };
+        Self {
+            pieces: InOrderPieces::new(root, map.len),
+            marker: PhantomData::default(),
         }
     }
 
-    pub fn new(map: &'a BTreeMap<K, V, M>) -> Self {
-        let mut iter = Self {
-            current: unsafe { map.unsafe_cursor().current_link() },
-            rank: 0,
-            stack: Vec::default(),
-            marker: PhantomData::default(),
-        };
-        iter.push_left_most_chain();
-        iter
+    fn expand(node: &'a Node<K, V>) -> Expanded<&'a Node<K, V>, &'a Entry<K, V>> {
+        // Safety: 孩子指针都是合法的，且在`'a`期间不会被修改.
+        interleave(
+            node.elems.entrys.iter(),
+            node.elems
+                .childrens
+                .iter()
+                .map(|posi| unsafe { &*posi.as_ptr() }),
+        )
     }
 }
 
 impl<'a, K: Ord, V, const M: usize> Iterator for Iter<'a, K, V, M> {
-    type Item = &'a Entry<K, V>;
+    type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        unsafe {
-            if let Some(current) = self.current {
-                let target = current;
-                let rank = self.rank;
-                self.rank += 1;
-                self.push_left_most_chain();
-                let entry = (*target.as_ptr()).elems.entrys.get(rank).unwrap();
-                Some(entry)
-            } else {
-                None
-            }
+        self.pieces
+            .next_expand(Self::expand)
+            .map(|entry| (&entry.key, &entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pieces.len(), Some(self.pieces.len()))
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> DoubleEndedIterator for Iter<'a, K, V, M> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next_back_expand(Self::expand)
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> ExactSizeIterator for Iter<'a, K, V, M> {}
+
+pub struct IterMut<'a, K: Ord, V, const M: usize> {
+    pieces: InOrderPieces<NodePosi<K, V>, (&'a K, &'a mut V)>,
+    marker: PhantomData<&'a mut BTreeMap<K, V, M>>,
+}
+
+impl<'a, K: Ord, V, const M: usize> IterMut<'a, K, V, M> {
+    pub fn new(map: &'a mut BTreeMap<K, V, M>) -> Self {
+        let root = unsafe { map.unsafe_cursor().current_link() };
+        Self {
+            pieces: InOrderPieces::new(root, map.len),
+            marker: PhantomData::default(),
         }
     }
+
+    fn expand(node: NodePosi<K, V>) -> Expanded<NodePosi<K, V>, (&'a K, &'a mut V)> {
+        // Safety: 每个结点恰好被展开一次，因此不会产生重叠的可变引用.
+        let elems: &'a mut Elems<K, V> = unsafe { &mut (*node.as_ptr()).elems };
+        interleave(
+            elems
+                .entrys
+                .iter_mut()
+                .map(|entry| (&entry.key, &mut entry.value)),
+            elems.childrens.iter().copied(),
+        )
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> Iterator for IterMut<'a, K, V, M> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pieces.next_expand(Self::expand)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pieces.len(), Some(self.pieces.len()))
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> DoubleEndedIterator for IterMut<'a, K, V, M> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces.next_back_expand(Self::expand)
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> ExactSizeIterator for IterMut<'a, K, V, M> {}
+
+pub struct IntoIter<K: Ord, V, const M: usize> {
+    pieces: InOrderPieces<Node<K, V>, Entry<K, V>>,
+}
+
+impl<K: Ord, V, const M: usize> IntoIter<K, V, M> {
+    pub fn new(mut map: BTreeMap<K, V, M>) -> Self {
+        // 将根结点从哨兵中摘下，之后`map`析构时只会释放哨兵.
+        let root = unsafe {
+            map.root
+                .as_mut()
+                .elems
+                .childrens
+                .pop_front()
+                .map(|posi| *Box::from_raw(posi.as_ptr()))
+        };
+        Self {
+            pieces: InOrderPieces::new(root, map.len),
+        }
+    }
+
+    fn expand(node: Node<K, V>) -> Expanded<Node<K, V>, Entry<K, V>> {
+        // Safety: 孩子指针都来自`Node::leak`，且只会被展开一次.
+        let Elems { entrys, childrens } = node.elems;
+        interleave(
+            entrys.into_iter(),
+            childrens
+                .into_iter()
+                .map(|posi| unsafe { *Box::from_raw(posi.as_ptr()) }),
+        )
+    }
+}
+
+impl<K: Ord, V, const M: usize> Drop for IntoIter<K, V, M> {
+    /// 展开并释放剩余的所有结点.
+    fn drop(&mut self) {
+        while self.next().is_some() {}
+    }
+}
+
+impl<K: Ord, V, const M: usize> Iterator for IntoIter<K, V, M> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next_expand(Self::expand)
+            .map(|entry| (entry.key, entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.pieces.len(), Some(self.pieces.len()))
+    }
+}
+
+impl<K: Ord, V, const M: usize> DoubleEndedIterator for IntoIter<K, V, M> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.pieces
+            .next_back_expand(Self::expand)
+            .map(|entry| (entry.key, entry.value))
+    }
+}
+
+impl<K: Ord, V, const M: usize> ExactSizeIterator for IntoIter<K, V, M> {}
+
+pub type Keys<'a, K, V, const M: usize> = super::Keys<Iter<'a, K, V, M>>;
+
+pub type Values<'a, K, V, const M: usize> = super::Values<Iter<'a, K, V, M>>;
+
+impl<'a, K: Ord, V, const M: usize> IntoIterator for &'a BTreeMap<K, V, M> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> IntoIterator for &'a mut BTreeMap<K, V, M> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Ord, V, const M: usize> IntoIterator for BTreeMap<K, V, M> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
 }
 
 impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
@@ -222,10 +389,157 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
         Self {
             root: Node::leak(node),
             len: 0,
+            stats: Counter::default(),
+            marker: PhantomData::default(),
+        }
+    }
+
+    /// 由按键严格递增的键值对序列自底向上地批量构造，复杂度为`O(n)`.
+    /// # Panics
+    /// 键必须严格递增.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::build(super::sorted_entries(iter, false))
+    }
+
+    /// 自底向上逐层构造.
+    ///
+    /// 某层的`n`个词条连同末尾的一个空位尽量均匀地分为若干组，每组不超过`M`个.
+    /// 每组的最后一个词条作为上一层的词条，其余词条构成本层的一个结点，
+    /// 因此除根以外每个结点的词条数都不少于下限.
+    fn build(entries: Vec<(K, V)>) -> Self {
+        let mut map = Self::new();
+        if entries.is_empty() {
+            return map;
+        }
+        map.len = entries.len();
+        let mut entries: Vec<_> = entries
+            .into_iter()
+            .map(|(key, value)| Entry { key, value })
+            .collect();
+        let mut childrens: Option<std::vec::IntoIter<NodePosi<K, V>>> = None;
+        loop {
+            let mut entrys = entries.into_iter();
+            let mut nodes = Vec::new();
+            let mut separators = Vec::new();
+            for size in chunk_sizes(entrys.len() + 1, M) {
+                let mut node = Node::new();
+                node.elems.entrys.extend(entrys.by_ref().take(size - 1));
+                if let Some(childrens) = &mut childrens {
+                    for mut posi in childrens.by_ref().take(size) {
+                        unsafe {
+                            posi.as_mut().parent = Some(NonNull::new(node.as_mut()).unwrap());
+                        }
+                        node.elems.childrens.push_back(posi);
+                    }
+                }
+                nodes.push(Node::leak(node));
+                // 最后一组没有分隔词条.
+                separators.extend(entrys.next());
+            }
+            if let [mut root] = nodes[..] {
+                unsafe {
+                    root.as_mut().parent = Some(map.root);
+                    map.root.as_mut().elems.childrens.push_back(root);
+                }
+                return map;
+            }
+            entries = separators;
+            childrens = Some(nodes.into_iter());
+        }
+    }
+
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, K, V, M> {
+        Iter::new(self)
+    }
+
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V, M> {
+        IterMut::new(self)
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, K, V, M> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, K, V, M> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, K, V, M>> {
+        let cursor = self.lower_bound_cursor(key);
+        match cursor.key() {
+            Some(found) if found == key => Some(cursor),
+            _ => None,
+        }
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, K, V, M> {
+        Cursor {
+            root: self.root,
+            // Safety: 游标存活期间字典被不可变借用.
+            current: unsafe { lower_bound(self.root, key) },
             marker: PhantomData::default(),
         }
     }
 
+    /// 返回指向`key`的可变游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at_mut(&mut self, key: &K) -> Option<CursorMut<'_, K, V, M>> {
+        let cursor = self.lower_bound_cursor_mut(key);
+        match cursor.key() {
+            Some(found) if found == key => Some(cursor),
+            _ => None,
+        }
+    }
+
+    /// 返回指向第一个不小于`key`的词条的可变游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor_mut(&mut self, key: &K) -> CursorMut<'_, K, V, M> {
+        CursorMut {
+            root: self.root,
+            // Safety: 游标存活期间字典被可变借用.
+            current: unsafe { lower_bound(self.root, key) },
+            len: &mut self.len,
+            marker: PhantomData::default(),
+        }
+    }
+
+    /// 检查键的顺序、长度，以及每个结点的词条数、孩子数、父母指针与叶子的深度.
+    pub fn validate(&self) -> Validation {
+        let mut validator = Validator {
+            min: (M + 1) / 2 - 1,
+            max: M - 1,
+            position: 0,
+            leaf_depth: None,
+            last: None,
+            violations: Vec::new(),
+        };
+        // Safety: `validator`只在本函数中读取字典.
+        unsafe {
+            let sentinel = &self.root.as_ref().elems;
+            if !sentinel.entrys.is_empty() || sentinel.childrens.len() > 1 {
+                validator.violations.push(Violation::Childrens {
+                    position: 0,
+                    entrys: sentinel.entrys.len(),
+                    childrens: sentinel.childrens.len(),
+                });
+            }
+            if let Some(root) = sentinel.childrens.front() {
+                validator.visit(*root, self.root, 0);
+            }
+        }
+        if validator.position != self.len {
+            validator.violations.push(Violation::Length {
+                recorded: self.len,
+                actual: validator.position,
+            });
+        }
+        validate::into_validation(validator.violations)
+    }
+
     fn unsafe_cursor(&self) -> UnsafeCursor<K, V> {
         UnsafeCursor {
             hot: self.root,
@@ -237,6 +551,7 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
         while cursor.current_link().is_some() {
             if let Some(idx) = cursor.search(key) {
                 let entry = cursor.get(idx).unwrap();
+                stats::record(|stats| stats.comparisons += 1);
                 if entry.key == *key {
                     return Some(idx);
                 } else {
@@ -249,19 +564,27 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
         None
     }
 
-    unsafe fn solve_overflow(&mut self, cursor: UnsafeCursor<K, V>) {
+    /// 分裂上溢的结点. `track`是需要追踪的词条位置，分裂后更新为该词条的新位置.
+    unsafe fn solve_overflow(
+        mut sentinel: NodePosi<K, V>,
+        cursor: UnsafeCursor<K, V>,
+        track: &mut Position<K, V>,
+    ) {
         // 刚刚插入的词条在`cursor.hot`所指结点的`cursor.current`处.
         // `cursor.hot`是叶子.
         let mut hot = cursor.hot;
         while hot.as_ref().elems.entrys.len() + 1 > M {
+            // 分裂后，前`mid`个词条移入新的左结点，第`mid`个词条上升，其余的留在原结点.
+            let mid_rank = hot.as_ref().elems.entrys.len() / 2;
+            let origin = hot;
             let node = Box::from_raw(hot.as_ptr());
-            hot = if node.parent != Some(self.root) {
+            hot = if node.parent != Some(sentinel) {
                 node.parent.unwrap()
             } else {
                 let mut node = Node::new();
-                node.parent = Some(self.root);
+                node.parent = Some(sentinel);
                 let parent = Node::leak(node);
-                *self.root.as_mut().elems.childrens.get_mut(0).unwrap() = parent;
+                *sentinel.as_mut().elems.childrens.get_mut(0).unwrap() = parent;
                 parent
             };
             let (mut left, mid, mut right) = Node::split(node);
@@ -272,8 +595,23 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
             };
             left.parent = Some(hot);
             right.parent = Some(hot);
+            let left = Node::leak(left);
+            if let Some((node, idx)) = track {
+                if *node == origin {
+                    if *idx < mid_rank {
+                        *node = left;
+                    } else if *idx == mid_rank {
+                        *node = hot;
+                        *idx = rank;
+                    } else {
+                        *idx -= mid_rank + 1;
+                    }
+                } else if *node == hot && *idx >= rank {
+                    *idx += 1;
+                }
+            }
             hot.as_mut().elems.entrys.insert(rank, mid);
-            hot.as_mut().elems.childrens.insert(rank, Node::leak(left));
+            hot.as_mut().elems.childrens.insert(rank, left);
             if let Some(child) = hot.as_mut().elems.childrens.get_mut(rank + 1) {
                 *child = Node::leak(right);
             } else {
@@ -282,8 +620,13 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
         }
     }
 
-    unsafe fn solve_underflow(&mut self, mut cursor: UnsafeCursor<K, V>) {
-        while (cursor.hot == self.root
+    /// 修复下溢的结点. `track`是需要追踪的词条位置，修复后更新为该词条的新位置.
+    unsafe fn solve_underflow(
+        mut sentinel: NodePosi<K, V>,
+        mut cursor: UnsafeCursor<K, V>,
+        track: &mut Position<K, V>,
+    ) {
+        while (cursor.hot == sentinel
             && cursor
                 .current_link()
                 .unwrap()
@@ -291,16 +634,16 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
                 .elems
                 .entrys
                 .is_empty())
-            || (cursor.hot != self.root
+            || (cursor.hot != sentinel
                 && cursor.current_link().unwrap().as_ref().elems.entrys.len() + 1 < (M + 1) / 2)
         {
             // 判断是否为根
-            if cursor.hot == self.root {
-                let root = self.root.as_mut().elems.childrens.pop_front().unwrap();
+            if cursor.hot == sentinel {
+                let root = sentinel.as_mut().elems.childrens.pop_front().unwrap();
                 let mut root = Box::from_raw(root.as_ptr());
                 if let Some(mut posi) = root.elems.childrens.pop_front() {
-                    self.root.as_mut().elems.childrens.push_back(posi);
-                    posi.as_mut().parent = Some(self.root);
+                    sentinel.as_mut().elems.childrens.push_back(posi);
+                    posi.as_mut().parent = Some(sentinel);
                 }
                 break;
             } else {
@@ -315,6 +658,19 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
                         .copied()
                         .unwrap();
                     if left.as_ref().elems.entrys.len() + 1 >= (M + 1) / 2 + 1 {
+                        // 左兄弟的最后一个词条上升，父母中的分隔词条下降为当前结点的第一个词条.
+                        if let Some((node, idx)) = track {
+                            let current = cursor.current_link().unwrap();
+                            if *node == left && *idx + 1 == left.as_ref().elems.entrys.len() {
+                                *node = cursor.hot;
+                                *idx = cursor.current - 1;
+                            } else if *node == cursor.hot && *idx + 1 == cursor.current {
+                                *node = current;
+                                *idx = 0;
+                            } else if *node == current {
+                                *idx += 1;
+                            }
+                        }
                         let left_entry = left.as_mut().elems.entrys.back_mut().unwrap();
                         let parent_entry = cursor
                             .hot
@@ -359,6 +715,21 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
                 {
                     // 找右兄弟
                     if right.as_ref().elems.entrys.len() + 1 >= (M + 1) / 2 + 1 {
+                        // 右兄弟的第一个词条上升，父母中的分隔词条下降为当前结点的最后一个词条.
+                        if let Some((node, idx)) = track {
+                            let current = cursor.current_link().unwrap();
+                            if *node == right {
+                                if *idx == 0 {
+                                    *node = cursor.hot;
+                                    *idx = cursor.current;
+                                } else {
+                                    *idx -= 1;
+                                }
+                            } else if *node == cursor.hot && *idx == cursor.current {
+                                *node = current;
+                                *idx = current.as_ref().elems.entrys.len();
+                            }
+                        }
                         let right_entry = right.as_mut().elems.entrys.front_mut().unwrap();
                         let parent_entry = cursor
                             .hot
@@ -394,6 +765,19 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
                     None
                 };
                 if let Some(left) = left {
+                    // 分隔词条与当前结点并入左兄弟.
+                    if let Some((node, idx)) = track {
+                        let len = left.as_ref().elems.entrys.len();
+                        if *node == cursor.hot && *idx + 1 == cursor.current {
+                            *node = left;
+                            *idx = len;
+                        } else if *node == cursor.hot && *idx + 1 > cursor.current {
+                            *idx -= 1;
+                        } else if Some(*node) == cursor.current_link() {
+                            *node = left;
+                            *idx += len + 1;
+                        }
+                    }
                     let entry = cursor
                         .hot
                         .as_mut()
@@ -432,6 +816,20 @@ impl<K: Ord, V, const M: usize> BTreeMap<K, V, M> {
                     cursor.hot = parent;
                     cursor.current = rank;
                 } else if let Some(right) = right {
+                    // 分隔词条与右兄弟并入当前结点.
+                    if let Some((node, idx)) = track {
+                        let current = cursor.current_link().unwrap();
+                        let len = current.as_ref().elems.entrys.len();
+                        if *node == cursor.hot && *idx == cursor.current {
+                            *node = current;
+                            *idx = len;
+                        } else if *node == cursor.hot && *idx > cursor.current {
+                            *idx -= 1;
+                        } else if *node == right {
+                            *node = current;
+                            *idx += len + 1;
+                        }
+                    }
                     let entry = cursor
                         .hot
                         .as_mut()
@@ -502,12 +900,35 @@ impl<K: Ord, V, const M: usize> Default for BTreeMap<K, V, M> {
     }
 }
 
-impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
+impl<K: Ord, V, const M: usize> FromIterator<(K, V)> for BTreeMap<K, V, M> {
+    /// 输入的键已经严格递增时复杂度为`O(n)`，否则先排序. 相等的键只保留最后一个值.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::build(super::sorted_entries(iter, true))
+    }
+}
+
+impl<K: Ord, V, const M: usize> Extend<(K, V)> for BTreeMap<K, V, M> {
+    /// 字典为空时直接构造，否则逐个插入.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        if self.is_empty() {
+            let stats = mem::take(&mut self.stats);
+            *self = Self::from_iter(iter);
+            self.stats = stats;
+        } else {
+            for (key, value) in iter {
+                self.insert(key, value);
+            }
+        }
+    }
+}
+
+impl<K: Ord, V, const M: usize> UnorderedMap<K, V> for BTreeMap<K, V, M> {
     fn len(&self) -> usize {
         self.len
     }
 
     fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
         unsafe {
             let mut cursor = self.unsafe_cursor();
             Self::move_to_target(&mut cursor, key)
@@ -516,6 +937,7 @@ impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
     }
 
     fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let _stats = self.stats.enter();
         unsafe {
             let mut cursor = self.unsafe_cursor();
             Self::move_to_target(&mut cursor, key)
@@ -524,6 +946,7 @@ impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
     }
 
     fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let _stats = self.stats.enter();
         unsafe {
             let mut cursor = self.unsafe_cursor();
             if cursor.current_link().is_some() {
@@ -537,16 +960,12 @@ impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
                             value: default,
                         },
                     );
-                    // Safety: 必须非常小心地验证`value_ptr`不会在`solve_overflow`过程中发生`move`.
-                    let value_ptr = &mut (*cursor.hot.as_ptr())
-                        .elems
-                        .entrys
-                        .get_mut(cursor.current)
-                        .unwrap()
-                        .value;
-                    self.solve_overflow(cursor);
+                    // 分裂会在结点之间搬移词条，因此追踪新词条的位置.
+                    let mut track = Some((cursor.hot, cursor.current));
+                    Self::solve_overflow(self.root, cursor, &mut track);
                     self.len += 1;
-                    value_ptr
+                    let (node, idx) = track.unwrap();
+                    &mut (*node.as_ptr()).elems.entrys.get_mut(idx).unwrap().value
                 }
             } else {
                 let mut elems = Elems::default();
@@ -554,6 +973,7 @@ impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
                     key,
                     value: default,
                 });
+                stats::record(|stats| stats.allocations += 1);
                 let node = Box::new(Node {
                     parent: Some(self.root),
                     elems,
@@ -567,6 +987,7 @@ impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
     }
 
     fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let _stats = self.stats.enter();
         unsafe {
             let mut cursor = self.unsafe_cursor();
             if cursor.current_link().is_some() {
@@ -581,13 +1002,14 @@ impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
                         .elems
                         .entrys
                         .insert(cursor.current, Entry { key, value });
-                    self.solve_overflow(cursor);
+                    Self::solve_overflow(self.root, cursor, &mut None);
                     self.len += 1;
                     None
                 }
             } else {
                 let mut elems = Elems::default();
                 elems.entrys.push_front(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 let node = Box::new(Node {
                     parent: Some(self.root),
                     elems,
@@ -604,6 +1026,7 @@ impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
     }
 
     fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.stats.enter();
         unsafe {
             let mut cursor = self.unsafe_cursor();
             if let Some(mut idx) = Self::move_to_target(&mut cursor, key) {
@@ -628,7 +1051,7 @@ impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
                     .entrys
                     .remove(idx)
                     .unwrap();
-                self.solve_underflow(cursor);
+                Self::solve_underflow(self.root, cursor, &mut None);
                 self.len -= 1;
                 Some(entry.value)
             } else {
@@ -638,12 +1061,412 @@ impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {
     }
 
     fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
-        Box::new(Iter::new(self).map(|entry| (&entry.key, &entry.value)))
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<K: Ord, V, const M: usize> Map<K, V> for BTreeMap<K, V, M> {}
+
+/// B树的检查状态.
+struct Validator<'a, K: Ord> {
+    /// 非根结点的最少词条数.
+    min: usize,
+    /// 结点的最多词条数.
+    max: usize,
+    /// 下一个词条的次序.
+    position: usize,
+    /// 第一个叶子的深度.
+    leaf_depth: Option<usize>,
+    /// 上一个词条的键.
+    last: Option<&'a K>,
+    violations: Vec<Violation>,
+}
+
+impl<'a, K: Ord> Validator<'a, K> {
+    /// 中序遍历以`node`为根的子树. 根结点的深度为0.
+    /// # Safety
+    /// `node`与`parent`必须是同一棵合法B树中的结点，且在`'a`内不被修改.
+    unsafe fn visit<V: 'a>(&mut self, node: NodePosi<K, V>, parent: NodePosi<K, V>, depth: usize) {
+        let node_ref: &'a Node<K, V> = &*node.as_ptr();
+        let Elems { entrys, childrens } = &node_ref.elems;
+        let position = self.position;
+        if node_ref.parent != Some(parent) {
+            self.violations.push(Violation::ParentLink { position });
+        }
+        let min = if depth == 0 { 1 } else { self.min };
+        if entrys.len() < min || entrys.len() > self.max {
+            self.violations.push(Violation::Occupancy {
+                position,
+                entrys: entrys.len(),
+                min,
+                max: self.max,
+            });
+        }
+        if childrens.is_empty() {
+            match self.leaf_depth {
+                None => self.leaf_depth = Some(depth),
+                Some(expected) if expected != depth => self.violations.push(Violation::LeafDepth {
+                    position,
+                    depth,
+                    expected,
+                }),
+                _ => (),
+            }
+        } else if childrens.len() != entrys.len() + 1 {
+            self.violations.push(Violation::Childrens {
+                position,
+                entrys: entrys.len(),
+                childrens: childrens.len(),
+            });
+        }
+        for idx in 0..entrys.len().max(childrens.len()) {
+            if let Some(child) = childrens.get(idx) {
+                self.visit(*child, node, depth + 1);
+            }
+            if let Some(entry) = entrys.get(idx) {
+                if matches!(self.last, Some(last) if *last >= entry.key) {
+                    self.violations.push(Violation::Unordered {
+                        position: self.position,
+                    });
+                }
+                self.last = Some(&entry.key);
+                self.position += 1;
+            }
+        }
     }
 }
 
+/// 游标位置: 结点与词条在结点中的秩. `None`表示幽灵位置.
+type Position<K, V> = Option<(NodePosi<K, V>, usize)>;
+
+/// 第一个不小于`key`的词条的位置.
+/// # Safety
+/// `root`必须是合法的哨兵结点.
+unsafe fn lower_bound<K: Ord, V>(root: NodePosi<K, V>, key: &K) -> Position<K, V> {
+    let mut candidate = None;
+    let mut link = root.as_ref().elems.childrens.front().copied();
+    while let Some(node) = link {
+        let elems = &node.as_ref().elems;
+        let idx = elems
+            .entrys
+            .iter()
+            .position(|entry| entry.key >= *key)
+            .unwrap_or(elems.entrys.len());
+        if let Some(entry) = elems.entrys.get(idx) {
+            if entry.key == *key {
+                return Some((node, idx));
+            }
+            candidate = Some((node, idx));
+        }
+        link = elems.childrens.get(idx).copied();
+    }
+    candidate
+}
+
+/// 子树中的第一个(`forward`)或最后一个词条的位置.
+/// # Safety
+/// `node`必须是合法的结点.
+unsafe fn descend<K: Ord, V>(mut node: NodePosi<K, V>, forward: bool) -> Position<K, V> {
+    loop {
+        let elems = &node.as_ref().elems;
+        let next = if forward {
+            elems.childrens.front()
+        } else {
+            elems.childrens.back()
+        };
+        match next {
+            Some(child) => node = *child,
+            None if elems.entrys.is_empty() => return None,
+            None if forward => return Some((node, 0)),
+            None => return Some((node, elems.entrys.len() - 1)),
+        }
+    }
+}
+
+/// `forward`时返回后继的位置，否则返回前驱的位置.
+/// 最后一个词条的后继与第一个词条的前驱都是幽灵位置，幽灵位置的后继与前驱分别是第一个与最后一个词条.
+/// # Safety
+/// `root`必须是合法的哨兵结点，`current`必须是其中合法的位置.
+unsafe fn step<K: Ord, V>(
+    root: NodePosi<K, V>,
+    current: Position<K, V>,
+    forward: bool,
+) -> Position<K, V> {
+    let (mut node, idx) = match current {
+        Some(current) => current,
+        None => return descend(*root.as_ref().elems.childrens.front()?, forward),
+    };
+    let elems = &node.as_ref().elems;
+    if !elems.childrens.is_empty() {
+        let child = elems.childrens[if forward { idx + 1 } else { idx }];
+        return descend(child, forward);
+    }
+    if forward && idx + 1 < elems.entrys.len() {
+        return Some((node, idx + 1));
+    }
+    if !forward && idx > 0 {
+        return Some((node, idx - 1));
+    }
+    // 回溯至第一个位于相应一侧的祖先词条.
+    loop {
+        let parent = node.as_ref().parent.unwrap();
+        if parent == root {
+            return None;
+        }
+        let elems = &parent.as_ref().elems;
+        let rank = elems
+            .childrens
+            .iter()
+            .position(|child| *child == node)
+            .unwrap();
+        if forward && rank < elems.entrys.len() {
+            return Some((parent, rank));
+        }
+        if !forward && rank > 0 {
+            return Some((parent, rank - 1));
+        }
+        node = parent;
+    }
+}
+
+/// B树上的只读游标.
+///
+/// 游标指向某个词条，或者指向最后一个词条之后、第一个词条之前的幽灵位置.
+pub struct Cursor<'a, K: Ord, V, const M: usize> {
+    root: NodePosi<K, V>,
+    current: Position<K, V>,
+    marker: PhantomData<&'a BTreeMap<K, V, M>>,
+}
+
+impl<'a, K: Ord, V, const M: usize> Clone for Cursor<'a, K, V, M> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root,
+            current: self.current,
+            marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<'a, K: Ord, V, const M: usize> Cursor<'a, K, V, M> {
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        // Safety: 游标存活期间字典被不可变借用，位置始终合法.
+        self.current = unsafe { step(self.root, self.current, true) };
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        // Safety: 同`move_next`.
+        self.current = unsafe { step(self.root, self.current, false) };
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        // Safety: 同`move_next`.
+        self.current.map(|(node, idx)| unsafe {
+            let entry = &node.as_ref().elems.entrys[idx];
+            (&entry.key, &entry.value)
+        })
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.key_value().map(|(_, value)| value)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_next();
+        cursor.key_value()
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_prev();
+        cursor.key_value()
+    }
+}
+
+/// B树上的可变游标.
+///
+/// 分裂与合并会在结点之间搬移词条，插入与删除时游标随之追踪当前词条的新位置.
+pub struct CursorMut<'a, K: Ord, V, const M: usize> {
+    root: NodePosi<K, V>,
+    current: Position<K, V>,
+    len: &'a mut usize,
+    marker: PhantomData<&'a mut BTreeMap<K, V, M>>,
+}
+
+impl<'a, K: Ord, V, const M: usize> CursorMut<'a, K, V, M> {
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        // Safety: 游标存活期间字典被可变借用，位置始终合法.
+        self.current = unsafe { step(self.root, self.current, true) };
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        // Safety: 同`move_next`.
+        self.current = unsafe { step(self.root, self.current, false) };
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&K, &V)> {
+        // Safety: 同`move_next`.
+        self.current.map(|(node, idx)| unsafe {
+            let entry = &node.as_ref().elems.entrys[idx];
+            (&entry.key, &entry.value)
+        })
+    }
+
+    /// 当前词条，其中值是可变的. 位于幽灵位置时返回`None`.
+    pub fn key_value_mut(&mut self) -> Option<(&K, &mut V)> {
+        // Safety: 同`move_next`，且返回的引用借用了游标，不会同时存在其它引用.
+        self.current.map(|(mut node, idx)| unsafe {
+            let entry = &mut node.as_mut().elems.entrys[idx];
+            (&entry.key, &mut entry.value)
+        })
+    }
+
+    pub fn key(&self) -> Option<&K> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        self.key_value_mut().map(|(_, value)| value)
+    }
+
+    /// 在当前词条之后插入. 位于幽灵位置时插入为第一个词条. 游标位置不变.
+    /// # Panics
+    /// `key`必须严格位于当前词条与其后继之间.
+    pub fn insert_after(&mut self, key: K, value: V) {
+        self.insert_neighbour(Entry { key, value }, true)
+    }
+
+    /// 在当前词条之前插入. 位于幽灵位置时插入为最后一个词条. 游标位置不变.
+    /// # Panics
+    /// `key`必须严格位于当前词条与其前驱之间.
+    pub fn insert_before(&mut self, key: K, value: V) {
+        self.insert_neighbour(Entry { key, value }, false)
+    }
+
+    fn insert_neighbour(&mut self, entry: Entry<K, V>, forward: bool) {
+        // 沿`forward`的方向，当前词条、`key`与相邻词条必须严格递增.
+        let ordered = |lhs: &K, rhs: &K| if forward { lhs < rhs } else { rhs < lhs };
+        // Safety: 游标存活期间字典被可变借用，位置始终合法.
+        let neighbour = unsafe {
+            step(self.root, self.current, forward)
+                .and_then(|(node, idx)| (*node.as_ptr()).elems.entrys.get(idx))
+                .map(|entry| &entry.key)
+        };
+        let before = !matches!(self.key(), Some(current) if !ordered(current, &entry.key));
+        let after = !matches!(neighbour, Some(neighbour) if !ordered(&entry.key, neighbour));
+        assert!(
+            before && after,
+            "插入的键必须严格位于当前词条与其相邻词条之间."
+        );
+        *self.len += 1;
+        // Safety: 同上.
+        unsafe {
+            let root = match self.root.as_ref().elems.childrens.front() {
+                Some(root) => *root,
+                None => {
+                    let mut node = Node::new();
+                    node.elems.entrys.push_back(entry);
+                    node.parent = Some(self.root);
+                    self.root
+                        .as_mut()
+                        .elems
+                        .childrens
+                        .push_back(Node::leak(node));
+                    return;
+                }
+            };
+            // 新词条总是插入叶子: 内部结点的后继(前驱)是相应孩子子树中的第一个(最后一个)词条.
+            let (mut leaf, idx) = match self.current {
+                Some((node, idx)) if !node.as_ref().elems.childrens.is_empty() => {
+                    let child = node.as_ref().elems.childrens[if forward { idx + 1 } else { idx }];
+                    let (leaf, rank) = descend(child, forward).unwrap();
+                    (leaf, if forward { rank } else { rank + 1 })
+                }
+                Some((node, idx)) => (node, if forward { idx + 1 } else { idx }),
+                None => {
+                    let (leaf, rank) = descend(root, forward).unwrap();
+                    (leaf, if forward { rank } else { rank + 1 })
+                }
+            };
+            leaf.as_mut().elems.entrys.insert(idx, entry);
+            if let Some((node, rank)) = &mut self.current {
+                if *node == leaf && *rank >= idx {
+                    *rank += 1;
+                }
+            }
+            let cursor = UnsafeCursor {
+                hot: leaf,
+                current: idx,
+            };
+            BTreeMap::<K, V, M>::solve_overflow(self.root, cursor, &mut self.current);
+        }
+    }
+
+    /// 删除当前词条并返回，之后游标指向其后继. 位于幽灵位置时返回`None`.
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        let (node, idx) = self.current?;
+        *self.len -= 1;
+        // Safety: 同`insert_neighbour`.
+        unsafe {
+            // 内部结点中的词条先与后继交换，转而从叶子中删除后继; 删除后其后继仍在原处.
+            let (mut leaf, idx, mut track) = if node.as_ref().elems.childrens.is_empty() {
+                let track = match step(self.root, self.current, true) {
+                    Some((succ, rank)) if succ == node => Some((succ, rank - 1)),
+                    succ => succ,
+                };
+                (node, idx, track)
+            } else {
+                let child = node.as_ref().elems.childrens[idx + 1];
+                let (leaf, rank) = descend(child, true).unwrap();
+                mem::swap(
+                    (*node.as_ptr()).elems.entrys.get_mut(idx).unwrap(),
+                    (*leaf.as_ptr()).elems.entrys.get_mut(rank).unwrap(),
+                );
+                (leaf, rank, Some((node, idx)))
+            };
+            let entry = leaf.as_mut().elems.entrys.remove(idx).unwrap();
+            let parent = leaf.as_ref().parent.unwrap();
+            let current = parent
+                .as_ref()
+                .elems
+                .childrens
+                .iter()
+                .position(|child| *child == leaf)
+                .unwrap();
+            let cursor = UnsafeCursor {
+                hot: parent,
+                current,
+            };
+            BTreeMap::<K, V, M>::solve_underflow(self.root, cursor, &mut track);
+            self.current = track;
+            Some((entry.key, entry.value))
+        }
+    }
+}
+
+/// 基于B树的有序集合.
+pub type BTreeSet<K, const M: usize> = MapSet<BTreeMap<K, (), M>>;
+
 #[cfg(test)]
 mod test {
+    use super::super::test::check_iter;
     use super::*;
     use proptest::prelude::*;
     use std::collections::HashMap;
@@ -743,6 +1566,7 @@ mod test {
             // insert
             for (k, v) in data.clone() {
                 assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -767,6 +1591,7 @@ mod test {
             for k in data.keys().cloned() {
                 let elem = data.get(&k).copied().unwrap();
                 assert_eq!(map.insert(k, elem), Some(elem + 1));
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -774,9 +1599,147 @@ mod test {
             // remove
             for (idx, k) in data.keys().enumerate() {
                 assert_eq!(map.remove(k), data.get(k).copied());
+                prop_assert_eq!(map.validate(), Ok(()));
                 assert_eq!(map.len(), data.len() - idx - 1);
             }
             assert!(map.is_empty());
         }
+
+        #[test]
+        fn test_iter(data: HashMap<i64, i64>) {
+            check_iter::<BTreeMap<_, _, 3>, _>(data, BTreeMap::validate)?;
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_cursor(data: Vec<(u8, u8)>, removes: Vec<u8>, probes: Vec<u8>) {
+            let mut map = BTreeMap::<_, _, 3>::default();
+            let mut expected = std::collections::BTreeMap::new();
+            for (k, v) in data {
+                map.insert(k, v);
+                prop_assert_eq!(map.validate(), Ok(()));
+                expected.insert(k, v);
+            }
+            for k in removes {
+                map.remove(&k);
+                prop_assert_eq!(map.validate(), Ok(()));
+                expected.remove(&k);
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(map.cursor_at(&probe).is_some(), expected.contains_key(&probe));
+                assert_eq!(cursor.peek_prev(), expected.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                let mut walk = expected.range(probe..).chain(expected.range(..probe));
+                for _ in 0..=expected.len() {
+                    match cursor.key_value() {
+                        Some(entry) => assert_eq!(Some(entry), walk.next()),
+                        None => assert_eq!(cursor.peek_next(), expected.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                assert_eq!(cursor.key_value(), expected.range(probe..).next());
+
+                // 向前修改所有不小于`probe`的值.
+                let mut cursor = map.lower_bound_cursor_mut(&probe);
+                while let Some(value) = cursor.value_mut() {
+                    *value = value.wrapping_add(1);
+                    cursor.move_next();
+                }
+                cursor.move_prev();
+                assert_eq!(cursor.key(), expected.keys().next_back());
+                for (_, value) in expected.range_mut(probe..) {
+                    *value = value.wrapping_add(1);
+                }
+                assert!(map.iter().eq(expected.iter()));
+            }
+        }
+
+        #[test]
+        fn test_cursor_mut(data: Vec<(u8, u32)>, ops: Vec<(u8, u8, u32)>) {
+            let mut map: BTreeMap<_, _, 3> = data.iter().copied().collect();
+            let mut expected: std::collections::BTreeMap<_, _> = data.into_iter().collect();
+            for (probe, op, value) in ops {
+                let current = expected.range(probe..).next().map(|(k, _)| *k);
+                let mut cursor = map.lower_bound_cursor_mut(&probe);
+                match op % 3 {
+                    0 => {
+                        let next = expected.range(probe..).nth(1).map(|(k, _)| *k);
+                        let removed = current.map(|k| (k, expected.remove(&k).unwrap()));
+                        assert_eq!(cursor.remove_current(), removed);
+                        assert_eq!(cursor.key().copied(), next);
+                    }
+                    1 => {
+                        let prev = expected.range(..probe).next_back().map(|(k, _)| *k);
+                        if let Some(key) = probe.checked_sub(1).filter(|key| prev < Some(*key)) {
+                            cursor.insert_before(key, value);
+                            expected.insert(key, value);
+                        }
+                        assert_eq!(cursor.key().copied(), current);
+                    }
+                    _ => {
+                        let key = match current {
+                            Some(key) => key.checked_add(1),
+                            None => Some(0),
+                        };
+                        if let Some(key) = key.filter(|key| !expected.contains_key(key)) {
+                            cursor.insert_after(key, value);
+                            expected.insert(key, value);
+                        }
+                        assert_eq!(cursor.key().copied(), current);
+                    }
+                }
+                prop_assert_eq!(map.validate(), Ok(()));
+                assert_eq!(map.len(), expected.len());
+                assert!(map.iter().eq(expected.iter()));
+            }
+        }
+
+        #[test]
+        fn test_from_iter(data: Vec<(u16, u8)>, more: Vec<(u16, u8)>) {
+            let mut expected: std::collections::BTreeMap<_, _> = data.iter().copied().collect();
+            let map = BTreeMap::<_, _, 3>::from_sorted_iter(expected.clone());
+            prop_assert_eq!(map.validate(), Ok(()));
+            assert!(map.iter().eq(expected.iter()));
+            let map = BTreeMap::<_, _, 6>::from_sorted_iter(expected.clone());
+            prop_assert_eq!(map.validate(), Ok(()));
+            assert!(map.iter().eq(expected.iter()));
+
+            let mut map: BTreeMap<_, _, 4> = data.iter().copied().collect();
+            prop_assert_eq!(map.validate(), Ok(()));
+            assert!(map.iter().eq(expected.iter()));
+            map.extend(more.iter().copied());
+            expected.extend(more.iter().copied());
+            prop_assert_eq!(map.validate(), Ok(()));
+            assert!(map.iter().eq(expected.iter()));
+            for (k, _) in data {
+                assert_eq!(map.remove(&k), expected.remove(&k));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            assert!(map.iter().eq(expected.iter()));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_unsorted_iter() {
+        BTreeMap::<_, _, 3>::from_sorted_iter(vec![(1, 0), (0, 0)]);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = BTreeMap::<_, _, 3>::default();
+        for key in 0..100 {
+            map.insert(key, ());
+        }
+        let stats = map.stats();
+        assert!(stats.splits > 0);
+        assert_eq!(stats.merges, 0);
+        for key in 0..100 {
+            map.remove(&key);
+        }
+        assert!(map.stats().merges > 0);
     }
 }
@@ -0,0 +1,362 @@
+use super::augment::{Augment, Monoid};
+use super::avlt::{self, AVLEntry, AVLTreeMap};
+use super::{Map, UnorderedMap};
+use crate::ch4::stats::Stats;
+use crate::ch4::validate::{self, Validation, Violation};
+use crate::ch4::{BinTreeCursor, BinTreeMut, MoveParentBinTreeMut};
+use std::ops::Bound;
+
+/// 半开区间`[start, end)`. 先按起点、再按终点比较.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T: Ord> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Ord> Interval<T> {
+    /// # Panics
+    /// 若`start > end`.
+    pub fn new(start: T, end: T) -> Self {
+        assert!(start <= end, "interval start must not exceed its end");
+        Self { start, end }
+    }
+
+    /// 是否为空区间. 空区间不与任何区间相交.
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    /// 是否包含点`point`.
+    pub fn contains(&self, point: &T) -> bool {
+        self.start <= *point && *point < self.end
+    }
+
+    /// 是否与另一区间相交.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start < other.end && other.start < self.end && !self.is_empty() && !other.is_empty()
+    }
+}
+
+/// 子树中非空区间终点的最大值.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxEnd<T>(pub Option<T>);
+
+impl<T: Ord + Clone> Monoid for MaxEnd<T> {
+    fn identity() -> Self {
+        MaxEnd(None)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        MaxEnd(self.0.clone().max(other.0.clone()))
+    }
+}
+
+impl<T: Ord + Clone, V> Augment<Interval<T>, V> for MaxEnd<T> {
+    fn from_entry(key: &Interval<T>, _: &V) -> Self {
+        if key.is_empty() {
+            MaxEnd(None)
+        } else {
+            MaxEnd(Some(key.end.clone()))
+        }
+    }
+}
+
+pub type IntervalEntry<T, V> = AVLEntry<Interval<T>, V, MaxEnd<T>>;
+
+/// 区间树.
+///
+/// 基于以区间为键的AVL树，每个结点维护子树中区间终点的最大值，据此剪枝，
+/// 使得查找任意一个相交区间的复杂度为`O(log n)`，报告所有`k`个相交区间的复杂度为`O(k log n)`.
+pub struct IntervalTreeMap<Tree: BinTreeMut<Elem = IntervalEntry<T, V>>, T: Ord + Clone, V> {
+    map: AVLTreeMap<Tree, Interval<T>, V, MaxEnd<T>>,
+}
+
+impl<Tree, T: Ord + Clone, V> Default for IntervalTreeMap<Tree, T, V>
+where
+    Tree: Default + BinTreeMut<Elem = IntervalEntry<T, V>>,
+{
+    fn default() -> Self {
+        Self {
+            map: AVLTreeMap::default(),
+        }
+    }
+}
+
+impl<Tree, T: Ord + Clone, V> IntervalTreeMap<Tree, T, V>
+where
+    Tree: Default + BinTreeMut<Elem = IntervalEntry<T, V>>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 返回任意一个与`query`相交的区间.
+    pub fn find_overlap(&self, query: &Interval<T>) -> Option<(&Interval<T>, &V)> {
+        if query.is_empty() {
+            return None;
+        }
+        let mut cursor = self.map.bst.tree.cursor();
+        while let Some(entry) = cursor.as_ref() {
+            if entry.key.overlaps(query) {
+                return cursor.into_ref().map(|entry| (&entry.key, &*entry.value));
+            }
+            // 若左子树中有终点大于`query.start`的区间却不与之相交，则该区间以及右子树中所有区间的起点都不小于`query.end`.
+            match cursor.left() {
+                Some(left) if matches!(&left.value.augment().0, Some(end) if *end > query.start) => {
+                    cursor.move_left()
+                }
+                _ => cursor.move_right(),
+            }
+        }
+        None
+    }
+
+    /// 按升序返回所有与`query`相交的区间.
+    pub fn overlaps(&self, query: &Interval<T>) -> Vec<(&Interval<T>, &V)> {
+        let mut result = Vec::new();
+        if !query.is_empty() {
+            Self::search(
+                self.map.bst.tree.cursor(),
+                &query.start,
+                Bound::Excluded(&query.end),
+                &mut result,
+            );
+        }
+        result
+    }
+
+    /// 按升序返回所有包含点`point`的区间.
+    pub fn stab(&self, point: &T) -> Vec<(&Interval<T>, &V)> {
+        let mut result = Vec::new();
+        Self::search(
+            self.map.bst.tree.cursor(),
+            point,
+            Bound::Included(point),
+            &mut result,
+        );
+        result
+    }
+
+    /// 按中序收集子树中终点大于`lower`、起点不超过`upper`的非空区间.
+    fn search<'a, C>(
+        cursor: C,
+        lower: &T,
+        upper: Bound<&T>,
+        result: &mut Vec<(&'a Interval<T>, &'a V)>,
+    ) where
+        C: BinTreeCursor<'a, Elem = IntervalEntry<T, V>> + Clone,
+    {
+        match cursor.as_ref() {
+            Some(entry) if matches!(&entry.value.augment().0, Some(end) if end > lower) => {}
+            _ => return,
+        }
+        let (left, right) = cursor.split();
+        if let Some(left) = left {
+            Self::search(left, lower, upper, result);
+        }
+        let entry = cursor.into_ref().unwrap();
+        let before_upper = match upper {
+            Bound::Included(upper) => entry.key.start <= *upper,
+            Bound::Excluded(upper) => entry.key.start < *upper,
+            Bound::Unbounded => true,
+        };
+        // 右子树中区间的起点都不小于当前区间的起点.
+        if !before_upper {
+            return;
+        }
+        if !entry.key.is_empty() && entry.key.end > *lower {
+            result.push((&entry.key, &*entry.value));
+        }
+        if let Some(right) = right {
+            Self::search(right, lower, upper, result);
+        }
+    }
+}
+
+impl<Tree, T: Ord + Clone, V> UnorderedMap<Interval<T>, V> for IntervalTreeMap<Tree, T, V>
+where
+    Tree: Default
+        + MoveParentBinTreeMut<Elem = IntervalEntry<T, V>>
+        + BinTreeMut<Elem = IntervalEntry<T, V>>,
+{
+    fn get(&self, key: &Interval<T>) -> Option<&V> {
+        self.map.get(key)
+    }
+
+    fn get_mut(&mut self, key: &Interval<T>) -> Option<&mut V> {
+        self.map.get_mut(key)
+    }
+
+    fn insert(&mut self, key: Interval<T>, value: V) -> Option<V> {
+        self.map.insert(key, value)
+    }
+
+    fn get_mut_or_insert(&mut self, key: Interval<T>, default: V) -> &mut V {
+        self.map.get_mut_or_insert(key, default)
+    }
+
+    fn remove(&mut self, key: &Interval<T>) -> Option<V> {
+        self.map.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&Interval<T>, &V)>> {
+        Box::new(self.map.iter())
+    }
+
+    fn stats(&self) -> Stats {
+        self.map.stats()
+    }
+}
+
+impl<Tree, T: Ord + Clone, V> Map<Interval<T>, V> for IntervalTreeMap<Tree, T, V> where
+    Tree: Default
+        + MoveParentBinTreeMut<Elem = IntervalEntry<T, V>>
+        + BinTreeMut<Elem = IntervalEntry<T, V>>
+{
+}
+
+impl<Tree, T: Ord + Clone, V> IntervalTreeMap<Tree, T, V>
+where
+    Tree: BinTreeMut<Elem = IntervalEntry<T, V>>,
+{
+    /// 按区间的升序返回迭代器.
+    pub fn iter(&self) -> Iter<'_, Tree, T, V> {
+        self.map.iter()
+    }
+
+    /// 按升序返回所有区间的迭代器.
+    pub fn keys(&self) -> Keys<'_, Tree, T, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按区间的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, Tree, T, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 检查AVL树的不变式，以及每个结点记录的子树最大终点.
+    pub fn validate(&self) -> Validation {
+        let mut violations = match self.map.validate() {
+            Ok(()) => Vec::new(),
+            Err(violations) => violations,
+        };
+        let mut position = 0;
+        Self::validate_at(self.map.bst.tree.cursor(), &mut position, &mut violations);
+        validate::into_validation(violations)
+    }
+
+    /// 中序遍历，返回子树中非空区间终点的最大值.
+    fn validate_at<'a, C>(
+        cursor: C,
+        position: &mut usize,
+        violations: &mut Vec<Violation>,
+    ) -> Option<T>
+    where
+        C: BinTreeCursor<'a, Elem = IntervalEntry<T, V>> + Clone,
+        T: 'a,
+        V: 'a,
+    {
+        let (left, right) = cursor.split();
+        let entry = cursor.into_ref()?;
+        let mut max_end = left.and_then(|left| Self::validate_at(left, position, violations));
+        let current = *position;
+        *position += 1;
+        if !entry.key.is_empty() {
+            max_end = max_end.max(Some(entry.key.end.clone()));
+        }
+        max_end =
+            max_end.max(right.and_then(|right| Self::validate_at(right, position, violations)));
+        if entry.value.augment().0 != max_end {
+            violations.push(Violation::Augment { position: current });
+        }
+        max_end
+    }
+}
+
+/// 区间树的中序迭代器.
+pub type Iter<'a, Tree, T, V> = avlt::Iter<'a, Tree, Interval<T>, V, MaxEnd<T>>;
+
+pub type Keys<'a, Tree, T, V> = super::Keys<Iter<'a, Tree, T, V>>;
+
+pub type Values<'a, Tree, T, V> = super::Values<Iter<'a, Tree, T, V>>;
+
+impl<'a, Tree, T: Ord + Clone, V> IntoIterator for &'a IntervalTreeMap<Tree, T, V>
+where
+    Tree: BinTreeMut<Elem = IntervalEntry<T, V>>,
+{
+    type Item = (&'a Interval<T>, &'a V);
+    type IntoIter = Iter<'a, Tree, T, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
+    use crate::ch4::vec_binary_tree::VecBinaryTree;
+    use proptest::prelude::*;
+
+    type Tree = DoublyLinkedBinaryTree<IntervalEntry<u8, u32>>;
+
+    fn interval((start, len): (u8, u8)) -> Interval<u8> {
+        Interval::new(start, start.saturating_add(len % 32))
+    }
+
+    #[test]
+    fn test_reservations() {
+        let mut map = IntervalTreeMap::<VecBinaryTree<_>, _, _>::new();
+        map.insert(Interval::new(9, 12), "standup");
+        map.insert(Interval::new(13, 14), "lunch");
+        map.insert(Interval::new(11, 15), "review");
+        let conflicts = map.overlaps(&Interval::new(12, 13));
+        assert_eq!(conflicts, vec![(&Interval::new(11, 15), &"review")]);
+        assert!(map.find_overlap(&Interval::new(15, 18)).is_none());
+        assert!(map.find_overlap(&Interval::new(8, 9)).is_none());
+        assert_eq!(
+            map.stab(&11)
+                .into_iter()
+                .map(|(_, v)| *v)
+                .collect::<Vec<_>>(),
+            vec!["standup", "review"]
+        );
+        assert!(map.stab(&12).iter().all(|(_, v)| **v == "review"));
+    }
+
+    proptest! {
+        #[test]
+        fn test_interval_tree(
+            entrys: Vec<((u8, u8), u32)>,
+            removes: Vec<(u8, u8)>,
+            queries: Vec<(u8, u8)>,
+        ) {
+            let mut map = IntervalTreeMap::<Tree, _, _>::new();
+            let mut expected = std::collections::BTreeMap::new();
+            for (key, value) in entrys {
+                prop_assert_eq!(map.insert(interval(key), value), expected.insert(interval(key), value));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for key in removes {
+                prop_assert_eq!(map.remove(&interval(key)), expected.remove(&interval(key)));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            prop_assert!(map.iter().eq(expected.iter()));
+            for query in queries.into_iter().map(interval) {
+                let overlaps = expected.iter().filter(|(key, _)| key.overlaps(&query)).collect::<Vec<_>>();
+                prop_assert_eq!(map.overlaps(&query), overlaps.clone());
+                match map.find_overlap(&query) {
+                    Some((key, _)) => prop_assert!(key.overlaps(&query)),
+                    None => prop_assert!(overlaps.is_empty()),
+                }
+                let stab = expected.iter().filter(|(key, _)| key.contains(&query.start)).collect::<Vec<_>>();
+                prop_assert_eq!(map.stab(&query.start), stab);
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use super::Entry;
+use crate::ch4::{BinTree, BinTreeCursor, BinTreeCursorMut, BinTreeMut};
+use std::cmp::Ordering;
+
+/// 带秩的树. AVL树的秩为高度加一，红黑树的秩为黑高度，空树的秩都为0.
+pub(crate) type Ranked<Tree> = (Tree, usize);
+
+/// 拆出根结点后的`(左子树, 只含根结点的树, 右子树)`.
+pub(crate) type Exposed<Tree> = (Ranked<Tree>, Tree, Ranked<Tree>);
+
+/// 按键拆分后的`(小于键的部分, 只含命中结点的树, 大于键的部分)`.
+pub(crate) type Parts<Tree> = (Ranked<Tree>, Option<Tree>, Ranked<Tree>);
+
+/// 基于`join`的平衡树.
+///
+/// 拆分与集合运算都只依赖于各平衡树的`join`，
+/// 参见Blelloch, Ferizovic, Sun. Just Join for Parallel Ordered Sets.
+pub(crate) trait Join<K: Ord, V> {
+    type Node;
+    type Tree: Default + BinTreeMut<Elem = Entry<K, Self::Node>>;
+
+    /// 由键值对创建词条. 结点的平衡信息由`join`重新设置.
+    fn entry(key: K, value: V) -> Entry<K, Self::Node>;
+
+    fn into_value(node: Self::Node) -> V;
+
+    /// 拆出根结点. 要求树非空.
+    fn expose(tree: Ranked<Self::Tree>) -> Exposed<Self::Tree>;
+
+    /// 以只含一个结点的`mid`连接`left`与`right`.
+    /// 要求`left`中的键都小于`mid`的键，`right`中的键都大于`mid`的键.
+    fn join(
+        left: Ranked<Self::Tree>,
+        mid: Self::Tree,
+        right: Ranked<Self::Tree>,
+    ) -> Ranked<Self::Tree>;
+}
+
+/// 摘下根结点的两棵子树，返回`(左子树, 只含根结点的树, 右子树)`. 要求树非空.
+pub(crate) fn take_children<Tree: BinTreeMut>(mut tree: Tree) -> (Tree, Tree, Tree) {
+    let (left, right) = {
+        let mut cursor = tree.cursor_mut();
+        (cursor.take_left().unwrap(), cursor.take_right().unwrap())
+    };
+    (left, tree, right)
+}
+
+/// 以`mid`为根，`left`与`right`分别为左右子树.
+pub(crate) fn attach<Tree: BinTreeMut>(left: Tree, mut mid: Tree, right: Tree) -> Tree {
+    {
+        let mut cursor = mid.cursor_mut();
+        cursor.append_left(left);
+        cursor.append_right(right);
+    }
+    mid
+}
+
+fn singleton<Tree: Default + BinTreeMut>(elem: Tree::Elem) -> Tree {
+    let mut tree = Tree::default();
+    tree.cursor_mut().insert_as_root(elem);
+    tree
+}
+
+pub(crate) fn into_entry<Tree: BinTreeMut>(mut tree: Tree) -> Tree::Elem {
+    tree.cursor_mut().into_inner().unwrap()
+}
+
+fn key_of<'a, K: 'a + Ord, N: 'a, Tree: BinTree<Elem = Entry<K, N>>>(tree: &'a Tree) -> &'a K {
+    &tree.cursor().into_ref().unwrap().key
+}
+
+/// 最小的键.
+pub(crate) fn first_key<'a, K: 'a + Ord, N: 'a, Tree: BinTree<Elem = Entry<K, N>>>(
+    tree: &'a Tree,
+) -> Option<&'a K> {
+    let mut cursor = tree.cursor();
+    while cursor.left().is_some() {
+        cursor.move_left();
+    }
+    cursor.into_ref().map(|entry| &entry.key)
+}
+
+/// 最大的键.
+pub(crate) fn last_key<'a, K: 'a + Ord, N: 'a, Tree: BinTree<Elem = Entry<K, N>>>(
+    tree: &'a Tree,
+) -> Option<&'a K> {
+    let mut cursor = tree.cursor();
+    while cursor.right().is_some() {
+        cursor.move_right();
+    }
+    cursor.into_ref().map(|entry| &entry.key)
+}
+
+/// 由按键严格升序排列的`len`个键值对构造完全平衡的树.
+///
+/// 两侧子树的规模至多相差1，因此每次`join`的复杂度为`O(1)`，总复杂度为`O(n)`.
+pub(crate) fn build<K: Ord, V, J: Join<K, V>, I: Iterator<Item = (K, V)>>(
+    entries: &mut I,
+    len: usize,
+) -> Ranked<J::Tree> {
+    if len == 0 {
+        return (J::Tree::default(), 0);
+    }
+    let mid = len / 2;
+    let left = build::<K, V, J, I>(entries, mid);
+    let (key, value) = entries.next().unwrap();
+    let right = build::<K, V, J, I>(entries, len - mid - 1);
+    J::join(left, singleton(J::entry(key, value)), right)
+}
+
+/// 按`key`拆分，复杂度为`O(log n)`.
+pub(crate) fn split<K: Ord, V, J: Join<K, V>>(tree: Ranked<J::Tree>, key: &K) -> Parts<J::Tree> {
+    if tree.0.is_empty() {
+        return (tree, None, (J::Tree::default(), 0));
+    }
+    let (left, mid, right) = J::expose(tree);
+    let ordering = key.cmp(key_of(&mid));
+    match ordering {
+        Ordering::Equal => (left, Some(mid), right),
+        Ordering::Less => {
+            let (less, found, greater) = split::<K, V, J>(left, key);
+            (less, found, J::join(greater, mid, right))
+        }
+        Ordering::Greater => {
+            let (less, found, greater) = split::<K, V, J>(right, key);
+            (J::join(left, mid, less), found, greater)
+        }
+    }
+}
+
+/// 拆出最大的结点. 要求树非空.
+fn split_last<K: Ord, V, J: Join<K, V>>(tree: Ranked<J::Tree>) -> (Ranked<J::Tree>, J::Tree) {
+    let (left, mid, right) = J::expose(tree);
+    if right.0.is_empty() {
+        (left, mid)
+    } else {
+        let (rest, last) = split_last::<K, V, J>(right);
+        (J::join(left, mid, rest), last)
+    }
+}
+
+/// 连接两棵树，要求`left`中的键都小于`right`中的键.
+pub(crate) fn join2<K: Ord, V, J: Join<K, V>>(
+    left: Ranked<J::Tree>,
+    right: Ranked<J::Tree>,
+) -> Ranked<J::Tree> {
+    if left.0.is_empty() {
+        right
+    } else {
+        let (rest, last) = split_last::<K, V, J>(left);
+        J::join(rest, last, right)
+    }
+}
+
+/// 并集. 两侧都有的键，其值为`resolve(key, lhs中的值, rhs中的值)`.
+pub(crate) fn union<K: Ord, V, J: Join<K, V>, F: FnMut(&K, V, V) -> V>(
+    lhs: Ranked<J::Tree>,
+    rhs: Ranked<J::Tree>,
+    resolve: &mut F,
+) -> Ranked<J::Tree> {
+    if lhs.0.is_empty() {
+        return rhs;
+    }
+    if rhs.0.is_empty() {
+        return lhs;
+    }
+    let (rhs_left, mid, rhs_right) = J::expose(rhs);
+    let (lhs_left, found, lhs_right) = split::<K, V, J>(lhs, key_of(&mid));
+    let left = union::<K, V, J, F>(lhs_left, rhs_left, resolve);
+    let right = union::<K, V, J, F>(lhs_right, rhs_right, resolve);
+    let mid = match found {
+        Some(found) => {
+            let Entry { key, value: lhs } = into_entry(found);
+            let value = resolve(
+                &key,
+                J::into_value(lhs),
+                J::into_value(into_entry(mid).value),
+            );
+            singleton(J::entry(key, value))
+        }
+        None => mid,
+    };
+    J::join(left, mid, right)
+}
+
+/// 交集. 值为`resolve(key, lhs中的值, rhs中的值)`.
+pub(crate) fn intersection<K: Ord, V, J: Join<K, V>, F: FnMut(&K, V, V) -> V>(
+    lhs: Ranked<J::Tree>,
+    rhs: Ranked<J::Tree>,
+    resolve: &mut F,
+) -> Ranked<J::Tree> {
+    if lhs.0.is_empty() || rhs.0.is_empty() {
+        return (J::Tree::default(), 0);
+    }
+    let (rhs_left, mid, rhs_right) = J::expose(rhs);
+    let (lhs_left, found, lhs_right) = split::<K, V, J>(lhs, key_of(&mid));
+    let left = intersection::<K, V, J, F>(lhs_left, rhs_left, resolve);
+    let right = intersection::<K, V, J, F>(lhs_right, rhs_right, resolve);
+    match found {
+        Some(found) => {
+            let Entry { key, value: lhs } = into_entry(found);
+            let value = resolve(
+                &key,
+                J::into_value(lhs),
+                J::into_value(into_entry(mid).value),
+            );
+            J::join(left, singleton(J::entry(key, value)), right)
+        }
+        None => join2::<K, V, J>(left, right),
+    }
+}
+
+/// 差集，即`lhs`中不在`rhs`中的键.
+pub(crate) fn difference<K: Ord, V, J: Join<K, V>>(
+    lhs: Ranked<J::Tree>,
+    rhs: Ranked<J::Tree>,
+) -> Ranked<J::Tree> {
+    if lhs.0.is_empty() || rhs.0.is_empty() {
+        return lhs;
+    }
+    let (rhs_left, mid, rhs_right) = J::expose(rhs);
+    let (lhs_left, _, lhs_right) = split::<K, V, J>(lhs, key_of(&mid));
+    let left = difference::<K, V, J>(lhs_left, rhs_left);
+    let right = difference::<K, V, J>(lhs_right, rhs_right);
+    join2::<K, V, J>(left, right)
+}
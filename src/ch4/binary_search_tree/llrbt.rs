@@ -1,7 +1,10 @@
 use super::{
     bst2::{self, TreeMap},
-    Map,
+    set::MapSet,
+    Map, UnorderedMap,
 };
+use crate::ch4::stats::{self, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
 use std::cmp::Ordering;
 
 type Link<K, V> = Option<BoxedNode<K, V>>;
@@ -28,6 +31,7 @@ fn size<K: Ord, V>(link: &Link<K, V>) -> usize {
 }
 
 fn zig<K: Ord, V>(mut node: BoxedNode<K, V>) -> BoxedNode<K, V> {
+    stats::record(|stats| stats.rotations += 1);
     let mut x = node.right.take().unwrap();
     node.right = x.left.take();
     x.value.color = node.value.color;
@@ -39,6 +43,7 @@ fn zig<K: Ord, V>(mut node: BoxedNode<K, V>) -> BoxedNode<K, V> {
 }
 
 fn zag<K: Ord, V>(mut node: BoxedNode<K, V>) -> BoxedNode<K, V> {
+    stats::record(|stats| stats.rotations += 1);
     let mut x = node.left.take().unwrap();
     node.left = x.right.take();
     x.value.color = node.value.color;
@@ -61,6 +66,7 @@ fn new_node<K: Ord, V>(key: K, value: V, color: bool) -> BoxedNode<K, V> {
 
 fn insert<K: Ord, V>(link: Link<K, V>, key: K, value: V) -> (BoxedNode<K, V>, Option<V>) {
     if let Some(mut node) = link {
+        stats::record(|stats| stats.comparisons += 1);
         let value = match key.cmp(&node.key) {
             Ordering::Less => {
                 let (left, value) = insert(node.left.take(), key, value);
@@ -103,6 +109,7 @@ fn get_mut_or_insert<'a, K: Ord, V>(
     value: V,
 ) -> (*mut V, BoxedNode<K, V>) {
     if let Some(mut node) = link {
+        stats::record(|stats| stats.comparisons += 1);
         let target = match key.cmp(&node.key) {
             Ordering::Less => {
                 let (target, left) = get_mut_or_insert(node.left.take(), key, value);
@@ -172,6 +179,7 @@ fn delete_min<K: Ord, V>(mut node: BoxedNode<K, V>) -> (Link<K, V>, BoxedNode<K,
 }
 
 fn delete<K: Ord, V>(mut node: BoxedNode<K, V>, key: &K) -> (Link<K, V>, Link<K, V>) {
+    stats::record(|stats| stats.comparisons += 1);
     match key.cmp(&node.key) {
         Ordering::Less => {
             if node.left.is_none() {
@@ -190,6 +198,7 @@ fn delete<K: Ord, V>(mut node: BoxedNode<K, V>, key: &K) -> (Link<K, V>, Link<K,
                 // 为了确保不会出现，右边为空，但左边不为空的情况.
                 node = zag(node);
             }
+            stats::record(|stats| stats.comparisons += 1);
             let ordering = key.cmp(&node.key);
             if matches!(ordering, Ordering::Equal) && node.right.is_none() {
                 // 左边必为空.
@@ -201,6 +210,7 @@ fn delete<K: Ord, V>(mut node: BoxedNode<K, V>, key: &K) -> (Link<K, V>, Link<K,
             if !is_red(&node.right) && !is_red(&node.right.as_ref().unwrap().left) {
                 node = move_red_right(node);
             }
+            stats::record(|stats| stats.comparisons += 1);
             let ordering = key.cmp(&node.key);
             if matches!(ordering, Ordering::Equal) {
                 // 右子树的根或它的左孩子之一必然是红的.(由`move_red_right`保证)
@@ -234,7 +244,7 @@ impl<K: Ord, V> Default for RBTreeMap<K, V> {
     }
 }
 
-impl<K: Ord, V> Map<K, V> for RBTreeMap<K, V> {
+impl<K: Ord, V> UnorderedMap<K, V> for RBTreeMap<K, V> {
     fn get(&self, key: &K) -> Option<&V> {
         self.bst.get(key).map(|node| &node.elem)
     }
@@ -248,10 +258,15 @@ impl<K: Ord, V> Map<K, V> for RBTreeMap<K, V> {
     }
 
     fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
-        Box::new(self.bst.iter().map(|(k, v)| (k, &v.elem)))
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.bst.stats.get()
     }
 
     fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let _stats = self.bst.stats.enter();
         let (mut root, value) = insert(self.bst.root.take(), key, value);
         root.value.color = BLACK;
         self.bst.root = Some(root);
@@ -259,6 +274,7 @@ impl<K: Ord, V> Map<K, V> for RBTreeMap<K, V> {
     }
 
     fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let _stats = self.bst.stats.enter();
         let (target, mut root) = get_mut_or_insert(self.bst.root.take(), key, default);
         root.value.color = BLACK;
         self.bst.root = Some(root);
@@ -266,6 +282,7 @@ impl<K: Ord, V> Map<K, V> for RBTreeMap<K, V> {
     }
 
     fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.bst.stats.enter();
         if let Some(root) = self.bst.root.take() {
             let (mut root, deleted) = delete(root, key);
             root.as_mut().map(|node| node.value.color = BLACK);
@@ -277,11 +294,241 @@ impl<K: Ord, V> Map<K, V> for RBTreeMap<K, V> {
     }
 }
 
+impl<K: Ord, V> Map<K, V> for RBTreeMap<K, V> {}
+
+impl<K: Ord, V> RBTreeMap<K, V> {
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.bst.iter(),
+        }
+    }
+
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.bst.iter_mut(),
+        }
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, K, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, K, V>> {
+        self.bst.cursor_at(key).map(|inner| Cursor { inner })
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, K, V> {
+        Cursor {
+            inner: self.bst.lower_bound_cursor(key),
+        }
+    }
+
+    /// 检查二叉查找树的不变式，以及颜色、左倾与黑高度.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let root = self.bst.validate_with(
+            &mut violations,
+            |position, node, left: Option<&(usize, bool)>, right, violations| {
+                let (lh, left_color) = left.copied().unwrap_or((0, BLACK));
+                let (rh, right_color) = right.copied().unwrap_or((0, BLACK));
+                let color = node.value.color;
+                if right_color == RED {
+                    violations.push(Violation::RightLeaning { position });
+                }
+                if color == RED && (left_color == RED || right_color == RED) {
+                    violations.push(Violation::DoubleRed { position });
+                }
+                if lh != rh {
+                    violations.push(Violation::BlackHeight {
+                        position,
+                        left: lh,
+                        right: rh,
+                    });
+                }
+                (lh.max(rh) + (color == BLACK) as usize, color)
+            },
+        );
+        if matches!(root, Some((_, RED))) {
+            violations.push(Violation::RedRoot);
+        }
+        validate::into_validation(violations)
+    }
+}
+
+/// 左倾红黑树上的只读游标，参见`bst2::Cursor`.
+pub struct Cursor<'a, K, V> {
+    inner: bst2::Cursor<'a, K, Node<V>>,
+}
+
+impl<'a, K, V> Clone for Cursor<'a, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Cursor<'a, K, V> {
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.inner.move_next()
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev()
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.key_value().map(|(key, node)| (key, &node.elem))
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.inner.key()
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.inner.value().map(|node| &node.elem)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_next().map(|(key, node)| (key, &node.elem))
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_prev().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    inner: bst2::Iter<'a, K, Node<V>>,
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: Ord, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+impl<'a, K: Ord, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+pub struct IterMut<'a, K, V> {
+    inner: bst2::IterMut<'a, K, Node<V>>,
+}
+
+impl<'a, K: Ord, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &mut node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: Ord, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(key, node)| (key, &mut node.elem))
+    }
+}
+
+impl<'a, K: Ord, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+pub struct IntoIter<K, V> {
+    inner: bst2::IntoIter<K, Node<V>>,
+}
+
+impl<K: Ord, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Ord, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, node.elem))
+    }
+}
+
+impl<K: Ord, V> ExactSizeIterator for IntoIter<K, V> {}
+
+pub type Keys<'a, K, V> = super::Keys<Iter<'a, K, V>>;
+
+pub type Values<'a, K, V> = super::Values<Iter<'a, K, V>>;
+
+impl<'a, K: Ord, V> IntoIterator for &'a RBTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a mut RBTreeMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Ord, V> IntoIterator for RBTreeMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.bst.into_iter(),
+        }
+    }
+}
+
+/// 基于左倾红黑树的有序集合.
+pub type RBTreeSet<K> = MapSet<RBTreeMap<K, ()>>;
+
 #[cfg(test)]
 mod test {
+    use super::super::test::check_iter;
     use super::*;
     use proptest::prelude::*;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn test_map_basic() {
@@ -320,6 +567,7 @@ mod test {
             // insert
             for (k, v) in data.clone() {
                 assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -344,6 +592,7 @@ mod test {
             for k in data.keys().cloned() {
                 let elem = data.get(&k).copied().unwrap();
                 assert_eq!(map.insert(k, elem), Some(elem + 1));
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -351,9 +600,38 @@ mod test {
             // remove
             for (idx, k) in data.keys().enumerate() {
                 assert_eq!(map.remove(k), data.get(k).copied());
+                prop_assert_eq!(map.validate(), Ok(()));
                 assert_eq!(map.len(), data.len() - idx - 1);
             }
             assert!(map.is_empty());
         }
+
+        #[test]
+        fn test_iter(data: HashMap<String, i64>) {
+            check_iter::<RBTreeMap<_, _>, _>(data, RBTreeMap::validate)?;
+        }
+
+        #[test]
+        fn test_cursor(data: BTreeMap<u8, u8>, probes: Vec<u8>) {
+            let mut map = RBTreeMap::<_, _>::default();
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(map.cursor_at(&probe).is_some(), data.contains_key(&probe));
+                assert_eq!(cursor.peek_prev(), data.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                let mut walk = data.range(probe..).chain(data.range(..probe));
+                for _ in 0..=data.len() {
+                    match cursor.key_value() {
+                        Some(entry) => assert_eq!(Some(entry), walk.next()),
+                        None => assert_eq!(cursor.peek_next(), data.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                assert_eq!(cursor.key_value(), data.range(probe..).next());
+            }
+        }
     }
 }
@@ -1,6 +1,12 @@
 /// 左倾红黑树(只允许存在红左链)
 pub mod llrbt;
 
+/// 平衡树结点的附加信息.
+pub mod augment;
+
+/// 基于`join`的拆分与集合运算.
+pub(crate) mod join;
+
 /// 二叉查找树(非generic)
 pub mod bst2;
 
@@ -10,6 +16,12 @@ pub mod rbt;
 /// B树.
 pub mod bt;
 
+/// B+树.
+pub mod bpt;
+
+/// 页式B树(基于文件与缓冲池).
+pub mod pbt;
+
 /// 伸展树(Splay Tree).
 pub mod st;
 
@@ -19,8 +31,40 @@ pub mod avlt;
 /// 基础二叉查找树.
 pub mod bst;
 
-use std::collections::{BTreeMap, HashMap};
-use std::hash::Hash;
+/// 树堆(Treap).
+pub mod treap;
+
+/// 替罪羊树.
+pub mod sgt;
+
+/// 加权平衡树(Weight-Balanced Tree).
+pub mod wbt;
+
+/// 持久化AVL树(结构共享).
+pub mod persistent;
+
+/// 字典树(Trie).
+pub mod trie;
+
+/// 基数树(压缩字典树).
+pub mod radix;
+
+/// 跳表.
+pub mod skip_list;
+
+/// 区间树.
+pub mod interval;
+
+/// 集合.
+pub mod set;
+
+/// 多重字典(允许重复键).
+pub mod multimap;
+
+use crate::ch4::stats::Stats;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{BuildHasher, Hash};
+use std::iter;
 
 /// 词条结构，表示一个键值对.
 #[derive(Debug)]
@@ -49,8 +93,8 @@ impl<K: Ord, V> Ord for Entry<K, V> {
     }
 }
 
-/// 字典特质.
-pub trait Map<K: Ord, V>: Default {
+/// 无序字典特质: 只要求键可判等. 散列表与各种有序字典都实现了它.
+pub trait UnorderedMap<K: Eq, V>: Default {
     /// 返回键所对应的值的引用.
     fn get(&self, key: &K) -> Option<&V>;
 
@@ -78,10 +122,19 @@ pub trait Map<K: Ord, V>: Default {
         self.len() == 0
     }
 
+    /// 迭代所有键值对. 对`Map`而言按键的升序，否则顺序任意.
     fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>>;
+
+    /// 返回累计的操作次数. 只有启用`stats`特性时才会计数，不计数的实现总是返回全零.
+    fn stats(&self) -> Stats {
+        Stats::default()
+    }
 }
 
-impl<K: Ord, V> Map<K, V> for BTreeMap<K, V> {
+/// 字典特质: 键有序的`UnorderedMap`，`iter`按键的升序迭代.
+pub trait Map<K: Ord, V>: UnorderedMap<K, V> {}
+
+impl<K: Ord, V> UnorderedMap<K, V> for BTreeMap<K, V> {
     fn get(&self, key: &K) -> Option<&V> {
         self.get(key)
     }
@@ -111,7 +164,9 @@ impl<K: Ord, V> Map<K, V> for BTreeMap<K, V> {
     }
 }
 
-impl<K: Hash + Ord, V> Map<K, V> for HashMap<K, V> {
+impl<K: Ord, V> Map<K, V> for BTreeMap<K, V> {}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> UnorderedMap<K, V> for HashMap<K, V, S> {
     fn get(&self, key: &K) -> Option<&V> {
         self.get(key)
     }
@@ -120,14 +175,14 @@ impl<K: Hash + Ord, V> Map<K, V> for HashMap<K, V> {
         self.get_mut(key)
     }
 
-    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
-        self.entry(key).or_insert(default)
-    }
-
     fn insert(&mut self, key: K, value: V) -> Option<V> {
         self.insert(key, value)
     }
 
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        self.entry(key).or_insert(default)
+    }
+
     fn remove(&mut self, key: &K) -> Option<V> {
         self.remove(key)
     }
@@ -141,23 +196,312 @@ impl<K: Hash + Ord, V> Map<K, V> for HashMap<K, V> {
     }
 }
 
+/// 键迭代器. 由产生键值对的迭代器适配而来.
+pub struct Keys<I> {
+    inner: I,
+}
+
+impl<I> Keys<I> {
+    pub(crate) fn new(inner: I) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I> Iterator for Keys<I>
+where
+    I: Iterator<Item = (&'a K, &'a V)>,
+{
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I> DoubleEndedIterator for Keys<I>
+where
+    I: DoubleEndedIterator<Item = (&'a K, &'a V)>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, _)| key)
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I> ExactSizeIterator for Keys<I> where
+    I: ExactSizeIterator<Item = (&'a K, &'a V)>
+{
+}
+
+/// 值迭代器. 由产生键值对的迭代器适配而来.
+pub struct Values<I> {
+    inner: I,
+}
+
+impl<I> Values<I> {
+    pub(crate) fn new(inner: I) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I> Iterator for Values<I>
+where
+    I: Iterator<Item = (&'a K, &'a V)>,
+{
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I> DoubleEndedIterator for Values<I>
+where
+    I: DoubleEndedIterator<Item = (&'a K, &'a V)>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<'a, K: 'a, V: 'a, I> ExactSizeIterator for Values<I> where
+    I: ExactSizeIterator<Item = (&'a K, &'a V)>
+{
+}
+
+/// 中序遍历中尚未处理的片段: 尚未展开的子树，或已经从子树中分离出来的词条.
+pub(crate) enum Piece<T, E> {
+    Tree(T),
+    Entry(E),
+}
+
+/// 子树分裂的结果: `(左子树, 根词条, 右子树)`.
+pub(crate) type Split<T, E> = (Option<T>, E, Option<T>);
+
+/// 双端中序遍历的通用实现.
+///
+/// 用一个双端队列按中序保存所有尚未访问的片段. 每次从队首(或队尾)取出一个片段，
+/// 若它是子树，则把它展开为按中序排列的片段(二叉树为`左子树, 根词条, 右子树`)放回原处，直到取得一个词条为止.
+/// 每个结点恰好被展开一次，因此迭代的均摊复杂度为`O(1)`，而队列长度为`O(h)`(多路树为`O(Mh)`).
+pub(crate) struct InOrderPieces<T, E> {
+    pieces: VecDeque<Piece<T, E>>,
+    len: usize,
+}
+
+impl<T, E> InOrderPieces<T, E> {
+    /// 以`root`(若有)为根的子树创建，`len`为子树中的词条数.
+    pub(crate) fn new(root: Option<T>, len: usize) -> Self {
+        let mut pieces = VecDeque::new();
+        if let Some(root) = root {
+            pieces.push_back(Piece::Tree(root));
+        }
+        Self { pieces, len }
+    }
+
+    /// 剩余的词条数.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 取出中序最小的词条，`split`负责把子树分裂为`(左子树, 根词条, 右子树)`.
+    pub(crate) fn next<F>(&mut self, mut split: F) -> Option<E>
+    where
+        F: FnMut(T) -> Split<T, E>,
+    {
+        self.next_expand(|tree| Self::split_pieces(split(tree)))
+    }
+
+    /// 取出中序最大的词条，`split`的含义同`next`.
+    pub(crate) fn next_back<F>(&mut self, mut split: F) -> Option<E>
+    where
+        F: FnMut(T) -> Split<T, E>,
+    {
+        self.next_back_expand(|tree| Self::split_pieces(split(tree)))
+    }
+
+    /// 取出中序最小的词条，`expand`负责把子树展开为按中序排列的片段.
+    pub(crate) fn next_expand<F, I>(&mut self, mut expand: F) -> Option<E>
+    where
+        F: FnMut(T) -> I,
+        I: DoubleEndedIterator<Item = Piece<T, E>>,
+    {
+        while let Some(piece) = self.pieces.pop_front() {
+            match piece {
+                Piece::Entry(entry) => {
+                    self.len -= 1;
+                    return Some(entry);
+                }
+                Piece::Tree(tree) => {
+                    for piece in expand(tree).rev() {
+                        self.pieces.push_front(piece);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// 取出中序最大的词条，`expand`的含义同`next_expand`.
+    pub(crate) fn next_back_expand<F, I>(&mut self, mut expand: F) -> Option<E>
+    where
+        F: FnMut(T) -> I,
+        I: DoubleEndedIterator<Item = Piece<T, E>>,
+    {
+        while let Some(piece) = self.pieces.pop_back() {
+            match piece {
+                Piece::Entry(entry) => {
+                    self.len -= 1;
+                    return Some(entry);
+                }
+                Piece::Tree(tree) => {
+                    for piece in expand(tree) {
+                        self.pieces.push_back(piece);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn split_pieces(
+        (left, entry, right): Split<T, E>,
+    ) -> impl DoubleEndedIterator<Item = Piece<T, E>> {
+        left.map(Piece::Tree)
+            .into_iter()
+            .chain(iter::once(Piece::Entry(entry)))
+            .chain(right.map(Piece::Tree))
+    }
+}
+
+/// 将`len`个元素尽量均匀地分为若干组，每组不超过`max`个.
+pub(crate) fn chunk_sizes(len: usize, max: usize) -> impl Iterator<Item = usize> {
+    let count = (len - 1) / max + 1;
+    (0..count).map(move |idx| len / count + if idx < len % count { 1 } else { 0 })
+}
+
+/// 收集键值对，返回按键严格递增的序列.
+///
+/// 输入已经严格递增时只需`O(n)`. 否则若`sort`则先稳定排序，
+/// 相等的键只保留最后一个值，与逐个插入的结果一致.
+/// # Panics
+/// 不`sort`时键必须严格递增.
+pub(crate) fn sorted_entries<K: Ord, V, I>(iter: I, sort: bool) -> Vec<(K, V)>
+where
+    I: IntoIterator<Item = (K, V)>,
+{
+    let mut entries: Vec<_> = iter.into_iter().collect();
+    if entries.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+        return entries;
+    }
+    assert!(sort, "`from_sorted_iter`要求键严格递增.");
+    entries.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+    let mut sorted: Vec<(K, V)> = Vec::with_capacity(entries.len());
+    for (key, value) in entries {
+        match sorted.last_mut() {
+            Some(last) if last.0 == key => *last = (key, value),
+            _ => sorted.push((key, value)),
+        }
+    }
+    sorted
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::ch4::validate::Validation;
     use crate::ch4::{
-        doubly_linked_binary_tree::DoublyLinkedBinaryTree, linked_binary_tree::LinkedBinaryTree,
+        arena_binary_tree::ArenaBinaryTree, doubly_linked_binary_tree::DoublyLinkedBinaryTree,
+        linked_binary_tree::LinkedBinaryTree,
     };
     use ::test::Bencher;
     use avlt::AVLTreeMap;
     use bst::TreeMap;
+    use proptest::prelude::*;
+    use proptest::test_runner::TestCaseError;
     use random::Source;
     use st::SplayTreeMap;
     use std::collections::{BTreeMap, HashMap};
+    use std::fmt::Debug;
 
     const N: usize = 10000;
     const M: usize = 100;
     const SEEDS: [u64; 2] = [42, 69];
 
+    /// 以排好序的`Vec`为模型检查有序字典的迭代器: `iter`、`keys`、`values`、交替从两端迭代、`iter_mut`与`into_iter`.
+    /// 每次插入后都调用`validate`检查不变式.
+    pub(super) fn check_iter<T, K>(
+        data: HashMap<K, i64>,
+        validate: fn(&T) -> Validation,
+    ) -> Result<(), TestCaseError>
+    where
+        T: Map<K, i64> + IntoIterator<Item = (K, i64)>,
+        T::IntoIter: DoubleEndedIterator + ExactSizeIterator,
+        for<'a> &'a T: IntoIterator<Item = (&'a K, &'a i64)>,
+        for<'a> <&'a T as IntoIterator>::IntoIter: DoubleEndedIterator + ExactSizeIterator,
+        for<'a> &'a mut T: IntoIterator<Item = (&'a K, &'a mut i64)>,
+        for<'a> <&'a mut T as IntoIterator>::IntoIter: DoubleEndedIterator,
+        K: Ord + Clone + Debug,
+    {
+        let mut map = T::default();
+        for (k, v) in data.clone() {
+            map.insert(k, v);
+            prop_assert_eq!(validate(&map), Ok(()));
+        }
+        let mut expected = data.into_iter().collect::<Vec<_>>();
+        expected.sort();
+
+        // iter
+        let iter = || (&map).into_iter().map(|(k, v)| (k.clone(), *v));
+        prop_assert_eq!((&map).into_iter().len(), expected.len());
+        prop_assert!(iter().eq(expected.iter().cloned()));
+        prop_assert!(iter().rev().eq(expected.iter().rev().cloned()));
+        prop_assert!(Keys::new((&map).into_iter()).eq(expected.iter().map(|(k, _)| k)));
+        prop_assert!(Values::new((&map).into_iter()).eq(expected.iter().map(|(_, v)| v)));
+
+        // 交替从两端迭代
+        {
+            let mut iter = iter();
+            let mut expected_iter = expected.iter().cloned();
+            for idx in 0..=expected.len() {
+                prop_assert_eq!(iter.len(), expected_iter.len());
+                if idx % 2 == 0 {
+                    prop_assert_eq!(iter.next(), expected_iter.next());
+                } else {
+                    prop_assert_eq!(iter.next_back(), expected_iter.next_back());
+                }
+            }
+        }
+
+        // iter_mut
+        for (_, v) in &mut map {
+            *v = v.wrapping_add(1);
+        }
+        prop_assert!(Values::new((&map).into_iter())
+            .copied()
+            .eq(expected.iter().map(|(_, v)| v.wrapping_add(1))));
+        for (_, v) in (&mut map).into_iter().rev() {
+            *v = v.wrapping_sub(1);
+        }
+        prop_assert!((&map)
+            .into_iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .eq(expected.iter().cloned()));
+
+        // into_iter
+        let mut into_iter = map.into_iter();
+        prop_assert_eq!(into_iter.len(), expected.len());
+        prop_assert_eq!(into_iter.next_back(), expected.pop());
+        prop_assert!(into_iter.eq(expected.into_iter()));
+        Ok(())
+    }
+
     #[bench]
     fn bench_std_hm_random_insert(b: &mut Bencher) {
         let mut map = HashMap::<_, _>::default();
@@ -218,6 +562,50 @@ mod test {
         })
     }
 
+    #[bench]
+    fn bench_avl_arena_random_insert(b: &mut Bencher) {
+        let mut map = AVLTreeMap::<ArenaBinaryTree<_>, _, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.insert(source.read(), source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_avl_zipper_random_insert(b: &mut Bencher) {
+        let mut map = AVLTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.insert(source.read(), source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_avl_sequential_insert(b: &mut Bencher) {
+        b.iter(|| {
+            let mut map = AVLTreeMap::<DoublyLinkedBinaryTree<_>, _, _>::default();
+            for idx in 0..N {
+                map.insert(idx, idx);
+            }
+            map
+        })
+    }
+
+    #[bench]
+    fn bench_avl_from_sorted_iter(b: &mut Bencher) {
+        b.iter(|| {
+            AVLTreeMap::<DoublyLinkedBinaryTree<_>, _, _>::from_sorted_iter(
+                (0..N).map(|idx| (idx, idx)),
+            )
+        })
+    }
+
     #[bench]
     fn bench_std_hm_random_get(b: &mut Bencher) {
         let mut map = HashMap::<_, _>::default();
@@ -0,0 +1,353 @@
+use super::avlt::{self, AVLEntry, AVLTreeMap};
+use super::UnorderedMap;
+use crate::ch4::stats::Stats;
+use crate::ch4::validate::{self, Validation, Violation};
+use crate::ch4::{BinTreeMut, MoveParentBinTreeMut};
+use std::collections::{vec_deque, VecDeque};
+
+pub type MultiEntry<K, V> = AVLEntry<K, VecDeque<V>>;
+
+/// 允许重复键的有序字典.
+///
+/// 基于AVL树，每个键对应一个按插入顺序排列的非空值队列.
+pub struct TreeMultiMap<Tree: BinTreeMut<Elem = MultiEntry<K, V>>, K: Ord, V> {
+    map: AVLTreeMap<Tree, K, VecDeque<V>>,
+    len: usize,
+}
+
+impl<Tree, K: Ord, V> Default for TreeMultiMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = MultiEntry<K, V>>,
+{
+    fn default() -> Self {
+        Self {
+            map: AVLTreeMap::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<Tree, K: Ord, V> TreeMultiMap<Tree, K, V>
+where
+    Tree: Default
+        + MoveParentBinTreeMut<Elem = MultiEntry<K, V>>
+        + BinTreeMut<Elem = MultiEntry<K, V>>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 插入一个键值对. 键已存在时，新值排在它已有的值之后.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map
+            .get_mut_or_insert(key, VecDeque::new())
+            .push_back(value);
+        self.len += 1;
+    }
+
+    /// 按插入顺序返回键所对应的所有值的迭代器.
+    pub fn get_all(&self, key: &K) -> GetAll<'_, V> {
+        GetAll {
+            inner: self.map.get(key).map(VecDeque::iter),
+        }
+    }
+
+    /// 返回键所对应的值的个数.
+    pub fn count(&self, key: &K) -> usize {
+        self.map.get(key).map_or(0, VecDeque::len)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.get(key).is_some()
+    }
+
+    /// 移除键所对应的最早插入的值. 复杂度为`O(log n)`.
+    pub fn remove_one(&mut self, key: &K) -> Option<V> {
+        let values = self.map.get_mut(key)?;
+        let value = values.pop_front().unwrap();
+        if values.is_empty() {
+            self.map.remove(key);
+        }
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// 移除键所对应的所有值，并按插入顺序返回.
+    pub fn remove_all(&mut self, key: &K) -> Vec<V> {
+        let values = self.map.remove(key).unwrap_or_default();
+        self.len -= values.len();
+        values.into()
+    }
+
+    /// 返回值的总数.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 返回不同键的个数.
+    pub fn keys_len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// 返回底层AVL树累计的操作次数.
+    pub fn stats(&self) -> Stats {
+        self.map.stats()
+    }
+}
+
+impl<Tree, K: Ord, V> TreeMultiMap<Tree, K, V>
+where
+    Tree: BinTreeMut<Elem = MultiEntry<K, V>>,
+{
+    /// 按键的升序返回所有键值对的迭代器，相同键的值按插入顺序排列.
+    pub fn iter(&self) -> Iter<'_, Tree, K, V> {
+        Iter {
+            groups: self.map.iter(),
+            front: None,
+            back: None,
+            len: self.len,
+        }
+    }
+
+    /// 按升序返回所有不同键的迭代器.
+    pub fn keys(&self) -> avlt::Keys<'_, Tree, K, VecDeque<V>> {
+        self.map.keys()
+    }
+
+    /// 检查底层AVL树的不变式，以及记录的值的总数.
+    pub fn validate(&self) -> Validation {
+        let mut violations = self.map.validate().err().unwrap_or_default();
+        let actual = self.map.values().map(VecDeque::len).sum();
+        if actual != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual,
+            });
+        }
+        validate::into_validation(violations)
+    }
+}
+
+/// 键所对应的所有值的迭代器，见`TreeMultiMap::get_all`.
+pub struct GetAll<'a, V> {
+    inner: Option<vec_deque::Iter<'a, V>>,
+}
+
+impl<'a, V> Iterator for GetAll<'a, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner
+            .as_ref()
+            .map_or((0, Some(0)), Iterator::size_hint)
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for GetAll<'a, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next_back()
+    }
+}
+
+impl<'a, V> ExactSizeIterator for GetAll<'a, V> {}
+
+/// 多重字典的迭代器.
+pub struct Iter<'a, Tree: BinTreeMut<Elem = MultiEntry<K, V>>, K: Ord, V> {
+    groups: avlt::Iter<'a, Tree, K, VecDeque<V>>,
+    front: Option<(&'a K, vec_deque::Iter<'a, V>)>,
+    back: Option<(&'a K, vec_deque::Iter<'a, V>)>,
+    len: usize,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = MultiEntry<K, V>>, K: Ord, V> Iterator for Iter<'a, Tree, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, values)) = &mut self.front {
+                if let Some(value) = values.next() {
+                    self.len -= 1;
+                    return Some((*key, value));
+                }
+            }
+            match self.groups.next() {
+                Some((key, values)) => self.front = Some((key, values.iter())),
+                None => {
+                    // 剩余的值都在后端正在遍历的组中.
+                    let (key, values) = self.back.as_mut()?;
+                    let value = values.next()?;
+                    self.len -= 1;
+                    return Some((*key, value));
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = MultiEntry<K, V>>, K: Ord, V> DoubleEndedIterator
+    for Iter<'a, Tree, K, V>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, values)) = &mut self.back {
+                if let Some(value) = values.next_back() {
+                    self.len -= 1;
+                    return Some((*key, value));
+                }
+            }
+            match self.groups.next_back() {
+                Some((key, values)) => self.back = Some((key, values.iter())),
+                None => {
+                    let (key, values) = self.front.as_mut()?;
+                    let value = values.next_back()?;
+                    self.len -= 1;
+                    return Some((*key, value));
+                }
+            }
+        }
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = MultiEntry<K, V>>, K: Ord, V> ExactSizeIterator
+    for Iter<'a, Tree, K, V>
+{
+}
+
+impl<'a, Tree, K: Ord, V> IntoIterator for &'a TreeMultiMap<Tree, K, V>
+where
+    Tree: BinTreeMut<Elem = MultiEntry<K, V>>,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
+    type Tree = DoublyLinkedBinaryTree<MultiEntry<u8, u32>>;
+
+    #[test]
+    fn test_events() {
+        let mut events = TreeMultiMap::<DoublyLinkedBinaryTree<_>, _, _>::new();
+        events.insert(1000, "login");
+        events.insert(1005, "logout");
+        events.insert(1000, "click");
+        events.insert(1000, "scroll");
+        assert_eq!(events.len(), 4);
+        assert_eq!(events.keys_len(), 2);
+        assert!(events.get_all(&1000).eq(&["login", "click", "scroll"]));
+        assert_eq!(events.get_all(&1000).rev().next(), Some(&"scroll"));
+        assert_eq!(events.count(&1001), 0);
+        assert_eq!(events.remove_one(&1000), Some("login"));
+        assert_eq!(
+            events.iter().collect::<Vec<_>>(),
+            vec![(&1000, &"click"), (&1000, &"scroll"), (&1005, &"logout")]
+        );
+        assert_eq!(events.remove_all(&1000), vec!["click", "scroll"]);
+        assert!(!events.contains_key(&1000));
+        assert_eq!(events.remove_one(&1005), Some("logout"));
+        assert!(events.is_empty());
+        assert_eq!(events.validate(), Ok(()));
+    }
+
+    /// 多重字典的操作: 插入、移除一个值、移除所有值.
+    #[derive(Debug, Clone)]
+    enum Op {
+        Insert(u8, u32),
+        RemoveOne(u8),
+        RemoveAll(u8),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            3 => (0..16u8, any::<u32>()).prop_map(|(key, value)| Op::Insert(key, value)),
+            1 => (0..16u8).prop_map(Op::RemoveOne),
+            1 => (0..16u8).prop_map(Op::RemoveAll),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_multimap(ops in prop::collection::vec(op(), 0..200), queries: Vec<u8>) {
+            let mut map = TreeMultiMap::<Tree, _, _>::new();
+            let mut expected = BTreeMap::<u8, Vec<u32>>::new();
+            for op in ops {
+                match op {
+                    Op::Insert(key, value) => {
+                        map.insert(key, value);
+                        expected.entry(key).or_default().push(value);
+                    }
+                    Op::RemoveOne(key) => {
+                        let value = expected.get_mut(&key).map(|values| values.remove(0));
+                        if expected.get(&key).map_or(false, Vec::is_empty) {
+                            expected.remove(&key);
+                        }
+                        prop_assert_eq!(map.remove_one(&key), value);
+                    }
+                    Op::RemoveAll(key) => {
+                        prop_assert_eq!(map.remove_all(&key), expected.remove(&key).unwrap_or_default());
+                    }
+                }
+            }
+            prop_assert_eq!(map.validate(), Ok(()));
+            let pairs = expected
+                .iter()
+                .flat_map(|(key, values)| values.iter().map(move |value| (key, value)))
+                .collect::<Vec<_>>();
+            prop_assert_eq!(map.len(), pairs.len());
+            prop_assert_eq!(map.keys_len(), expected.len());
+            prop_assert!(map.keys().eq(expected.keys()));
+            prop_assert!(map.iter().eq(pairs.iter().copied()));
+            prop_assert!(map.iter().rev().eq(pairs.iter().rev().copied()));
+            for key in queries {
+                let values = expected.get(&key).map_or(&[][..], Vec::as_slice);
+                prop_assert_eq!(map.get_all(&key).len(), values.len());
+                prop_assert!(map.get_all(&key).eq(values.iter()));
+                prop_assert_eq!(map.count(&key), values.len());
+            }
+        }
+
+        #[test]
+        fn test_iter_both_ends(entrys: Vec<(u8, u32)>, fronts: Vec<bool>) {
+            let mut map = TreeMultiMap::<Tree, _, _>::new();
+            let mut expected = Vec::new();
+            for (key, value) in entrys {
+                map.insert(key % 8, value);
+                expected.push((key % 8, value));
+            }
+            // 稳定排序保持相同键的插入顺序.
+            expected.sort_by_key(|&(key, _)| key);
+            let mut iter = map.iter();
+            let mut expected = expected.iter().map(|(key, value)| (key, value));
+            for front in fronts {
+                prop_assert_eq!(iter.len(), expected.len());
+                if front {
+                    prop_assert_eq!(iter.next(), expected.next());
+                } else {
+                    prop_assert_eq!(iter.next_back(), expected.next_back());
+                }
+            }
+            prop_assert!(iter.eq(expected));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::convert::TryInto;
+
+/// 键值的编解码特质.
+///
+/// 编码必须是自界定的: `decode`只消耗`encode`写入的那些字节.
+pub trait Codec: Sized {
+    /// 将自身编码并追加到`buf`的末尾.
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    /// 从`buf`的头部解码，并将`buf`前移. 若字节不合法则返回`None`.
+    fn decode(buf: &mut &[u8]) -> Option<Self>;
+}
+
+/// 从`buf`的头部取出`len`个字节.
+pub(crate) fn take<'a>(buf: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if buf.len() < len {
+        None
+    } else {
+        let (head, tail) = buf.split_at(len);
+        *buf = tail;
+        Some(head)
+    }
+}
+
+impl Codec for u8 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(*self);
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        take(buf, 1).map(|bytes| bytes[0])
+    }
+}
+
+impl Codec for u32 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        take(buf, 4).map(|bytes| Self::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+impl Codec for u64 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        take(buf, 8).map(|bytes| Self::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+impl Codec for i32 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        take(buf, 4).map(|bytes| Self::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+impl Codec for i64 {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        take(buf, 8).map(|bytes| Self::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+/// 以`u32`长度前缀编码.
+impl Codec for Vec<u8> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (self.len() as u32).encode(buf);
+        buf.extend_from_slice(self);
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        let len = u32::decode(buf)? as usize;
+        take(buf, len).map(|bytes| bytes.to_vec())
+    }
+}
+
+/// 以`u32`长度前缀编码UTF-8字节.
+impl Codec for String {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (self.len() as u32).encode(buf);
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        let len = u32::decode(buf)? as usize;
+        take(buf, len).and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+    }
+}
+
+impl<A: Codec, B: Codec> Codec for (A, B) {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.0.encode(buf);
+        self.1.encode(buf);
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        let a = A::decode(buf)?;
+        let b = B::decode(buf)?;
+        Some((a, b))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn round_trip<T: Codec + PartialEq + std::fmt::Debug>(value: T) {
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        buf.push(0xff);
+        let mut bytes = &buf[..];
+        assert_eq!(T::decode(&mut bytes), Some(value));
+        assert_eq!(bytes, &[0xff]);
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut buf = Vec::new();
+        String::from("hello").encode(&mut buf);
+        buf.pop();
+        assert_eq!(String::decode(&mut &buf[..]), None);
+        assert_eq!(u64::decode(&mut &[1, 2, 3][..]), None);
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(a: u8, b: u32, c: i64, d: String, e: Vec<u8>) {
+            round_trip(a);
+            round_trip(b);
+            round_trip(c);
+            round_trip(d.clone());
+            round_trip(e.clone());
+            round_trip((d, e));
+        }
+    }
+}
@@ -0,0 +1,1017 @@
+//! 页式B树: 结点存放在本地文件的定长页面中，经由缓冲池访问.
+//!
+//! - 键与值通过`Codec`特质编码. 插入时限制词条的编码长度，使任何结点都能放进一个页面.
+//! - 缓冲池以LRU策略淘汰干净的页面，脏页只在提交时写回.
+//! - 提交经由预写日志(WAL)完成，崩溃后重新打开时总能得到某次提交后的状态.
+
+mod codec;
+mod pager;
+
+pub use codec::Codec;
+pub use pager::{PageId, PAGE_SIZE};
+
+use super::{Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use pager::{BufferPool, Header, Pager};
+use std::collections::HashSet;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs, process, thread};
+use thiserror::Error;
+
+/// 页式B树的错误类型.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// 读写文件失败.
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+
+    /// 文件头不合法.
+    #[error("not a valid paged b-tree file.")]
+    InvalidFile,
+
+    /// 页面内容无法解码.
+    #[error("page {0} is corrupted.")]
+    Corrupted(PageId),
+
+    /// 结点编码后超出了页面大小.
+    #[error("node in page {0} does not fit in a page.")]
+    PageOverflow(PageId),
+
+    /// 词条编码后的长度超出了上限.
+    #[error("entry of {0} bytes exceeds the limit of {1} bytes.")]
+    EntryTooLarge(usize, usize),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// 缓冲池的默认容量(页).
+pub const DEFAULT_CAPACITY: usize = 64;
+
+const NODE_PAGE: u8 = 1;
+const FREE_PAGE: u8 = 2;
+
+/// 结点页中除词条与孩子外的字节数: 页面类型与两个计数.
+const NODE_OVERHEAD: usize = 1 + 4 + 4;
+
+/// 分裂出的中间词条与右侧结点.
+type Split<K, V> = Option<(Entry<K, V>, PageId)>;
+
+struct Node<K: Ord, V> {
+    entrys: Vec<Entry<K, V>>,
+    childrens: Vec<PageId>,
+}
+
+impl<K: Ord + Codec, V: Codec> Node<K, V> {
+    fn is_leaf(&self) -> bool {
+        self.childrens.is_empty()
+    }
+
+    fn search(&self, key: &K) -> std::result::Result<usize, usize> {
+        self.entrys.binary_search_by(|entry| {
+            stats::record(|stats| stats.comparisons += 1);
+            entry.key.cmp(key)
+        })
+    }
+
+    fn encode(&self, id: PageId) -> Result<Vec<u8>> {
+        let mut page = vec![NODE_PAGE];
+        (self.entrys.len() as u32).encode(&mut page);
+        for entry in self.entrys.iter() {
+            entry.key.encode(&mut page);
+            entry.value.encode(&mut page);
+        }
+        (self.childrens.len() as u32).encode(&mut page);
+        for child in self.childrens.iter() {
+            child.encode(&mut page);
+        }
+        if page.len() > PAGE_SIZE {
+            return Err(Error::PageOverflow(id));
+        }
+        page.resize(PAGE_SIZE, 0);
+        Ok(page)
+    }
+
+    fn decode(mut page: &[u8]) -> Option<Self> {
+        if u8::decode(&mut page)? != NODE_PAGE {
+            return None;
+        }
+        let count = u32::decode(&mut page)?;
+        let mut entrys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let key = K::decode(&mut page)?;
+            let value = V::decode(&mut page)?;
+            entrys.push(Entry { key, value });
+        }
+        let count = u32::decode(&mut page)?;
+        let mut childrens = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            childrens.push(u64::decode(&mut page)?);
+        }
+        Some(Self { entrys, childrens })
+    }
+
+    fn load(pager: &Pager, id: PageId) -> Result<Self> {
+        Self::decode(&pager.read_page(id)?).ok_or(Error::Corrupted(id))
+    }
+}
+
+/// 空闲页: 记录空闲链表中的下一页.
+fn free_page(next: PageId) -> Vec<u8> {
+    let mut page = vec![FREE_PAGE];
+    next.encode(&mut page);
+    page.resize(PAGE_SIZE, 0);
+    page
+}
+
+/// 临时文件路径.
+fn temp_path() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+    env::temp_dir().join(format!("my-algo-pbt-{}-{}.db", process::id(), id))
+}
+
+/// `validate`的遍历状态.
+struct Validator<'a, K> {
+    /// 下一个词条的次序.
+    position: usize,
+    /// 第一个叶子的深度.
+    leaf_depth: Option<usize>,
+    /// 上一个词条的键.
+    last: Option<&'a K>,
+    /// 已访问的页面.
+    pages: HashSet<PageId>,
+    violations: Vec<Violation>,
+}
+
+/// 页式B树. `M`为B树的阶，即每个结点至多有`M - 1`个词条.
+///
+/// 除`flush`与`close`外的操作不会写文件. 当缓冲池中全是脏页而无法淘汰时，会自动进行一次提交.
+/// 析构时也会提交，若失败则`panic`，需要处理错误时应调用`close`.
+///
+/// 每个词条编码后至多`(PAGE_SIZE - 9 - 8M) / (M - 1)`字节，因此任何结点都能放进一个页面.
+/// 插入过大的词条会返回`EntryTooLarge`; 若经由`get_mut`使值变得过大，则提交时返回`PageOverflow`.
+///
+/// `Default`会在系统临时目录中创建一个新文件(失败时`panic`)，并在析构时连同日志一起删除，析构时不再提交.
+pub struct PagedBTreeMap<K: Ord + Codec, V: Codec, const M: usize> {
+    pager: Pager,
+    pool: BufferPool<Node<K, V>>,
+    header: Header,
+    /// 空闲页栈，栈顶是空闲链表的表头.
+    frees: Vec<PageId>,
+    /// `frees`中已写入文件的前缀长度.
+    committed: usize,
+    /// 自上次提交以来是否有修改.
+    modified: bool,
+    /// 由`Default`创建的临时文件，在析构时删除.
+    temporary: Option<PathBuf>,
+    stats: Counter,
+}
+
+impl<K: Ord + Codec, V: Codec, const M: usize> PagedBTreeMap<K, V, M> {
+    /// 打开(或创建)页式B树文件，缓冲池采用默认容量.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with_capacity(path, DEFAULT_CAPACITY)
+    }
+
+    /// 打开(或创建)页式B树文件，缓冲池至多缓存约`capacity`个页面.
+    pub fn open_with_capacity<P: AsRef<Path>>(path: P, capacity: usize) -> Result<Self> {
+        assert!(M >= 3, "B树的阶至少为`3`.");
+        let (pager, header) = Pager::open(path.as_ref())?;
+        let mut frees = Vec::new();
+        let mut next = header.free_head;
+        while next != 0 {
+            if frees.len() as u64 >= header.page_count {
+                return Err(Error::Corrupted(next));
+            }
+            frees.push(next);
+            let page = pager.read_page(next)?;
+            let mut buf = &page[..];
+            next = match (u8::decode(&mut buf), u64::decode(&mut buf)) {
+                (Some(FREE_PAGE), Some(next)) => next,
+                _ => return Err(Error::Corrupted(next)),
+            };
+        }
+        frees.reverse();
+        Ok(Self {
+            pager,
+            pool: BufferPool::new(capacity),
+            header,
+            committed: frees.len(),
+            frees,
+            modified: false,
+            temporary: None,
+            stats: Counter::default(),
+        })
+    }
+
+    /// 提交所有修改.
+    pub fn flush(&mut self) -> Result<()> {
+        if !self.modified {
+            return Ok(());
+        }
+        let pages = self.pending_pages()?;
+        self.pager.commit(pages)?;
+        self.pool.mark_clean();
+        self.committed = self.frees.len();
+        self.modified = false;
+        Ok(())
+    }
+
+    /// 提交所有修改并关闭文件.
+    pub fn close(mut self) -> Result<()> {
+        self.flush()
+    }
+
+    /// 待提交的页面: 脏结点、新释放的空闲页与文件头.
+    fn pending_pages(&mut self) -> Result<Vec<(PageId, Vec<u8>)>> {
+        let mut pages = Vec::new();
+        for (id, node) in self.pool.dirty_pages() {
+            pages.push((id, node.encode(id)?));
+        }
+        for idx in self.committed..self.frees.len() {
+            let next = if idx == 0 { 0 } else { self.frees[idx - 1] };
+            pages.push((self.frees[idx], free_page(next)));
+        }
+        self.header.free_head = self.frees.last().copied().unwrap_or(0);
+        pages.push((0, self.header.encode()));
+        pages.sort_by_key(|(id, _)| *id);
+        Ok(pages)
+    }
+
+    fn min_len() -> usize {
+        M - M / 2 - 1
+    }
+
+    /// 词条编码后的最大字节数: 保证有`M - 1`个词条与`M`个孩子的结点能放进一个页面.
+    pub fn max_entry_size() -> usize {
+        PAGE_SIZE.saturating_sub(NODE_OVERHEAD + 8 * M) / (M - 1)
+    }
+
+    fn node(&self, id: PageId) -> Result<&Node<K, V>> {
+        let pager = &self.pager;
+        self.pool.get(id, || Node::load(pager, id))
+    }
+
+    fn node_mut(&mut self, id: PageId) -> Result<&mut Node<K, V>> {
+        let pager = &self.pager;
+        self.modified = true;
+        self.pool.get_mut(id, || Node::load(pager, id))
+    }
+
+    /// 在修改前腾出缓冲池的空间.
+    fn reserve(&mut self) -> Result<()> {
+        if !self.pool.evict() {
+            self.flush()?;
+            self.pool.evict();
+        }
+        Ok(())
+    }
+
+    fn allocate(&mut self, node: Node<K, V>) -> PageId {
+        stats::record(|stats| stats.allocations += 1);
+        let id = match self.frees.pop() {
+            Some(id) => {
+                self.committed = self.committed.min(self.frees.len());
+                id
+            }
+            None => {
+                self.header.page_count += 1;
+                self.header.page_count - 1
+            }
+        };
+        self.modified = true;
+        self.pool.insert(id, node);
+        id
+    }
+
+    fn release(&mut self, id: PageId) -> Result<Node<K, V>> {
+        let node = match self.pool.remove(id) {
+            Some(node) => node,
+            None => Node::load(&self.pager, id)?,
+        };
+        self.modified = true;
+        self.frees.push(id);
+        Ok(node)
+    }
+
+    /// 在计数下执行一次需要`&mut self`的操作. 守卫会借用计数器，因此先将它取出.
+    fn counted<R, F: FnOnce(&mut Self) -> Result<R>>(&mut self, f: F) -> Result<R> {
+        let stats = mem::take(&mut self.stats);
+        let result = {
+            let _stats = stats.enter();
+            f(self)
+        };
+        self.stats = stats;
+        result
+    }
+
+    /// 查找键所在的页面与下标. 途经的结点只是临时借用，不会被钉在缓冲池中.
+    fn find(&self, key: &K) -> Result<Option<(PageId, usize)>> {
+        let _stats = self.stats.enter();
+        let pager = &self.pager;
+        let mut id = self.header.root;
+        while id != 0 {
+            let load = || Node::load(pager, id);
+            // `Ok`为找到的下标，`Err`为下一个要访问的结点.
+            let step = self.pool.with(id, load, |node| match node.search(key) {
+                Ok(idx) => Ok(idx),
+                Err(_) if node.is_leaf() => Err(0),
+                Err(idx) => Err(node.childrens[idx]),
+            })?;
+            match step {
+                Ok(idx) => return Ok(Some((id, idx))),
+                Err(child) => id = child,
+            }
+        }
+        Ok(None)
+    }
+
+    /// 返回键所对应的值的引用. 值所在的页面会留在缓冲池中，直到下一次修改.
+    pub fn try_get(&self, key: &K) -> Result<Option<&V>> {
+        match self.find(key)? {
+            Some((id, idx)) => Ok(Some(&self.node(id)?.entrys[idx].value)),
+            None => Ok(None),
+        }
+    }
+
+    /// 以键所对应的值调用`f`. 与`try_get`不同，访问过的页面之后都可以被淘汰.
+    pub fn get_with<R, F: FnOnce(&V) -> R>(&self, key: &K, f: F) -> Result<Option<R>> {
+        match self.find(key)? {
+            Some((id, idx)) => {
+                let pager = &self.pager;
+                let load = || Node::load(pager, id);
+                self.pool
+                    .with(id, load, |node| Some(f(&node.entrys[idx].value)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 返回键所对应的值的可变引用.
+    pub fn try_get_mut(&mut self, key: &K) -> Result<Option<&mut V>> {
+        match self.find(key)? {
+            Some((id, idx)) => Ok(Some(&mut self.node_mut(id)?.entrys[idx].value)),
+            None => Ok(None),
+        }
+    }
+
+    /// 插入一个键值对，返回被替换的旧值. 词条过大时返回`EntryTooLarge`，且不做任何修改.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>> {
+        self.counted(|map| map.insert_entry(key, value))
+    }
+
+    fn insert_entry(&mut self, key: K, value: V) -> Result<Option<V>> {
+        let mut buf = Vec::new();
+        key.encode(&mut buf);
+        value.encode(&mut buf);
+        if buf.len() > Self::max_entry_size() {
+            return Err(Error::EntryTooLarge(buf.len(), Self::max_entry_size()));
+        }
+        self.reserve()?;
+        let entry = Entry { key, value };
+        let root = self.header.root;
+        if root == 0 {
+            self.header.root = self.allocate(Node {
+                entrys: vec![entry],
+                childrens: Vec::new(),
+            });
+        } else {
+            let (old, split) = self.insert_at(root, entry)?;
+            if old.is_some() {
+                return Ok(old);
+            }
+            if let Some((mid, right)) = split {
+                self.header.root = self.allocate(Node {
+                    entrys: vec![mid],
+                    childrens: vec![root, right],
+                });
+            }
+        }
+        self.header.len += 1;
+        Ok(None)
+    }
+
+    /// 插入到以`id`为根的子树中，返回被替换的旧值与上溢时分裂出的部分.
+    fn insert_at(&mut self, id: PageId, entry: Entry<K, V>) -> Result<(Option<V>, Split<K, V>)> {
+        let node = self.node(id)?;
+        match node.search(&entry.key) {
+            Ok(idx) => {
+                let value = &mut self.node_mut(id)?.entrys[idx].value;
+                return Ok((Some(mem::replace(value, entry.value)), None));
+            }
+            Err(idx) if node.is_leaf() => self.node_mut(id)?.entrys.insert(idx, entry),
+            Err(idx) => {
+                let child = node.childrens[idx];
+                match self.insert_at(child, entry)? {
+                    (old, None) => return Ok((old, None)),
+                    (_, Some((mid, right))) => {
+                        let node = self.node_mut(id)?;
+                        node.entrys.insert(idx, mid);
+                        node.childrens.insert(idx + 1, right);
+                    }
+                }
+            }
+        }
+        let node = self.node_mut(id)?;
+        if node.entrys.len() < M {
+            return Ok((None, None));
+        }
+        let mid = node.entrys.len() / 2;
+        let entrys = node.entrys.split_off(mid + 1);
+        let mid_entry = node.entrys.pop().unwrap();
+        let childrens = if node.is_leaf() {
+            Vec::new()
+        } else {
+            node.childrens.split_off(mid + 1)
+        };
+        stats::record(|stats| stats.splits += 1);
+        let right = self.allocate(Node { entrys, childrens });
+        Ok((None, Some((mid_entry, right))))
+    }
+
+    /// 移除一个键值对，并返回它的值.
+    pub fn try_remove(&mut self, key: &K) -> Result<Option<V>> {
+        self.counted(|map| map.remove_entry(key))
+    }
+
+    fn remove_entry(&mut self, key: &K) -> Result<Option<V>> {
+        self.reserve()?;
+        let root = self.header.root;
+        if root == 0 {
+            return Ok(None);
+        }
+        let removed = self.remove_at(root, key)?;
+        if removed.is_some() {
+            self.header.len -= 1;
+            let node = self.node(root)?;
+            if node.entrys.is_empty() {
+                self.header.root = node.childrens.first().copied().unwrap_or(0);
+                self.release(root)?;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn remove_at(&mut self, id: PageId, key: &K) -> Result<Option<V>> {
+        let node = self.node(id)?;
+        match node.search(key) {
+            Ok(idx) if node.is_leaf() => Ok(Some(self.node_mut(id)?.entrys.remove(idx).value)),
+            Ok(idx) => {
+                // 用后继替换，再修复右子树.
+                let child = node.childrens[idx + 1];
+                let succ = self.remove_min(child)?;
+                let old = mem::replace(&mut self.node_mut(id)?.entrys[idx], succ);
+                self.fix_child(id, idx + 1)?;
+                Ok(Some(old.value))
+            }
+            Err(_) if node.is_leaf() => Ok(None),
+            Err(idx) => {
+                let child = node.childrens[idx];
+                let removed = self.remove_at(child, key)?;
+                if removed.is_some() {
+                    self.fix_child(id, idx)?;
+                }
+                Ok(removed)
+            }
+        }
+    }
+
+    fn remove_min(&mut self, id: PageId) -> Result<Entry<K, V>> {
+        let node = self.node(id)?;
+        if node.is_leaf() {
+            Ok(self.node_mut(id)?.entrys.remove(0))
+        } else {
+            let child = node.childrens[0];
+            let entry = self.remove_min(child)?;
+            self.fix_child(id, 0)?;
+            Ok(entry)
+        }
+    }
+
+    /// 修复`id`的第`idx`个孩子的下溢: 先尝试向左右兄弟借，否则与兄弟合并.
+    fn fix_child(&mut self, id: PageId, idx: usize) -> Result<()> {
+        let node = self.node(id)?;
+        let child = node.childrens[idx];
+        let left = idx.checked_sub(1).map(|idx| node.childrens[idx]);
+        let right = node.childrens.get(idx + 1).copied();
+        if self.node(child)?.entrys.len() >= Self::min_len() {
+            return Ok(());
+        }
+        if let Some(left) = left {
+            if self.node(left)?.entrys.len() > Self::min_len() {
+                let left = self.node_mut(left)?;
+                let entry = left.entrys.pop().unwrap();
+                let grandchild = left.childrens.pop();
+                let sep = mem::replace(&mut self.node_mut(id)?.entrys[idx - 1], entry);
+                let child = self.node_mut(child)?;
+                child.entrys.insert(0, sep);
+                child.childrens.splice(0..0, grandchild);
+                return Ok(());
+            }
+        }
+        if let Some(right) = right {
+            if self.node(right)?.entrys.len() > Self::min_len() {
+                let right = self.node_mut(right)?;
+                let entry = right.entrys.remove(0);
+                let grandchild = if right.is_leaf() {
+                    None
+                } else {
+                    Some(right.childrens.remove(0))
+                };
+                let sep = mem::replace(&mut self.node_mut(id)?.entrys[idx], entry);
+                let child = self.node_mut(child)?;
+                child.entrys.push(sep);
+                child.childrens.extend(grandchild);
+                return Ok(());
+            }
+        }
+        self.merge(id, if left.is_some() { idx - 1 } else { idx })
+    }
+
+    /// 将`id`的第`idx + 1`个孩子连同分隔词条并入第`idx`个孩子，并释放前者的页面.
+    fn merge(&mut self, id: PageId, idx: usize) -> Result<()> {
+        stats::record(|stats| stats.merges += 1);
+        let node = self.node_mut(id)?;
+        let sep = node.entrys.remove(idx);
+        let right = node.childrens.remove(idx + 1);
+        let left = node.childrens[idx];
+        let right = self.release(right)?;
+        let left = self.node_mut(left)?;
+        left.entrys.push(sep);
+        left.entrys.extend(right.entrys);
+        left.childrens.extend(right.childrens);
+        Ok(())
+    }
+
+    /// 检查结点规模、孩子数、键的顺序、叶子深度、长度，以及每个结点独占一个已分配且不空闲的页面.
+    ///
+    /// 访问过的页面会留在缓冲池中，直到下一次修改. 读文件失败时返回错误.
+    pub fn validate(&self) -> Result<Validation> {
+        let mut validator = Validator {
+            position: 0,
+            leaf_depth: None,
+            last: None,
+            pages: HashSet::new(),
+            violations: Vec::new(),
+        };
+        if self.header.root != 0 {
+            self.validate_at(self.header.root, 0, &mut validator)?;
+        }
+        if validator.position != self.header.len as usize {
+            validator.violations.push(Violation::Length {
+                recorded: self.header.len as usize,
+                actual: validator.position,
+            });
+        }
+        Ok(validate::into_validation(validator.violations))
+    }
+
+    /// 按键的顺序遍历页面`id`中的子树. 根结点的深度为0.
+    fn validate_at<'a>(
+        &'a self,
+        id: PageId,
+        depth: usize,
+        validator: &mut Validator<'a, K>,
+    ) -> Result<()> {
+        let position = validator.position;
+        if id == 0
+            || id >= self.header.page_count
+            || self.frees.contains(&id)
+            || !validator.pages.insert(id)
+        {
+            validator
+                .violations
+                .push(Violation::Page { position, page: id });
+            return Ok(());
+        }
+        let node = self.node(id)?;
+        let min = if depth == 0 { 1 } else { Self::min_len() };
+        if node.entrys.len() < min || node.entrys.len() >= M {
+            validator.violations.push(Violation::Occupancy {
+                position,
+                entrys: node.entrys.len(),
+                min,
+                max: M - 1,
+            });
+        }
+        if node.is_leaf() {
+            match validator.leaf_depth {
+                Some(expected) if expected != depth => {
+                    validator.violations.push(Violation::LeafDepth {
+                        position,
+                        depth,
+                        expected,
+                    })
+                }
+                Some(_) => {}
+                None => validator.leaf_depth = Some(depth),
+            }
+        } else if node.childrens.len() != node.entrys.len() + 1 {
+            validator.violations.push(Violation::Childrens {
+                position,
+                entrys: node.entrys.len(),
+                childrens: node.childrens.len(),
+            });
+        }
+        for idx in 0..=node.entrys.len() {
+            if let Some(&child) = node.childrens.get(idx) {
+                self.validate_at(child, depth + 1, validator)?;
+            }
+            if let Some(entry) = node.entrys.get(idx) {
+                if matches!(validator.last, Some(last) if *last >= entry.key) {
+                    validator.violations.push(Violation::Unordered {
+                        position: validator.position,
+                    });
+                }
+                validator.last = Some(&entry.key);
+                validator.position += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// 按键的升序返回迭代器.
+    pub fn iter(&self) -> Iter<'_, K, V, M> {
+        let mut iter = Iter {
+            map: self,
+            stack: Vec::new(),
+            len: self.header.len as usize,
+        };
+        iter.descend(self.header.root);
+        iter
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, K, V, M> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, K, V, M> {
+        super::Values::new(self.iter())
+    }
+}
+
+impl<K: Ord + Codec, V: Codec, const M: usize> Drop for PagedBTreeMap<K, V, M> {
+    fn drop(&mut self) {
+        match self.temporary.take() {
+            Some(path) => {
+                let _ = fs::remove_file(Pager::wal_path(&path));
+                let _ = fs::remove_file(path);
+            }
+            None => {
+                if let Err(err) = self.flush() {
+                    if !thread::panicking() {
+                        panic!("析构时提交失败: {}. 需要处理错误时应调用`close`.", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 在临时目录中创建一个新文件，析构时删除.
+impl<K: Ord + Codec, V: Codec, const M: usize> Default for PagedBTreeMap<K, V, M> {
+    fn default() -> Self {
+        let path = temp_path();
+        let mut map = Self::open(&path).unwrap();
+        map.temporary = Some(path);
+        map
+    }
+}
+
+/// 读写文件失败或插入的词条过大时会`panic`. 需要处理错误时应使用对应的`try_`方法.
+impl<K: Ord + Codec + Clone, V: Codec, const M: usize> UnorderedMap<K, V>
+    for PagedBTreeMap<K, V, M>
+{
+    fn get(&self, key: &K) -> Option<&V> {
+        self.try_get(key).unwrap()
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.try_get_mut(key).unwrap()
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.try_insert(key, value).unwrap()
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        if self.find(&key).unwrap().is_none() {
+            self.try_insert(key.clone(), default).unwrap();
+        }
+        self.try_get_mut(&key).unwrap().unwrap()
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.try_remove(key).unwrap()
+    }
+
+    fn len(&self) -> usize {
+        self.header.len as usize
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(PagedBTreeMap::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<K: Ord + Codec + Clone, V: Codec, const M: usize> Map<K, V> for PagedBTreeMap<K, V, M> {}
+
+/// 页式B树的中序迭代器. 读文件失败时会`panic`.
+pub struct Iter<'a, K: Ord + Codec, V: Codec, const M: usize> {
+    map: &'a PagedBTreeMap<K, V, M>,
+    stack: Vec<(&'a Node<K, V>, usize)>,
+    len: usize,
+}
+
+impl<'a, K: Ord + Codec, V: Codec, const M: usize> Iter<'a, K, V, M> {
+    /// 沿最左侧的路径下降.
+    fn descend(&mut self, mut id: PageId) {
+        while id != 0 {
+            let node = self.map.node(id).unwrap();
+            self.stack.push((node, 0));
+            id = node.childrens.first().copied().unwrap_or(0);
+        }
+    }
+}
+
+impl<'a, K: Ord + Codec, V: Codec, const M: usize> Iterator for Iter<'a, K, V, M> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let &mut (node, ref mut idx) = self.stack.last_mut()?;
+            if *idx < node.entrys.len() {
+                let entry = &node.entrys[*idx];
+                *idx += 1;
+                if !node.is_leaf() {
+                    let child = node.childrens[*idx];
+                    self.descend(child);
+                }
+                self.len -= 1;
+                return Some((&entry.key, &entry.value));
+            }
+            self.stack.pop();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K: Ord + Codec, V: Codec, const M: usize> ExactSizeIterator for Iter<'a, K, V, M> {}
+
+pub type Keys<'a, K, V, const M: usize> = super::Keys<Iter<'a, K, V, M>>;
+
+pub type Values<'a, K, V, const M: usize> = super::Values<Iter<'a, K, V, M>>;
+
+impl<'a, K: Ord + Codec, V: Codec, const M: usize> IntoIterator for &'a PagedBTreeMap<K, V, M> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, M>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, HashMap};
+    use std::fs::OpenOptions;
+
+    type PagedMap = PagedBTreeMap<u32, String, 4>;
+
+    /// 删除测试文件.
+    fn remove_files(path: &Path) {
+        let _ = fs::remove_file(Pager::wal_path(path));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_persistence() {
+        let path = temp_path();
+        let mut map = PagedMap::open_with_capacity(&path, 4).unwrap();
+        for key in 0..500 {
+            map.insert(key, key.to_string());
+        }
+        for key in (0..500).step_by(3) {
+            assert_eq!(map.remove(&key), Some(key.to_string()));
+        }
+        assert_eq!(map.validate().unwrap(), Ok(()));
+        map.close().unwrap();
+
+        let mut map = PagedMap::open(&path).unwrap();
+        assert_eq!(map.validate().unwrap(), Ok(()));
+        assert_eq!(map.len(), 333);
+        assert!(map
+            .iter()
+            .map(|(key, _)| *key)
+            .eq((0..500).filter(|key| key % 3 != 0)));
+        for key in 250..500 {
+            map.remove(&key);
+        }
+        map.close().unwrap();
+
+        // 已释放的页面会被复用.
+        let mut map = PagedMap::open(&path).unwrap();
+        let frees = map.frees.len();
+        assert!(frees > 0);
+        for key in 0..500 {
+            map.insert(key, key.to_string());
+        }
+        assert!(map.frees.len() < frees);
+        map.close().unwrap();
+
+        let map = PagedMap::open(&path).unwrap();
+        assert!(map.iter().map(|(key, _)| *key).eq(0..500));
+        drop(map);
+        remove_files(&path);
+    }
+
+    #[test]
+    fn test_wal_replay() {
+        let path = temp_path();
+        let mut map = PagedMap::open(&path).unwrap();
+        for key in 0..100 {
+            map.insert(key, key.to_string());
+        }
+        // 模拟日志写入完成后、写回主文件前崩溃.
+        let pages = map.pending_pages().unwrap();
+        map.pager.log(&pages).unwrap();
+        mem::forget(map);
+
+        let map = PagedMap::open(&path).unwrap();
+        assert_eq!(map.validate().unwrap(), Ok(()));
+        assert!(map.iter().map(|(key, _)| *key).eq(0..100));
+        drop(map);
+        remove_files(&path);
+    }
+
+    #[test]
+    fn test_torn_wal() {
+        let path = temp_path();
+        let mut map = PagedMap::open(&path).unwrap();
+        for key in 0..100 {
+            map.insert(key, key.to_string());
+        }
+        map.flush().unwrap();
+        for key in 100..200 {
+            map.insert(key, key.to_string());
+        }
+        // 模拟写日志的过程中崩溃.
+        let pages = map.pending_pages().unwrap();
+        map.pager.log(&pages).unwrap();
+        mem::forget(map);
+        let wal = OpenOptions::new()
+            .write(true)
+            .open(Pager::wal_path(&path))
+            .unwrap();
+        wal.set_len(wal.metadata().unwrap().len() / 2).unwrap();
+
+        let map = PagedMap::open(&path).unwrap();
+        assert_eq!(map.validate().unwrap(), Ok(()));
+        assert!(map.iter().map(|(key, _)| *key).eq(0..100));
+        drop(map);
+        remove_files(&path);
+    }
+
+    #[test]
+    fn test_page_overflow() {
+        type BlobMap = PagedBTreeMap<u32, Vec<u8>, 4>;
+        let path = temp_path();
+        let mut map = BlobMap::open(&path).unwrap();
+        // 键与长度前缀共占8字节.
+        let limit = BlobMap::max_entry_size() - 8;
+        for key in 0..100 {
+            map.insert(key, vec![key as u8; limit]);
+        }
+        match map.try_insert(100, vec![0; limit + 1]) {
+            Err(Error::EntryTooLarge(size, max)) => assert!(size > max),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(map.len(), 100);
+        map.flush().unwrap();
+
+        // 经由`get_mut`变大的值在提交时才能发现，改回后即可正常提交.
+        map.get_mut(&0).unwrap().resize(PAGE_SIZE, 0);
+        match map.flush() {
+            Err(Error::PageOverflow(_)) => {}
+            other => panic!("{:?}", other),
+        }
+        map.get_mut(&0).unwrap().truncate(limit);
+        map.close().unwrap();
+
+        let map = BlobMap::open(&path).unwrap();
+        assert_eq!(map.validate().unwrap(), Ok(()));
+        assert!(map
+            .iter()
+            .all(|(key, value)| *value == vec![*key as u8; limit]));
+        drop(map);
+        remove_files(&path);
+    }
+
+    #[test]
+    fn test_read_eviction() {
+        let path = temp_path();
+        let mut map = PagedMap::open_with_capacity(&path, 4).unwrap();
+        for key in 0..500 {
+            map.insert(key, key.to_string());
+        }
+        map.close().unwrap();
+
+        let map = PagedMap::open_with_capacity(&path, 4).unwrap();
+        for key in 0..500 {
+            assert_eq!(
+                map.get_with(&key, |value| value.clone()).unwrap(),
+                Some(key.to_string())
+            );
+            assert!(map.pool.len() <= 4);
+        }
+        // `get`只钉住值所在的页面，途经的结点仍可被淘汰.
+        assert_eq!(map.get(&0), Some(&0.to_string()));
+        assert!(map.pool.len() <= 4);
+        drop(map);
+        remove_files(&path);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = PagedBTreeMap::<u32, u32, 3>::default();
+        for key in 0..100 {
+            map.insert(key, key);
+        }
+        let stats = map.stats();
+        assert!(stats.splits > 0);
+        assert!(stats.allocations > stats.splits);
+        assert_eq!(stats.merges, 0);
+        for key in 0..100 {
+            map.remove(&key);
+        }
+        assert!(map.stats().merges > 0);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+        #[test]
+        fn test_paged_btree_map(
+            entrys: Vec<(u32, String)>,
+            removes: Vec<prop::sample::Index>,
+            capacity in 1..8usize,
+        ) {
+            let path = temp_path();
+            let mut map = PagedMap::open_with_capacity(&path, capacity).unwrap();
+            let mut expected = HashMap::new();
+            for (key, value) in entrys.iter() {
+                prop_assert_eq!(map.insert(*key, value.clone()), expected.insert(*key, value.clone()));
+                prop_assert_eq!(map.validate().unwrap(), Ok(()));
+            }
+            for idx in removes.iter() {
+                let key = entrys.get(idx.index(entrys.len().max(1))).map_or(0, |entry| entry.0);
+                prop_assert_eq!(map.remove(&key), expected.remove(&key));
+                prop_assert_eq!(map.len(), expected.len());
+                prop_assert_eq!(map.validate().unwrap(), Ok(()));
+            }
+            map.close().unwrap();
+
+            let mut map = PagedMap::open_with_capacity(&path, capacity).unwrap();
+            prop_assert_eq!(map.validate().unwrap(), Ok(()));
+            let sorted = expected.iter().collect::<BTreeMap<_, _>>();
+            prop_assert!(map.iter().eq(sorted.into_iter()));
+            for (key, value) in expected.iter() {
+                prop_assert_eq!(map.get(key), Some(value));
+                map.get_mut_or_insert(*key, String::new()).push('!');
+            }
+            prop_assert!(map.values().all(|value| value.ends_with('!')));
+            drop(map);
+            remove_files(&path);
+        }
+
+        #[test]
+        fn test_default(entrys: Vec<(u32, u32)>) {
+            let mut map = PagedBTreeMap::<u32, u32, 3>::default();
+            let mut expected = HashMap::new();
+            for (key, value) in entrys.iter() {
+                prop_assert_eq!(map.insert(*key, *value), expected.insert(*key, *value));
+                prop_assert_eq!(map.validate().unwrap(), Ok(()));
+            }
+            for (key, _) in entrys.iter().step_by(2) {
+                prop_assert_eq!(map.remove(key), expected.remove(key));
+                prop_assert_eq!(map.validate().unwrap(), Ok(()));
+            }
+            prop_assert_eq!(map.iter().count(), expected.len());
+        }
+    }
+}
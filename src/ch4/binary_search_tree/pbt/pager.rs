@@ -0,0 +1,476 @@
+use super::codec::{take, Codec};
+use super::{Error, Result};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+
+/// 页面大小(字节).
+pub const PAGE_SIZE: usize = 4096;
+
+/// 页号. 第`0`页是文件头，因此`0`也用来表示"无".
+pub type PageId = u64;
+
+const MAGIC: &[u8; 8] = b"MYALGOPB";
+const WAL_MAGIC: &[u8; 8] = b"MYALGOWL";
+const WAL_COMMIT: &[u8; 8] = b"COMMIT!!";
+
+/// FNV-1a校验和.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// 文件头(第`0`页).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Header {
+    pub(crate) root: PageId,
+    pub(crate) len: u64,
+    pub(crate) page_count: u64,
+    pub(crate) free_head: PageId,
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            root: 0,
+            len: 0,
+            page_count: 1,
+            free_head: 0,
+        }
+    }
+}
+
+impl Header {
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut page = MAGIC.to_vec();
+        (PAGE_SIZE as u64).encode(&mut page);
+        self.root.encode(&mut page);
+        self.len.encode(&mut page);
+        self.page_count.encode(&mut page);
+        self.free_head.encode(&mut page);
+        page.resize(PAGE_SIZE, 0);
+        page
+    }
+
+    fn decode(page: &[u8]) -> Result<Self> {
+        let mut buf = page;
+        if take(&mut buf, MAGIC.len()) != Some(&MAGIC[..])
+            || u64::decode(&mut buf) != Some(PAGE_SIZE as u64)
+        {
+            return Err(Error::InvalidFile);
+        }
+        let mut field = || u64::decode(&mut buf).ok_or(Error::InvalidFile);
+        Ok(Self {
+            root: field()?,
+            len: field()?,
+            page_count: field()?,
+            free_head: field()?,
+        })
+    }
+}
+
+/// 页面文件及其预写日志(WAL).
+///
+/// 提交时先把所有脏页的完整映像连同校验和写入日志并落盘，再原地写回主文件并落盘，最后清空日志.
+/// 打开时若日志完整则重放(重放是幂等的)，否则说明上次提交在写日志时中断，直接丢弃.
+/// 因此主文件总是处于某次提交之后的一致状态.
+pub(crate) struct Pager {
+    file: File,
+    wal: File,
+}
+
+impl Pager {
+    /// 日志文件的路径: 在主文件名后追加`.wal`.
+    pub(crate) fn wal_path(path: &Path) -> PathBuf {
+        let mut wal = path.as_os_str().to_owned();
+        wal.push(".wal");
+        PathBuf::from(wal)
+    }
+
+    /// 打开(或创建)页面文件，完成崩溃恢复后返回文件头.
+    pub(crate) fn open(path: &Path) -> Result<(Self, Header)> {
+        let options = {
+            let mut options = OpenOptions::new();
+            options.read(true).write(true).create(true);
+            options
+        };
+        let mut pager = Self {
+            file: options.open(path)?,
+            wal: options.open(Self::wal_path(path))?,
+        };
+        pager.recover()?;
+        let header = if pager.file.metadata()?.len() == 0 {
+            let header = Header::default();
+            pager.commit(vec![(0, header.encode())])?;
+            header
+        } else {
+            Header::decode(&pager.read_page(0)?)?
+        };
+        Ok((pager, header))
+    }
+
+    pub(crate) fn read_page(&self, id: PageId) -> Result<Vec<u8>> {
+        let mut page = vec![0; PAGE_SIZE];
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(id * PAGE_SIZE as u64))?;
+        file.read_exact(&mut page)?;
+        Ok(page)
+    }
+
+    /// 把页面映像写入日志并落盘. 只有完整写入的日志才会在恢复时被重放.
+    pub(crate) fn log(&mut self, pages: &[(PageId, Vec<u8>)]) -> Result<()> {
+        let mut record = WAL_MAGIC.to_vec();
+        (pages.len() as u64).encode(&mut record);
+        for (id, page) in pages {
+            debug_assert_eq!(page.len(), PAGE_SIZE);
+            id.encode(&mut record);
+            record.extend_from_slice(page);
+        }
+        checksum(&record).encode(&mut record);
+        record.extend_from_slice(WAL_COMMIT);
+        self.wal.set_len(0)?;
+        self.wal.seek(SeekFrom::Start(0))?;
+        self.wal.write_all(&record)?;
+        self.wal.sync_all()?;
+        Ok(())
+    }
+
+    /// 原地写回页面并落盘，之后清空日志.
+    fn apply(&mut self, pages: &[(PageId, Vec<u8>)]) -> Result<()> {
+        for (id, page) in pages {
+            self.file.seek(SeekFrom::Start(id * PAGE_SIZE as u64))?;
+            self.file.write_all(page)?;
+        }
+        self.file.sync_all()?;
+        self.wal.set_len(0)?;
+        self.wal.sync_all()?;
+        Ok(())
+    }
+
+    /// 原子地提交一批页面.
+    pub(crate) fn commit(&mut self, pages: Vec<(PageId, Vec<u8>)>) -> Result<()> {
+        self.log(&pages)?;
+        self.apply(&pages)
+    }
+
+    /// 解析完整的日志记录. 不完整或校验失败时返回`None`.
+    fn parse_log(record: &[u8]) -> Option<Vec<(PageId, Vec<u8>)>> {
+        let body_len = record.len().checked_sub(16)?;
+        let (body, mut trailer) = record.split_at(body_len);
+        if u64::decode(&mut trailer)? != checksum(body) || trailer != WAL_COMMIT {
+            return None;
+        }
+        let mut buf = body;
+        if take(&mut buf, WAL_MAGIC.len())? != WAL_MAGIC {
+            return None;
+        }
+        let count = u64::decode(&mut buf)?;
+        let mut pages = Vec::new();
+        for _ in 0..count {
+            let id = u64::decode(&mut buf)?;
+            pages.push((id, take(&mut buf, PAGE_SIZE)?.to_vec()));
+        }
+        if buf.is_empty() {
+            Some(pages)
+        } else {
+            None
+        }
+    }
+
+    fn recover(&mut self) -> Result<()> {
+        let mut record = Vec::new();
+        self.wal.seek(SeekFrom::Start(0))?;
+        self.wal.read_to_end(&mut record)?;
+        if record.is_empty() {
+            return Ok(());
+        }
+        match Self::parse_log(&record) {
+            Some(pages) => self.apply(&pages),
+            None => {
+                self.wal.set_len(0)?;
+                self.wal.sync_all()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+struct Frame<T> {
+    id: PageId,
+    content: T,
+    dirty: bool,
+    tick: Cell<u64>,
+    /// 是否有引用经由`get`交给了调用者.
+    pinned: Cell<bool>,
+    /// 正在进行的临时访问(`with`)数.
+    borrows: Cell<usize>,
+}
+
+impl<T> Frame<T> {
+    fn new(id: PageId, content: T, dirty: bool) -> Self {
+        Self {
+            id,
+            content,
+            dirty,
+            tick: Cell::new(0),
+            pinned: Cell::new(false),
+            borrows: Cell::new(0),
+        }
+    }
+
+    /// 帧中没有任何外部引用，可以被淘汰.
+    fn is_evictable(&self) -> bool {
+        !self.dirty && !self.pinned.get() && self.borrows.get() == 0
+    }
+}
+
+/// 缓冲池: 以LRU策略缓存解码后的页面，只淘汰干净的页面.
+///
+/// `get`交出的引用与`&self`同寿，因此它所在的帧会被钉住，直到下一次`evict`(需要`&mut self`，此时不可能还有引用).
+/// `with`只在回调期间借出页面，之后即可淘汰. 两者在载入页面后都会淘汰未被钉住的干净页面，
+/// 所以只读访问也不会使缓冲池无限增长.
+pub(crate) struct BufferPool<T> {
+    frames: RefCell<HashMap<PageId, NonNull<Frame<T>>>>,
+    /// 按最近访问时间排序的页号.
+    lru: RefCell<BTreeMap<u64, PageId>>,
+    clock: Cell<u64>,
+    capacity: usize,
+}
+
+impl<T> BufferPool<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            frames: RefCell::new(HashMap::new()),
+            lru: RefCell::new(BTreeMap::new()),
+            clock: Cell::new(0),
+            capacity,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.frames.borrow().len()
+    }
+
+    fn touch(&self, frame: &Frame<T>) {
+        let mut lru = self.lru.borrow_mut();
+        lru.remove(&frame.tick.get());
+        self.clock.set(self.clock.get() + 1);
+        frame.tick.set(self.clock.get());
+        lru.insert(frame.tick.get(), frame.id);
+    }
+
+    /// 取得页面所在的帧，若不在池中则用`load`载入.
+    fn frame<F>(&self, id: PageId, load: F) -> Result<NonNull<Frame<T>>>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        let cached = self.frames.borrow().get(&id).copied();
+        let posi = match cached {
+            Some(posi) => posi,
+            None => {
+                let frame = Box::new(Frame::new(id, load()?, false));
+                let posi = NonNull::new(Box::leak(frame)).unwrap();
+                self.frames.borrow_mut().insert(id, posi);
+                posi
+            }
+        };
+        // Safety: 刚刚取得的帧还没有被淘汰的机会.
+        self.touch(unsafe { posi.as_ref() });
+        Ok(posi)
+    }
+
+    /// 取得页面的引用，并钉住页面直到下一次`evict`.
+    pub(crate) fn get<F>(&self, id: PageId, load: F) -> Result<&T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        // Safety: 钉住的帧只会在`&mut self`的方法中被释放，在`&self`期间也不会被修改.
+        let frame = unsafe { &*self.frame(id, load)?.as_ptr() };
+        frame.pinned.set(true);
+        self.shrink();
+        Ok(&frame.content)
+    }
+
+    /// 在回调期间借出页面，之后页面即可被淘汰.
+    pub(crate) fn with<F, G, R>(&self, id: PageId, load: F, f: G) -> Result<R>
+    where
+        F: FnOnce() -> Result<T>,
+        G: FnOnce(&T) -> R,
+    {
+        // Safety: 借出期间帧不会被淘汰. 若`f`中途`panic`，帧只是不再能被淘汰.
+        let frame = unsafe { &*self.frame(id, load)?.as_ptr() };
+        frame.borrows.set(frame.borrows.get() + 1);
+        let result = f(&frame.content);
+        frame.borrows.set(frame.borrows.get() - 1);
+        self.shrink();
+        Ok(result)
+    }
+
+    /// 取得页面的可变引用，并将它标记为脏页.
+    pub(crate) fn get_mut<F>(&mut self, id: PageId, load: F) -> Result<&mut T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        // Safety: `&mut self`保证了引用的唯一性.
+        let frame = unsafe { &mut *self.frame(id, load)?.as_ptr() };
+        frame.dirty = true;
+        Ok(&mut frame.content)
+    }
+
+    /// 放入一个新的脏页.
+    pub(crate) fn insert(&mut self, id: PageId, content: T) {
+        self.remove(id);
+        let frame = Box::new(Frame::new(id, content, true));
+        self.touch(&frame);
+        let posi = NonNull::new(Box::leak(frame)).unwrap();
+        self.frames.get_mut().insert(id, posi);
+    }
+
+    /// 丢弃页面，返回它的内容(若在池中).
+    pub(crate) fn remove(&mut self, id: PageId) -> Option<T> {
+        let posi = self.frames.get_mut().remove(&id)?;
+        // Safety: 帧已从池中摘下，且没有其它引用.
+        let frame = unsafe { Box::from_raw(posi.as_ptr()) };
+        self.lru.get_mut().remove(&frame.tick.get());
+        Some(frame.content)
+    }
+
+    /// 所有脏页.
+    pub(crate) fn dirty_pages(&self) -> impl '_ + Iterator<Item = (PageId, &T)> {
+        let frames = self.frames.borrow().values().copied().collect::<Vec<_>>();
+        frames.into_iter().filter_map(|posi| {
+            // Safety: 在`&self`期间帧不会被释放.
+            let frame = unsafe { &*posi.as_ptr() };
+            if frame.dirty {
+                Some((frame.id, &frame.content))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// 将所有页面标记为干净的(它们已被提交).
+    pub(crate) fn mark_clean(&mut self) {
+        for posi in self.frames.get_mut().values() {
+            // Safety: `&mut self`保证了引用的唯一性.
+            unsafe {
+                (*posi.as_ptr()).dirty = false;
+            }
+        }
+    }
+
+    /// 解除所有页面的钉住状态，再按LRU顺序淘汰干净的页面，直到不超过容量.
+    /// 若因脏页过多而无法做到，则返回`false`.
+    pub(crate) fn evict(&mut self) -> bool {
+        for posi in self.frames.get_mut().values() {
+            // Safety: `&mut self`保证了此前交出的引用都已失效.
+            unsafe { posi.as_ref() }.pinned.set(false);
+        }
+        self.shrink()
+    }
+
+    /// 按LRU顺序淘汰可淘汰的页面，直到不超过容量. 返回是否做到.
+    fn shrink(&self) -> bool {
+        let mut frames = self.frames.borrow_mut();
+        let mut lru = self.lru.borrow_mut();
+        let excess = frames.len().saturating_sub(self.capacity);
+        let victims = lru
+            .iter()
+            .filter(|(_, id)| {
+                // Safety: 帧只会在这里以及`&mut self`的方法中被释放.
+                unsafe { frames[id].as_ref() }.is_evictable()
+            })
+            .map(|(tick, id)| (*tick, *id))
+            .take(excess)
+            .collect::<Vec<_>>();
+        for (tick, id) in victims.iter() {
+            lru.remove(tick);
+            let posi = frames.remove(id).unwrap();
+            // Safety: 可淘汰的帧没有任何外部引用.
+            unsafe {
+                drop(Box::from_raw(posi.as_ptr()));
+            }
+        }
+        victims.len() == excess
+    }
+}
+
+impl<T> Drop for BufferPool<T> {
+    fn drop(&mut self) {
+        for (_, posi) in self.frames.get_mut().drain() {
+            // Safety: 每个帧恰好被释放一次.
+            unsafe {
+                drop(Box::from_raw(posi.as_ptr()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lru() {
+        let mut pool = BufferPool::new(2);
+        for id in 1..=3 {
+            pool.insert(id, id * 10);
+        }
+        // 脏页不会被淘汰.
+        assert!(!pool.evict());
+        assert_eq!(pool.len(), 3);
+        pool.mark_clean();
+        // 访问第`1`页，第`2`页成为最久未使用的页面，读取时即被淘汰.
+        assert_eq!(pool.get(1, || unreachable!()).ok(), Some(&10));
+        assert_eq!(pool.len(), 2);
+        // 钉住的第`1`页不会被淘汰.
+        assert_eq!(pool.get(2, || Ok(20)).ok(), Some(&20));
+        assert_eq!(pool.get(1, || unreachable!()).ok(), Some(&10));
+        assert_eq!(pool.get(3, || Ok(30)).ok(), Some(&30));
+        assert_eq!(pool.len(), 3);
+        assert!(pool.evict());
+        assert_eq!(pool.len(), 2);
+        // 临时访问不会钉住页面.
+        for id in 4..10 {
+            assert_eq!(
+                pool.with(id, || Ok(id * 10), |content| *content).ok(),
+                Some(id * 10)
+            );
+            assert_eq!(pool.len(), 2);
+        }
+        // 借出期间的页面不会被淘汰.
+        pool.with(
+            1,
+            || Ok(10),
+            |outer| {
+                assert_eq!(pool.get(2, || Ok(20)).ok(), Some(&20));
+                assert_eq!(pool.with(3, || Ok(30), |inner| *inner).ok(), Some(30));
+                assert_eq!(*outer, 10);
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let pages = vec![(1, vec![1; PAGE_SIZE]), (3, vec![3; PAGE_SIZE])];
+        let mut record = WAL_MAGIC.to_vec();
+        (pages.len() as u64).encode(&mut record);
+        for (id, page) in pages.iter() {
+            id.encode(&mut record);
+            record.extend_from_slice(page);
+        }
+        checksum(&record).encode(&mut record);
+        record.extend_from_slice(WAL_COMMIT);
+        assert_eq!(Pager::parse_log(&record), Some(pages));
+        // 任何截断或损坏都会使日志失效.
+        assert_eq!(Pager::parse_log(&record[..record.len() - 1]), None);
+        record[20] ^= 1;
+        assert_eq!(Pager::parse_log(&record), None);
+    }
+}
@@ -0,0 +1,502 @@
+use super::Entry;
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+type Link<K, V> = Option<Rc<Node<K, V>>>;
+
+struct Node<K: Ord, V> {
+    entry: Rc<Entry<K, V>>,
+    height: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn height<K: Ord, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn node<K: Ord, V>(entry: Rc<Entry<K, V>>, left: Link<K, V>, right: Link<K, V>) -> Rc<Node<K, V>> {
+    stats::record(|stats| stats.allocations += 1);
+    Rc::new(Node {
+        entry,
+        height: height(&left).max(height(&right)) + 1,
+        left,
+        right,
+    })
+}
+
+/// 以`entry`为根、`left`与`right`为子树构造新结点，必要时通过旋转恢复AVL平衡.
+///
+/// 要求两棵子树的高度至多相差`2`. 旋转时只复制沿途的结点，词条本身是共享的.
+fn balance<K: Ord, V>(
+    entry: Rc<Entry<K, V>>,
+    left: Link<K, V>,
+    right: Link<K, V>,
+) -> Rc<Node<K, V>> {
+    let (lhs, rhs) = (height(&left), height(&right));
+    if lhs > rhs + 1 {
+        let l = left.unwrap();
+        if height(&l.left) >= height(&l.right) {
+            stats::record(|stats| stats.rotations += 1);
+            node(
+                l.entry.clone(),
+                l.left.clone(),
+                Some(node(entry, l.right.clone(), right)),
+            )
+        } else {
+            stats::record(|stats| stats.rotations += 2);
+            let lr = l.right.as_ref().unwrap();
+            node(
+                lr.entry.clone(),
+                Some(node(l.entry.clone(), l.left.clone(), lr.left.clone())),
+                Some(node(entry, lr.right.clone(), right)),
+            )
+        }
+    } else if rhs > lhs + 1 {
+        let r = right.unwrap();
+        if height(&r.right) >= height(&r.left) {
+            stats::record(|stats| stats.rotations += 1);
+            node(
+                r.entry.clone(),
+                Some(node(entry, left, r.left.clone())),
+                r.right.clone(),
+            )
+        } else {
+            stats::record(|stats| stats.rotations += 2);
+            let rl = r.left.as_ref().unwrap();
+            node(
+                rl.entry.clone(),
+                Some(node(entry, left, rl.left.clone())),
+                Some(node(r.entry.clone(), rl.right.clone(), r.right.clone())),
+            )
+        }
+    } else {
+        node(entry, left, right)
+    }
+}
+
+fn compare<K: Ord>(lhs: &K, rhs: &K) -> Ordering {
+    stats::record(|stats| stats.comparisons += 1);
+    lhs.cmp(rhs)
+}
+
+/// 插入到子树中，返回新的子树以及键是否已存在.
+fn insert_at<K: Ord, V>(link: &Link<K, V>, entry: Entry<K, V>) -> (Rc<Node<K, V>>, bool) {
+    match link {
+        None => (node(Rc::new(entry), None, None), false),
+        Some(n) => match compare(&entry.key, &n.entry.key) {
+            Ordering::Less => {
+                let (left, existed) = insert_at(&n.left, entry);
+                (
+                    balance(n.entry.clone(), Some(left), n.right.clone()),
+                    existed,
+                )
+            }
+            Ordering::Greater => {
+                let (right, existed) = insert_at(&n.right, entry);
+                (
+                    balance(n.entry.clone(), n.left.clone(), Some(right)),
+                    existed,
+                )
+            }
+            Ordering::Equal => (node(Rc::new(entry), n.left.clone(), n.right.clone()), true),
+        },
+    }
+}
+
+/// 摘除子树中的最小词条，返回该词条与剩下的子树.
+fn remove_min<K: Ord, V>(n: &Node<K, V>) -> (Rc<Entry<K, V>>, Link<K, V>) {
+    match &n.left {
+        None => (n.entry.clone(), n.right.clone()),
+        Some(left) => {
+            let (min, left) = remove_min(left);
+            (min, Some(balance(n.entry.clone(), left, n.right.clone())))
+        }
+    }
+}
+
+/// 从子树中删除键，返回新的子树. 若键不存在则返回`None`.
+fn remove_at<K: Ord, V>(link: &Link<K, V>, key: &K) -> Option<Link<K, V>> {
+    let n = link.as_ref()?;
+    match compare(key, &n.entry.key) {
+        Ordering::Less => {
+            let left = remove_at(&n.left, key)?;
+            Some(Some(balance(n.entry.clone(), left, n.right.clone())))
+        }
+        Ordering::Greater => {
+            let right = remove_at(&n.right, key)?;
+            Some(Some(balance(n.entry.clone(), n.left.clone(), right)))
+        }
+        Ordering::Equal => match (&n.left, &n.right) {
+            (None, child) | (child, None) => Some(child.clone()),
+            (Some(_), Some(right)) => {
+                let (succ, right) = remove_min(right);
+                Some(Some(balance(succ, n.left.clone(), right)))
+            }
+        },
+    }
+}
+
+/// 中序遍历，检查键的顺序、记录的高度与平衡因子. 返回`(树高, 子树中最小的键, 子树中最大的键)`.
+fn validate_at<'a, K: Ord, V>(
+    link: &'a Link<K, V>,
+    position: &mut usize,
+    violations: &mut Vec<Violation>,
+) -> Option<(usize, &'a K, &'a K)> {
+    let node = link.as_ref()?;
+    let key = &node.entry.key;
+    let left = validate_at(&node.left, position, violations);
+    let current = *position;
+    *position += 1;
+    let right = validate_at(&node.right, position, violations);
+    let unordered = matches!(left, Some((_, _, max)) if max >= key)
+        || matches!(right, Some((_, min, _)) if min <= key);
+    if unordered {
+        violations.push(Violation::Unordered { position: current });
+    }
+    let (lh, rh) = (
+        left.map_or(0, |left| left.0),
+        right.map_or(0, |right| right.0),
+    );
+    let height = lh.max(rh) + 1;
+    if node.height != height {
+        violations.push(Violation::Height {
+            position: current,
+            recorded: node.height as isize,
+            actual: height as isize,
+        });
+    }
+    if lh > rh + 1 || rh > lh + 1 {
+        violations.push(Violation::BalanceFactor {
+            position: current,
+            factor: lh as isize - rh as isize,
+        });
+    }
+    let min = left.map_or(key, |(_, min, _)| min);
+    let max = right.map_or(key, |(_, _, max)| max);
+    Some((height, min, max))
+}
+
+/// 持久化字典: 基于结构共享的AVL树.
+///
+/// `insert`与`remove`不修改原字典，而是返回一个新版本. 新版本只复制根到目标结点路径上的`O(log n)`个结点，
+/// 其余结点(以及所有词条)都与旧版本共享，因此`clone`是`O(1)`的，保留任意多个历史版本的代价也很低.
+pub struct PersistentMap<K: Ord, V> {
+    root: Link<K, V>,
+    len: usize,
+    /// 新版本继承旧版本的计数，产生它的操作只记在新版本上.
+    stats: Counter,
+}
+
+impl<K: Ord, V> Clone for PersistentMap<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+            stats: self.inherit_stats(),
+        }
+    }
+}
+
+impl<K: Ord, V> Default for PersistentMap<K, V> {
+    fn default() -> Self {
+        Self {
+            root: None,
+            len: 0,
+            stats: Counter::default(),
+        }
+    }
+}
+
+impl<K: Ord, V> PersistentMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 继承了本版本计数的新计数器.
+    fn inherit_stats(&self) -> Counter {
+        let counter = Counter::default();
+        let stats = self.stats.get();
+        counter.record(|total| *total += stats);
+        counter
+    }
+
+    /// 返回键所对应的值的引用.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match compare(key, &node.entry.key) {
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => link = &node.right,
+                Ordering::Equal => return Some(&node.entry.value),
+            }
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 返回字典的大小.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 字典是否为空.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 返回插入键值对后的新版本. 若键已存在，新版本中它的值被更新.
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let stats = self.inherit_stats();
+        let (root, existed) = {
+            let _stats = stats.enter();
+            insert_at(&self.root, Entry { key, value })
+        };
+        Self {
+            root: Some(root),
+            len: if existed { self.len } else { self.len + 1 },
+            stats,
+        }
+    }
+
+    /// 返回删除键后的新版本. 若键不存在，新版本与原字典完全共享.
+    pub fn remove(&self, key: &K) -> Self {
+        let stats = self.inherit_stats();
+        let root = {
+            let _stats = stats.enter();
+            remove_at(&self.root, key)
+        };
+        match root {
+            Some(root) => Self {
+                root,
+                len: self.len - 1,
+                stats,
+            },
+            None => Self {
+                root: self.root.clone(),
+                len: self.len,
+                stats,
+            },
+        }
+    }
+
+    /// 两个版本是否共享同一个根，即内容必然相同.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(lhs), Some(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (lhs, rhs) => lhs.is_none() && rhs.is_none(),
+        }
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.iter().next_back()
+    }
+
+    /// 按键的升序返回迭代器.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            fronts: Vec::new(),
+            backs: Vec::new(),
+            len: self.len,
+        };
+        iter.push_fronts(&self.root);
+        iter.push_backs(&self.root);
+        iter
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, K, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回累计的操作次数，包括产生本版本的所有历史操作. 未启用`stats`特性时总是返回全零.
+    pub fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+
+    /// 检查键的顺序与长度，以及每个结点记录的高度与AVL平衡.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let mut position = 0;
+        validate_at(&self.root, &mut position, &mut violations);
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        validate::into_validation(violations)
+    }
+}
+
+/// 持久化字典的中序迭代器.
+pub struct Iter<'a, K: Ord, V> {
+    fronts: Vec<&'a Node<K, V>>,
+    backs: Vec<&'a Node<K, V>>,
+    len: usize,
+}
+
+impl<'a, K: Ord, V> Iter<'a, K, V> {
+    fn push_fronts(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.fronts.push(node);
+            link = &node.left;
+        }
+    }
+
+    fn push_backs(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.backs.push(node);
+            link = &node.right;
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.fronts.pop()?;
+        self.push_fronts(&node.right);
+        self.len -= 1;
+        Some((&node.entry.key, &node.entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K: Ord, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.backs.pop()?;
+        self.push_backs(&node.left);
+        self.len -= 1;
+        Some((&node.entry.key, &node.entry.value))
+    }
+}
+
+impl<'a, K: Ord, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+pub type Keys<'a, K, V> = super::Keys<Iter<'a, K, V>>;
+
+pub type Values<'a, K, V> = super::Values<Iter<'a, K, V>>;
+
+impl<'a, K: Ord, V> IntoIterator for &'a PersistentMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, HashSet};
+
+    fn collect_nodes<K: Ord, V>(link: &Link<K, V>, nodes: &mut HashSet<*const Node<K, V>>) {
+        if let Some(node) = link {
+            nodes.insert(Rc::as_ptr(node));
+            collect_nodes(&node.left, nodes);
+            collect_nodes(&node.right, nodes);
+        }
+    }
+
+    #[test]
+    fn test_sharing() {
+        let map = (0..1000).fold(PersistentMap::new(), |map, key| map.insert(key, key));
+        assert_eq!(map.validate(), Ok(()));
+        let height = height(&map.root);
+        let mut olds = HashSet::new();
+        collect_nodes(&map.root, &mut olds);
+        for updated in [map.insert(500, 0), map.insert(1000, 0), map.remove(&0)].iter() {
+            let mut news = HashSet::new();
+            collect_nodes(&updated.root, &mut news);
+            // 新版本只复制了`O(log n)`个结点.
+            assert!(news.difference(&olds).count() <= 2 * height);
+        }
+        assert!(map.remove(&1000).ptr_eq(&map));
+        assert_eq!(map.len(), 1000);
+        assert!(map.iter().map(|(key, _)| *key).eq(0..1000));
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let map = (0..100).fold(PersistentMap::new(), |map, key| map.insert(key, ()));
+        let stats = map.stats();
+        assert!(stats.rotations > 0);
+        assert!(stats.allocations >= 100);
+        // 旧版本的计数不受新版本的操作影响.
+        let updated = map.insert(100, ());
+        assert_eq!(map.stats(), stats);
+        assert!(updated.stats().allocations > stats.allocations);
+    }
+
+    proptest! {
+        #[test]
+        fn test_versions(ops: Vec<(bool, u8, u32)>) {
+            let mut versions = vec![PersistentMap::new()];
+            let mut expecteds = vec![BTreeMap::new()];
+            for (is_insert, key, value) in ops {
+                let (map, mut expected) = (versions.last().unwrap(), expecteds.last().unwrap().clone());
+                let map = if is_insert {
+                    expected.insert(key, value);
+                    map.insert(key, value)
+                } else {
+                    expected.remove(&key);
+                    map.remove(&key)
+                };
+                prop_assert_eq!(map.validate(), Ok(()));
+                versions.push(map);
+                expecteds.push(expected);
+            }
+            // 所有历史版本都保持不变.
+            for (map, expected) in versions.iter().zip(expecteds.iter()) {
+                prop_assert_eq!(map.validate(), Ok(()));
+                prop_assert_eq!(map.len(), expected.len());
+                prop_assert!(map.iter().eq(expected.iter()));
+                prop_assert!(map.iter().rev().eq(expected.iter().rev()));
+                for key in 0..=u8::MAX {
+                    prop_assert_eq!(map.get(&key), expected.get(&key));
+                }
+            }
+        }
+
+        #[test]
+        fn test_iter(keys: Vec<u32>, take: usize) {
+            let map = keys.iter().fold(PersistentMap::new(), |map, key| map.insert(*key, ()));
+            let expected = keys.iter().map(|key| (*key, ())).collect::<BTreeMap<_, _>>();
+            let take = take % (map.len() + 1);
+            let mut iter = map.keys();
+            let fronts = iter.by_ref().take(take).collect::<Vec<_>>();
+            let backs = iter.rev().collect::<Vec<_>>();
+            prop_assert_eq!(fronts.len() + backs.len(), expected.len());
+            prop_assert!(fronts.into_iter().chain(backs.into_iter().rev()).eq(expected.keys()));
+        }
+    }
+}
@@ -0,0 +1,508 @@
+use super::{Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use std::mem;
+
+/// 一条边: 标签非空，且同一结点的各边标签首字节互不相同.
+struct Edge<K: Ord, V> {
+    label: Vec<u8>,
+    node: Node<K, V>,
+}
+
+struct Node<K: Ord, V> {
+    entry: Option<Entry<K, V>>,
+    /// 按标签首字节升序排列的边.
+    edges: Vec<Edge<K, V>>,
+    /// 子树中的键数.
+    count: usize,
+}
+
+impl<K: Ord, V> Default for Node<K, V> {
+    fn default() -> Self {
+        Self {
+            entry: None,
+            edges: Vec::new(),
+            count: 0,
+        }
+    }
+}
+
+/// 两个字节串的公共前缀长度.
+fn common_prefix(lhs: &[u8], rhs: &[u8]) -> usize {
+    lhs.iter().zip(rhs).take_while(|(a, b)| a == b).count()
+}
+
+impl<K: Ord, V> Node<K, V> {
+    /// 查找首字节为`byte`的边.
+    fn search(&self, byte: u8) -> Result<usize, usize> {
+        self.edges.binary_search_by_key(&byte, |edge| edge.label[0])
+    }
+
+    /// 沿`bytes`下降，返回到达的结点. 若`bytes`终止于某条边的中间，则返回该边指向的结点.
+    fn descend(&self, bytes: &[u8]) -> Option<&Self> {
+        let mut node = self;
+        let mut rest = bytes;
+        while let Some(byte) = rest.first() {
+            let edge = &node.edges[node.search(*byte).ok()?];
+            let len = common_prefix(&edge.label, rest);
+            if len < rest.len() && len < edge.label.len() {
+                return None;
+            }
+            rest = &rest[len..];
+            node = &edge.node;
+        }
+        Some(node)
+    }
+
+    /// 精确地沿`bytes`下降，返回键为`bytes`的结点.
+    fn find(&self, bytes: &[u8]) -> Option<&Self> {
+        let mut node = self;
+        let mut rest = bytes;
+        while let Some(byte) = rest.first() {
+            let edge = &node.edges[node.search(*byte).ok()?];
+            if !rest.starts_with(&edge.label) {
+                return None;
+            }
+            rest = &rest[edge.label.len()..];
+            node = &edge.node;
+        }
+        Some(node)
+    }
+
+    fn find_mut(&mut self, bytes: &[u8]) -> Option<&mut Self> {
+        let mut node = self;
+        let mut rest = bytes;
+        while let Some(byte) = rest.first() {
+            let idx = node.search(*byte).ok()?;
+            let edge = &mut node.edges[idx];
+            if !rest.starts_with(&edge.label) {
+                return None;
+            }
+            rest = &rest[edge.label.len()..];
+            node = &mut edge.node;
+        }
+        Some(node)
+    }
+
+    /// 插入词条，`rest`是键在当前结点之下的部分.
+    fn insert(&mut self, rest: &[u8], entry: Entry<K, V>) -> Option<V> {
+        let old = match rest.first() {
+            None => match &mut self.entry {
+                Some(old) => return Some(mem::replace(&mut old.value, entry.value)),
+                None => {
+                    self.entry = Some(entry);
+                    None
+                }
+            },
+            Some(byte) => match self.search(*byte) {
+                Err(idx) => {
+                    stats::record(|stats| stats.allocations += 1);
+                    let mut node = Node::default();
+                    node.insert(&[], entry);
+                    self.edges.insert(
+                        idx,
+                        Edge {
+                            label: rest.to_vec(),
+                            node,
+                        },
+                    );
+                    None
+                }
+                Ok(idx) => {
+                    let edge = &mut self.edges[idx];
+                    let len = common_prefix(&edge.label, rest);
+                    if len < edge.label.len() {
+                        // 在公共前缀处将边分裂为两段.
+                        stats::record(|stats| stats.allocations += 1);
+                        let suffix = edge.label.split_off(len);
+                        let lower = mem::take(&mut edge.node);
+                        edge.node.count = lower.count;
+                        edge.node.edges.push(Edge {
+                            label: suffix,
+                            node: lower,
+                        });
+                    }
+                    edge.node.insert(&rest[len..], entry)
+                }
+            },
+        };
+        if old.is_none() {
+            self.count += 1;
+        }
+        old
+    }
+
+    /// 删除键，并剪除变空的子树、合并只有一条出边的空结点.
+    fn remove(&mut self, rest: &[u8]) -> Option<V> {
+        let removed = match rest.first() {
+            None => self.entry.take().map(|entry| entry.value),
+            Some(byte) => {
+                let idx = self.search(*byte).ok()?;
+                let edge = &mut self.edges[idx];
+                if !rest.starts_with(&edge.label) {
+                    return None;
+                }
+                let removed = edge.node.remove(&rest[edge.label.len()..]);
+                if edge.node.count == 0 {
+                    self.edges.remove(idx);
+                } else if edge.node.entry.is_none() && edge.node.edges.len() == 1 {
+                    let lower = edge.node.edges.pop().unwrap();
+                    edge.label.extend(lower.label);
+                    edge.node = lower.node;
+                }
+                removed
+            }
+        };
+        if removed.is_some() {
+            self.count -= 1;
+        }
+        removed
+    }
+}
+
+impl<K: Ord + AsRef<[u8]>, V> Node<K, V> {
+    /// 先序遍历，检查边的标签与顺序、词条所在的路径、记录的键数以及路径压缩. 返回子树的键数.
+    fn validate_at(
+        &self,
+        path: &mut Vec<u8>,
+        is_root: bool,
+        position: &mut usize,
+        violations: &mut Vec<Violation>,
+    ) -> usize {
+        let current = *position;
+        match &self.entry {
+            Some(entry) => {
+                if entry.key.as_ref() != &path[..] {
+                    violations.push(Violation::Path { position: current });
+                }
+                *position += 1;
+            }
+            // 不存键的非根结点至少有两条出边，否则应被剪除或与唯一的孩子合并.
+            None if !is_root && self.edges.len() < 2 => {
+                violations.push(Violation::Redundant { position: current });
+            }
+            None => {}
+        }
+        if self.edges.iter().any(|edge| edge.label.is_empty()) {
+            violations.push(Violation::Redundant { position: current });
+        } else if self
+            .edges
+            .windows(2)
+            .any(|pair| pair[0].label[0] >= pair[1].label[0])
+        {
+            violations.push(Violation::Unordered { position: current });
+        }
+        let mut count = self.entry.iter().count();
+        for edge in self.edges.iter() {
+            let len = path.len();
+            path.extend_from_slice(&edge.label);
+            count += edge.node.validate_at(path, false, position, violations);
+            path.truncate(len);
+        }
+        if self.count != count {
+            violations.push(Violation::Size {
+                position: current,
+                recorded: self.count,
+                actual: count,
+            });
+        }
+        count
+    }
+}
+
+/// 基数树(Radix Tree)，即压缩的字典树: 只有一个孩子且不存键的结点被合并到边中.
+///
+/// 键可以是任何`AsRef<[u8]>`的类型，如`String`与`Vec<u8>`. 要求键的`Ord`与字节序一致.
+pub struct RadixTreeMap<K: Ord + AsRef<[u8]>, V> {
+    root: Node<K, V>,
+    stats: Counter,
+}
+
+impl<K: Ord + AsRef<[u8]>, V> Default for RadixTreeMap<K, V> {
+    fn default() -> Self {
+        Self {
+            root: Node::default(),
+            stats: Counter::default(),
+        }
+    }
+}
+
+impl<K: Ord + AsRef<[u8]>, V> RadixTreeMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按键的升序返回以`prefix`为前缀的所有键值对.
+    pub fn prefix_iter<P: AsRef<[u8]>>(&self, prefix: P) -> Iter<'_, K, V> {
+        Iter::new(self.root.descend(prefix.as_ref()))
+    }
+
+    /// 以`prefix`为前缀的键数.
+    pub fn count_prefix<P: AsRef<[u8]>>(&self, prefix: P) -> usize {
+        self.root
+            .descend(prefix.as_ref())
+            .map_or(0, |node| node.count)
+    }
+
+    /// 返回是`key`的前缀的最长的键及其值.
+    pub fn longest_prefix_match<P: AsRef<[u8]>>(&self, key: P) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+        let mut rest = key.as_ref();
+        let mut founded = node.entry.as_ref();
+        while let Some(byte) = rest.first() {
+            let edge = match node.search(*byte) {
+                Ok(idx) if rest.starts_with(&node.edges[idx].label) => &node.edges[idx],
+                _ => break,
+            };
+            rest = &rest[edge.label.len()..];
+            node = &edge.node;
+            founded = node.entry.as_ref().or(founded);
+        }
+        founded.map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// 按键的升序返回迭代器.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(Some(&self.root))
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, K, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 检查边的标签与顺序、词条所在的路径、每个结点记录的键数，以及路径压缩.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        self.root
+            .validate_at(&mut Vec::new(), true, &mut 0, &mut violations);
+        validate::into_validation(violations)
+    }
+}
+
+impl<K: Ord + AsRef<[u8]>, V> UnorderedMap<K, V> for RadixTreeMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
+        self.root
+            .find(key.as_ref())?
+            .entry
+            .as_ref()
+            .map(|entry| &entry.value)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let _stats = self.stats.enter();
+        self.root
+            .find_mut(key.as_ref())?
+            .entry
+            .as_mut()
+            .map(|entry| &mut entry.value)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let _stats = self.stats.enter();
+        let bytes = key.as_ref().to_vec();
+        self.root.insert(&bytes, Entry { key, value })
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let _stats = self.stats.enter();
+        let bytes = key.as_ref().to_vec();
+        if self.get(&key).is_none() {
+            self.root.insert(
+                &bytes,
+                Entry {
+                    key,
+                    value: default,
+                },
+            );
+        }
+        let node = self.root.find_mut(&bytes).unwrap();
+        &mut node.entry.as_mut().unwrap().value
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.stats.enter();
+        self.root.remove(key.as_ref())
+    }
+
+    fn len(&self) -> usize {
+        self.root.count
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(RadixTreeMap::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<K: Ord + AsRef<[u8]>, V> Map<K, V> for RadixTreeMap<K, V> {}
+
+/// 基数树的迭代器(先序遍历，即键的升序).
+pub struct Iter<'a, K: Ord, V> {
+    stack: Vec<&'a Node<K, V>>,
+    len: usize,
+}
+
+impl<'a, K: Ord, V> Iter<'a, K, V> {
+    fn new(root: Option<&'a Node<K, V>>) -> Self {
+        Self {
+            stack: root.into_iter().collect(),
+            len: root.map_or(0, |node| node.count),
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            self.stack
+                .extend(node.edges.iter().rev().map(|edge| &edge.node));
+            if let Some(entry) = &node.entry {
+                self.len -= 1;
+                return Some((&entry.key, &entry.value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K: Ord, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+pub type Keys<'a, K, V> = super::Keys<Iter<'a, K, V>>;
+
+pub type Values<'a, K, V> = super::Values<Iter<'a, K, V>>;
+
+impl<'a, K: Ord + AsRef<[u8]>, V> IntoIterator for &'a RadixTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_prefix() {
+        let mut map = RadixTreeMap::new();
+        for route in ["/", "/api", "/api/users", "/api/user", "/static"].iter() {
+            map.insert(route.to_string(), route.len());
+        }
+        assert_eq!(map.validate(), Ok(()));
+        // 前缀可以终止于边的中间.
+        assert!(map.prefix_iter("/api/us").map(|(key, _)| key.as_str()).eq([
+            "/api/user",
+            "/api/users"
+        ]
+        .iter()
+        .copied()));
+        assert_eq!(map.count_prefix("/a"), 3);
+        assert_eq!(map.count_prefix("/b"), 0);
+        assert_eq!(
+            map.longest_prefix_match("/api/users/42")
+                .map(|(key, _)| key.as_str()),
+            Some("/api/users")
+        );
+        assert_eq!(
+            map.longest_prefix_match("/api/use")
+                .map(|(key, _)| key.as_str()),
+            Some("/api")
+        );
+        assert_eq!(map.remove(&"/api".to_string()), Some(4));
+        assert_eq!(map.remove(&"/api".to_string()), None);
+        assert_eq!(map.get(&"/api/user".to_string()), Some(&9));
+        assert_eq!(map.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut map = RadixTreeMap::new();
+        map.insert("ab".to_string(), ());
+        map.insert("ac".to_string(), ());
+        assert_eq!(map.validate(), Ok(()));
+        // 剪掉一条边后，中间的空结点只剩一个孩子.
+        map.root.edges[0].node.edges.pop();
+        let size = Violation::Size {
+            position: 0,
+            recorded: 2,
+            actual: 1,
+        };
+        assert_eq!(
+            map.validate(),
+            Err(vec![
+                Violation::Redundant { position: 0 },
+                size.clone(),
+                size
+            ])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_radix_tree_map(
+            entrys in prop::collection::vec(("[abc]{0,5}", any::<u32>()), 0..64),
+            removes in prop::collection::vec("[abc]{0,5}", 0..64),
+            queries in prop::collection::vec("[abc]{0,6}", 0..16),
+        ) {
+            let mut map = RadixTreeMap::new();
+            let mut expected = BTreeMap::new();
+            for (key, value) in entrys.iter() {
+                prop_assert_eq!(map.insert(key.clone(), *value), expected.insert(key.clone(), *value));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for key in removes.iter() {
+                prop_assert_eq!(map.remove(key), expected.remove(key));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            prop_assert_eq!(map.len(), expected.len());
+            prop_assert!(map.iter().eq(expected.iter()));
+            for query in queries.iter() {
+                let prefixed = expected.iter().filter(|(key, _)| key.starts_with(query.as_str()));
+                prop_assert!(map.prefix_iter(query).eq(prefixed.clone()));
+                prop_assert_eq!(map.count_prefix(query), prefixed.count());
+                let longest = expected
+                    .iter()
+                    .filter(|(key, _)| query.starts_with(key.as_str()))
+                    .max_by_key(|(key, _)| key.len());
+                prop_assert_eq!(map.longest_prefix_match(query), longest);
+                *map.get_mut_or_insert(query.clone(), 0) += 1;
+                *expected.entry(query.clone()).or_insert(0) += 1;
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for (key, value) in expected.iter() {
+                prop_assert_eq!(map.get(key), Some(value));
+            }
+        }
+
+        #[test]
+        fn test_bytes(keys: Vec<Vec<u8>>) {
+            let mut map = RadixTreeMap::new();
+            let mut expected = BTreeMap::new();
+            for key in keys.iter() {
+                prop_assert_eq!(map.insert(key.clone(), ()), expected.insert(key.clone(), ()));
+            }
+            prop_assert_eq!(map.validate(), Ok(()));
+            prop_assert!(map.keys().eq(expected.keys()));
+        }
+    }
+}
@@ -1,19 +1,33 @@
-use super::bst::TreeMap;
-use super::{Entry, Map};
+use super::augment::{self, Augment};
+use super::bst::{self, TreeMap};
+use super::join::{self, Exposed, Join, Ranked};
+use super::{Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
 use crate::ch4::{
     BinTree, BinTreeCursor, BinTreeCursorMut, BinTreeMut, MoveParentBinTreeMut, MoveParentCursor,
 };
 use std::cmp::Ordering;
+use std::iter::FromIterator;
 use std::mem;
 use std::ops::{Deref, DerefMut};
 
 #[derive(Debug)]
-pub struct RBNode<T> {
+pub struct RBNode<T, A = ()> {
     elem: T,
     is_black: bool,
+    size: usize,
+    augment: A,
 }
 
-impl<T> Deref for RBNode<T> {
+impl<T, A> RBNode<T, A> {
+    /// 以该结点为根的子树的附加信息.
+    pub fn augment(&self) -> &A {
+        &self.augment
+    }
+}
+
+impl<T, A> Deref for RBNode<T, A> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -21,22 +35,24 @@ impl<T> Deref for RBNode<T> {
     }
 }
 
-impl<T> DerefMut for RBNode<T> {
+impl<T, A> DerefMut for RBNode<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.elem
     }
 }
 
-pub type RBEntry<K, V> = Entry<K, RBNode<V>>;
+pub type RBEntry<K, V, A = ()> = Entry<K, RBNode<V, A>>;
 
 /// 基于红黑树的字典(Map).
-pub struct RBTreeMap<Tree: BinTreeMut<Elem = RBEntry<K, V>>, K: Ord, V> {
-    bst: TreeMap<Tree, K, RBNode<V>>,
+///
+/// `A`是结点的附加信息，默认不维护任何附加信息.
+pub struct RBTreeMap<Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A = ()> {
+    pub(crate) bst: TreeMap<Tree, K, RBNode<V, A>>,
 }
 
-impl<Tree, K: Ord, V> Default for RBTreeMap<Tree, K, V>
+impl<Tree, K: Ord, V, A> Default for RBTreeMap<Tree, K, V, A>
 where
-    Tree: Default + BinTreeMut<Elem = RBEntry<K, V>>,
+    Tree: Default + BinTreeMut<Elem = RBEntry<K, V, A>>,
 {
     fn default() -> Self {
         Self {
@@ -45,14 +61,91 @@ where
     }
 }
 
-impl<Tree, K: Ord, V> RBTreeMap<Tree, K, V>
+impl<Tree, K: Ord, V, A: Augment<K, V>> RBTreeMap<Tree, K, V, A>
 where
-    Tree: Default + BinTreeMut<Elem = RBEntry<K, V>>,
+    Tree: Default + BinTreeMut<Elem = RBEntry<K, V, A>>,
 {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// 由孩子重新计算规模与附加信息. 若为空树则`no-op`.
+    fn update_augment<'a, C>(cursor: &mut C)
+    where
+        C: BinTreeCursorMut<'a, Elem = Tree::Elem>,
+    {
+        let size = cursor.left().map_or(0, |node| node.value.size)
+            + cursor.right().map_or(0, |node| node.value.size)
+            + 1;
+        let augment = match cursor.as_ref() {
+            Some(entry) => augment::summarize(
+                cursor.left().map(|node| &node.value.augment),
+                &entry.key,
+                &entry.value.elem,
+                cursor.right().map(|node| &node.value.augment),
+            ),
+            None => return,
+        };
+        let node = &mut cursor.as_mut().unwrap().value;
+        node.size = size;
+        node.augment = augment;
+    }
+
+    /// 更新当前结点及其所有祖先的附加信息，之后游标回到原处.
+    fn update_path<'a, C>(cursor: &mut C)
+    where
+        C: MoveParentCursor<'a, Elem = Tree::Elem> + BinTreeCursorMut<'a, Elem = Tree::Elem>,
+    {
+        let mut path = Vec::new();
+        Self::update_augment(cursor);
+        while cursor.parent().is_some() {
+            path.push(cursor.is_left_child());
+            cursor.move_parent();
+            Self::update_augment(cursor);
+        }
+        while let Some(is_left) = path.pop() {
+            if is_left {
+                cursor.move_left()
+            } else {
+                cursor.move_right()
+            }
+        }
+    }
+
+    /// 顺时针旋转，并更新被旋转的两个结点的附加信息. `track`记录某个结点自根的路径，随旋转修正.
+    fn zig<'a, C>(cursor: &mut C, track: &mut Option<Vec<bool>>)
+    where
+        C: MoveParentCursor<'a, Elem = Tree::Elem>
+            + BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+    {
+        stats::record(|stats| stats.rotations += 1);
+        if track.is_some() {
+            bst::track_rotation(track, &bst::path_of(cursor), true);
+        }
+        cursor.zig();
+        cursor.move_right();
+        Self::update_augment(cursor);
+        cursor.move_parent();
+        Self::update_augment(cursor);
+    }
+
+    /// 逆时针旋转，并更新被旋转的两个结点的附加信息. `track`记录某个结点自根的路径，随旋转修正.
+    fn zag<'a, C>(cursor: &mut C, track: &mut Option<Vec<bool>>)
+    where
+        C: MoveParentCursor<'a, Elem = Tree::Elem>
+            + BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+    {
+        stats::record(|stats| stats.rotations += 1);
+        if track.is_some() {
+            bst::track_rotation(track, &bst::path_of(cursor), false);
+        }
+        cursor.zag();
+        cursor.move_left();
+        Self::update_augment(cursor);
+        cursor.move_parent();
+        Self::update_augment(cursor);
+    }
+
     fn is_black<'a, C>(cursor: &C) -> bool
     where
         C: BinTreeCursor<'a, Elem = Tree::Elem>,
@@ -79,7 +172,13 @@ where
     }
 
     /// 修复双红错误. 若最后`cursor`指向调用点，则返回`None`；否则`cursor`保持拓扑调整后的指向，并返回所进行的调整.
-    fn solve_double_red<'a, C>(cursor: &mut C, v_flag: bool) -> Option<(bool, bool)>
+    ///
+    /// `track`同`zig`.
+    fn solve_double_red<'a, C>(
+        cursor: &mut C,
+        v_flag: bool,
+        track: &mut Option<Vec<bool>>,
+    ) -> Option<(bool, bool)>
     where
         C: MoveParentCursor<'a, Elem = Tree::Elem>
             + BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
@@ -106,30 +205,30 @@ where
                     Self::set_black(cursor);
                     cursor.move_parent();
                     Self::set_red(cursor);
-                    cursor.zig();
+                    Self::zig(cursor, track);
                     Some((true, true))
                 }
                 (false, false) => {
                     Self::set_black(cursor);
                     cursor.move_parent();
                     Self::set_red(cursor);
-                    cursor.zag();
+                    Self::zag(cursor, track);
                     Some((false, false))
                 }
                 (true, false) => {
-                    cursor.zag();
+                    Self::zag(cursor, track);
                     Self::set_black(cursor);
                     cursor.move_parent();
                     Self::set_red(cursor);
-                    cursor.zig();
+                    Self::zig(cursor, track);
                     Some((true, false))
                 }
                 (false, true) => {
-                    cursor.zig();
+                    Self::zig(cursor, track);
                     Self::set_black(cursor);
                     cursor.move_parent();
                     Self::set_red(cursor);
-                    cursor.zag();
+                    Self::zag(cursor, track);
                     Some((false, true))
                 }
             }
@@ -143,7 +242,7 @@ where
                 let is_left = cursor.is_left_child();
                 cursor.move_parent();
                 if !Self::is_black(cursor) {
-                    if let Some((pz, vz)) = Self::solve_double_red(cursor, is_left) {
+                    if let Some((pz, vz)) = Self::solve_double_red(cursor, is_left, track) {
                         match (pz, vz) {
                             (true, true) | (false, false) => (),
                             (true, false) | (false, true) => {
@@ -175,7 +274,8 @@ where
         }
     }
 
-    fn solve_double_black<'a, C>(cursor: &mut C)
+    /// 修复双黑错误. `track`同`zig`.
+    fn solve_double_black<'a, C>(cursor: &mut C, track: &mut Option<Vec<bool>>)
     where
         C: MoveParentCursor<'a, Elem = Tree::Elem>
             + BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
@@ -210,7 +310,7 @@ where
             Self::set_black(cursor);
             match (p_flag, v_flag) {
                 (true, true) => {
-                    cursor.zig();
+                    Self::zig(cursor, track);
                     if g_black {
                         Self::set_black(cursor)
                     } else {
@@ -220,7 +320,7 @@ where
                     Self::set_black(cursor);
                 }
                 (false, false) => {
-                    cursor.zag();
+                    Self::zag(cursor, track);
                     if g_black {
                         Self::set_black(cursor)
                     } else {
@@ -236,14 +336,14 @@ where
                         cursor.move_right()
                     }
                     Self::set_black(cursor);
-                    cursor.zag();
+                    Self::zag(cursor, track);
                     if g_black {
                         Self::set_black(cursor)
                     } else {
                         Self::set_red(cursor)
                     }
                     cursor.move_parent();
-                    cursor.zig();
+                    Self::zig(cursor, track);
                 }
                 (false, true) => {
                     if p_flag {
@@ -252,14 +352,14 @@ where
                         cursor.move_right()
                     }
                     Self::set_black(cursor);
-                    cursor.zig();
+                    Self::zig(cursor, track);
                     if g_black {
                         Self::set_black(cursor)
                     } else {
                         Self::set_red(cursor)
                     }
                     cursor.move_parent();
-                    cursor.zag();
+                    Self::zag(cursor, track);
                 }
             }
         } else if p_black && !v_red {
@@ -274,7 +374,7 @@ where
                 Self::set_red(cursor);
                 cursor.move_parent();
                 if cursor.parent().is_some() {
-                    Self::solve_double_black(cursor);
+                    Self::solve_double_black(cursor, track);
                 }
             } else {
                 // BB-2
@@ -291,9 +391,9 @@ where
             cursor.move_parent();
             Self::set_red(cursor);
             if p_flag {
-                cursor.zig()
+                Self::zig(cursor, track)
             } else {
-                cursor.zag()
+                Self::zag(cursor, track)
             }
             Self::set_black(cursor);
             if p_flag {
@@ -303,14 +403,54 @@ where
                 cursor.move_left();
                 cursor.move_left();
             }
-            Self::solve_double_black(cursor);
+            Self::solve_double_black(cursor, track);
+        }
+    }
+
+    /// 插入红色的新结点后修复. `parent`指向新结点的父母，`is_left`表示新结点是否为左孩子. `track`同`zig`.
+    fn fix_after_insert<'a, C>(parent: &mut C, is_left: bool, track: &mut Option<Vec<bool>>)
+    where
+        C: MoveParentCursor<'a, Elem = Tree::Elem>
+            + BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+        Tree: MoveParentBinTreeMut,
+    {
+        Self::update_path(parent);
+        if !Self::is_black(parent) {
+            Self::solve_double_red(parent, is_left, track);
+        }
+    }
+
+    /// 摘除结点后修复. `cursor`指向被摘除结点的位置，`removed_black`表示被摘除的结点是否为黑色. `track`同`zig`.
+    fn fix_after_remove<'a, C>(cursor: &mut C, removed_black: bool, track: &mut Option<Vec<bool>>)
+    where
+        C: MoveParentCursor<'a, Elem = Tree::Elem>
+            + BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+        Tree: MoveParentBinTreeMut,
+    {
+        Self::update_path(cursor);
+        if cursor.parent().is_none() {
+            Self::set_black(cursor);
+        } else if removed_black {
+            if Self::is_black(cursor) {
+                Self::solve_double_black(cursor, track);
+            } else {
+                Self::set_black(cursor);
+            }
         }
     }
+
+    /// 交换两个词条的键与值，保留结点的颜色.
+    fn swap_entry(x: &mut Tree::Elem, y: &mut Tree::Elem) {
+        mem::swap(&mut x.key, &mut y.key);
+        mem::swap(&mut x.value.elem, &mut y.value.elem);
+    }
 }
 
-impl<Tree, K: Ord, V> Map<K, V> for RBTreeMap<Tree, K, V>
+impl<Tree, K: Ord, V, A: Augment<K, V>> UnorderedMap<K, V> for RBTreeMap<Tree, K, V, A>
 where
-    Tree: Default + MoveParentBinTreeMut<Elem = RBEntry<K, V>> + BinTreeMut<Elem = RBEntry<K, V>>,
+    Tree: Default
+        + MoveParentBinTreeMut<Elem = RBEntry<K, V, A>>
+        + BinTreeMut<Elem = RBEntry<K, V, A>>,
 {
     fn get(&self, key: &K) -> Option<&V> {
         self.bst.get(key).map(|node| &**node)
@@ -325,10 +465,13 @@ where
     }
 
     fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let _stats = self.bst.stats.enter();
         let mut parent = self.bst.tree.move_parent_cursor_mut();
         let mut value = RBNode {
+            augment: A::from_entry(&key, &default),
             elem: default,
             is_black: false,
+            size: 1,
         };
         let is_left = match TreeMap::<Tree, _, _>::move_to_target(&mut parent, &key) {
             Some(Ordering::Equal) => {
@@ -336,23 +479,27 @@ where
             }
             Some(Ordering::Less) => {
                 parent.insert_as_left(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 true
             }
             Some(Ordering::Greater) => {
                 parent.insert_as_right(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 false
             }
             None => {
                 value.is_black = true;
                 parent.insert_as_root(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 return &mut parent.into_mut().unwrap().value.elem;
             }
         };
+        Self::update_path(&mut parent);
         if !Self::is_black(&parent) {
-            if let Some((pz, vz)) = Self::solve_double_red(&mut parent, is_left) {
+            if let Some((pz, vz)) = Self::solve_double_red(&mut parent, is_left, &mut None) {
                 match (pz, vz) {
                     (true, true) | (false, false) => (),
                     (true, false) | (false, true) => {
@@ -370,67 +517,51 @@ where
     }
 
     fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let _stats = self.bst.stats.enter();
         let mut parent = self.bst.tree.move_parent_cursor_mut();
         let mut value = RBNode {
+            augment: A::from_entry(&key, &value),
             elem: value,
             is_black: false,
+            size: 1,
         };
         let is_left = match TreeMap::<Tree, _, _>::move_to_target(&mut parent, &key) {
             Some(Ordering::Equal) => {
-                value.is_black = Self::is_black(&parent);
-                mem::swap(&mut parent.into_mut().unwrap().value, &mut value);
+                mem::swap(&mut parent.as_mut().unwrap().value.elem, &mut value.elem);
+                Self::update_path(&mut parent);
                 return Some(value.elem);
             }
             Some(Ordering::Less) => {
                 parent.insert_as_left(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 true
             }
             Some(Ordering::Greater) => {
                 parent.insert_as_right(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 false
             }
             None => {
                 value.is_black = true;
                 parent.insert_as_root(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 return None;
             }
         };
-        if !Self::is_black(&parent) {
-            Self::solve_double_red(&mut parent, is_left);
-        }
+        Self::fix_after_insert(&mut parent, is_left, &mut None);
         None
     }
 
     fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.bst.stats.enter();
         let mut cursor = self.bst.tree.move_parent_cursor_mut();
         if let Some(Ordering::Equal) = TreeMap::<Tree, _, _>::move_to_target(&mut cursor, key) {
             self.bst.len -= 1;
-            let node = TreeMap::<Tree, _, _>::delete_at(&mut cursor, |x, y| {
-                let Entry {
-                    key: x_key,
-                    value: x_node,
-                } = x;
-                let Entry {
-                    key: y_key,
-                    value: y_node,
-                } = y;
-                let x_value = &mut x_node.elem;
-                let y_value = &mut y_node.elem;
-                mem::swap(x_key, y_key);
-                mem::swap(x_value, y_value);
-            });
-            if cursor.parent().is_none() {
-                Self::set_black(&mut cursor);
-            } else if node.is_black {
-                if Self::is_black(&cursor) {
-                    Self::solve_double_black(&mut cursor);
-                } else {
-                    Self::set_black(&mut cursor);
-                }
-            }
+            let node = TreeMap::<Tree, _, _>::delete_at(&mut cursor, Self::swap_entry);
+            Self::fix_after_remove(&mut cursor, node.is_black, &mut None);
             Some(node.elem)
         } else {
             None
@@ -438,16 +569,680 @@ where
     }
 
     fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
-        Box::new(self.bst.iter().map(|(k, node)| (k, &node.elem)))
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.bst.stats.get()
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> Map<K, V> for RBTreeMap<Tree, K, V, A> where
+    Tree: Default
+        + MoveParentBinTreeMut<Elem = RBEntry<K, V, A>>
+        + BinTreeMut<Elem = RBEntry<K, V, A>>
+{
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> RBTreeMap<Tree, K, V, A>
+where
+    Tree: Default + BinTreeMut<Elem = RBEntry<K, V, A>>,
+{
+    fn set_color(tree: &mut Tree, is_black: bool) {
+        if let Some(entry) = tree.cursor_mut().as_mut() {
+            entry.value.is_black = is_black;
+        }
+    }
+
+    /// 若根为红色，则将其染黑，黑高度随之加一.
+    fn blacken((mut tree, rank): Ranked<Tree>) -> Ranked<Tree> {
+        if Self::is_black(&tree.cursor()) {
+            (tree, rank)
+        } else {
+            Self::set_color(&mut tree, true);
+            (tree, rank + 1)
+        }
+    }
+
+    /// 以`mid`为根接入两棵子树，并设置根的颜色.
+    fn node(left: Tree, mut mid: Tree, right: Tree, is_black: bool) -> Tree {
+        Self::set_color(&mut mid, is_black);
+        let mut tree = join::attach(left, mid, right);
+        Self::update_augment(&mut tree.cursor_mut());
+        tree
+    }
+
+    /// `right`时`outer`为左子树，否则为右子树.
+    fn node_at(outer: Tree, mid: Tree, inner: Tree, is_black: bool, right: bool) -> Tree {
+        if right {
+            Self::node(outer, mid, inner, is_black)
+        } else {
+            Self::node(inner, mid, outer, is_black)
+        }
+    }
+
+    /// 旋转整棵树. `promote_right`时提升右孩子，否则提升左孩子.
+    fn rotate(tree: &mut Tree, promote_right: bool) {
+        {
+            let mut cursor = tree.cursor_mut();
+            if promote_right {
+                cursor.zag();
+                cursor.move_left();
+            } else {
+                cursor.zig();
+                cursor.move_right();
+            }
+            Self::update_augment(&mut cursor);
+        }
+        Self::update_augment(&mut tree.cursor_mut());
+    }
+
+    /// 沿黑高度较大的`big`靠近`small`一侧的链下降，直至黑高度与`small`相同的黑结点处，以红色的`mid`接入.
+    /// 要求`small`的根为黑色. `right`时`big`位于左侧.
+    ///
+    /// 返回树的黑高度仍为`big_rank`，但其根与根的内侧孩子可能同为红色.
+    fn join_side(
+        (big, big_rank): Ranked<Tree>,
+        mid: Tree,
+        (small, small_rank): Ranked<Tree>,
+        right: bool,
+    ) -> Tree {
+        let is_black = Self::is_black(&big.cursor());
+        if is_black && big_rank == small_rank {
+            return Self::node_at(big, mid, small, false, right);
+        }
+        let (left, node, rest) = join::take_children(big);
+        let (outer, inner) = if right { (left, rest) } else { (rest, left) };
+        let inner_rank = big_rank - is_black as usize;
+        let inner = Self::join_side((inner, inner_rank), mid, (small, small_rank), right);
+        let mut tree = Self::node_at(outer, node, inner, is_black, right);
+        if is_black {
+            // 修复内侧孩子与其内侧孩子的双红.
+            let mut cursor = tree.cursor_mut();
+            if right {
+                cursor.move_right();
+            } else {
+                cursor.move_left();
+            }
+            let double_red = !Self::is_black(&cursor)
+                && matches!(if right { cursor.right() } else { cursor.left() }, Some(entry) if !entry.value.is_black);
+            if double_red {
+                if right {
+                    cursor.move_right();
+                } else {
+                    cursor.move_left();
+                }
+                Self::set_black(&mut cursor);
+                drop(cursor);
+                Self::rotate(&mut tree, right);
+            }
+        }
+        tree
+    }
+
+    /// 黑高度.
+    fn rank(tree: &Tree) -> usize {
+        let mut cursor = tree.cursor();
+        let mut rank = 0;
+        while cursor.as_ref().is_some() {
+            rank += Self::is_black(&cursor) as usize;
+            cursor.move_left();
+        }
+        rank
+    }
+
+    fn into_ranked(self) -> Ranked<Tree> {
+        let rank = Self::rank(&self.bst.tree);
+        (self.bst.tree, rank)
+    }
+
+    fn from_ranked(tree: Ranked<Tree>) -> Self {
+        let (tree, _) = Self::blacken(tree);
+        let len = tree.cursor().as_ref().map_or(0, |entry| entry.value.size);
+        Self {
+            bst: TreeMap::from_tree(tree, len),
+        }
+    }
+
+    /// 按`key`将字典拆分为三部分: 所有小于`key`的键、`key`对应的值与所有大于`key`的键.
+    ///
+    /// 复杂度为`O(log n)`.
+    pub fn split(self, key: &K) -> (Self, Option<V>, Self) {
+        let (left, found, right) = join::split::<K, V, Self>(self.into_ranked(), key);
+        (
+            Self::from_ranked(left),
+            found.map(|tree| join::into_entry(tree).value.elem),
+            Self::from_ranked(right),
+        )
+    }
+
+    /// 连接两个字典.
+    ///
+    /// 复杂度为`O(log n)`.
+    /// # Panics
+    /// `left`所有的键都必须小于`right`中的键.
+    pub fn join(left: Self, right: Self) -> Self {
+        if let (Some(max), Some(min)) = (
+            join::last_key(&left.bst.tree),
+            join::first_key(&right.bst.tree),
+        ) {
+            assert!(max < min, "`join`要求左侧所有的键都小于右侧的键.");
+        }
+        Self::from_ranked(join::join2::<K, V, Self>(
+            left.into_ranked(),
+            right.into_ranked(),
+        ))
+    }
+
+    /// 并集. 两者都有的键，其值为`resolve(key, 自身的值, other的值)`.
+    ///
+    /// 复杂度为`O(m log(n / m + 1))`，其中`m <= n`分别为两者的规模.
+    pub fn union<F: FnMut(&K, V, V) -> V>(self, other: Self, mut resolve: F) -> Self {
+        Self::from_ranked(join::union::<K, V, Self, F>(
+            self.into_ranked(),
+            other.into_ranked(),
+            &mut resolve,
+        ))
+    }
+
+    /// 交集. 值为`resolve(key, 自身的值, other的值)`.
+    ///
+    /// 复杂度为`O(m log(n / m + 1))`，其中`m <= n`分别为两者的规模.
+    pub fn intersection<F: FnMut(&K, V, V) -> V>(self, other: Self, mut resolve: F) -> Self {
+        Self::from_ranked(join::intersection::<K, V, Self, F>(
+            self.into_ranked(),
+            other.into_ranked(),
+            &mut resolve,
+        ))
+    }
+
+    /// 差集，即自身中不在`other`中的键.
+    ///
+    /// 复杂度为`O(m log(n / m + 1))`，其中`m <= n`分别为两者的规模.
+    pub fn difference(self, other: Self) -> Self {
+        Self::from_ranked(join::difference::<K, V, Self>(
+            self.into_ranked(),
+            other.into_ranked(),
+        ))
+    }
+
+    /// 由按键严格递增的键值对序列构造完全平衡的树，复杂度为`O(n)`.
+    /// # Panics
+    /// 键必须严格递增.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::build(super::sorted_entries(iter, false))
+    }
+
+    fn build(entries: Vec<(K, V)>) -> Self {
+        let len = entries.len();
+        Self::from_ranked(join::build::<K, V, Self, _>(&mut entries.into_iter(), len))
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> FromIterator<(K, V)> for RBTreeMap<Tree, K, V, A>
+where
+    Tree: Default + BinTreeMut<Elem = RBEntry<K, V, A>>,
+{
+    /// 输入的键已经严格递增时复杂度为`O(n)`，否则先排序. 相等的键只保留最后一个值.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::build(super::sorted_entries(iter, true))
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> Extend<(K, V)> for RBTreeMap<Tree, K, V, A>
+where
+    Tree: Default + BinTreeMut<Elem = RBEntry<K, V, A>>,
+{
+    /// 将新的键值对构造为一棵树后与自身求并集，已有的键取新的值.
+    ///
+    /// 输入有序时复杂度为`O(m log(n / m + 1))`，其中`m <= n`分别为两者的规模.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let other = Self::from_iter(iter);
+        let stats = mem::take(&mut self.bst.stats);
+        *self = mem::take(self).union(other, |_, _, value| value);
+        self.bst.stats = stats;
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> Join<K, V> for RBTreeMap<Tree, K, V, A>
+where
+    Tree: Default + BinTreeMut<Elem = RBEntry<K, V, A>>,
+{
+    type Node = RBNode<V, A>;
+    type Tree = Tree;
+
+    fn entry(key: K, value: V) -> RBEntry<K, V, A> {
+        let value = RBNode {
+            augment: A::from_entry(&key, &value),
+            elem: value,
+            is_black: true,
+            size: 1,
+        };
+        Entry { key, value }
+    }
+
+    fn into_value(node: Self::Node) -> V {
+        node.elem
+    }
+
+    fn expose((tree, rank): Ranked<Tree>) -> Exposed<Tree> {
+        let rank = rank - Self::is_black(&tree.cursor()) as usize;
+        let (left, mid, right) = join::take_children(tree);
+        ((left, rank), mid, (right, rank))
+    }
+
+    fn join(left: Ranked<Tree>, mid: Tree, right: Ranked<Tree>) -> Ranked<Tree> {
+        let left = Self::blacken(left);
+        let right = Self::blacken(right);
+        let (tree, rank) = if left.1 > right.1 {
+            let rank = left.1;
+            (Self::join_side(left, mid, right, true), rank)
+        } else if right.1 > left.1 {
+            let rank = right.1;
+            (Self::join_side(right, mid, left, false), rank)
+        } else {
+            let rank = left.1 + 1;
+            return (Self::node(left.0, mid, right.0, true), rank);
+        };
+        Self::blacken((tree, rank))
+    }
+}
+
+impl<Tree, K: Ord, V, A> RBTreeMap<Tree, K, V, A>
+where
+    Tree: BinTreeMut<Elem = RBEntry<K, V, A>>,
+{
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, Tree, K, V, A> {
+        Iter {
+            inner: self.bst.iter(),
+        }
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, Tree, K, V, A> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, Tree, K, V, A> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, Tree, K, V, A>> {
+        self.bst.cursor_at(key).map(|inner| Cursor { inner })
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, Tree, K, V, A> {
+        Cursor {
+            inner: self.bst.lower_bound_cursor(key),
+        }
+    }
+
+    /// 检查二叉查找树的不变式，以及颜色、黑高度与每个结点记录的规模.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let root = self.bst.validate_with(
+            &mut violations,
+            |position, entry, left: Option<&(usize, usize, bool)>, right, violations| {
+                let (lh, ls, left_black) = left.copied().unwrap_or((0, 0, true));
+                let (rh, rs, right_black) = right.copied().unwrap_or((0, 0, true));
+                let is_black = entry.value.is_black;
+                if !is_black && !(left_black && right_black) {
+                    violations.push(Violation::DoubleRed { position });
+                }
+                if lh != rh {
+                    violations.push(Violation::BlackHeight {
+                        position,
+                        left: lh,
+                        right: rh,
+                    });
+                }
+                let size = ls + rs + 1;
+                if entry.value.size != size {
+                    violations.push(Violation::Size {
+                        position,
+                        recorded: entry.value.size,
+                        actual: size,
+                    });
+                }
+                (lh.max(rh) + is_black as usize, size, is_black)
+            },
+        );
+        if matches!(root, Some((_, _, false))) {
+            violations.push(Violation::RedRoot);
+        }
+        validate::into_validation(violations)
+    }
+}
+
+impl<Tree, K: Ord, V, A> RBTreeMap<Tree, K, V, A>
+where
+    Tree: BinTreeMut<Elem = RBEntry<K, V, A>>,
+{
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Tree, K, V, A> {
+        IterMut {
+            inner: self.bst.iter_mut(),
+        }
+    }
+}
+
+impl<Tree, K: Ord, V, A: Augment<K, V>> RBTreeMap<Tree, K, V, A>
+where
+    Tree: Default + MoveParentBinTreeMut<Elem = RBEntry<K, V, A>>,
+{
+    /// 返回指向`key`的可变游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at_mut(&mut self, key: &K) -> Option<CursorMut<'_, Tree, K, V, A>> {
+        self.bst.cursor_at_mut(key).map(|inner| CursorMut { inner })
+    }
+
+    /// 返回指向第一个不小于`key`的词条的可变游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor_mut(&mut self, key: &K) -> CursorMut<'_, Tree, K, V, A> {
+        CursorMut {
+            inner: self.bst.lower_bound_cursor_mut(key),
+        }
+    }
+}
+
+/// 红黑树上的只读游标，参见`bst::Cursor`.
+pub struct Cursor<
+    'a,
+    Tree: 'a + BinTreeMut<Elem = RBEntry<K, V, A>>,
+    K: 'a + Ord,
+    V: 'a,
+    A: 'a = (),
+> {
+    inner: bst::Cursor<'a, Tree, K, RBNode<V, A>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> Clone
+    for Cursor<'a, Tree, K, V, A>
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> Cursor<'a, Tree, K, V, A> {
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.inner.move_next()
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev()
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.key_value().map(|(key, node)| (key, &node.elem))
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.inner.key()
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.inner.value().map(|node| &node.elem)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_next().map(|(key, node)| (key, &node.elem))
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_prev().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+/// 红黑树上的可变游标，参见`bst::CursorMut`.
+///
+/// 插入与删除前记下目标词条自根的路径，自底向上修复双红或双黑时随每次旋转修正，之后沿路径回到目标词条.
+pub struct CursorMut<
+    'a,
+    Tree: 'a + MoveParentBinTreeMut<Elem = RBEntry<K, V, A>>,
+    K: 'a + Ord,
+    V: 'a,
+    A: 'a = (),
+> {
+    inner: bst::CursorMut<'a, Tree, K, RBNode<V, A>>,
+}
+
+impl<'a, Tree, K: Ord, V, A: Augment<K, V>> CursorMut<'a, Tree, K, V, A>
+where
+    Tree: Default + MoveParentBinTreeMut<Elem = RBEntry<K, V, A>>,
+{
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.inner.move_next()
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev()
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&K, &V)> {
+        self.inner.key_value().map(|(key, node)| (key, &node.elem))
+    }
+
+    /// 当前词条，其中值是可变的. 与`get_mut`相同，修改值不会重新计算附加信息.
+    pub fn key_value_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.inner
+            .key_value_mut()
+            .map(|(key, node)| (key, &mut node.elem))
+    }
+
+    pub fn key(&self) -> Option<&K> {
+        self.inner.key()
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        self.key_value_mut().map(|(_, value)| value)
+    }
+
+    /// 在当前词条之后插入. 位于幽灵位置时插入为第一个词条. 游标位置不变.
+    /// # Panics
+    /// `key`必须严格位于当前词条与其后继之间.
+    pub fn insert_after(&mut self, key: K, value: V) {
+        self.insert_neighbour(key, value, true)
+    }
+
+    /// 在当前词条之前插入. 位于幽灵位置时插入为最后一个词条. 游标位置不变.
+    /// # Panics
+    /// `key`必须严格位于当前词条与其前驱之间.
+    pub fn insert_before(&mut self, key: K, value: V) {
+        self.insert_neighbour(key, value, false)
+    }
+
+    fn insert_neighbour(&mut self, key: K, value: V, forward: bool) {
+        self.inner.check_neighbour(&key, forward);
+        let mut track = self.inner.path();
+        let mut entry = <RBTreeMap<Tree, K, V, A> as Join<K, V>>::entry(key, value);
+        entry.value.is_black = self.inner.cursor.is_empty_subtree();
+        if let Some((is_left, _)) = self.inner.insert_leaf(entry, forward) {
+            RBTreeMap::<Tree, K, V, A>::fix_after_insert(
+                &mut self.inner.cursor,
+                is_left,
+                &mut track,
+            );
+        }
+        self.inner.follow(track);
+    }
+
+    /// 删除当前词条并返回，之后游标指向其后继. 位于幽灵位置时返回`None`.
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        if self.inner.ghost {
+            return None;
+        }
+        let mut track = self.inner.succ_path();
+        let Entry { key, value } = self
+            .inner
+            .remove_node(RBTreeMap::<Tree, K, V, A>::swap_entry);
+        RBTreeMap::<Tree, K, V, A>::fix_after_remove(
+            &mut self.inner.cursor,
+            value.is_black,
+            &mut track,
+        );
+        self.inner.follow(track);
+        Some((key, value.elem))
+    }
+}
+
+/// 红黑树的中序迭代器.
+pub struct Iter<'a, Tree: 'a + BinTreeMut<Elem = RBEntry<K, V, A>>, K: 'a + Ord, V: 'a, A: 'a = ()>
+{
+    inner: bst::Iter<'a, Tree, K, RBNode<V, A>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> Iterator
+    for Iter<'a, Tree, K, V, A>
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> DoubleEndedIterator
+    for Iter<'a, Tree, K, V, A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> ExactSizeIterator
+    for Iter<'a, Tree, K, V, A>
+{
+}
+
+/// 红黑树的可变中序迭代器.
+pub struct IterMut<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A = ()> {
+    inner: bst::IterMut<'a, Tree, K, RBNode<V, A>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: 'a + Ord, V: 'a, A: 'a> Iterator
+    for IterMut<'a, Tree, K, V, A>
+{
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &mut node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: 'a + Ord, V: 'a, A: 'a> DoubleEndedIterator
+    for IterMut<'a, Tree, K, V, A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(key, node)| (key, &mut node.elem))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: 'a + Ord, V: 'a, A: 'a> ExactSizeIterator
+    for IterMut<'a, Tree, K, V, A>
+{
+}
+
+/// 红黑树的所有权中序迭代器.
+pub struct IntoIter<Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A = ()> {
+    inner: bst::IntoIter<Tree, K, RBNode<V, A>>,
+}
+
+impl<Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> Iterator for IntoIter<Tree, K, V, A> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> DoubleEndedIterator
+    for IntoIter<Tree, K, V, A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, node.elem))
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> ExactSizeIterator
+    for IntoIter<Tree, K, V, A>
+{
+}
+
+/// 红黑树的键迭代器.
+pub type Keys<'a, Tree, K, V, A = ()> = super::Keys<Iter<'a, Tree, K, V, A>>;
+
+/// 红黑树的值迭代器.
+pub type Values<'a, Tree, K, V, A = ()> = super::Values<Iter<'a, Tree, K, V, A>>;
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> IntoIterator
+    for &'a RBTreeMap<Tree, K, V, A>
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, Tree, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> IntoIterator
+    for &'a mut RBTreeMap<Tree, K, V, A>
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, Tree, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = RBEntry<K, V, A>>, K: Ord, V, A> IntoIterator
+    for RBTreeMap<Tree, K, V, A>
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<Tree, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.bst.into_iter(),
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::super::test::check_iter;
     use super::*;
     use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
     use proptest::prelude::*;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn test_map_basic_dlbt() {
@@ -514,6 +1309,7 @@ mod test {
             // insert
             for (k, v) in data.clone() {
                 assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -538,6 +1334,7 @@ mod test {
             for k in data.keys().cloned() {
                 let elem = data.get(&k).copied().unwrap();
                 assert_eq!(map.insert(k, elem), Some(elem + 1));
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -545,9 +1342,166 @@ mod test {
             // remove
             for (idx, k) in data.keys().enumerate() {
                 assert_eq!(map.remove(k), data.get(k).copied());
+                prop_assert_eq!(map.validate(), Ok(()));
                 assert_eq!(map.len(), data.len() - idx - 1);
             }
             assert!(map.is_empty());
         }
+
+        #[test]
+        fn test_iter(data: HashMap<String, i64>) {
+            check_iter::<RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>, _>(data, RBTreeMap::validate)?;
+        }
+    }
+
+    type JoinMap = RBTreeMap<DoublyLinkedBinaryTree<RBEntry<u8, u32>>, u8, u32>;
+
+    fn join_map(entrys: &BTreeMap<u8, u32>) -> JoinMap {
+        let mut map = JoinMap::default();
+        for (k, v) in entrys {
+            map.insert(*k, *v);
+        }
+        map
+    }
+
+    fn assert_join_map(map: &JoinMap, expected: &BTreeMap<u8, u32>) {
+        assert_eq!(map.validate(), Ok(()));
+        assert_eq!(map.len(), expected.len());
+        assert!(map.iter().eq(expected.iter()));
+    }
+
+    proptest! {
+        #[test]
+        fn test_join(lhs: BTreeMap<u8, u32>, rhs: BTreeMap<u8, u32>, key: u8) {
+            // split & join
+            let (left, found, right) = join_map(&lhs).split(&key);
+            assert_eq!(found, lhs.get(&key).copied());
+            let mut less = lhs.clone();
+            let mut greater = less.split_off(&key);
+            greater.remove(&key);
+            assert_join_map(&left, &less);
+            assert_join_map(&right, &greater);
+            less.append(&mut greater);
+            assert_join_map(&RBTreeMap::join(left, right), &less);
+
+            // union
+            let map = join_map(&lhs).union(join_map(&rhs), |_, x, y| x.wrapping_sub(y));
+            let mut expected = rhs.clone();
+            for (k, v) in lhs.iter() {
+                let value = rhs.get(k).map_or(*v, |y| v.wrapping_sub(*y));
+                expected.insert(*k, value);
+            }
+            assert_join_map(&map, &expected);
+
+            // intersection
+            let map = join_map(&lhs).intersection(join_map(&rhs), |_, x, y| x.wrapping_sub(y));
+            let expected = lhs
+                .iter()
+                .filter_map(|(k, x)| rhs.get(k).map(|y| (*k, x.wrapping_sub(*y))))
+                .collect();
+            assert_join_map(&map, &expected);
+
+            // difference
+            let map = join_map(&lhs).difference(join_map(&rhs));
+            let expected = lhs
+                .iter()
+                .filter(|(k, _)| !rhs.contains_key(k))
+                .map(|(k, v)| (*k, *v))
+                .collect();
+            assert_join_map(&map, &expected);
+        }
+
+        #[test]
+        fn test_cursor(mut data: BTreeMap<u8, u32>, ops: Vec<(u8, u8, u32)>) {
+            let mut map = join_map(&data);
+            for (probe, op, value) in ops {
+                let current = data.range(probe..).next().map(|(k, _)| *k);
+                let cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(cursor.key_value(), data.range(probe..).next());
+                assert_eq!(cursor.peek_prev(), data.range(..probe).next_back());
+                assert_eq!(map.cursor_at(&probe).is_some(), data.contains_key(&probe));
+                let mut cursor = map.lower_bound_cursor_mut(&probe);
+                match op % 3 {
+                    0 => {
+                        let next = data.range(probe..).nth(1).map(|(k, _)| *k);
+                        let removed = current.map(|k| (k, data.remove(&k).unwrap()));
+                        assert_eq!(cursor.remove_current(), removed);
+                        assert_eq!(cursor.key().copied(), next);
+                    }
+                    1 => {
+                        let prev = data.range(..probe).next_back().map(|(k, _)| *k);
+                        if let Some(key) = probe.checked_sub(1).filter(|key| prev < Some(*key)) {
+                            cursor.insert_before(key, value);
+                            data.insert(key, value);
+                        }
+                        assert_eq!(cursor.key().copied(), current);
+                    }
+                    _ => {
+                        let key = current.map_or(Some(0), |key| key.checked_add(1));
+                        if let Some(key) = key.filter(|key| !data.contains_key(key)) {
+                            cursor.insert_after(key, value);
+                            data.insert(key, value);
+                        }
+                        assert_eq!(cursor.key().copied(), current);
+                    }
+                }
+                assert_join_map(&map, &data);
+            }
+        }
+
+        #[test]
+        fn test_from_iter(data: Vec<(u8, u32)>, more: Vec<(u8, u32)>) {
+            let mut expected: BTreeMap<_, _> = data.iter().copied().collect();
+            assert_join_map(&JoinMap::from_sorted_iter(expected.clone()), &expected);
+            let mut map: JoinMap = data.into_iter().collect();
+            assert_join_map(&map, &expected);
+            map.extend(more.iter().copied());
+            expected.extend(more.iter().copied());
+            assert_join_map(&map, &expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_unsorted_iter() {
+        JoinMap::from_sorted_iter(vec![(1, 0), (0, 0)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_join_overlap() {
+        let lhs = join_map(&(0..10).map(|k| (k, 0)).collect());
+        let rhs = join_map(&(9..20).map(|k| (k, 0)).collect());
+        RBTreeMap::join(lhs, rhs);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut map = join_map(&(1..=3).map(|k| (k, 0)).collect());
+        assert_eq!(map.validate(), Ok(()));
+        map.bst.tree.cursor_mut().as_mut().unwrap().value.is_black = false;
+        assert_eq!(
+            map.validate(),
+            Err(vec![
+                Violation::DoubleRed { position: 1 },
+                Violation::RedRoot
+            ])
+        );
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = RBTreeMap::<DoublyLinkedBinaryTree<_>, _, _>::default();
+        for key in 0..100 {
+            map.insert(key, ());
+        }
+        let stats = map.stats();
+        assert_eq!(stats.allocations, 100);
+        assert!(stats.rotations > 0);
+        for key in 0..100 {
+            map.remove(&key);
+        }
+        assert!(map.stats().comparisons > stats.comparisons);
     }
 }
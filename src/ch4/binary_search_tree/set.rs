@@ -0,0 +1,401 @@
+use super::Map;
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+/// 集合特质.
+pub trait Set<K: Ord>: Default {
+    fn contains(&self, key: &K) -> bool;
+
+    /// 插入一个键. 若键已存在则返回`false`.
+    fn insert(&mut self, key: K) -> bool;
+
+    /// 删除一个键. 若键不存在则返回`false`.
+    fn remove(&mut self, key: &K) -> bool;
+
+    /// 返回集合的大小.
+    fn len(&self) -> usize;
+
+    /// 集合是否为空.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &K>>;
+
+    /// 是否为`other`的子集.
+    fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.iter().all(|key| other.contains(key))
+    }
+
+    /// 是否为`other`的超集.
+    fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+}
+
+/// 由值为`()`的有序字典适配而来的有序集合.
+///
+/// `iter`按升序产生键，因此子集判断与`union`等集合运算都是对两个升序序列的归并，复杂度为`O(n + m)`.
+#[derive(Default)]
+pub struct MapSet<M> {
+    map: M,
+}
+
+impl<M: Default> MapSet<M> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K: Ord, M: Map<K, ()>> Set<K> for MapSet<M> {
+    fn contains(&self, key: &K) -> bool {
+        self.map.get(key).is_some()
+    }
+
+    fn insert(&mut self, key: K) -> bool {
+        self.map.insert(key, ()).is_none()
+    }
+
+    fn remove(&mut self, key: &K) -> bool {
+        self.map.remove(key).is_some()
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = &K>> {
+        Box::new(self.map.iter().map(|(key, _)| key))
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.difference(other).next().is_none()
+    }
+}
+
+/// `MapSet`按升序产生键的迭代器.
+pub type SetIter<'a, K> = Box<dyn 'a + Iterator<Item = &'a K>>;
+
+impl<M> MapSet<M> {
+    /// 按升序惰性地返回并集.
+    pub fn union<'a, K: Ord>(&'a self, other: &'a Self) -> Union<SetIter<'a, K>>
+    where
+        M: Map<K, ()>,
+    {
+        Union::new(Set::iter(self), Set::iter(other))
+    }
+
+    /// 按升序惰性地返回交集.
+    pub fn intersection<'a, K: Ord>(&'a self, other: &'a Self) -> Intersection<SetIter<'a, K>>
+    where
+        M: Map<K, ()>,
+    {
+        Intersection::new(Set::iter(self), Set::iter(other))
+    }
+
+    /// 按升序惰性地返回差集，即自身中不在`other`中的键.
+    pub fn difference<'a, K: Ord>(&'a self, other: &'a Self) -> Difference<SetIter<'a, K>>
+    where
+        M: Map<K, ()>,
+    {
+        Difference::new(Set::iter(self), Set::iter(other))
+    }
+
+    /// 按升序惰性地返回对称差，即只在其中一者中的键.
+    pub fn symmetric_difference<'a, K: Ord>(
+        &'a self,
+        other: &'a Self,
+    ) -> SymmetricDifference<SetIter<'a, K>>
+    where
+        M: Map<K, ()>,
+    {
+        SymmetricDifference::new(Set::iter(self), Set::iter(other))
+    }
+}
+
+/// 有序集合上的惰性集合运算. 要求两个迭代器都按升序产生互不相同的键.
+struct Merge<I: Iterator> {
+    lhs: Peekable<I>,
+    rhs: Peekable<I>,
+}
+
+impl<I: Iterator> Merge<I>
+where
+    I::Item: Ord,
+{
+    fn new(lhs: I, rhs: I) -> Self {
+        Self {
+            lhs: lhs.peekable(),
+            rhs: rhs.peekable(),
+        }
+    }
+
+    /// 取出两侧中较小的键. 两侧相同时同时取出，返回的键来自左侧.
+    fn next(&mut self) -> Option<(I::Item, Ordering)> {
+        let ordering = match (self.lhs.peek(), self.rhs.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(lhs), Some(rhs)) => lhs.cmp(rhs),
+        };
+        match ordering {
+            Ordering::Less => self.lhs.next(),
+            Ordering::Greater => self.rhs.next(),
+            Ordering::Equal => {
+                self.rhs.next();
+                self.lhs.next()
+            }
+        }
+        .map(|key| (key, ordering))
+    }
+
+    fn size_hint(&self) -> ((usize, Option<usize>), (usize, Option<usize>)) {
+        (self.lhs.size_hint(), self.rhs.size_hint())
+    }
+}
+
+/// 并集的升序迭代器.
+pub struct Union<I: Iterator> {
+    inner: Merge<I>,
+}
+
+impl<I: Iterator> Union<I>
+where
+    I::Item: Ord,
+{
+    pub(crate) fn new(lhs: I, rhs: I) -> Self {
+        Self {
+            inner: Merge::new(lhs, rhs),
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for Union<I>
+where
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let ((lhs_lower, lhs_upper), (rhs_lower, rhs_upper)) = self.inner.size_hint();
+        let upper = match (lhs_upper, rhs_upper) {
+            (Some(lhs), Some(rhs)) => lhs.checked_add(rhs),
+            _ => None,
+        };
+        (lhs_lower.max(rhs_lower), upper)
+    }
+}
+
+/// 交集的升序迭代器.
+pub struct Intersection<I: Iterator> {
+    inner: Merge<I>,
+}
+
+impl<I: Iterator> Intersection<I>
+where
+    I::Item: Ord,
+{
+    pub(crate) fn new(lhs: I, rhs: I) -> Self {
+        Self {
+            inner: Merge::new(lhs, rhs),
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for Intersection<I>
+where
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.inner.lhs.peek().is_some() && self.inner.rhs.peek().is_some() {
+            if let Some((key, Ordering::Equal)) = self.inner.next() {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let ((_, lhs_upper), (_, rhs_upper)) = self.inner.size_hint();
+        let upper = match (lhs_upper, rhs_upper) {
+            (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+            (upper, None) | (None, upper) => upper,
+        };
+        (0, upper)
+    }
+}
+
+/// 差集的升序迭代器，即左侧中不在右侧中的键.
+pub struct Difference<I: Iterator> {
+    inner: Merge<I>,
+}
+
+impl<I: Iterator> Difference<I>
+where
+    I::Item: Ord,
+{
+    pub(crate) fn new(lhs: I, rhs: I) -> Self {
+        Self {
+            inner: Merge::new(lhs, rhs),
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for Difference<I>
+where
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.inner.lhs.peek().is_some() {
+            if let Some((key, Ordering::Less)) = self.inner.next() {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let ((_, lhs_upper), _) = self.inner.size_hint();
+        (0, lhs_upper)
+    }
+}
+
+/// 对称差的升序迭代器，即只在一侧出现的键.
+pub struct SymmetricDifference<I: Iterator> {
+    inner: Merge<I>,
+}
+
+impl<I: Iterator> SymmetricDifference<I>
+where
+    I::Item: Ord,
+{
+    pub(crate) fn new(lhs: I, rhs: I) -> Self {
+        Self {
+            inner: Merge::new(lhs, rhs),
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for SymmetricDifference<I>
+where
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.inner.next() {
+                Some((_, Ordering::Equal)) => continue,
+                other => return other.map(|(key, _)| key),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let ((_, lhs_upper), (_, rhs_upper)) = self.inner.size_hint();
+        let upper = match (lhs_upper, rhs_upper) {
+            (Some(lhs), Some(rhs)) => lhs.checked_add(rhs),
+            _ => None,
+        };
+        (0, upper)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::avlt::AVLTreeMap;
+    use super::super::{bt, llrbt};
+    use super::*;
+    use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn check<S: Set<u8>>(keys: &[u8], removes: &[u8]) {
+        let mut set = S::default();
+        let mut expected = BTreeSet::new();
+        for key in keys {
+            assert_eq!(set.insert(*key), expected.insert(*key));
+        }
+        for key in removes {
+            assert_eq!(set.remove(key), expected.remove(key));
+            assert!(!set.contains(key));
+        }
+        assert_eq!(set.len(), expected.len());
+        let mut keys = set.iter().copied().collect::<Vec<_>>();
+        keys.sort_unstable();
+        assert!(keys.iter().eq(expected.iter()));
+        let mut subset = S::default();
+        for key in expected.iter().step_by(2) {
+            subset.insert(*key);
+        }
+        assert!(subset.is_subset(&set) && set.is_superset(&subset));
+        assert_eq!(set.is_subset(&subset), set.len() == subset.len());
+    }
+
+    /// 以`std::collections::BTreeSet`为模型检查有序集合的惰性集合运算与子集判断.
+    fn check_ops<M: Map<u8, ()>>(lhs: &[u8], rhs: &[u8]) {
+        let mut set = MapSet::<M>::new();
+        let mut other = MapSet::<M>::new();
+        let expected = lhs.iter().collect::<BTreeSet<_>>();
+        let expected_other = rhs.iter().collect::<BTreeSet<_>>();
+        for key in lhs {
+            set.insert(*key);
+        }
+        for key in rhs {
+            other.insert(*key);
+        }
+        assert!(set.iter().eq(expected.iter().copied()));
+        assert!(set
+            .union(&other)
+            .eq(expected.union(&expected_other).copied()));
+        assert!(set
+            .intersection(&other)
+            .eq(expected.intersection(&expected_other).copied()));
+        assert!(set
+            .difference(&other)
+            .eq(expected.difference(&expected_other).copied()));
+        assert!(set
+            .symmetric_difference(&other)
+            .eq(expected.symmetric_difference(&expected_other).copied()));
+        assert_eq!(set.is_subset(&other), expected.is_subset(&expected_other));
+        assert_eq!(
+            set.is_superset(&other),
+            expected.is_superset(&expected_other)
+        );
+        assert!(set.is_subset(&set) && set.is_superset(&set));
+    }
+
+    proptest! {
+        #[test]
+        fn test_map_set(keys: Vec<u8>, removes: Vec<u8>) {
+            check::<MapSet<BTreeMap<u8, ()>>>(&keys, &removes);
+            check::<MapSet<AVLTreeMap<DoublyLinkedBinaryTree<_>, u8, ()>>>(&keys, &removes);
+            check::<bt::BTreeSet<u8, 4>>(&keys, &removes);
+            check::<llrbt::RBTreeSet<u8>>(&keys, &removes);
+        }
+
+        #[test]
+        fn test_ops(lhs: Vec<u8>, rhs: Vec<u8>) {
+            check_ops::<BTreeMap<u8, ()>>(&lhs, &rhs);
+            check_ops::<AVLTreeMap<DoublyLinkedBinaryTree<_>, u8, ()>>(&lhs, &rhs);
+            check_ops::<bt::BTreeMap<u8, (), 4>>(&lhs, &rhs);
+            check_ops::<llrbt::RBTreeMap<u8, ()>>(&lhs, &rhs);
+        }
+
+        #[test]
+        fn test_merge(lhs: BTreeSet<u8>, rhs: BTreeSet<u8>) {
+            assert!(Union::new(lhs.iter(), rhs.iter()).eq(lhs.union(&rhs)));
+            assert!(Intersection::new(lhs.iter(), rhs.iter()).eq(lhs.intersection(&rhs)));
+            assert!(Difference::new(lhs.iter(), rhs.iter()).eq(lhs.difference(&rhs)));
+            assert!(SymmetricDifference::new(lhs.iter(), rhs.iter()).eq(lhs.symmetric_difference(&rhs)));
+        }
+    }
+}
@@ -0,0 +1,496 @@
+use super::{bst, bst::TreeMap, Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use crate::ch4::{BinTreeCursor, BinTreeCursorMut, BinTreeMut};
+use std::mem;
+
+/// 取`α = 2/3`时，`len`个结点的替罪羊树所允许的最大深度`⌊log_{3/2}(len)⌋`.
+fn max_depth(len: usize) -> usize {
+    if len == 0 {
+        0
+    } else {
+        ((len as f64).ln() / 1.5f64.ln()).floor() as usize
+    }
+}
+
+/// 统计游标所指子树的规模.
+fn subtree_size<'a, C: BinTreeCursor<'a> + Clone>(cursor: C) -> usize {
+    if cursor.is_empty_subtree() {
+        0
+    } else {
+        let (lhs, rhs) = cursor.split();
+        lhs.map_or(0, subtree_size) + rhs.map_or(0, subtree_size) + 1
+    }
+}
+
+/// 在规模为`len`的完全平衡树(由`build`构造)中，中序秩为`rank`的结点的路径(`true`表示向左).
+fn balanced_path(mut len: usize, mut rank: usize) -> Vec<bool> {
+    let mut path = Vec::new();
+    loop {
+        let mid = len / 2;
+        match rank.cmp(&mid) {
+            std::cmp::Ordering::Equal => break,
+            std::cmp::Ordering::Less => {
+                path.push(true);
+                len = mid;
+            }
+            std::cmp::Ordering::Greater => {
+                path.push(false);
+                rank -= mid + 1;
+                len -= mid + 1;
+            }
+        }
+    }
+    path
+}
+
+/// 替罪羊树.
+///
+/// 结点中不记录任何平衡信息. 取`α = 2/3`，当插入使新结点的深度超过`log_{3/2}(n)`时，
+/// 沿插入路径向上找到第一个不满足`α`-规模平衡的祖先(替罪羊)，并把以它为根的子树重建为完全平衡的树;
+/// 当删除使规模降至历史最大规模的`α`倍以下时，重建整棵树. 各操作的均摊复杂度为`O(log n)`.
+pub struct ScapegoatTreeMap<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> {
+    bst: TreeMap<Tree, K, V>,
+    max_len: usize,
+}
+
+impl<Tree, K: Ord, V> Default for ScapegoatTreeMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = Entry<K, V>>,
+{
+    fn default() -> Self {
+        Self {
+            bst: TreeMap::default(),
+            max_len: 0,
+        }
+    }
+}
+
+impl<Tree, K: Ord, V> ScapegoatTreeMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = Entry<K, V>>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 沿`path`(`true`表示向左)从根下降.
+    fn cursor_along<'a>(tree: &'a mut Tree, path: &[bool]) -> Tree::CursorMut<'a> {
+        let mut cursor = tree.cursor_mut();
+        for &is_left in path {
+            if is_left {
+                cursor.move_left();
+            } else {
+                cursor.move_right();
+            }
+        }
+        cursor
+    }
+
+    /// 由有序的词条序列构造一棵规模为`len`的完全平衡树.
+    fn build<I>(entries: &mut I, len: usize) -> Tree
+    where
+        I: Iterator<Item = Entry<K, V>>,
+    {
+        let mut tree = Tree::default();
+        if len > 0 {
+            let mid = len / 2;
+            let left = Self::build(entries, mid);
+            let entry = entries.next().unwrap();
+            let right = Self::build(entries, len - mid - 1);
+            let mut cursor = tree.cursor_mut();
+            cursor.insert_as_root(entry);
+            cursor.append_left(left);
+            cursor.append_right(right);
+        }
+        tree
+    }
+
+    /// 将游标所指的子树(规模为`len`)重建为完全平衡的树.
+    fn rebuild<'a, C>(cursor: &mut C, len: usize)
+    where
+        C: BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+    {
+        let tree = cursor.take();
+        let mut entries = TreeMap::from_tree(tree, len)
+            .into_iter()
+            .map(|(key, value)| Entry { key, value });
+        cursor.append(Self::build(&mut entries, len));
+    }
+
+    /// 插入一个不存在的键，并返回新结点最终所在的路径.
+    fn insert_new(&mut self, key: K, value: V) -> Vec<bool> {
+        let _stats = self.bst.stats.enter();
+        let mut path = Vec::new();
+        let mut cursor = self.bst.tree.cursor_mut();
+        if cursor.is_empty_subtree() {
+            cursor.insert_as_root(Entry { key, value });
+        } else {
+            loop {
+                stats::record(|stats| stats.comparisons += 1);
+                if key < cursor.as_ref().unwrap().key {
+                    path.push(true);
+                    if cursor.left().is_none() {
+                        cursor.insert_as_left(Entry { key, value });
+                        break;
+                    }
+                    cursor.move_left();
+                } else {
+                    path.push(false);
+                    if cursor.right().is_none() {
+                        cursor.insert_as_right(Entry { key, value });
+                        break;
+                    }
+                    cursor.move_right();
+                }
+            }
+        }
+        drop(cursor);
+        stats::record(|stats| stats.allocations += 1);
+        self.bst.len += 1;
+        self.max_len = self.max_len.max(self.bst.len);
+
+        if path.len() > max_depth(self.bst.len) {
+            // 自底向上寻找替罪羊，同时记录新结点在祖先子树中的中序秩.
+            let (mut size, mut rank) = (1, 0);
+            for depth in (0..path.len()).rev() {
+                let sibling = {
+                    let mut cursor = self.bst.tree.cursor();
+                    for &is_left in &path[..depth] {
+                        if is_left {
+                            cursor.move_left();
+                        } else {
+                            cursor.move_right();
+                        }
+                    }
+                    if path[depth] {
+                        cursor.move_right();
+                    } else {
+                        cursor.move_left();
+                    }
+                    subtree_size(cursor)
+                };
+                if !path[depth] {
+                    rank += sibling + 1;
+                }
+                let parent_size = size + sibling + 1;
+                if 3 * size > 2 * parent_size {
+                    let mut cursor = Self::cursor_along(&mut self.bst.tree, &path[..depth]);
+                    Self::rebuild(&mut cursor, parent_size);
+                    path.truncate(depth);
+                    path.append(&mut balanced_path(parent_size, rank));
+                    break;
+                }
+                size = parent_size;
+            }
+        }
+        path
+    }
+
+    /// 检查二叉查找树的不变式，以及树高不超过由历史最大规模决定的上限.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let height =
+            self.bst
+                .validate_with(&mut violations, |_, _, left: Option<&usize>, right, _| {
+                    left.copied().unwrap_or(0).max(right.copied().unwrap_or(0)) + 1
+                });
+        if let Some(height) = height {
+            let max = max_depth(self.max_len);
+            if height - 1 > max {
+                violations.push(Violation::Depth {
+                    depth: height - 1,
+                    max,
+                });
+            }
+        }
+        validate::into_validation(violations)
+    }
+}
+
+impl<Tree, K: Ord, V> UnorderedMap<K, V> for ScapegoatTreeMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = Entry<K, V>>,
+{
+    fn get(&self, key: &K) -> Option<&V> {
+        self.bst.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.bst.get_mut(key)
+    }
+
+    fn len(&self) -> usize {
+        self.bst.len()
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        if self.bst.get(&key).is_some() {
+            self.bst.get_mut(&key).unwrap()
+        } else {
+            let path = self.insert_new(key, default);
+            let cursor = Self::cursor_along(&mut self.bst.tree, &path);
+            &mut cursor.into_mut().unwrap().value
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.bst.get_mut(&key) {
+            Some(mem::replace(old, value))
+        } else {
+            self.insert_new(key, value);
+            None
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let value = self.bst.remove(key)?;
+        if 3 * self.bst.len < 2 * self.max_len {
+            let len = self.bst.len;
+            Self::rebuild(&mut self.bst.tree.cursor_mut(), len);
+            self.max_len = len;
+        }
+        Some(value)
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.bst.stats.get()
+    }
+}
+
+impl<Tree, K: Ord, V> Map<K, V> for ScapegoatTreeMap<Tree, K, V> where
+    Tree: Default + BinTreeMut<Elem = Entry<K, V>>
+{
+}
+
+impl<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> ScapegoatTreeMap<Tree, K, V> {
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, Tree, K, V> {
+        self.bst.iter()
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, Tree, K, V> {
+        self.bst.keys()
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, Tree, K, V> {
+        self.bst.values()
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, Tree, K, V>> {
+        self.bst.cursor_at(key)
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, Tree, K, V> {
+        self.bst.lower_bound_cursor(key)
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> ScapegoatTreeMap<Tree, K, V> {
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Tree, K, V> {
+        self.bst.iter_mut()
+    }
+}
+
+/// 替罪羊树上的只读游标.
+pub type Cursor<'a, Tree, K, V> = bst::Cursor<'a, Tree, K, V>;
+
+/// 替罪羊树的中序迭代器.
+pub type Iter<'a, Tree, K, V> = bst::Iter<'a, Tree, K, V>;
+
+/// 替罪羊树的可变中序迭代器.
+pub type IterMut<'a, Tree, K, V> = bst::IterMut<'a, Tree, K, V>;
+
+/// 替罪羊树的所有权中序迭代器.
+pub type IntoIter<Tree, K, V> = bst::IntoIter<Tree, K, V>;
+
+/// 替罪羊树的键迭代器.
+pub type Keys<'a, Tree, K, V> = bst::Keys<'a, Tree, K, V>;
+
+/// 替罪羊树的值迭代器.
+pub type Values<'a, Tree, K, V> = bst::Values<'a, Tree, K, V>;
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIterator
+    for &'a ScapegoatTreeMap<Tree, K, V>
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIterator
+    for &'a mut ScapegoatTreeMap<Tree, K, V>
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIterator
+    for ScapegoatTreeMap<Tree, K, V>
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.bst.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
+    use crate::ch4::linked_binary_tree::LinkedBinaryTree;
+    use crate::ch4::vec_binary_tree::VecBinaryTree;
+    use crate::ch4::BinTree;
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_balanced_path() {
+        for len in 1..50 {
+            for rank in 0..len {
+                let mut entries = (0..len).map(|key| Entry { key, value: () });
+                let mut tree: LinkedBinaryTree<_> =
+                    ScapegoatTreeMap::<LinkedBinaryTree<_>, _, _>::build(&mut entries, len);
+                let cursor = ScapegoatTreeMap::<LinkedBinaryTree<_>, _, _>::cursor_along(
+                    &mut tree,
+                    &balanced_path(len, rank),
+                );
+                assert_eq!(cursor.as_ref().map(|entry| entry.key), Some(rank));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sequential() {
+        let mut map = ScapegoatTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+        for idx in 0..1000 {
+            assert_eq!(*map.get_mut_or_insert(idx, idx), idx);
+            assert_eq!(subtree_size(map.bst.tree.cursor()), idx + 1);
+            assert_eq!(map.validate(), Ok(()));
+        }
+        for idx in 0..1000 {
+            assert_eq!(map.get(&idx), Some(&idx));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_map_lbt(mut data: HashMap<String, i64>, random: String) {
+            let mut map = ScapegoatTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+            assert!(map.is_empty());
+            // insert
+            for (k, v) in data.clone() {
+                assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+
+            assert_eq!(map.len(), data.len());
+
+            // get
+            for k in data.keys() {
+                assert_eq!(map.get(k), data.get(k));
+            }
+
+            // random get
+            assert_eq!(map.get(&random), data.get(&random));
+
+            // get_mut
+            for k in data.keys() {
+                if let Some(elem) = map.get_mut(k) { *elem += 1 };
+                assert_eq!(map.get(k).copied(), data.get(k).map(|elem| elem + 1));
+            }
+
+            assert_eq!(map.len(), data.len());
+
+            // replace by insert
+            for k in data.keys().cloned() {
+                let elem = data.get(&k).copied().unwrap();
+                assert_eq!(map.insert(k, elem), Some(elem + 1));
+            }
+
+            assert_eq!(map.len(), data.len());
+
+            // remove
+            for (idx, k) in data.keys().enumerate() {
+                assert_eq!(map.remove(k), data.get(k).copied());
+                assert_eq!(map.len(), data.len() - idx - 1);
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            assert!(map.is_empty());
+        }
+
+        #[test]
+        fn test_map_dlbt(data: HashMap<String, i64>) {
+            let mut map = ScapegoatTreeMap::<DoublyLinkedBinaryTree<_>, _, _>::default();
+            for (k, v) in data.clone() {
+                assert_eq!(*map.get_mut_or_insert(k.clone(), v), v);
+                *map.get_mut_or_insert(k, 0) += 1;
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            assert_eq!(map.len(), data.len());
+            for (k, v) in &mut map {
+                assert_eq!(*v, data[k] + 1);
+                *v -= 1;
+            }
+            let mut expected = data.into_iter().collect::<Vec<_>>();
+            expected.sort();
+            assert!(map.into_iter().eq(expected.into_iter()));
+        }
+
+        #[test]
+        fn test_map_vbt(data: HashMap<String, i64>) {
+            let mut map = ScapegoatTreeMap::<VecBinaryTree<_>, _, _>::default();
+            for (k, v) in data.clone() {
+                assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for (k, v) in data.iter() {
+                assert_eq!(map.get(k), Some(v));
+            }
+            for (idx, k) in data.keys().enumerate() {
+                assert_eq!(map.remove(k), data.get(k).copied());
+                assert_eq!(map.len(), data.len() - idx - 1);
+            }
+        }
+        #[test]
+        fn test_cursor(data: BTreeMap<u8, u8>, probes: Vec<u8>) {
+            let mut map = ScapegoatTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(map.cursor_at(&probe).is_some(), data.contains_key(&probe));
+                assert_eq!(cursor.peek_prev(), data.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                let mut walk = data.range(probe..).chain(data.range(..probe));
+                for _ in 0..=data.len() {
+                    match cursor.key_value() {
+                        Some(entry) => assert_eq!(Some(entry), walk.next()),
+                        None => assert_eq!(cursor.peek_next(), data.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                assert_eq!(cursor.key_value(), data.range(probe..).next());
+            }
+        }
+    }
+}
@@ -0,0 +1,983 @@
+use super::treap::PrioritySource;
+use super::{Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr::NonNull;
+
+/// 最大层数.
+pub const MAX_LEVEL: usize = 32;
+
+type NodePosi<K, V> = NonNull<Node<K, V>>;
+
+/// 指向下一个结点的链接. 在"位置"的语境下，`None`表示头结点.
+type Link<K, V> = Option<NodePosi<K, V>>;
+
+/// 各层的前驱位置及其秩.
+type Predecessors<K, V> = ([Link<K, V>; MAX_LEVEL], [usize; MAX_LEVEL]);
+
+/// 某一层上的链接.
+///
+/// `span`是沿这条链接前进时在底层跨过的结点数. 若`next`为空，则`span`为之后剩余的结点数，
+/// 这使得插入与删除时无需区分链接是否为空.
+struct Level<K: Ord, V> {
+    next: Link<K, V>,
+    span: usize,
+}
+
+struct Node<K: Ord, V> {
+    entry: Entry<K, V>,
+    /// 底层的前驱，`None`表示头结点.
+    prev: Link<K, V>,
+    levels: Vec<Level<K, V>>,
+}
+
+/// 跳表.
+///
+/// 每个结点的层数按几何分布随机产生(每高一层的概率为`1/2`)，查找、插入与删除的期望复杂度为`O(log n)`.
+/// 链接上记录了跨度，因此还支持`O(log n)`期望时间的`rank`与`select`.
+/// 层数由带种子的伪随机源产生，相同的种子与操作序列总是得到相同的结构.
+pub struct SkipListMap<K: Ord, V> {
+    head: Vec<Level<K, V>>,
+    tail: Link<K, V>,
+    /// 当前层数.
+    level: usize,
+    len: usize,
+    levels: PrioritySource,
+    stats: Counter,
+    marker: PhantomData<Box<Node<K, V>>>,
+}
+
+impl<K: Ord, V> Default for SkipListMap<K, V> {
+    fn default() -> Self {
+        Self::with_source(PrioritySource::default())
+    }
+}
+
+impl<K: Ord, V> SkipListMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 用给定的种子创建.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_source(PrioritySource::with_seed(seed))
+    }
+
+    fn with_source(levels: PrioritySource) -> Self {
+        Self {
+            head: (0..MAX_LEVEL)
+                .map(|_| Level {
+                    next: None,
+                    span: 0,
+                })
+                .collect(),
+            tail: None,
+            level: 0,
+            len: 0,
+            levels,
+            stats: Counter::default(),
+            marker: PhantomData::default(),
+        }
+    }
+
+    fn random_level(&mut self) -> usize {
+        (self.levels.next_priority().trailing_zeros() as usize + 1).min(MAX_LEVEL)
+    }
+
+    fn levels(&self, posi: Link<K, V>) -> &[Level<K, V>] {
+        match posi {
+            None => &self.head,
+            // Safety: 结点由跳表独占，且只在`&mut self`的方法中被释放.
+            Some(node) => unsafe { &(*node.as_ptr()).levels },
+        }
+    }
+
+    fn levels_mut(&mut self, posi: Link<K, V>) -> &mut [Level<K, V>] {
+        match posi {
+            None => &mut self.head,
+            // Safety: 同上，且`&mut self`保证了引用的唯一性.
+            Some(node) => unsafe { &mut (*node.as_ptr()).levels },
+        }
+    }
+
+    /// 结点中的词条. `node`必须是本表中的结点.
+    fn entry(&self, node: NodePosi<K, V>) -> &Entry<K, V> {
+        // Safety: 表中的结点在`&self`期间存活，且不会被修改.
+        unsafe { &(*node.as_ptr()).entry }
+    }
+
+    /// 从高层到低层，找出每一层中最后一个键满足`before`的位置及其秩(头结点的秩为`0`).
+    fn predecessors<F: Fn(&K) -> bool>(&self, before: F) -> Predecessors<K, V> {
+        let mut posis = [None; MAX_LEVEL];
+        let mut ranks = [0; MAX_LEVEL];
+        let mut posi = None;
+        let mut rank = 0;
+        for level in (0..self.level).rev() {
+            loop {
+                let link = &self.levels(posi)[level];
+                let next = match link.next {
+                    Some(next) => next,
+                    None => break,
+                };
+                stats::record(|stats| stats.comparisons += 1);
+                if !before(&self.entry(next).key) {
+                    break;
+                }
+                rank += link.span;
+                posi = Some(next);
+            }
+            posis[level] = posi;
+            ranks[level] = rank;
+        }
+        (posis, ranks)
+    }
+
+    /// 键等于`key`的结点.
+    fn find(&self, key: &K) -> Link<K, V> {
+        let (posis, _) = self.predecessors(|k| k < key);
+        self.levels(posis[0])[0].next.filter(|node| {
+            stats::record(|stats| stats.comparisons += 1);
+            self.entry(*node).key == *key
+        })
+    }
+
+    /// 插入一个不在表中的键，`posis`与`ranks`是`key`的各层前驱.
+    ///
+    /// 调用时不在操作之中(需要`&mut self`)，因此直接在计数器上记录分配.
+    fn insert_new(
+        &mut self,
+        (mut posis, mut ranks): Predecessors<K, V>,
+        entry: Entry<K, V>,
+    ) -> NodePosi<K, V> {
+        let level = self.random_level();
+        if level > self.level {
+            for idx in self.level..level {
+                posis[idx] = None;
+                ranks[idx] = 0;
+                self.head[idx] = Level {
+                    next: None,
+                    span: self.len,
+                };
+            }
+            self.level = level;
+        }
+        let node = Box::new(Node {
+            entry,
+            prev: posis[0],
+            levels: Vec::with_capacity(level),
+        });
+        let node = NonNull::new(Box::leak(node)).unwrap();
+        self.stats.record(|stats| stats.allocations += 1);
+        for idx in 0..level {
+            let gap = ranks[0] - ranks[idx];
+            let link = &mut self.levels_mut(posis[idx])[idx];
+            let next = link.next.replace(node);
+            let span = link.span - gap;
+            link.span = gap + 1;
+            // Safety: 结点刚刚创建，没有其它引用.
+            unsafe { (*node.as_ptr()).levels.push(Level { next, span }) };
+        }
+        for (idx, posi) in posis.iter().enumerate().take(self.level).skip(level) {
+            self.levels_mut(*posi)[idx].span += 1;
+        }
+        match self.levels(Some(node))[0].next {
+            // Safety: 同上.
+            Some(next) => unsafe { (*next.as_ptr()).prev = Some(node) },
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+        node
+    }
+
+    /// 摘除结点`node`，`posis`是它的各层前驱.
+    fn unlink(&mut self, posis: [Link<K, V>; MAX_LEVEL], node: NodePosi<K, V>) -> Entry<K, V> {
+        // Safety: 结点已从表中摘除，且没有其它引用.
+        let boxed = unsafe { Box::from_raw(node.as_ptr()) };
+        for (idx, posi) in posis.iter().enumerate().take(self.level) {
+            let link = &mut self.levels_mut(*posi)[idx];
+            match boxed.levels.get(idx) {
+                Some(removed) if link.next == Some(node) => {
+                    link.next = removed.next;
+                    link.span = link.span + removed.span - 1;
+                }
+                _ => link.span -= 1,
+            }
+        }
+        match boxed.levels[0].next {
+            // Safety: 同上.
+            Some(next) => unsafe { (*next.as_ptr()).prev = boxed.prev },
+            None => self.tail = boxed.prev,
+        }
+        while self.level > 0 && self.head[self.level - 1].next.is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        boxed.entry
+    }
+
+    /// 返回最小的键值对.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.head[0].next.map(|node| {
+            let entry = self.entry(node);
+            (&entry.key, &entry.value)
+        })
+    }
+
+    /// 返回最大的键值对.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.tail.map(|node| {
+            let entry = self.entry(node);
+            (&entry.key, &entry.value)
+        })
+    }
+
+    /// 返回不大于`key`的最大的键值对.
+    pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        let (posis, _) = self.predecessors(|k| k <= key);
+        posis[0].map(|node| {
+            let entry = self.entry(node);
+            (&entry.key, &entry.value)
+        })
+    }
+
+    /// 返回不小于`key`的最小的键值对.
+    pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        let (posis, _) = self.predecessors(|k| k < key);
+        self.levels(posis[0])[0].next.map(|node| {
+            let entry = self.entry(node);
+            (&entry.key, &entry.value)
+        })
+    }
+
+    /// 返回小于`key`的键的个数.
+    pub fn rank(&self, key: &K) -> usize {
+        let (_, ranks) = self.predecessors(|k| k < key);
+        ranks[0]
+    }
+
+    /// 返回按键升序排列的第`rank`个(从`0`开始)键值对.
+    pub fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        if rank >= self.len {
+            return None;
+        }
+        // 寻找秩为`rank + 1`的结点.
+        let mut posi = None;
+        let mut traversed = 0;
+        for level in (0..self.level).rev() {
+            loop {
+                let link = &self.levels(posi)[level];
+                match link.next {
+                    Some(next) if traversed + link.span <= rank + 1 => {
+                        traversed += link.span;
+                        posi = Some(next);
+                    }
+                    _ => break,
+                }
+            }
+            if traversed == rank + 1 {
+                break;
+            }
+        }
+        posi.map(|node| {
+            let entry = self.entry(node);
+            (&entry.key, &entry.value)
+        })
+    }
+
+    /// 按键的升序返回键落在`range`中的所有键值对的迭代器. 起点在终点之后时迭代器为空.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        // 第一个结点及其秩.
+        let (front, front_rank) = match range.start_bound() {
+            Bound::Unbounded => (self.head[0].next, 1),
+            bound => {
+                let (posis, ranks) = match bound {
+                    Bound::Included(start) => self.predecessors(|k| k < start),
+                    _ => self.predecessors(|k| match range.start_bound() {
+                        Bound::Excluded(start) => k <= start,
+                        _ => unreachable!(),
+                    }),
+                };
+                (self.levels(posis[0])[0].next, ranks[0] + 1)
+            }
+        };
+        // 最后一个结点及其秩.
+        let (back, back_rank) = match range.end_bound() {
+            Bound::Unbounded => (self.tail, self.len),
+            Bound::Included(end) => {
+                let (posis, ranks) = self.predecessors(|k| k <= end);
+                (posis[0], ranks[0])
+            }
+            Bound::Excluded(end) => {
+                let (posis, ranks) = self.predecessors(|k| k < end);
+                (posis[0], ranks[0])
+            }
+        };
+        Range {
+            front,
+            back,
+            len: (back_rank + 1).saturating_sub(front_rank),
+            marker: PhantomData::default(),
+        }
+    }
+
+    /// 按键的升序返回迭代器.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, K, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, K, V>> {
+        self.find(key).map(|node| Cursor {
+            map: self,
+            current: Some(node),
+        })
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, K, V> {
+        let (posis, _) = self.predecessors(|k| k < key);
+        Cursor {
+            map: self,
+            current: self.levels(posis[0])[0].next,
+        }
+    }
+
+    /// 返回指向`key`的可变游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at_mut(&mut self, key: &K) -> Option<CursorMut<'_, K, V>> {
+        let current = self.find(key)?;
+        Some(CursorMut {
+            map: self,
+            current: Some(current),
+        })
+    }
+
+    /// 返回指向第一个不小于`key`的词条的可变游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor_mut(&mut self, key: &K) -> CursorMut<'_, K, V> {
+        let (posis, _) = self.predecessors(|k| k < key);
+        let current = self.levels(posis[0])[0].next;
+        CursorMut { map: self, current }
+    }
+
+    /// `forward`时返回底层的后继，否则返回前驱. `None`表示幽灵位置.
+    fn step(&self, current: Link<K, V>, forward: bool) -> Link<K, V> {
+        match current {
+            None if forward => self.head[0].next,
+            None => self.tail,
+            Some(node) if forward => self.levels(Some(node))[0].next,
+            // Safety: 表中的结点在`&self`期间存活.
+            Some(node) => unsafe { node.as_ref().prev },
+        }
+    }
+
+    /// 检查键的顺序、前驱链接、各层的链接与跨度、层数与长度.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        // 底层上的所有结点，结点的秩为下标加一.
+        let mut nodes: Vec<NodePosi<K, V>> = Vec::new();
+        let mut link = self.head[0].next;
+        while let Some(node) = link {
+            let position = nodes.len();
+            let prev = nodes.last().copied();
+            // Safety: 表中的结点在`&self`期间存活，且不会被修改.
+            let node_ref = unsafe { &*node.as_ptr() };
+            if node_ref.prev != prev {
+                violations.push(Violation::SiblingLink { position });
+            }
+            if matches!(prev, Some(prev) if self.entry(prev).key >= node_ref.entry.key) {
+                violations.push(Violation::Unordered { position });
+            }
+            nodes.push(node);
+            match node_ref.levels.first() {
+                Some(level) => link = level.next,
+                None => {
+                    violations.push(Violation::SiblingLink { position });
+                    break;
+                }
+            }
+        }
+        if self.tail != nodes.last().copied() {
+            violations.push(Violation::SiblingLink {
+                position: nodes.len().saturating_sub(1),
+            });
+        }
+        if nodes.len() != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: nodes.len(),
+            });
+        }
+        let height = |idx: usize| self.levels(Some(nodes[idx])).len();
+        let max_height = (0..nodes.len()).map(height).max().unwrap_or(0);
+        if max_height != self.level {
+            violations.push(Violation::Level {
+                recorded: self.level,
+                actual: max_height,
+            });
+        }
+        let ranks = nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (*node, idx + 1))
+            .collect::<HashMap<_, _>>();
+        for level in 0..self.level {
+            let (mut posi, mut rank) = (None, 0);
+            loop {
+                let link = &self.levels(posi)[level];
+                // 每一层都应当恰好链接所有高于该层的结点.
+                let expected = (rank..nodes.len())
+                    .find(|idx| height(*idx) > level)
+                    .map(|idx| idx + 1);
+                let target = link.next.and_then(|next| ranks.get(&next).copied());
+                if target != expected || link.next.is_some() != target.is_some() {
+                    violations.push(Violation::SiblingLink { position: rank });
+                    break;
+                }
+                let span = target.unwrap_or(nodes.len()) - rank;
+                if link.span != span {
+                    violations.push(Violation::Span {
+                        position: rank,
+                        level,
+                        recorded: link.span,
+                        actual: span,
+                    });
+                }
+                match (link.next, target) {
+                    (Some(next), Some(target)) => {
+                        posi = Some(next);
+                        rank = target;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        validate::into_validation(violations)
+    }
+}
+
+impl<K: Ord, V> Drop for SkipListMap<K, V> {
+    fn drop(&mut self) {
+        let mut link = self.head[0].next;
+        while let Some(node) = link {
+            // Safety: 每个结点恰好被释放一次.
+            let boxed = unsafe { Box::from_raw(node.as_ptr()) };
+            link = boxed.levels[0].next;
+        }
+    }
+}
+
+impl<K: Ord, V> UnorderedMap<K, V> for SkipListMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
+        self.find(key).map(|node| &self.entry(node).value)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let _stats = self.stats.enter();
+        // Safety: `&mut self`保证了引用的唯一性.
+        self.find(key)
+            .map(|node| unsafe { &mut (*node.as_ptr()).entry.value })
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let predecessors = {
+            let _stats = self.stats.enter();
+            match self.find(&key) {
+                // Safety: 同上.
+                Some(node) => {
+                    let old = unsafe { &mut (*node.as_ptr()).entry.value };
+                    return Some(mem::replace(old, value));
+                }
+                None => self.predecessors(|k| *k < key),
+            }
+        };
+        self.insert_new(predecessors, Entry { key, value });
+        None
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let predecessors = {
+            let _stats = self.stats.enter();
+            match self.find(&key) {
+                // Safety: 同上.
+                Some(node) => return unsafe { &mut (*node.as_ptr()).entry.value },
+                None => self.predecessors(|k| *k < key),
+            }
+        };
+        let node = self.insert_new(
+            predecessors,
+            Entry {
+                key,
+                value: default,
+            },
+        );
+        // Safety: 同上.
+        unsafe { &mut (*node.as_ptr()).entry.value }
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let (posis, node) = {
+            let _stats = self.stats.enter();
+            let (posis, _) = self.predecessors(|k| k < key);
+            let node = self.levels(posis[0])[0].next?;
+            stats::record(|stats| stats.comparisons += 1);
+            if self.entry(node).key != *key {
+                return None;
+            }
+            (posis, node)
+        };
+        Some(self.unlink(posis, node).value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(SkipListMap::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<K: Ord, V> Map<K, V> for SkipListMap<K, V> {}
+
+/// 跳表的区间迭代器.
+pub struct Range<'a, K: Ord, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    len: usize,
+    marker: PhantomData<&'a Node<K, V>>,
+}
+
+impl<'a, K: Ord, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // Safety: 迭代器存活期间跳表不可变.
+        let node = unsafe { &*self.front?.as_ptr() };
+        self.front = node.levels[0].next;
+        self.len -= 1;
+        Some((&node.entry.key, &node.entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K: Ord, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // Safety: 同上.
+        let node = unsafe { &*self.back?.as_ptr() };
+        self.back = node.prev;
+        self.len -= 1;
+        Some((&node.entry.key, &node.entry.value))
+    }
+}
+
+impl<'a, K: Ord, V> ExactSizeIterator for Range<'a, K, V> {}
+
+/// 跳表的迭代器，即覆盖全部键的区间迭代器.
+pub type Iter<'a, K, V> = Range<'a, K, V>;
+
+pub type Keys<'a, K, V> = super::Keys<Iter<'a, K, V>>;
+
+pub type Values<'a, K, V> = super::Values<Iter<'a, K, V>>;
+
+impl<'a, K: Ord, V> IntoIterator for &'a SkipListMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// 跳表上的只读游标.
+///
+/// 游标指向某个结点，或者指向最后一个结点之后、第一个结点之前的幽灵位置. 沿底层链接移动，复杂度为`O(1)`.
+pub struct Cursor<'a, K: Ord, V> {
+    map: &'a SkipListMap<K, V>,
+    current: Link<K, V>,
+}
+
+impl<'a, K: Ord, V> Clone for Cursor<'a, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map,
+            current: self.current,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Cursor<'a, K, V> {
+    /// 移动至后继. 最后一个结点的后继是幽灵位置，幽灵位置的后继是第一个结点.
+    pub fn move_next(&mut self) {
+        self.current = self.map.step(self.current, true);
+    }
+
+    /// 移动至前驱. 第一个结点的前驱是幽灵位置，幽灵位置的前驱是最后一个结点.
+    pub fn move_prev(&mut self) {
+        self.current = self.map.step(self.current, false);
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        let map = self.map;
+        self.current.map(|node| {
+            let entry = map.entry(node);
+            (&entry.key, &entry.value)
+        })
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.key_value().map(|(_, value)| value)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_next();
+        cursor.key_value()
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let mut cursor = self.clone();
+        cursor.move_prev();
+        cursor.key_value()
+    }
+}
+
+/// 跳表上的可变游标.
+///
+/// 只能修改值. 插入与删除需要各层的前驱，而结点中只记录了底层的前驱，仍需通过`Map`的方法从头结点查找.
+pub struct CursorMut<'a, K: Ord, V> {
+    map: &'a mut SkipListMap<K, V>,
+    current: Link<K, V>,
+}
+
+impl<'a, K: Ord, V> CursorMut<'a, K, V> {
+    /// 移动至后继. 最后一个结点的后继是幽灵位置，幽灵位置的后继是第一个结点.
+    pub fn move_next(&mut self) {
+        self.current = self.map.step(self.current, true);
+    }
+
+    /// 移动至前驱. 第一个结点的前驱是幽灵位置，幽灵位置的前驱是最后一个结点.
+    pub fn move_prev(&mut self) {
+        self.current = self.map.step(self.current, false);
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&K, &V)> {
+        let map = &*self.map;
+        self.current.map(|node| {
+            let entry = map.entry(node);
+            (&entry.key, &entry.value)
+        })
+    }
+
+    /// 当前词条，其中值是可变的. 位于幽灵位置时返回`None`.
+    pub fn key_value_mut(&mut self) -> Option<(&K, &mut V)> {
+        // Safety: 游标可变借用了跳表，返回的引用又借用了游标，因此是唯一的.
+        self.current.map(|node| unsafe {
+            let entry = &mut (*node.as_ptr()).entry;
+            (&entry.key, &mut entry.value)
+        })
+    }
+
+    pub fn key(&self) -> Option<&K> {
+        self.key_value().map(|(key, _)| key)
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        self.key_value_mut().map(|(_, value)| value)
+    }
+}
+
+/// 基于跳表的有序集合.
+pub struct SkipListSet<K: Ord> {
+    map: SkipListMap<K, ()>,
+}
+
+impl<K: Ord> Default for SkipListSet<K> {
+    fn default() -> Self {
+        Self {
+            map: SkipListMap::default(),
+        }
+    }
+}
+
+impl<K: Ord> SkipListSet<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 用给定的种子创建.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            map: SkipListMap::with_seed(seed),
+        }
+    }
+
+    /// 插入一个键. 若键已存在则返回`false`.
+    pub fn insert(&mut self, key: K) -> bool {
+        self.map.insert(key, ()).is_none()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.get(key).is_some()
+    }
+
+    /// 删除一个键. 若键不存在则返回`false`.
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.map.remove(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn first(&self) -> Option<&K> {
+        self.map.first_key_value().map(|(key, _)| key)
+    }
+
+    pub fn last(&self) -> Option<&K> {
+        self.map.last_key_value().map(|(key, _)| key)
+    }
+
+    /// 返回不大于`key`的最大的键.
+    pub fn floor(&self, key: &K) -> Option<&K> {
+        self.map.floor(key).map(|(key, _)| key)
+    }
+
+    /// 返回不小于`key`的最小的键.
+    pub fn ceiling(&self, key: &K) -> Option<&K> {
+        self.map.ceiling(key).map(|(key, _)| key)
+    }
+
+    /// 返回小于`key`的键的个数.
+    pub fn rank(&self, key: &K) -> usize {
+        self.map.rank(key)
+    }
+
+    /// 返回升序排列的第`rank`个(从`0`开始)键.
+    pub fn select(&self, rank: usize) -> Option<&K> {
+        self.map.select(rank).map(|(key, _)| key)
+    }
+
+    /// 按升序返回落在`range`中的所有键的迭代器.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Keys<'_, K, ()> {
+        super::Keys::new(self.map.range(range))
+    }
+
+    /// 按升序返回迭代器.
+    pub fn iter(&self) -> Keys<'_, K, ()> {
+        self.map.keys()
+    }
+}
+
+impl<'a, K: Ord> IntoIterator for &'a SkipListSet<K> {
+    type Item = &'a K;
+    type IntoIter = Keys<'a, K, ()>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    #[test]
+    fn test_seeded() {
+        let heights = |seed| {
+            let mut map = SkipListMap::with_seed(seed);
+            for key in 0..100 {
+                map.insert(key, key);
+            }
+            assert_eq!(map.validate(), Ok(()));
+            let mut heights = Vec::new();
+            let mut link = map.head[0].next;
+            while let Some(node) = link {
+                heights.push(map.levels(link).len());
+                link = map.levels(Some(node))[0].next;
+            }
+            heights
+        };
+        assert_eq!(heights(42), heights(42));
+        assert_ne!(heights(42), heights(69));
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = SkipListMap::with_seed(42);
+        for key in 0..100 {
+            map.insert(key, ());
+        }
+        let stats = map.stats();
+        assert_eq!(stats.allocations, 100);
+        assert!(stats.comparisons > 0);
+        map.insert(0, ());
+        assert_eq!(map.stats().allocations, 100);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut set = SkipListSet::with_seed(7);
+        for key in [5, 1, 9, 3, 7].iter() {
+            assert!(set.insert(*key));
+        }
+        assert!(!set.insert(3));
+        assert!(set.remove(&9));
+        assert!(!set.remove(&9));
+        assert!(set.iter().copied().eq(vec![1, 3, 5, 7]));
+        assert!(set.range(2..=5).copied().eq(vec![3, 5]));
+        assert_eq!(set.floor(&4), Some(&3));
+        assert_eq!(set.ceiling(&4), Some(&5));
+        assert_eq!(set.ceiling(&8), None);
+        assert_eq!(set.rank(&5), 2);
+        assert_eq!(set.select(3), Some(&7));
+        assert_eq!((set.first(), set.last()), (Some(&1), Some(&7)));
+    }
+
+    fn bound(key: Option<u8>, inclusive: bool) -> Bound<u8> {
+        match key {
+            None => Bound::Unbounded,
+            Some(key) if inclusive => Bound::Included(key),
+            Some(key) => Bound::Excluded(key),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_skip_list_map(entrys: Vec<(u8, u32)>, removes: Vec<u8>, seed: u64) {
+            let mut map = SkipListMap::with_seed(seed);
+            let mut expected = BTreeMap::new();
+            for (key, value) in entrys.iter() {
+                prop_assert_eq!(map.insert(*key, *value), expected.insert(*key, *value));
+            }
+            prop_assert_eq!(map.validate(), Ok(()));
+            for key in removes.iter() {
+                prop_assert_eq!(map.remove(key), expected.remove(key));
+                *map.get_mut_or_insert(key.wrapping_mul(3), 0) += 1;
+                *expected.entry(key.wrapping_mul(3)).or_insert(0) += 1;
+            }
+            prop_assert_eq!(map.validate(), Ok(()));
+            prop_assert_eq!(map.len(), expected.len());
+            prop_assert!(map.iter().eq(expected.iter()));
+            prop_assert!(map.iter().rev().eq(expected.iter().rev()));
+            for key in 0..=u8::MAX {
+                prop_assert_eq!(map.get(&key), expected.get(&key));
+            }
+        }
+
+        #[test]
+        fn test_cursor(data: BTreeMap<u8, u8>, removes: Vec<u8>, probes: Vec<u8>, seed: u64) {
+            let mut map = SkipListMap::with_seed(seed);
+            let mut expected = data.clone();
+            for (k, v) in data {
+                map.insert(k, v);
+            }
+            for k in removes {
+                map.remove(&k);
+                expected.remove(&k);
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                prop_assert_eq!(map.cursor_at(&probe).is_some(), expected.contains_key(&probe));
+                prop_assert_eq!(cursor.peek_prev(), expected.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                let mut walk = expected.range(probe..).chain(expected.range(..probe));
+                for _ in 0..=expected.len() {
+                    match cursor.key_value() {
+                        Some(entry) => prop_assert_eq!(Some(entry), walk.next()),
+                        None => prop_assert_eq!(cursor.peek_next(), expected.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                prop_assert_eq!(cursor.key_value(), expected.range(probe..).next());
+
+                // 向前修改所有小于`probe`的值.
+                let mut cursor = map.lower_bound_cursor_mut(&probe);
+                cursor.move_prev();
+                while let Some(value) = cursor.value_mut() {
+                    *value = value.wrapping_add(1);
+                    cursor.move_prev();
+                }
+                for (_, value) in expected.range_mut(..probe) {
+                    *value = value.wrapping_add(1);
+                }
+                prop_assert!(map.iter().eq(expected.iter()));
+            }
+        }
+
+        #[test]
+        fn test_ordered(keys: Vec<u8>, queries: Vec<(Option<u8>, bool, Option<u8>, bool)>) {
+            let mut map = SkipListMap::with_seed(0);
+            let mut expected = BTreeSet::new();
+            for key in keys.iter() {
+                map.insert(*key, ());
+                expected.insert(*key);
+            }
+            for (rank, key) in expected.iter().enumerate() {
+                prop_assert_eq!(map.select(rank).map(|(key, _)| key), Some(key));
+            }
+            prop_assert_eq!(map.select(expected.len()), None);
+            for key in 0..=u8::MAX {
+                prop_assert_eq!(map.rank(&key), expected.range(..key).count());
+                prop_assert_eq!(map.floor(&key).map(|(key, _)| key), expected.range(..=key).next_back());
+                prop_assert_eq!(map.ceiling(&key).map(|(key, _)| key), expected.range(key..).next());
+            }
+            for (start, start_inclusive, end, end_inclusive) in queries {
+                let range = (bound(start, start_inclusive), bound(end, end_inclusive));
+                let valid = match (start, end) {
+                    (Some(start), Some(end)) => start < end || (start == end && start_inclusive && end_inclusive),
+                    _ => true,
+                };
+                let iter = map.range(range).map(|(key, _)| key);
+                if valid {
+                    prop_assert_eq!(iter.len(), expected.range(range).count());
+                    prop_assert!(iter.eq(expected.range(range)));
+                    prop_assert!(map.range(range).rev().map(|(key, _)| key).eq(expected.range(range).rev()));
+                } else {
+                    prop_assert_eq!(iter.count(), 0);
+                }
+            }
+        }
+    }
+}
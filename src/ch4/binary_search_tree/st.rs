@@ -1,4 +1,6 @@
-use super::{bst::TreeMap, Entry, Map};
+use super::{bst, bst::TreeMap, Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Stats};
+use crate::ch4::validate::Validation;
 use crate::ch4::{BinTreeCursorMut, MoveParentBinTreeMut, MoveParentCursorMut};
 use std::cmp::Ordering;
 use std::mem;
@@ -22,6 +24,10 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> SplayT
                 let vflag = cursor.is_left_child();
                 cursor.move_parent();
                 if cursor.parent().is_some() {
+                    stats::record(|stats| {
+                        stats.splay_steps += 1;
+                        stats.rotations += 2;
+                    });
                     let pflag = cursor.is_left_child();
                     match (vflag, pflag) {
                         (true, true) => {
@@ -45,10 +51,16 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> SplayT
                             cursor.zig();
                         }
                     }
-                } else if vflag {
-                    cursor.zig();
                 } else {
-                    cursor.zag();
+                    stats::record(|stats| {
+                        stats.splay_steps += 1;
+                        stats.rotations += 1;
+                    });
+                    if vflag {
+                        cursor.zig();
+                    } else {
+                        cursor.zag();
+                    }
                 }
             }
         }
@@ -75,7 +87,7 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Defaul
     }
 }
 
-impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K, V>
+impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> UnorderedMap<K, V>
     for SplayTreeMap<Tree, K, V>
 {
     /// 返回键所对应的值的引用.
@@ -90,6 +102,7 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
     ///
     /// 无论是否命中，都会将树中与`key`最接近的某个结点伸展至到根.
     fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let _stats = self.bst.stats.enter();
         let mut cursor = self.bst.tree.move_parent_cursor_mut();
         if let Some(Ordering::Equal) = Self::move_to_target_and_splay(&mut cursor, key) {
             cursor.into_mut().map(|entry| &mut entry.value)
@@ -102,11 +115,19 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
         self.bst.len()
     }
 
+    /// 返回按键升序排列的键值对迭代器.
+    ///
+    /// 迭代不会改变树结构，不会进行伸展操作.
     fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
-        self.bst.iter()
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.bst.stats.get()
     }
 
     fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let _stats = self.bst.stats.enter();
         let mut parent = self.bst.tree.move_parent_cursor_mut();
         match Self::move_to_target_and_splay(&mut parent, &key) {
             Some(Ordering::Equal) => (),
@@ -120,6 +141,7 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
                 });
                 parent.append_left(pl);
                 parent.append_right(p);
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
             }
             Some(Ordering::Greater) => {
@@ -132,6 +154,7 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
                 });
                 parent.append_right(pr);
                 parent.append_left(p);
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
             }
             None => {
@@ -139,6 +162,7 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
                     key,
                     value: default,
                 });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
             }
         }
@@ -146,6 +170,7 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
     }
 
     fn insert(&mut self, key: K, mut value: V) -> Option<V> {
+        let _stats = self.bst.stats.enter();
         let mut parent = self.bst.tree.move_parent_cursor_mut();
         match Self::move_to_target_and_splay(&mut parent, &key) {
             Some(Ordering::Equal) => {
@@ -159,6 +184,7 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
                 parent.insert_as_root(Entry { key, value });
                 parent.append_left(pl);
                 parent.append_right(p);
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 None
             }
@@ -169,11 +195,13 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
                 parent.insert_as_root(Entry { key, value });
                 parent.append_right(pr);
                 parent.append_left(p);
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 None
             }
             None => {
                 parent.insert_as_root(Entry { key, value });
+                stats::record(|stats| stats.allocations += 1);
                 self.bst.len += 1;
                 None
             }
@@ -181,6 +209,7 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
     }
 
     fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.bst.stats.enter();
         let mut cursor = self.bst.tree.move_parent_cursor_mut();
         if let Some(Ordering::Equal) = Self::move_to_target_and_splay(&mut cursor, key) {
             self.bst.len -= 1;
@@ -202,14 +231,108 @@ impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K,
     }
 }
 
+impl<Tree: Default + MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> Map<K, V>
+    for SplayTreeMap<Tree, K, V>
+{
+}
+
+impl<Tree: MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> SplayTreeMap<Tree, K, V> {
+    /// 按键的升序返回所有键值对的迭代器. 不会进行伸展操作.
+    pub fn iter(&self) -> Iter<'_, Tree, K, V> {
+        self.bst.iter()
+    }
+
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的. 不会进行伸展操作.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Tree, K, V> {
+        self.bst.iter_mut()
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, Tree, K, V> {
+        self.bst.keys()
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, Tree, K, V> {
+        self.bst.values()
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`. 不会进行伸展操作.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, Tree, K, V>> {
+        self.bst.cursor_at(key)
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置. 不会进行伸展操作.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, Tree, K, V> {
+        self.bst.lower_bound_cursor(key)
+    }
+
+    /// 检查键的顺序与长度. 伸展树没有额外的平衡条件.
+    pub fn validate(&self) -> Validation {
+        self.bst.validate()
+    }
+}
+
+/// 伸展树上的只读游标.
+pub type Cursor<'a, Tree, K, V> = bst::Cursor<'a, Tree, K, V>;
+
+/// 伸展树的中序迭代器.
+pub type Iter<'a, Tree, K, V> = bst::Iter<'a, Tree, K, V>;
+
+/// 伸展树的可变中序迭代器.
+pub type IterMut<'a, Tree, K, V> = bst::IterMut<'a, Tree, K, V>;
+
+/// 伸展树的所有权中序迭代器.
+pub type IntoIter<Tree, K, V> = bst::IntoIter<Tree, K, V>;
+
+/// 伸展树的键迭代器.
+pub type Keys<'a, Tree, K, V> = bst::Keys<'a, Tree, K, V>;
+
+/// 伸展树的值迭代器.
+pub type Values<'a, Tree, K, V> = bst::Values<'a, Tree, K, V>;
+
+impl<'a, Tree: MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIterator
+    for &'a SplayTreeMap<Tree, K, V>
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, Tree: MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIterator
+    for &'a mut SplayTreeMap<Tree, K, V>
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<Tree: MoveParentBinTreeMut<Elem = Entry<K, V>>, K: Ord, V> IntoIterator
+    for SplayTreeMap<Tree, K, V>
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.bst.into_iter()
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use super::super::test::check_iter;
     use super::*;
     use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
     // use crate::ch4::vec_binary_tree::VecBinaryTree;
     use crate::ch4::{BinTree, BinTreeCursor};
     use proptest::prelude::*;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     // #[test]
     // fn test_map_basic() {
@@ -282,6 +405,7 @@ mod test {
             // insert
             for (k, v) in data.clone() {
                 assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
             }
 
             assert_eq!(map.len(), data.len());
@@ -297,6 +421,7 @@ mod test {
             // get_mut
             for k in data.keys() {
                 if let Some(elem) = map.get_mut(k) { *elem += 1 };
+                prop_assert_eq!(map.validate(), Ok(()));
                 assert_eq!(map.get(k).copied(), data.get(k).map(|elem| elem + 1));
                 // 伸展性
                 assert_eq!(map.bst.tree.cursor().into_ref().map(|entry| &entry.key), Some(k))
@@ -308,6 +433,7 @@ mod test {
             for k in data.keys().cloned() {
                 let elem = data.get(&k).copied().unwrap();
                 assert_eq!(map.insert(k.clone(), elem), Some(elem + 1));
+                prop_assert_eq!(map.validate(), Ok(()));
                 // 伸展性
                 assert_eq!(map.bst.tree.cursor().into_ref().map(|entry| &entry.key), Some(&k))
             }
@@ -317,9 +443,52 @@ mod test {
             // remove
             for (idx, k) in data.keys().enumerate() {
                 assert_eq!(map.remove(k), data.get(k).copied());
+                prop_assert_eq!(map.validate(), Ok(()));
                 assert_eq!(map.len(), data.len() - idx - 1);
             }
             assert!(map.is_empty());
         }
+
+        #[test]
+        fn test_iter(data: HashMap<String, i64>) {
+            check_iter::<SplayTreeMap<DoublyLinkedBinaryTree<_>, _, _>, _>(data, SplayTreeMap::validate)?;
+        }
+        #[test]
+        fn test_cursor(data: BTreeMap<u8, u8>, probes: Vec<u8>) {
+            let mut map = SplayTreeMap::<DoublyLinkedBinaryTree<_>, _, _>::default();
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(map.cursor_at(&probe).is_some(), data.contains_key(&probe));
+                assert_eq!(cursor.peek_prev(), data.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                let mut walk = data.range(probe..).chain(data.range(..probe));
+                for _ in 0..=data.len() {
+                    match cursor.key_value() {
+                        Some(entry) => assert_eq!(Some(entry), walk.next()),
+                        None => assert_eq!(cursor.peek_next(), data.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                assert_eq!(cursor.key_value(), data.range(probe..).next());
+            }
+        }
+    }
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = SplayTreeMap::<DoublyLinkedBinaryTree<_>, _, _>::default();
+        for key in 0..10 {
+            map.insert(key, ());
+        }
+        let stats = map.stats();
+        // 升序插入时新结点总是作为根的右孩子接入，无需伸展.
+        assert_eq!(stats.splay_steps, 0);
+        map.get_mut(&0);
+        let stats = map.stats();
+        assert_eq!(stats.splay_steps, 5);
+        assert_eq!(stats.rotations, 9);
     }
 }
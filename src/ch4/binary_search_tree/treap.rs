@@ -0,0 +1,883 @@
+use super::bst::{self, TreeMap};
+use super::{Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use crate::ch4::{BinTreeCursor, BinTreeCursorMut, BinTreeMut};
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+/// 伪随机优先级源(xorshift64*).
+///
+/// 相同的种子总是产生相同的优先级序列，便于复现树的结构.
+#[derive(Debug, Clone)]
+pub struct PrioritySource {
+    state: u64,
+}
+
+impl PrioritySource {
+    pub fn with_seed(seed: u64) -> Self {
+        // 状态不能为`0`.
+        Self {
+            state: (seed ^ 0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    /// 产生下一个优先级.
+    pub fn next_priority(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl Default for PrioritySource {
+    /// 以随机种子创建.
+    fn default() -> Self {
+        Self::with_seed(RandomState::new().build_hasher().finish())
+    }
+}
+
+#[derive(Debug)]
+pub struct TreapNode<T> {
+    elem: T,
+    priority: u64,
+    size: usize,
+}
+
+impl<T> Deref for TreapNode<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.elem
+    }
+}
+
+impl<T> DerefMut for TreapNode<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.elem
+    }
+}
+
+pub type TreapEntry<K, V> = Entry<K, TreapNode<V>>;
+
+/// 基于树堆(Treap)的字典.
+///
+/// 按键是一棵二叉查找树，按优先级是一个大顶堆. 由于优先级是随机的，树高的期望为`O(log n)`.
+/// 结点中还记录了子树规模，以支持`O(log n)`期望时间的`split`与`join`.
+pub struct TreapMap<Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> {
+    bst: TreeMap<Tree, K, TreapNode<V>>,
+    priorities: PrioritySource,
+}
+
+impl<Tree, K: Ord, V> Default for TreapMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = TreapEntry<K, V>>,
+{
+    fn default() -> Self {
+        Self {
+            bst: TreeMap::default(),
+            priorities: PrioritySource::default(),
+        }
+    }
+}
+
+impl<Tree, K: Ord, V> TreapMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = TreapEntry<K, V>>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 以给定种子的优先级源创建. 相同的操作序列总是得到相同的树.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            bst: TreeMap::default(),
+            priorities: PrioritySource::with_seed(seed),
+        }
+    }
+
+    fn size<'a, C>(cursor: &C) -> usize
+    where
+        C: BinTreeCursor<'a, Elem = Tree::Elem>,
+    {
+        cursor.as_ref().map_or(0, |entry| entry.value.size)
+    }
+
+    fn left_size<'a, C>(cursor: &C) -> usize
+    where
+        C: BinTreeCursor<'a, Elem = Tree::Elem>,
+    {
+        cursor.left().map_or(0, |entry| entry.value.size)
+    }
+
+    fn right_size<'a, C>(cursor: &C) -> usize
+    where
+        C: BinTreeCursor<'a, Elem = Tree::Elem>,
+    {
+        cursor.right().map_or(0, |entry| entry.value.size)
+    }
+
+    fn update_size<'a, C>(cursor: &mut C)
+    where
+        C: BinTreeCursorMut<'a, Elem = Tree::Elem>,
+    {
+        let size = Self::left_size(cursor) + Self::right_size(cursor) + 1;
+        if let Some(entry) = cursor.as_mut() {
+            entry.value.size = size;
+        }
+    }
+
+    /// 提升左孩子(`zig`)，并维护规模. 游标所指位置不变.
+    fn rotate_right<'a, C>(cursor: &mut C)
+    where
+        C: BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+    {
+        let lr = {
+            let mut lc = cursor.cursor();
+            lc.move_left();
+            lc.right().map_or(0, |entry| entry.value.size)
+        };
+        let total = Self::size(cursor);
+        let pr = Self::right_size(cursor);
+        stats::record(|stats| stats.rotations += 1);
+        cursor.zig();
+        cursor.as_mut().unwrap().value.size = total;
+        cursor.right_mut().unwrap().value.size = lr + pr + 1;
+    }
+
+    /// 提升右孩子(`zag`)，并维护规模. 游标所指位置不变.
+    fn rotate_left<'a, C>(cursor: &mut C)
+    where
+        C: BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+    {
+        let rl = {
+            let mut rc = cursor.cursor();
+            rc.move_right();
+            rc.left().map_or(0, |entry| entry.value.size)
+        };
+        let total = Self::size(cursor);
+        let pl = Self::left_size(cursor);
+        stats::record(|stats| stats.rotations += 1);
+        cursor.zag();
+        cursor.as_mut().unwrap().value.size = total;
+        cursor.left_mut().unwrap().value.size = pl + rl + 1;
+    }
+
+    /// 沿`path`(`true`表示向左)从根下降.
+    fn cursor_along<'a>(tree: &'a mut Tree, path: &[bool]) -> Tree::CursorMut<'a> {
+        let mut cursor = tree.cursor_mut();
+        for &is_left in path {
+            if is_left {
+                cursor.move_left();
+            } else {
+                cursor.move_right();
+            }
+        }
+        cursor
+    }
+
+    /// 插入一个不存在的键，并返回新结点最终所在的路径.
+    ///
+    /// 先作为叶子插入，再沿路径不断旋转直至满足堆序. 旋转次数的期望不超过`2`，
+    /// 而每次旋转都要从根重新下降，因此期望复杂度仍为`O(log n)`.
+    fn insert_new(&mut self, key: K, value: V) -> Vec<bool> {
+        let _stats = self.bst.stats.enter();
+        let priority = self.priorities.next_priority();
+        let mut path = Vec::new();
+        let node = TreapNode {
+            elem: value,
+            priority,
+            size: 1,
+        };
+        let mut cursor = self.bst.tree.cursor_mut();
+        if cursor.is_empty_subtree() {
+            cursor.insert_as_root(Entry { key, value: node });
+        } else {
+            loop {
+                let entry = cursor.as_mut().unwrap();
+                entry.value.size += 1;
+                stats::record(|stats| stats.comparisons += 1);
+                if key < entry.key {
+                    path.push(true);
+                    if cursor.left().is_none() {
+                        cursor.insert_as_left(Entry { key, value: node });
+                        break;
+                    }
+                    cursor.move_left();
+                } else {
+                    path.push(false);
+                    if cursor.right().is_none() {
+                        cursor.insert_as_right(Entry { key, value: node });
+                        break;
+                    }
+                    cursor.move_right();
+                }
+            }
+        }
+        drop(cursor);
+        stats::record(|stats| stats.allocations += 1);
+        self.bst.len += 1;
+
+        while let Some(&is_left) = path.last() {
+            let mut cursor = Self::cursor_along(&mut self.bst.tree, &path[..path.len() - 1]);
+            let child = if is_left {
+                cursor.left()
+            } else {
+                cursor.right()
+            };
+            if child.unwrap().value.priority <= cursor.as_ref().unwrap().value.priority {
+                break;
+            }
+            if is_left {
+                Self::rotate_right(&mut cursor);
+            } else {
+                Self::rotate_left(&mut cursor);
+            }
+            path.pop();
+        }
+        path
+    }
+
+    fn tree_size(tree: &Tree) -> usize {
+        Self::size(&tree.cursor())
+    }
+
+    /// 将`tree`分裂为键小于`key`与不小于`key`的两棵树.
+    fn split_tree(mut tree: Tree, key: &K) -> (Tree, Tree) {
+        if tree.is_empty() {
+            return (tree, Tree::default());
+        }
+        let goes_left = tree.cursor().as_ref().unwrap().key < *key;
+        if goes_left {
+            let rest = {
+                let mut cursor = tree.cursor_mut();
+                let right = cursor.take_right().unwrap();
+                let (lhs, rhs) = Self::split_tree(right, key);
+                cursor.append_right(lhs);
+                Self::update_size(&mut cursor);
+                rhs
+            };
+            (tree, rest)
+        } else {
+            let rest = {
+                let mut cursor = tree.cursor_mut();
+                let left = cursor.take_left().unwrap();
+                let (lhs, rhs) = Self::split_tree(left, key);
+                cursor.append_left(rhs);
+                Self::update_size(&mut cursor);
+                lhs
+            };
+            (rest, tree)
+        }
+    }
+
+    /// 合并两棵树，要求`lhs`中的键都小于`rhs`中的键.
+    fn join_tree(mut lhs: Tree, mut rhs: Tree) -> Tree {
+        let lp = lhs.cursor().as_ref().map(|entry| entry.value.priority);
+        let rp = rhs.cursor().as_ref().map(|entry| entry.value.priority);
+        match (lp, rp) {
+            (None, _) => rhs,
+            (_, None) => lhs,
+            (Some(lp), Some(rp)) => {
+                if lp > rp {
+                    {
+                        let mut cursor = lhs.cursor_mut();
+                        let right = cursor.take_right().unwrap();
+                        cursor.append_right(Self::join_tree(right, rhs));
+                        Self::update_size(&mut cursor);
+                    }
+                    lhs
+                } else {
+                    {
+                        let mut cursor = rhs.cursor_mut();
+                        let left = cursor.take_left().unwrap();
+                        cursor.append_left(Self::join_tree(lhs, left));
+                        Self::update_size(&mut cursor);
+                    }
+                    rhs
+                }
+            }
+        }
+    }
+
+    /// 返回最小的键.
+    pub fn first_key(&self) -> Option<&K> {
+        let mut cursor = self.bst.tree.cursor();
+        while cursor.left().is_some() {
+            cursor.move_left();
+        }
+        cursor.into_ref().map(|entry| &entry.key)
+    }
+
+    /// 返回最大的键.
+    pub fn last_key(&self) -> Option<&K> {
+        let mut cursor = self.bst.tree.cursor();
+        while cursor.right().is_some() {
+            cursor.move_right();
+        }
+        cursor.into_ref().map(|entry| &entry.key)
+    }
+
+    /// 按`key`将字典一分为二: 自身保留所有小于`key`的键，返回所有不小于`key`的键.
+    ///
+    /// 期望复杂度为`O(log n)`.
+    pub fn split(&mut self, key: &K) -> Self {
+        let tree = mem::take(&mut self.bst.tree);
+        let (lhs, rhs) = Self::split_tree(tree, key);
+        self.bst.len = Self::tree_size(&lhs);
+        self.bst.tree = lhs;
+        let len = Self::tree_size(&rhs);
+        Self {
+            bst: TreeMap::from_tree(rhs, len),
+            priorities: PrioritySource::with_seed(self.priorities.next_priority()),
+        }
+    }
+
+    /// 将`other`中的所有词条并入自身.
+    ///
+    /// 期望复杂度为`O(log n)`.
+    /// # Panics
+    /// 自身所有的键都必须小于`other`中的键.
+    pub fn join(&mut self, other: Self) {
+        if let (Some(max), Some(min)) = (self.last_key(), other.first_key()) {
+            assert!(max < min, "`join`要求左侧所有的键都小于右侧的键.");
+        }
+        let lhs = mem::take(&mut self.bst.tree);
+        self.bst.tree = Self::join_tree(lhs, other.bst.tree);
+        self.bst.len += other.bst.len;
+    }
+
+    /// 检查二叉查找树的不变式，以及优先级的堆序与每个结点记录的规模.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        self.bst.validate_with(
+            &mut violations,
+            |position, entry, left: Option<&(u64, usize)>, right, violations| {
+                let (lp, ls) = left.copied().unwrap_or((0, 0));
+                let (rp, rs) = right.copied().unwrap_or((0, 0));
+                if lp > entry.value.priority || rp > entry.value.priority {
+                    violations.push(Violation::HeapOrder { position });
+                }
+                let size = ls + rs + 1;
+                if entry.value.size != size {
+                    violations.push(Violation::Size {
+                        position,
+                        recorded: entry.value.size,
+                        actual: size,
+                    });
+                }
+                (entry.value.priority, size)
+            },
+        );
+        validate::into_validation(violations)
+    }
+}
+
+impl<Tree, K: Ord, V> UnorderedMap<K, V> for TreapMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = TreapEntry<K, V>>,
+{
+    fn get(&self, key: &K) -> Option<&V> {
+        self.bst.get(key).map(|node| &**node)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.bst.get_mut(key).map(|node| &mut **node)
+    }
+
+    fn len(&self) -> usize {
+        self.bst.len()
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let path = if self.bst.get(&key).is_some() {
+            // 命中时的路径.
+            let _stats = self.bst.stats.enter();
+            let mut path = Vec::new();
+            let mut cursor = self.bst.tree.cursor();
+            while let Some(entry) = cursor.as_ref() {
+                stats::record(|stats| stats.comparisons += 1);
+                match key.cmp(&entry.key) {
+                    Ordering::Equal => break,
+                    Ordering::Less => {
+                        path.push(true);
+                        cursor.move_left();
+                    }
+                    Ordering::Greater => {
+                        path.push(false);
+                        cursor.move_right();
+                    }
+                }
+            }
+            path
+        } else {
+            self.insert_new(key, default)
+        };
+        let cursor = Self::cursor_along(&mut self.bst.tree, &path);
+        &mut cursor.into_mut().unwrap().value.elem
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(node) = self.bst.get_mut(&key) {
+            Some(mem::replace(&mut node.elem, value))
+        } else {
+            self.insert_new(key, value);
+            None
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.bst.get(key)?;
+        let _stats = self.bst.stats.enter();
+        self.bst.len -= 1;
+        let mut cursor = self.bst.tree.cursor_mut();
+        loop {
+            let entry = cursor.as_mut().unwrap();
+            stats::record(|stats| stats.comparisons += 1);
+            match key.cmp(&entry.key) {
+                Ordering::Equal => break,
+                Ordering::Less => {
+                    entry.value.size -= 1;
+                    cursor.move_left();
+                }
+                Ordering::Greater => {
+                    entry.value.size -= 1;
+                    cursor.move_right();
+                }
+            }
+        }
+        // 将目标结点旋转至最多只有一个孩子.
+        while let (Some(lc), Some(rc)) = (cursor.left(), cursor.right()) {
+            if lc.value.priority > rc.value.priority {
+                Self::rotate_right(&mut cursor);
+                cursor.as_mut().unwrap().value.size -= 1;
+                cursor.move_right();
+            } else {
+                Self::rotate_left(&mut cursor);
+                cursor.as_mut().unwrap().value.size -= 1;
+                cursor.move_left();
+            }
+        }
+        let child = if cursor.left().is_none() {
+            cursor.take_right()
+        } else {
+            cursor.take_left()
+        };
+        let entry = cursor.take().cursor_mut().into_inner().unwrap();
+        cursor.append(child.unwrap());
+        Some(entry.value.elem)
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.bst.stats.get()
+    }
+}
+
+impl<Tree, K: Ord, V> Map<K, V> for TreapMap<Tree, K, V> where
+    Tree: Default + BinTreeMut<Elem = TreapEntry<K, V>>
+{
+}
+
+impl<Tree, K: Ord, V> TreapMap<Tree, K, V>
+where
+    Tree: BinTreeMut<Elem = TreapEntry<K, V>>,
+{
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, Tree, K, V> {
+        Iter {
+            inner: self.bst.iter(),
+        }
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, Tree, K, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, Tree, K, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, Tree, K, V>> {
+        self.bst.cursor_at(key).map(|inner| Cursor { inner })
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, Tree, K, V> {
+        Cursor {
+            inner: self.bst.lower_bound_cursor(key),
+        }
+    }
+}
+
+impl<Tree, K: Ord, V> TreapMap<Tree, K, V>
+where
+    Tree: BinTreeMut<Elem = TreapEntry<K, V>>,
+{
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Tree, K, V> {
+        IterMut {
+            inner: self.bst.iter_mut(),
+        }
+    }
+}
+
+/// 树堆上的只读游标，参见`bst::Cursor`.
+pub struct Cursor<'a, Tree: 'a + BinTreeMut<Elem = TreapEntry<K, V>>, K: 'a + Ord, V: 'a> {
+    inner: bst::Cursor<'a, Tree, K, TreapNode<V>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> Clone for Cursor<'a, Tree, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> Cursor<'a, Tree, K, V> {
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.inner.move_next()
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev()
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.key_value().map(|(key, node)| (key, &node.elem))
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.inner.key()
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.inner.value().map(|node| &node.elem)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_next().map(|(key, node)| (key, &node.elem))
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_prev().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+/// 树堆的中序迭代器.
+pub struct Iter<'a, Tree: 'a + BinTreeMut<Elem = TreapEntry<K, V>>, K: 'a + Ord, V: 'a> {
+    inner: bst::Iter<'a, Tree, K, TreapNode<V>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> Iterator for Iter<'a, Tree, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> DoubleEndedIterator
+    for Iter<'a, Tree, K, V>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> ExactSizeIterator
+    for Iter<'a, Tree, K, V>
+{
+}
+
+/// 树堆的可变中序迭代器.
+pub struct IterMut<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> {
+    inner: bst::IterMut<'a, Tree, K, TreapNode<V>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: 'a + Ord, V: 'a> Iterator
+    for IterMut<'a, Tree, K, V>
+{
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &mut node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: 'a + Ord, V: 'a> DoubleEndedIterator
+    for IterMut<'a, Tree, K, V>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(key, node)| (key, &mut node.elem))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: 'a + Ord, V: 'a> ExactSizeIterator
+    for IterMut<'a, Tree, K, V>
+{
+}
+
+/// 树堆的所有权中序迭代器.
+pub struct IntoIter<Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> {
+    inner: bst::IntoIter<Tree, K, TreapNode<V>>,
+}
+
+impl<Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> Iterator for IntoIter<Tree, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> DoubleEndedIterator
+    for IntoIter<Tree, K, V>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, node.elem))
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> ExactSizeIterator
+    for IntoIter<Tree, K, V>
+{
+}
+
+/// 树堆的键迭代器.
+pub type Keys<'a, Tree, K, V> = super::Keys<Iter<'a, Tree, K, V>>;
+
+/// 树堆的值迭代器.
+pub type Values<'a, Tree, K, V> = super::Values<Iter<'a, Tree, K, V>>;
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> IntoIterator
+    for &'a TreapMap<Tree, K, V>
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> IntoIterator
+    for &'a mut TreapMap<Tree, K, V>
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = TreapEntry<K, V>>, K: Ord, V> IntoIterator for TreapMap<Tree, K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.bst.into_iter(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
+    use crate::ch4::linked_binary_tree::LinkedBinaryTree;
+    use crate::ch4::{BinTree, BinTreeCursorExt};
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_map_basic() {
+        let mut data = HashMap::new();
+        data.insert("Hello", 1);
+        data.insert("World", 2);
+        data.insert("!", 3);
+        data.insert("Good", 4);
+        data.insert("Job", 5);
+        let mut map = TreapMap::<LinkedBinaryTree<_>, _, _>::with_seed(42);
+        for (k, v) in data.clone() {
+            assert!(map.insert(k, v).is_none());
+        }
+        for k in data.keys() {
+            if let Some(elem) = map.get_mut(k) {
+                *elem += 1
+            }
+            assert_eq!(map.get(k).copied(), data.get(k).map(|elem| elem + 1));
+        }
+        for k in data.keys().cloned() {
+            let elem = data.get(&k).copied().unwrap();
+            assert_eq!(map.insert(k, elem), Some(elem + 1));
+        }
+        for k in data.keys() {
+            assert_eq!(map.remove(k), data.get(k).copied())
+        }
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_seeded() {
+        let mut lhs = TreapMap::<LinkedBinaryTree<_>, _, _>::with_seed(69);
+        let mut rhs = TreapMap::<LinkedBinaryTree<_>, _, _>::with_seed(69);
+        for idx in 0..100 {
+            lhs.insert(idx, idx);
+            rhs.insert(idx, idx);
+        }
+        let priorities = |map: &TreapMap<LinkedBinaryTree<_>, i32, i32>| {
+            map.bst
+                .tree
+                .cursor()
+                .pre_order_iter()
+                .map(|entry| entry.value.priority)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(priorities(&lhs), priorities(&rhs));
+    }
+
+    proptest! {
+        #[test]
+        fn test_map_lbt(mut data: HashMap<String, i64>, random: String, seed: u64) {
+            let mut map = TreapMap::<LinkedBinaryTree<_>, _, _>::with_seed(seed);
+            assert!(map.is_empty());
+            // insert
+            for (k, v) in data.clone() {
+                assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+
+            assert_eq!(map.len(), data.len());
+
+            // get
+            for k in data.keys() {
+                assert_eq!(map.get(k), data.get(k));
+            }
+
+            // random get
+            assert_eq!(map.get(&random), data.get(&random));
+
+            // get_mut
+            for k in data.keys() {
+                if let Some(elem) = map.get_mut(k) { *elem += 1 };
+                assert_eq!(map.get(k).copied(), data.get(k).map(|elem| elem + 1));
+            }
+
+            assert_eq!(map.len(), data.len());
+
+            // replace by insert
+            for k in data.keys().cloned() {
+                let elem = data.get(&k).copied().unwrap();
+                assert_eq!(map.insert(k, elem), Some(elem + 1));
+            }
+
+            assert_eq!(map.len(), data.len());
+
+            // remove
+            for (idx, k) in data.keys().enumerate() {
+                assert_eq!(map.remove(k), data.get(k).copied());
+                assert_eq!(map.len(), data.len() - idx - 1);
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            assert!(map.is_empty());
+        }
+
+        #[test]
+        fn test_map_dlbt(data: HashMap<String, i64>, seed: u64) {
+            let mut map = TreapMap::<DoublyLinkedBinaryTree<_>, _, _>::with_seed(seed);
+            for (k, v) in data.clone() {
+                assert_eq!(*map.get_mut_or_insert(k.clone(), v), v);
+                *map.get_mut_or_insert(k, 0) += 1;
+            }
+            prop_assert_eq!(map.validate(), Ok(()));
+            assert_eq!(map.len(), data.len());
+            for (k, v) in map.iter_mut() {
+                assert_eq!(*v, data[k] + 1);
+                *v -= 1;
+            }
+            let mut expected = data.into_iter().collect::<Vec<_>>();
+            expected.sort();
+            assert!(map.into_iter().eq(expected.into_iter()));
+        }
+
+        #[test]
+        fn test_split_join(data: HashMap<String, i64>, key: String, seed: u64) {
+            let mut map = TreapMap::<LinkedBinaryTree<_>, _, _>::with_seed(seed);
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+            }
+            let rhs = map.split(&key);
+            prop_assert_eq!(map.validate(), Ok(()));
+            prop_assert_eq!(rhs.validate(), Ok(()));
+            assert_eq!(map.len(), data.keys().filter(|k| **k < key).count());
+            assert_eq!(rhs.len(), data.keys().filter(|k| **k >= key).count());
+            assert!(map.keys().all(|k| *k < key));
+            assert!(rhs.keys().all(|k| *k >= key));
+
+            map.join(rhs);
+            prop_assert_eq!(map.validate(), Ok(()));
+            assert_eq!(map.len(), data.len());
+            for (k, v) in data.iter() {
+                assert_eq!(map.get(k), Some(v));
+            }
+        }
+        #[test]
+        fn test_cursor(data: BTreeMap<u8, u8>, probes: Vec<u8>, seed: u64) {
+            let mut map = TreapMap::<LinkedBinaryTree<_>, _, _>::with_seed(seed);
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(map.cursor_at(&probe).is_some(), data.contains_key(&probe));
+                assert_eq!(cursor.peek_prev(), data.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                let mut walk = data.range(probe..).chain(data.range(..probe));
+                for _ in 0..=data.len() {
+                    match cursor.key_value() {
+                        Some(entry) => assert_eq!(Some(entry), walk.next()),
+                        None => assert_eq!(cursor.peek_next(), data.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                assert_eq!(cursor.key_value(), data.range(probe..).next());
+            }
+        }
+    }
+}
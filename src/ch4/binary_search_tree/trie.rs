@@ -0,0 +1,430 @@
+use super::{Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use std::mem;
+
+struct Node<K: Ord, V> {
+    entry: Option<Entry<K, V>>,
+    /// 按字节升序排列的孩子.
+    childrens: Vec<(u8, Node<K, V>)>,
+    /// 子树中的键数.
+    count: usize,
+}
+
+impl<K: Ord, V> Default for Node<K, V> {
+    fn default() -> Self {
+        Self {
+            entry: None,
+            childrens: Vec::new(),
+            count: 0,
+        }
+    }
+}
+
+impl<K: Ord, V> Node<K, V> {
+    fn child(&self, byte: u8) -> Option<&Self> {
+        self.childrens
+            .binary_search_by_key(&byte, |(b, _)| *b)
+            .ok()
+            .map(|idx| &self.childrens[idx].1)
+    }
+
+    fn child_mut(&mut self, byte: u8) -> Option<&mut Self> {
+        match self.childrens.binary_search_by_key(&byte, |(b, _)| *b) {
+            Ok(idx) => Some(&mut self.childrens[idx].1),
+            Err(_) => None,
+        }
+    }
+
+    fn descend(&self, bytes: &[u8]) -> Option<&Self> {
+        bytes.iter().try_fold(self, |node, byte| node.child(*byte))
+    }
+
+    /// 插入词条，`rest`是键在当前结点之下的部分.
+    fn insert(&mut self, rest: &[u8], entry: Entry<K, V>) -> Option<V> {
+        let old = match rest.split_first() {
+            None => match &mut self.entry {
+                Some(old) => return Some(mem::replace(&mut old.value, entry.value)),
+                None => {
+                    self.entry = Some(entry);
+                    None
+                }
+            },
+            Some((byte, rest)) => {
+                let idx = match self.childrens.binary_search_by_key(byte, |(b, _)| *b) {
+                    Ok(idx) => idx,
+                    Err(idx) => {
+                        stats::record(|stats| stats.allocations += 1);
+                        self.childrens.insert(idx, (*byte, Node::default()));
+                        idx
+                    }
+                };
+                self.childrens[idx].1.insert(rest, entry)
+            }
+        };
+        if old.is_none() {
+            self.count += 1;
+        }
+        old
+    }
+
+    /// 删除键，并剪除变空的子树.
+    fn remove(&mut self, rest: &[u8]) -> Option<V> {
+        let removed = match rest.split_first() {
+            None => self.entry.take().map(|entry| entry.value),
+            Some((byte, rest)) => {
+                let idx = self
+                    .childrens
+                    .binary_search_by_key(byte, |(b, _)| *b)
+                    .ok()?;
+                let removed = self.childrens[idx].1.remove(rest);
+                if self.childrens[idx].1.count == 0 {
+                    self.childrens.remove(idx);
+                }
+                removed
+            }
+        };
+        if removed.is_some() {
+            self.count -= 1;
+        }
+        removed
+    }
+}
+
+impl<K: Ord + AsRef<[u8]>, V> Node<K, V> {
+    /// 先序遍历，检查孩子的顺序、词条所在的路径、记录的键数以及空子树是否被剪除. 返回子树的键数.
+    fn validate_at(
+        &self,
+        path: &mut Vec<u8>,
+        is_root: bool,
+        position: &mut usize,
+        violations: &mut Vec<Violation>,
+    ) -> usize {
+        let current = *position;
+        if let Some(entry) = &self.entry {
+            if entry.key.as_ref() != &path[..] {
+                violations.push(Violation::Path { position: current });
+            }
+            *position += 1;
+        }
+        if self.childrens.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            violations.push(Violation::Unordered { position: current });
+        }
+        let mut count = self.entry.iter().count();
+        for (byte, child) in self.childrens.iter() {
+            path.push(*byte);
+            count += child.validate_at(path, false, position, violations);
+            path.pop();
+        }
+        if !is_root && count == 0 {
+            violations.push(Violation::Redundant { position: current });
+        }
+        if self.count != count {
+            violations.push(Violation::Size {
+                position: current,
+                recorded: self.count,
+                actual: count,
+            });
+        }
+        count
+    }
+}
+
+/// 字典树(Trie). 键按字节逐层展开，每个结点至多有`256`个孩子.
+///
+/// 键可以是任何`AsRef<[u8]>`的类型，如`String`与`Vec<u8>`. 要求键的`Ord`与字节序一致.
+pub struct TrieMap<K: Ord + AsRef<[u8]>, V> {
+    root: Node<K, V>,
+    stats: Counter,
+}
+
+impl<K: Ord + AsRef<[u8]>, V> Default for TrieMap<K, V> {
+    fn default() -> Self {
+        Self {
+            root: Node::default(),
+            stats: Counter::default(),
+        }
+    }
+}
+
+impl<K: Ord + AsRef<[u8]>, V> TrieMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按键的升序返回以`prefix`为前缀的所有键值对.
+    pub fn prefix_iter<P: AsRef<[u8]>>(&self, prefix: P) -> Iter<'_, K, V> {
+        Iter::new(self.root.descend(prefix.as_ref()))
+    }
+
+    /// 以`prefix`为前缀的键数.
+    pub fn count_prefix<P: AsRef<[u8]>>(&self, prefix: P) -> usize {
+        self.root
+            .descend(prefix.as_ref())
+            .map_or(0, |node| node.count)
+    }
+
+    /// 返回是`key`的前缀的最长的键及其值.
+    pub fn longest_prefix_match<P: AsRef<[u8]>>(&self, key: P) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+        let mut founded = node.entry.as_ref();
+        for byte in key.as_ref() {
+            match node.child(*byte) {
+                Some(child) => node = child,
+                None => break,
+            }
+            founded = node.entry.as_ref().or(founded);
+        }
+        founded.map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// 按键的升序返回迭代器.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(Some(&self.root))
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, K, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 检查孩子的顺序、词条所在的路径、每个结点记录的键数，以及不存在不含键的结点.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        self.root
+            .validate_at(&mut Vec::new(), true, &mut 0, &mut violations);
+        validate::into_validation(violations)
+    }
+}
+
+impl<K: Ord + AsRef<[u8]>, V> UnorderedMap<K, V> for TrieMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
+        self.root
+            .descend(key.as_ref())?
+            .entry
+            .as_ref()
+            .map(|entry| &entry.value)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let _stats = self.stats.enter();
+        let mut node = &mut self.root;
+        for byte in key.as_ref() {
+            node = node.child_mut(*byte)?;
+        }
+        node.entry.as_mut().map(|entry| &mut entry.value)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let _stats = self.stats.enter();
+        let bytes = key.as_ref().to_vec();
+        self.root.insert(&bytes, Entry { key, value })
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let _stats = self.stats.enter();
+        let bytes = key.as_ref().to_vec();
+        let mut node = &mut self.root;
+        if node
+            .descend(&bytes)
+            .and_then(|node| node.entry.as_ref())
+            .is_none()
+        {
+            node.insert(
+                &bytes,
+                Entry {
+                    key,
+                    value: default,
+                },
+            );
+        }
+        for byte in bytes.iter() {
+            node = node.child_mut(*byte).unwrap();
+        }
+        &mut node.entry.as_mut().unwrap().value
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.stats.enter();
+        self.root.remove(key.as_ref())
+    }
+
+    fn len(&self) -> usize {
+        self.root.count
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(TrieMap::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<K: Ord + AsRef<[u8]>, V> Map<K, V> for TrieMap<K, V> {}
+
+/// 字典树的迭代器(先序遍历，即键的升序).
+pub struct Iter<'a, K: Ord, V> {
+    stack: Vec<&'a Node<K, V>>,
+    len: usize,
+}
+
+impl<'a, K: Ord, V> Iter<'a, K, V> {
+    fn new(root: Option<&'a Node<K, V>>) -> Self {
+        Self {
+            stack: root.into_iter().collect(),
+            len: root.map_or(0, |node| node.count),
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            self.stack
+                .extend(node.childrens.iter().rev().map(|(_, child)| child));
+            if let Some(entry) = &node.entry {
+                self.len -= 1;
+                return Some((&entry.key, &entry.value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K: Ord, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+pub type Keys<'a, K, V> = super::Keys<Iter<'a, K, V>>;
+
+pub type Values<'a, K, V> = super::Values<Iter<'a, K, V>>;
+
+impl<'a, K: Ord + AsRef<[u8]>, V> IntoIterator for &'a TrieMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_prefix() {
+        let mut map = TrieMap::new();
+        for word in ["to", "tea", "ted", "ten", "i", "in", "inn"].iter() {
+            map.insert(word.to_string(), word.len());
+        }
+        assert!(map
+            .prefix_iter("te")
+            .map(|(key, _)| key.as_str())
+            .eq(["tea", "ted", "ten"].iter().copied()));
+        assert_eq!(map.count_prefix("t"), 4);
+        assert_eq!(map.count_prefix(""), 7);
+        assert_eq!(map.count_prefix("x"), 0);
+        assert_eq!(
+            map.longest_prefix_match("inner")
+                .map(|(key, _)| key.as_str()),
+            Some("inn")
+        );
+        assert_eq!(
+            map.longest_prefix_match("ix").map(|(key, _)| key.as_str()),
+            Some("i")
+        );
+        assert_eq!(map.longest_prefix_match("x"), None);
+        assert_eq!(map.remove(&"inn".to_string()), Some(3));
+        assert_eq!(
+            map.longest_prefix_match("inner")
+                .map(|(key, _)| key.as_str()),
+            Some("in")
+        );
+        assert_eq!(map.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut map = TrieMap::new();
+        map.insert("a".to_string(), ());
+        map.insert("ab".to_string(), ());
+        assert_eq!(map.validate(), Ok(()));
+        map.root.childrens.push((b'b', Node::default()));
+        map.root.count = 3;
+        assert_eq!(
+            map.validate(),
+            Err(vec![
+                Violation::Redundant { position: 2 },
+                Violation::Size {
+                    position: 0,
+                    recorded: 3,
+                    actual: 2
+                }
+            ])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_trie_map(
+            entrys in prop::collection::vec(("[abc]{0,5}", any::<u32>()), 0..64),
+            removes in prop::collection::vec("[abc]{0,5}", 0..64),
+            queries in prop::collection::vec("[abc]{0,6}", 0..16),
+        ) {
+            let mut map = TrieMap::new();
+            let mut expected = BTreeMap::new();
+            for (key, value) in entrys.iter() {
+                prop_assert_eq!(map.insert(key.clone(), *value), expected.insert(key.clone(), *value));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for key in removes.iter() {
+                prop_assert_eq!(map.remove(key), expected.remove(key));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            prop_assert_eq!(map.len(), expected.len());
+            prop_assert!(map.iter().eq(expected.iter()));
+            for query in queries.iter() {
+                let prefixed = expected.iter().filter(|(key, _)| key.starts_with(query.as_str()));
+                prop_assert!(map.prefix_iter(query).eq(prefixed.clone()));
+                prop_assert_eq!(map.count_prefix(query), prefixed.count());
+                let longest = expected
+                    .iter()
+                    .filter(|(key, _)| query.starts_with(key.as_str()))
+                    .max_by_key(|(key, _)| key.len());
+                prop_assert_eq!(map.longest_prefix_match(query), longest);
+                *map.get_mut_or_insert(query.clone(), 0) += 1;
+                *expected.entry(query.clone()).or_insert(0) += 1;
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for (key, value) in expected.iter() {
+                prop_assert_eq!(map.get(key), Some(value));
+            }
+        }
+
+        #[test]
+        fn test_bytes(keys: Vec<Vec<u8>>) {
+            let mut map = TrieMap::new();
+            let mut expected = BTreeMap::new();
+            for key in keys.iter() {
+                prop_assert_eq!(map.insert(key.clone(), ()), expected.insert(key.clone(), ()));
+            }
+            prop_assert_eq!(map.validate(), Ok(()));
+            prop_assert!(map.keys().eq(expected.keys()));
+        }
+    }
+}
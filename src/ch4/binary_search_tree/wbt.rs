@@ -0,0 +1,806 @@
+use super::bst::{self, TreeMap};
+use super::{Entry, Map, UnorderedMap};
+use crate::ch4::stats::{self, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use crate::ch4::{BinTree, BinTreeCursor, BinTreeCursorMut, BinTreeMut};
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+/// 平衡参数: 一侧子树的权不得超过另一侧的`DELTA`倍.
+const DELTA: usize = 3;
+
+/// 旋转参数: 决定使用单旋还是双旋.
+const GAMMA: usize = 2;
+
+#[derive(Debug)]
+pub struct WBNode<T> {
+    elem: T,
+    size: usize,
+}
+
+impl<T> Deref for WBNode<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.elem
+    }
+}
+
+impl<T> DerefMut for WBNode<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.elem
+    }
+}
+
+pub type WBEntry<K, V> = Entry<K, WBNode<V>>;
+
+/// `size`个结点的子树的权.
+fn weight(size: usize) -> usize {
+    size + 1
+}
+
+/// 权为`lhs`与`rhs`的两棵子树是否平衡.
+fn is_balanced(lhs: usize, rhs: usize) -> bool {
+    DELTA * weight(lhs) >= weight(rhs) && DELTA * weight(rhs) >= weight(lhs)
+}
+
+/// 基于加权平衡树(Weight-Balanced Tree)的字典.
+///
+/// 每个结点记录子树规模，并要求左右子树的权(规模加一)之比不超过`DELTA`.
+/// 采用Hirai与Yamamoto给出的参数`(DELTA, GAMMA) = (3, 2)`，插入与删除后每个结点最多只需一次单旋或双旋.
+/// 由于记录了子树规模，还支持`O(log n)`的`rank`与`select`.
+pub struct WBTreeMap<Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> {
+    bst: TreeMap<Tree, K, WBNode<V>>,
+}
+
+impl<Tree, K: Ord, V> Default for WBTreeMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = WBEntry<K, V>>,
+{
+    fn default() -> Self {
+        Self {
+            bst: TreeMap::default(),
+        }
+    }
+}
+
+impl<Tree, K: Ord, V> WBTreeMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = WBEntry<K, V>>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn left_size<'a, C>(cursor: &C) -> usize
+    where
+        C: BinTreeCursor<'a, Elem = Tree::Elem>,
+    {
+        cursor.left().map_or(0, |entry| entry.value.size)
+    }
+
+    fn right_size<'a, C>(cursor: &C) -> usize
+    where
+        C: BinTreeCursor<'a, Elem = Tree::Elem>,
+    {
+        cursor.right().map_or(0, |entry| entry.value.size)
+    }
+
+    /// 提升左孩子(`zig`)，并维护规模. 游标所指位置不变.
+    fn rotate_right<'a, C>(cursor: &mut C)
+    where
+        C: BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+    {
+        let lr = {
+            let mut lc = cursor.cursor();
+            lc.move_left();
+            lc.right().map_or(0, |entry| entry.value.size)
+        };
+        let total = cursor.as_ref().unwrap().value.size;
+        let pr = Self::right_size(cursor);
+        stats::record(|stats| stats.rotations += 1);
+        cursor.zig();
+        cursor.as_mut().unwrap().value.size = total;
+        cursor.right_mut().unwrap().value.size = lr + pr + 1;
+    }
+
+    /// 提升右孩子(`zag`)，并维护规模. 游标所指位置不变.
+    fn rotate_left<'a, C>(cursor: &mut C)
+    where
+        C: BinTreeCursorMut<'a, Elem = Tree::Elem, SubTree = Tree>,
+    {
+        let rl = {
+            let mut rc = cursor.cursor();
+            rc.move_right();
+            rc.left().map_or(0, |entry| entry.value.size)
+        };
+        let total = cursor.as_ref().unwrap().value.size;
+        let pl = Self::left_size(cursor);
+        stats::record(|stats| stats.rotations += 1);
+        cursor.zag();
+        cursor.as_mut().unwrap().value.size = total;
+        cursor.left_mut().unwrap().value.size = pl + rl + 1;
+    }
+
+    /// 沿`path`(`true`表示向左)从根下降.
+    fn cursor_along<'a>(tree: &'a mut Tree, path: &[bool]) -> Tree::CursorMut<'a> {
+        let mut cursor = tree.cursor_mut();
+        for &is_left in path {
+            if is_left {
+                cursor.move_left();
+            } else {
+                cursor.move_right();
+            }
+        }
+        cursor
+    }
+
+    /// 将游标移动到中序秩为`rank`的结点.
+    fn move_to_rank<'a, C>(cursor: &mut C, mut rank: usize)
+    where
+        C: BinTreeCursor<'a, Elem = Tree::Elem>,
+    {
+        while !cursor.is_empty_subtree() {
+            let left = Self::left_size(cursor);
+            match rank.cmp(&left) {
+                Ordering::Equal => break,
+                Ordering::Less => cursor.move_left(),
+                Ordering::Greater => {
+                    rank -= left + 1;
+                    cursor.move_right();
+                }
+            }
+        }
+    }
+
+    /// 重新平衡`path`处的结点. 要求其左右子树本身都是平衡的.
+    fn rebalance(tree: &mut Tree, path: &[bool]) {
+        let mut cursor = Self::cursor_along(tree, path);
+        let (lhs, rhs) = (Self::left_size(&cursor), Self::right_size(&cursor));
+        if weight(rhs) > DELTA * weight(lhs) {
+            let (inner, outer) = {
+                let mut rc = cursor.cursor();
+                rc.move_right();
+                (Self::left_size(&rc), Self::right_size(&rc))
+            };
+            if weight(inner) >= GAMMA * weight(outer) {
+                cursor.move_right();
+                Self::rotate_right(&mut cursor);
+                drop(cursor);
+                cursor = Self::cursor_along(tree, path);
+            }
+            Self::rotate_left(&mut cursor);
+        } else if weight(lhs) > DELTA * weight(rhs) {
+            let (inner, outer) = {
+                let mut lc = cursor.cursor();
+                lc.move_left();
+                (Self::right_size(&lc), Self::left_size(&lc))
+            };
+            if weight(inner) >= GAMMA * weight(outer) {
+                cursor.move_left();
+                Self::rotate_left(&mut cursor);
+                drop(cursor);
+                cursor = Self::cursor_along(tree, path);
+            }
+            Self::rotate_right(&mut cursor);
+        }
+    }
+
+    /// 自深向浅地重新平衡`path`上深度在`depths`中的结点.
+    ///
+    /// 旋转不改变子树规模，因此祖先是否失衡只取决于更新后的规模，可在下降时预先确定.
+    fn rebalance_all(tree: &mut Tree, path: &[bool], depths: &[usize]) {
+        for &depth in depths.iter().rev() {
+            Self::rebalance(tree, &path[..depth]);
+        }
+    }
+
+    /// 插入一个不存在的键，并返回它的秩.
+    fn insert_new(&mut self, key: K, value: V) -> usize {
+        let _stats = self.bst.stats.enter();
+        let (mut path, mut depths, mut rank) = (Vec::new(), Vec::new(), 0);
+        let node = WBNode {
+            elem: value,
+            size: 1,
+        };
+        let mut cursor = self.bst.tree.cursor_mut();
+        if cursor.is_empty_subtree() {
+            cursor.insert_as_root(Entry { key, value: node });
+        } else {
+            loop {
+                let (lhs, rhs) = (Self::left_size(&cursor), Self::right_size(&cursor));
+                let entry = cursor.as_mut().unwrap();
+                entry.value.size += 1;
+                stats::record(|stats| stats.comparisons += 1);
+                if key < entry.key {
+                    if !is_balanced(lhs + 1, rhs) {
+                        depths.push(path.len());
+                    }
+                    path.push(true);
+                    if cursor.left().is_none() {
+                        cursor.insert_as_left(Entry { key, value: node });
+                        break;
+                    }
+                    cursor.move_left();
+                } else {
+                    if !is_balanced(lhs, rhs + 1) {
+                        depths.push(path.len());
+                    }
+                    rank += lhs + 1;
+                    path.push(false);
+                    if cursor.right().is_none() {
+                        cursor.insert_as_right(Entry { key, value: node });
+                        break;
+                    }
+                    cursor.move_right();
+                }
+            }
+        }
+        drop(cursor);
+        stats::record(|stats| stats.allocations += 1);
+        self.bst.len += 1;
+        Self::rebalance_all(&mut self.bst.tree, &path, &depths);
+        rank
+    }
+
+    /// 返回小于`key`的键的个数.
+    pub fn rank(&self, key: &K) -> usize {
+        let _stats = self.bst.stats.enter();
+        let mut rank = 0;
+        let mut cursor = self.bst.tree.cursor();
+        while let Some(entry) = cursor.as_ref() {
+            stats::record(|stats| stats.comparisons += 1);
+            match key.cmp(&entry.key) {
+                Ordering::Less => cursor.move_left(),
+                Ordering::Equal => return rank + Self::left_size(&cursor),
+                Ordering::Greater => {
+                    rank += Self::left_size(&cursor) + 1;
+                    cursor.move_right();
+                }
+            }
+        }
+        rank
+    }
+
+    /// 返回按键升序排列的第`rank`个(从`0`开始)键值对.
+    pub fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        if rank >= self.bst.len {
+            return None;
+        }
+        let mut cursor = self.bst.tree.cursor();
+        Self::move_to_rank(&mut cursor, rank);
+        cursor
+            .into_ref()
+            .map(|entry| (&entry.key, &entry.value.elem))
+    }
+
+    /// 检查二叉查找树的不变式，以及每个结点记录的规模与权平衡条件.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        self.bst.validate_with(
+            &mut violations,
+            |position, entry, left: Option<&usize>, right, violations| {
+                let (left, right) = (left.copied().unwrap_or(0), right.copied().unwrap_or(0));
+                let size = left + right + 1;
+                if entry.value.size != size {
+                    violations.push(Violation::Size {
+                        position,
+                        recorded: entry.value.size,
+                        actual: size,
+                    });
+                }
+                if !is_balanced(left, right) {
+                    violations.push(Violation::WeightBalance {
+                        position,
+                        left,
+                        right,
+                    });
+                }
+                size
+            },
+        );
+        validate::into_validation(violations)
+    }
+}
+
+impl<Tree, K: Ord, V> UnorderedMap<K, V> for WBTreeMap<Tree, K, V>
+where
+    Tree: Default + BinTreeMut<Elem = WBEntry<K, V>>,
+{
+    fn get(&self, key: &K) -> Option<&V> {
+        self.bst.get(key).map(|node| &**node)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.bst.get_mut(key).map(|node| &mut **node)
+    }
+
+    fn len(&self) -> usize {
+        self.bst.len()
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let rank = if self.bst.get(&key).is_some() {
+            self.rank(&key)
+        } else {
+            self.insert_new(key, default)
+        };
+        let mut cursor = self.bst.tree.cursor_mut();
+        Self::move_to_rank(&mut cursor, rank);
+        &mut cursor.into_mut().unwrap().value.elem
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(node) = self.bst.get_mut(&key) {
+            Some(mem::replace(&mut node.elem, value))
+        } else {
+            self.insert_new(key, value);
+            None
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.bst.stats.enter();
+        // 目标结点的路径，以及实际被摘除的结点(目标或其直接后继)的路径.
+        let (target, path) = {
+            let mut path = Vec::new();
+            let mut cursor = self.bst.tree.cursor();
+            loop {
+                let entry = cursor.as_ref()?;
+                stats::record(|stats| stats.comparisons += 1);
+                match key.cmp(&entry.key) {
+                    Ordering::Equal => break,
+                    Ordering::Less => {
+                        path.push(true);
+                        cursor.move_left();
+                    }
+                    Ordering::Greater => {
+                        path.push(false);
+                        cursor.move_right();
+                    }
+                }
+            }
+            let target = path.len();
+            if cursor.left().is_some() && cursor.right().is_some() {
+                path.push(false);
+                cursor.move_right();
+                while cursor.left().is_some() {
+                    path.push(true);
+                    cursor.move_left();
+                }
+            }
+            (target, path)
+        };
+
+        self.bst.len -= 1;
+        let mut depths = Vec::new();
+        let mut cursor = self.bst.tree.cursor_mut();
+        for (depth, &is_left) in path.iter().enumerate() {
+            let (lhs, rhs) = (Self::left_size(&cursor), Self::right_size(&cursor));
+            cursor.as_mut().unwrap().value.size -= 1;
+            let balanced = if is_left {
+                is_balanced(lhs - 1, rhs)
+            } else {
+                is_balanced(lhs, rhs - 1)
+            };
+            if !balanced {
+                depths.push(depth);
+            }
+            if is_left {
+                cursor.move_left();
+            } else {
+                cursor.move_right();
+            }
+        }
+        // 被摘除的结点最多只有一个孩子.
+        let child = if cursor.left().is_none() {
+            cursor.take_right()
+        } else {
+            cursor.take_left()
+        };
+        let mut entry = cursor.take().cursor_mut().into_inner().unwrap();
+        cursor.append(child.unwrap());
+        drop(cursor);
+        if target < path.len() {
+            // 用后继替换目标结点.
+            let mut cursor = Self::cursor_along(&mut self.bst.tree, &path[..target]);
+            let node = cursor.as_mut().unwrap();
+            mem::swap(&mut node.key, &mut entry.key);
+            mem::swap(&mut node.value.elem, &mut entry.value.elem);
+        }
+        Self::rebalance_all(&mut self.bst.tree, &path, &depths);
+        Some(entry.value.elem)
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(Self::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.bst.stats.get()
+    }
+}
+
+impl<Tree, K: Ord, V> Map<K, V> for WBTreeMap<Tree, K, V> where
+    Tree: Default + BinTreeMut<Elem = WBEntry<K, V>>
+{
+}
+
+impl<Tree, K: Ord, V> WBTreeMap<Tree, K, V>
+where
+    Tree: BinTreeMut<Elem = WBEntry<K, V>>,
+{
+    /// 按键的升序返回所有键值对的迭代器.
+    pub fn iter(&self) -> Iter<'_, Tree, K, V> {
+        Iter {
+            inner: self.bst.iter(),
+        }
+    }
+
+    /// 按键的升序返回所有键的迭代器.
+    pub fn keys(&self) -> Keys<'_, Tree, K, V> {
+        super::Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器.
+    pub fn values(&self) -> Values<'_, Tree, K, V> {
+        super::Values::new(self.iter())
+    }
+
+    /// 返回指向`key`的游标. 若`key`不存在则返回`None`.
+    pub fn cursor_at(&self, key: &K) -> Option<Cursor<'_, Tree, K, V>> {
+        self.bst.cursor_at(key).map(|inner| Cursor { inner })
+    }
+
+    /// 返回指向第一个不小于`key`的词条的游标. 若不存在则指向幽灵位置.
+    pub fn lower_bound_cursor(&self, key: &K) -> Cursor<'_, Tree, K, V> {
+        Cursor {
+            inner: self.bst.lower_bound_cursor(key),
+        }
+    }
+}
+
+impl<Tree, K: Ord, V> WBTreeMap<Tree, K, V>
+where
+    Tree: BinTreeMut<Elem = WBEntry<K, V>>,
+{
+    /// 按键的升序返回所有键值对的迭代器，其中值是可变的.
+    pub fn iter_mut(&mut self) -> IterMut<'_, Tree, K, V> {
+        IterMut {
+            inner: self.bst.iter_mut(),
+        }
+    }
+}
+
+/// 加权平衡树上的只读游标，参见`bst::Cursor`.
+pub struct Cursor<'a, Tree: 'a + BinTreeMut<Elem = WBEntry<K, V>>, K: 'a + Ord, V: 'a> {
+    inner: bst::Cursor<'a, Tree, K, WBNode<V>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> Clone for Cursor<'a, Tree, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> Cursor<'a, Tree, K, V> {
+    /// 移动至后继. 最后一个词条的后继是幽灵位置，幽灵位置的后继是第一个词条.
+    pub fn move_next(&mut self) {
+        self.inner.move_next()
+    }
+
+    /// 移动至前驱. 第一个词条的前驱是幽灵位置，幽灵位置的前驱是最后一个词条.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev()
+    }
+
+    /// 当前词条. 位于幽灵位置时返回`None`.
+    pub fn key_value(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.key_value().map(|(key, node)| (key, &node.elem))
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.inner.key()
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.inner.value().map(|node| &node.elem)
+    }
+
+    /// 后继词条，游标不移动.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_next().map(|(key, node)| (key, &node.elem))
+    }
+
+    /// 前驱词条，游标不移动.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        self.inner.peek_prev().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+/// 加权平衡树的中序迭代器.
+pub struct Iter<'a, Tree: 'a + BinTreeMut<Elem = WBEntry<K, V>>, K: 'a + Ord, V: 'a> {
+    inner: bst::Iter<'a, Tree, K, WBNode<V>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> Iterator for Iter<'a, Tree, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> DoubleEndedIterator
+    for Iter<'a, Tree, K, V>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, &node.elem))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> ExactSizeIterator
+    for Iter<'a, Tree, K, V>
+{
+}
+
+/// 加权平衡树的可变中序迭代器.
+pub struct IterMut<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> {
+    inner: bst::IterMut<'a, Tree, K, WBNode<V>>,
+}
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: 'a + Ord, V: 'a> Iterator
+    for IterMut<'a, Tree, K, V>
+{
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, &mut node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: 'a + Ord, V: 'a> DoubleEndedIterator
+    for IterMut<'a, Tree, K, V>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(key, node)| (key, &mut node.elem))
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: 'a + Ord, V: 'a> ExactSizeIterator
+    for IterMut<'a, Tree, K, V>
+{
+}
+
+/// 加权平衡树的所有权中序迭代器.
+pub struct IntoIter<Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> {
+    inner: bst::IntoIter<Tree, K, WBNode<V>>,
+}
+
+impl<Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> Iterator for IntoIter<Tree, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, node)| (key, node.elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> DoubleEndedIterator
+    for IntoIter<Tree, K, V>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, node)| (key, node.elem))
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> ExactSizeIterator for IntoIter<Tree, K, V> {}
+
+/// 加权平衡树的键迭代器.
+pub type Keys<'a, Tree, K, V> = super::Keys<Iter<'a, Tree, K, V>>;
+
+/// 加权平衡树的值迭代器.
+pub type Values<'a, Tree, K, V> = super::Values<Iter<'a, Tree, K, V>>;
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> IntoIterator
+    for &'a WBTreeMap<Tree, K, V>
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> IntoIterator
+    for &'a mut WBTreeMap<Tree, K, V>
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<Tree: BinTreeMut<Elem = WBEntry<K, V>>, K: Ord, V> IntoIterator for WBTreeMap<Tree, K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<Tree, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.bst.into_iter(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ch4::doubly_linked_binary_tree::DoublyLinkedBinaryTree;
+    use crate::ch4::linked_binary_tree::LinkedBinaryTree;
+    use crate::ch4::vec_binary_tree::VecBinaryTree;
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_sequential() {
+        let mut map = WBTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+        for idx in 0..500 {
+            assert_eq!(*map.get_mut_or_insert(idx, idx), idx);
+            assert_eq!(map.validate(), Ok(()));
+        }
+        for idx in (0..500).rev().step_by(2) {
+            assert_eq!(map.remove(&idx), Some(idx));
+            assert_eq!(map.validate(), Ok(()));
+        }
+        for idx in 0..250 {
+            assert_eq!(map.select(idx), Some((&(idx * 2), &(idx * 2))));
+            assert_eq!(map.rank(&(idx * 2 + 1)), idx + 1);
+        }
+    }
+
+    #[test]
+    fn test_map_basic_vbt() {
+        let data = [
+            "Hello", "World", "!", "Good", "Job", "Hhaha", "Xfwawd", "Gooo", "jiojoij",
+        ];
+        let mut map = WBTreeMap::<VecBinaryTree<_>, _, _>::default();
+        for (v, k) in data.iter().enumerate() {
+            assert!(map.insert(*k, v).is_none());
+            assert_eq!(map.validate(), Ok(()));
+        }
+        for (v, k) in data.iter().enumerate() {
+            assert_eq!(map.get(k), Some(&v));
+        }
+        for (v, k) in data.iter().enumerate() {
+            assert_eq!(map.remove(k), Some(v));
+            assert_eq!(map.validate(), Ok(()));
+        }
+        assert!(map.is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn test_map_lbt(mut data: HashMap<String, i64>, random: String) {
+            let mut map = WBTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+            assert!(map.is_empty());
+            // insert
+            for (k, v) in data.clone() {
+                assert!(map.insert(k, v).is_none());
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+
+            assert_eq!(map.len(), data.len());
+
+            // get
+            for k in data.keys() {
+                assert_eq!(map.get(k), data.get(k));
+            }
+
+            // random get
+            assert_eq!(map.get(&random), data.get(&random));
+
+            // get_mut
+            for k in data.keys() {
+                if let Some(elem) = map.get_mut(k) { *elem += 1 };
+                assert_eq!(map.get(k).copied(), data.get(k).map(|elem| elem + 1));
+            }
+
+            // replace by insert
+            for k in data.keys().cloned() {
+                let elem = data.get(&k).copied().unwrap();
+                assert_eq!(map.insert(k, elem), Some(elem + 1));
+            }
+
+            assert_eq!(map.len(), data.len());
+
+            // remove
+            for (idx, k) in data.keys().enumerate() {
+                assert_eq!(map.remove(k), data.get(k).copied());
+                assert_eq!(map.len(), data.len() - idx - 1);
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            assert!(map.is_empty());
+        }
+
+        #[test]
+        fn test_map_dlbt(data: HashMap<String, i64>) {
+            let mut map = WBTreeMap::<DoublyLinkedBinaryTree<_>, _, _>::default();
+            for (k, v) in data.clone() {
+                assert_eq!(*map.get_mut_or_insert(k.clone(), v), v);
+                *map.get_mut_or_insert(k, 0) += 1;
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            assert_eq!(map.len(), data.len());
+            for (k, v) in &mut map {
+                assert_eq!(*v, data[k] + 1);
+                *v -= 1;
+            }
+            let mut expected = data.into_iter().collect::<Vec<_>>();
+            expected.sort();
+            assert!(map.into_iter().eq(expected.into_iter()));
+        }
+
+        #[test]
+        fn test_rank_select(data: HashMap<i64, i64>, random: i64) {
+            let mut map = WBTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+            for (k, v) in data.clone() {
+                assert!(map.insert(k, v).is_none());
+            }
+            prop_assert_eq!(map.validate(), Ok(()));
+            let mut sorted = data.iter().collect::<Vec<_>>();
+            sorted.sort();
+            for (rank, (k, v)) in sorted.iter().enumerate() {
+                assert_eq!(map.rank(k), rank);
+                assert_eq!(map.select(rank), Some((*k, *v)));
+            }
+            assert_eq!(map.select(sorted.len()), None);
+            assert_eq!(map.rank(&random), data.keys().filter(|&&k| k < random).count());
+            for k in data.keys() {
+                assert_eq!(map.remove(k), data.get(k).copied());
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            assert!(map.is_empty());
+        }
+        #[test]
+        fn test_cursor(data: BTreeMap<u8, u8>, probes: Vec<u8>) {
+            let mut map = WBTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+            for (k, v) in data.clone() {
+                map.insert(k, v);
+            }
+            for probe in probes {
+                let mut cursor = map.lower_bound_cursor(&probe);
+                assert_eq!(map.cursor_at(&probe).is_some(), data.contains_key(&probe));
+                assert_eq!(cursor.peek_prev(), data.range(..probe).next_back());
+                // 向后走完一圈，途经幽灵位置.
+                let mut walk = data.range(probe..).chain(data.range(..probe));
+                for _ in 0..=data.len() {
+                    match cursor.key_value() {
+                        Some(entry) => assert_eq!(Some(entry), walk.next()),
+                        None => assert_eq!(cursor.peek_next(), data.iter().next()),
+                    }
+                    cursor.move_next();
+                }
+                assert_eq!(cursor.key_value(), data.range(probe..).next());
+            }
+        }
+    }
+}
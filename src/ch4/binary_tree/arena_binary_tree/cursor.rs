@@ -0,0 +1,440 @@
+use super::ArenaBinaryTree;
+use crate::ch4::{
+    BinTree, BinTreeCursor, BinTreeCursorMut, MoveParentBinTree, MoveParentCursor,
+    MoveParentCursorMut, SplitCursorMut,
+};
+use std::rc::Rc;
+
+/// 不可变游标.
+///
+/// 与`DoublyLinkedBinaryTree`的游标相同，游标记录当前位置的父母`parent`(可以是哨兵)以及当前位置是否为左孩子，
+/// 因此可以指向空子树.
+pub struct Cursor<'a, T> {
+    parent: usize,
+    is_left: bool,
+    tree: &'a ArenaBinaryTree<T>,
+}
+
+/// 可变游标.
+///
+/// 受限于生命期`'a`，不可能同时存在同一棵树的其它游标，因此可以安全地返回结点内容的可变引用.
+pub struct CursorMut<'a, T> {
+    parent: usize,
+    is_left: bool,
+    tree: &'a mut ArenaBinaryTree<T>,
+}
+
+impl<'a, T> Cursor<'a, T> {
+    /// 提供一个树的引用，返回一个指向根的只读游标.
+    pub fn new(tree: &'a ArenaBinaryTree<T>) -> Self {
+        Self {
+            parent: tree.root,
+            is_left: true,
+            tree,
+        }
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.tree.child(self.parent, self.is_left)
+    }
+
+    fn is_root(&self) -> bool {
+        self.parent == self.tree.root
+    }
+}
+
+impl<'a, T> Clone for Cursor<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            parent: self.parent,
+            is_left: self.is_left,
+            tree: self.tree,
+        }
+    }
+}
+
+impl<'a, T> BinTree for Cursor<'a, T> {
+    type Elem = T;
+    type Cursor<'b, E: 'b> = Cursor<'b, E>;
+
+    fn cursor(&self) -> Self::Cursor<'_, Self::Elem> {
+        self.clone()
+    }
+}
+
+impl<'a, T> BinTreeCursor<'a> for Cursor<'a, T> {
+    fn is_parent(&self, other: &Self) -> bool {
+        other.current() == Some(self.parent)
+    }
+
+    fn as_ref(&self) -> Option<&Self::Elem> {
+        self.tree.elem(self.current())
+    }
+
+    fn left(&self) -> Option<&Self::Elem> {
+        let left = self
+            .current()
+            .and_then(|index| self.tree.child(index, true));
+        self.tree.elem(left)
+    }
+
+    fn right(&self) -> Option<&Self::Elem> {
+        let right = self
+            .current()
+            .and_then(|index| self.tree.child(index, false));
+        self.tree.elem(right)
+    }
+
+    fn move_left(&mut self) {
+        if let Some(index) = self.current() {
+            self.parent = index;
+            self.is_left = true;
+        }
+    }
+
+    fn move_right(&mut self) {
+        if let Some(index) = self.current() {
+            self.parent = index;
+            self.is_left = false;
+        }
+    }
+
+    fn into_ref(self) -> Option<&'a Self::Elem>
+    where
+        Self: Sized,
+    {
+        self.tree.elem(self.current())
+    }
+}
+
+impl<'a, T> MoveParentBinTree for Cursor<'a, T> {
+    type MoveParentCursor<'b, E: 'b> = Cursor<'b, E>;
+
+    fn move_parent_cursor(&self) -> Self::MoveParentCursor<'_, Self::Elem> {
+        self.clone()
+    }
+}
+
+impl<'a, T> MoveParentCursor<'a> for Cursor<'a, T> {
+    fn move_parent(&mut self) {
+        if !self.is_root() {
+            // 非哨兵结点必有父母.
+            let current = self.parent;
+            self.parent = self.tree.parent_of(current).unwrap();
+            self.is_left = self.tree.child(self.parent, true) == Some(current);
+        }
+    }
+
+    fn parent(&self) -> Option<&Self::Elem> {
+        if !self.is_root() {
+            self.tree.elem(Some(self.parent))
+        } else {
+            None
+        }
+    }
+
+    fn is_left_child(&self) -> bool {
+        self.is_left
+    }
+}
+
+// 所有只读函数与`Cursor`是一致的.
+impl<'a, T> CursorMut<'a, T> {
+    /// 提供一个树的可变引用，返回一个指向根的可变游标.
+    pub fn new(tree: &'a mut ArenaBinaryTree<T>) -> Self {
+        Self {
+            parent: tree.root,
+            is_left: true,
+            tree,
+        }
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.tree.child(self.parent, self.is_left)
+    }
+
+    fn is_root(&self) -> bool {
+        self.parent == self.tree.root
+    }
+
+    /// 摘下`index`的左子树或右子树，作为一棵共享结点池的新树返回.
+    fn take_child(&mut self, index: usize, is_left: bool) -> ArenaBinaryTree<T> {
+        let tree = ArenaBinaryTree::in_arena(Rc::clone(&self.tree.arena));
+        let child = self.tree.replace_child(index, is_left, None);
+        tree.replace_child(tree.root, true, child);
+        tree
+    }
+
+    /// 把`other`接为`index`的左孩子或右孩子.
+    /// # Panics
+    /// 该孩子必须为空.
+    fn append_child(&mut self, index: usize, is_left: bool, other: ArenaBinaryTree<T>) {
+        assert!(
+            self.tree.child(index, is_left).is_none(),
+            "{}",
+            if index == self.parent {
+                "子树不为空!"
+            } else if is_left {
+                "左子树不为空!"
+            } else {
+                "右子树不为空!"
+            }
+        );
+        let root = self.tree.adopt(other);
+        self.tree.replace_child(index, is_left, root);
+    }
+
+    /// 顺时针(`is_left`)或逆时针旋转，提升当前结点的左孩子或右孩子.
+    fn rotate(&mut self, is_left: bool) {
+        if let Some(current) = self.current() {
+            if let Some(child) = self.tree.child(current, is_left) {
+                let inner = self.tree.replace_child(child, !is_left, Some(current));
+                self.tree.replace_child(current, is_left, inner);
+                self.tree
+                    .replace_child(self.parent, self.is_left, Some(child));
+            }
+        }
+    }
+}
+
+impl<'a, T> BinTreeCursor<'a> for CursorMut<'a, T> {
+    fn is_parent(&self, other: &Self) -> bool {
+        other.current() == Some(self.parent)
+    }
+
+    fn as_ref(&self) -> Option<&Self::Elem> {
+        self.tree.elem(self.current())
+    }
+
+    fn left(&self) -> Option<&Self::Elem> {
+        let left = self
+            .current()
+            .and_then(|index| self.tree.child(index, true));
+        self.tree.elem(left)
+    }
+
+    fn right(&self) -> Option<&Self::Elem> {
+        let right = self
+            .current()
+            .and_then(|index| self.tree.child(index, false));
+        self.tree.elem(right)
+    }
+
+    fn move_left(&mut self) {
+        if let Some(index) = self.current() {
+            self.parent = index;
+            self.is_left = true;
+        }
+    }
+
+    fn move_right(&mut self) {
+        if let Some(index) = self.current() {
+            self.parent = index;
+            self.is_left = false;
+        }
+    }
+
+    fn into_ref(self) -> Option<&'a Self::Elem>
+    where
+        Self: Sized,
+    {
+        let current = self.current();
+        let tree: &'a ArenaBinaryTree<T> = self.tree;
+        tree.elem(current)
+    }
+}
+
+impl<'a, T> MoveParentBinTree for CursorMut<'a, T> {
+    type MoveParentCursor<'b, E: 'b> = Cursor<'b, E>;
+
+    fn move_parent_cursor(&self) -> Self::MoveParentCursor<'_, Self::Elem> {
+        self.cursor()
+    }
+}
+
+impl<'a, T> MoveParentCursor<'a> for CursorMut<'a, T> {
+    fn move_parent(&mut self) {
+        if !self.is_root() {
+            let current = self.parent;
+            self.parent = self.tree.parent_of(current).unwrap();
+            self.is_left = self.tree.child(self.parent, true) == Some(current);
+        }
+    }
+
+    fn parent(&self) -> Option<&Self::Elem> {
+        if !self.is_root() {
+            self.tree.elem(Some(self.parent))
+        } else {
+            None
+        }
+    }
+
+    fn is_left_child(&self) -> bool {
+        self.is_left
+    }
+}
+
+impl<'a, T> BinTreeCursorMut<'a> for CursorMut<'a, T> {
+    type SubTree = ArenaBinaryTree<T>;
+
+    fn as_mut(&mut self) -> Option<&mut Self::Elem> {
+        // Safety: 可变游标独占整棵树.
+        unsafe { self.tree.elem_mut(self.current()) }
+    }
+
+    fn left_mut(&mut self) -> Option<&mut Self::Elem> {
+        let left = self
+            .current()
+            .and_then(|index| self.tree.child(index, true));
+        // Safety: 同`as_mut`.
+        unsafe { self.tree.elem_mut(left) }
+    }
+
+    fn right_mut(&mut self) -> Option<&mut Self::Elem> {
+        let right = self
+            .current()
+            .and_then(|index| self.tree.child(index, false));
+        // Safety: 同`as_mut`.
+        unsafe { self.tree.elem_mut(right) }
+    }
+
+    fn move_succ_and_split_mut(&mut self) -> (Option<&mut Self::Elem>, Option<&mut Self::Elem>) {
+        // Safety: `move_left`和`move_right`不会改变树，也不会移动结点，
+        // 且方法的默认实现会确保返回的两个结点内容是不同的.
+        unsafe { self.move_succ_and_split_mut_unchecked() }
+    }
+
+    fn insert_as_root(&mut self, elem: Self::Elem) -> Option<Self::Elem> {
+        if self.is_empty_subtree() {
+            let index = self.tree.arena.alloc(Some(elem));
+            self.tree
+                .replace_child(self.parent, self.is_left, Some(index));
+            None
+        } else {
+            Some(elem)
+        }
+    }
+
+    fn insert_as_left(&mut self, elem: Self::Elem) -> Option<Self::Elem> {
+        match self.current() {
+            Some(current) if self.tree.child(current, true).is_none() => {
+                let index = self.tree.arena.alloc(Some(elem));
+                self.tree.replace_child(current, true, Some(index));
+                None
+            }
+            _ => Some(elem),
+        }
+    }
+
+    fn insert_as_right(&mut self, elem: Self::Elem) -> Option<Self::Elem> {
+        match self.current() {
+            Some(current) if self.tree.child(current, false).is_none() => {
+                let index = self.tree.arena.alloc(Some(elem));
+                self.tree.replace_child(current, false, Some(index));
+                None
+            }
+            _ => Some(elem),
+        }
+    }
+
+    fn into_inner(self) -> Option<Self::Elem> {
+        let current = self.current()?;
+        for is_left in [true, false].iter().copied() {
+            let child = self.tree.replace_child(current, is_left, None);
+            self.tree.free_subtree(child);
+        }
+        self.tree.replace_child(self.parent, self.is_left, None);
+        self.tree.arena.dealloc(current)
+    }
+
+    fn append(&mut self, other: Self::SubTree) {
+        self.append_child(self.parent, self.is_left, other)
+    }
+
+    fn append_left(&mut self, other: Self::SubTree) {
+        let current = self.current().expect("子树为空!");
+        self.append_child(current, true, other)
+    }
+
+    fn append_right(&mut self, other: Self::SubTree) {
+        let current = self.current().expect("子树为空!");
+        self.append_child(current, false, other)
+    }
+
+    fn take(&mut self) -> Self::SubTree {
+        self.take_child(self.parent, self.is_left)
+    }
+
+    fn take_left(&mut self) -> Option<Self::SubTree>
+    where
+        Self::SubTree: Sized,
+    {
+        let current = self.current()?;
+        Some(self.take_child(current, true))
+    }
+
+    fn take_right(&mut self) -> Option<Self::SubTree>
+    where
+        Self::SubTree: Sized,
+    {
+        let current = self.current()?;
+        Some(self.take_child(current, false))
+    }
+
+    fn into_mut(self) -> Option<&'a mut Self::Elem>
+    where
+        Self: Sized,
+    {
+        // Safety: 游标被消耗，可变引用继承了它对整棵树的独占.
+        unsafe { self.tree.elem_mut(self.current()) }
+    }
+
+    fn zig(&mut self) {
+        self.rotate(true)
+    }
+
+    fn zag(&mut self) {
+        self.rotate(false)
+    }
+}
+
+impl<'a, T> SplitCursorMut<'a> for CursorMut<'a, T> {
+    unsafe fn split_mut(self) -> Option<(Option<Self>, &'a mut T, Option<Self>)> {
+        // 拆分所得的游标共享`tree`，但调用者保证它们只在各自的子树中移动与读写内容.
+        let current = self.current()?;
+        let tree: *mut ArenaBinaryTree<T> = self.tree;
+        let child = |is_left: bool| {
+            (*tree).child(current, is_left).map(|_| Self {
+                parent: current,
+                is_left,
+                tree: &mut *tree,
+            })
+        };
+        let (left, right) = (child(true), child(false));
+        Some((left, (*tree).elem_mut(Some(current)).unwrap(), right))
+    }
+}
+
+impl<'a, T> MoveParentCursorMut<'a> for CursorMut<'a, T> {
+    fn parent_mut(&mut self) -> Option<&mut Self::Elem> {
+        if !self.is_root() {
+            // Safety: 同`as_mut`.
+            unsafe { self.tree.elem_mut(Some(self.parent)) }
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> BinTree for CursorMut<'a, T> {
+    type Elem = T;
+    type Cursor<'b, E: 'b> = Cursor<'b, E>;
+
+    fn cursor(&self) -> Self::Cursor<'_, Self::Elem> {
+        Cursor {
+            parent: self.parent,
+            is_left: self.is_left,
+            tree: self.tree,
+        }
+    }
+}
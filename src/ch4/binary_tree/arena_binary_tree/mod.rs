@@ -0,0 +1,505 @@
+pub mod cursor;
+
+use super::{BinTree, BinTreeCursor, BinTreeMut, MoveParentBinTreeMut};
+use std::cell::{Cell, UnsafeCell};
+use std::mem;
+use std::ptr::{self, NonNull};
+use std::rc::Rc;
+
+/// 第一块的结点数. 之后每块的结点数加倍.
+const FIRST_CHUNK: usize = 8;
+
+/// 返回下标所在的块及其在块内的偏移. 第`k`块存放下标`[FIRST_CHUNK * (2^k - 1), FIRST_CHUNK * (2^(k + 1) - 1))`.
+fn locate(index: usize) -> (usize, usize) {
+    let n = index / FIRST_CHUNK + 1;
+    let chunk = mem::size_of::<usize>() * 8 - 1 - n.leading_zeros() as usize;
+    (chunk, index - FIRST_CHUNK * ((1 << chunk) - 1))
+}
+
+struct Node<T> {
+    parent: Option<usize>,
+    left: Option<usize>,
+    /// 空闲结点的`right`域指向空闲链表中的下一个结点.
+    right: Option<usize>,
+    /// 结点内容. 哨兵结点与空闲结点为`None`.
+    elem: Option<T>,
+}
+
+impl<T> Node<T> {
+    fn vacant() -> Self {
+        Self {
+            parent: None,
+            left: None,
+            right: None,
+            elem: None,
+        }
+    }
+}
+
+/// 结点池.
+///
+/// 结点按下标分块存放，块一经分配便不再移动，因此分配新结点不会使已有结点的引用失效.
+/// 释放的结点通过`right`域串成空闲链表，分配时优先复用.
+///
+/// # Safety
+/// 结点池可以被多棵树共享，每棵树独占自己的结点. 为此我们从不持有整块或块表的引用，
+/// 而总是经由裸指针访问单个结点的域，从而对不同结点的访问互不干扰.
+struct Arena<T> {
+    /// 各块首结点的指针. 第`k`块有`FIRST_CHUNK << k`个结点，来源于`Box<[Node<T>]>`.
+    chunks: UnsafeCell<Vec<NonNull<Node<T>>>>,
+    /// 曾经分配过的结点数，即下一个从未使用过的下标.
+    used: Cell<usize>,
+    /// 空闲链表的表头.
+    free: Cell<Option<usize>>,
+}
+
+impl<T> Arena<T> {
+    fn new() -> Self {
+        Self {
+            chunks: UnsafeCell::new(Vec::new()),
+            used: Cell::new(0),
+            free: Cell::new(None),
+        }
+    }
+
+    /// 返回下标所对应结点的指针.
+    ///
+    /// `index`必须是已分配过的下标.
+    fn node(&self, index: usize) -> *mut Node<T> {
+        let (chunk, offset) = locate(index);
+        // Safety: 块表只在`alloc`中被修改，而这里对块表的引用在返回前就已失效.
+        // 偏移不超过块的大小，因此指针运算是合法的.
+        unsafe { (&*self.chunks.get())[chunk].as_ptr().add(offset) }
+    }
+
+    /// 分配一个孤立的结点，并返回其下标.
+    fn alloc(&self, elem: Option<T>) -> usize {
+        let index = match self.free.get() {
+            Some(index) => {
+                // Safety: 空闲链表中的下标都已分配过.
+                self.free.set(unsafe { (*self.node(index)).right });
+                index
+            }
+            None => {
+                let index = self.used.get();
+                let (chunk, _) = locate(index);
+                // Safety: 不存在其它对块表的引用. 新增的块不会移动已有的块.
+                let chunks = unsafe { &mut *self.chunks.get() };
+                if chunk == chunks.len() {
+                    let nodes = (0..FIRST_CHUNK << chunk)
+                        .map(|_| Node::<T>::vacant())
+                        .collect::<Box<[_]>>();
+                    chunks.push(NonNull::new(Box::into_raw(nodes) as *mut Node<T>).unwrap());
+                }
+                self.used.set(index + 1);
+                index
+            }
+        };
+        // Safety: 空闲结点不属于任何树，因此不存在对它的引用.
+        unsafe {
+            let node = self.node(index);
+            (*node).parent = None;
+            (*node).left = None;
+            (*node).right = None;
+            (*node).elem = elem;
+        }
+        index
+    }
+
+    /// 释放一个结点，并返回其内容. 调用者须保证它已经与树脱离.
+    fn dealloc(&self, index: usize) -> Option<T> {
+        // Safety: 结点已与树脱离，因此不存在对它的引用.
+        let elem = unsafe {
+            let node = self.node(index);
+            (*node).parent = None;
+            (*node).left = None;
+            (*node).right = self.free.get();
+            (*node).elem.take()
+        };
+        self.free.set(Some(index));
+        elem
+    }
+}
+
+impl<T> Drop for Arena<T> {
+    fn drop(&mut self) {
+        for (chunk, base) in self.chunks.get_mut().drain(..).enumerate() {
+            // Safety: 每一块都来源于`Box<[Node<T>]>`，且大小为`FIRST_CHUNK << chunk`.
+            unsafe {
+                drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                    base.as_ptr(),
+                    FIRST_CHUNK << chunk,
+                )));
+            }
+        }
+    }
+}
+
+/// 结点池式二叉树.
+///
+/// 结点存放在按块增长的结点池中，以下标相互关联，释放的结点经空闲链表复用，因而比逐个分配的链式树更紧凑.
+/// 带哨兵根结点，根结点是它的左孩子.
+///
+/// 摘取的子树与原树共享同一个结点池，因此同一结点池内的`take`、`append`与旋转都只需修改下标，复杂度为`O(1)`；
+/// 接入来自其它结点池的树时需要逐个迁移结点，复杂度为该树的规模.
+pub struct ArenaBinaryTree<T> {
+    arena: Rc<Arena<T>>,
+    /// 哨兵结点的下标.
+    root: usize,
+}
+
+impl<T> Default for ArenaBinaryTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArenaBinaryTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cursor().as_ref() == other.cursor().as_ref()
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for ArenaBinaryTree<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self.cursor().as_ref(), other.cursor().as_ref()) {
+            (Some(lc), Some(rc)) => lc.partial_cmp(rc),
+            _ => None,
+        }
+    }
+}
+
+impl<T> ArenaBinaryTree<T> {
+    /// 创建一棵使用新结点池的空树.
+    pub fn new() -> Self {
+        Self::in_arena(Rc::new(Arena::new()))
+    }
+
+    /// 在给定的结点池中创建一棵空树.
+    fn in_arena(arena: Rc<Arena<T>>) -> Self {
+        let root = arena.alloc(None);
+        Self { arena, root }
+    }
+
+    fn node(&self, index: usize) -> *mut Node<T> {
+        self.arena.node(index)
+    }
+
+    /// 返回结点的左孩子或右孩子.
+    fn child(&self, index: usize, is_left: bool) -> Option<usize> {
+        // Safety: 树中的下标都是已分配的.
+        unsafe {
+            let node = self.node(index);
+            if is_left {
+                (*node).left
+            } else {
+                (*node).right
+            }
+        }
+    }
+
+    /// 返回结点的父母. 哨兵结点与子树的根的父母分别为`None`与哨兵.
+    fn parent_of(&self, index: usize) -> Option<usize> {
+        // Safety: 同`child`.
+        unsafe { (*self.node(index)).parent }
+    }
+
+    /// 用`child`替换结点的左孩子或右孩子，并设置`child`的父母，返回原来的孩子.
+    ///
+    /// 原来孩子的`parent`域保持不变.
+    fn replace_child(&self, index: usize, is_left: bool, child: Option<usize>) -> Option<usize> {
+        // Safety: 同`child`. 这里只写下标域，不会与对`elem`域的引用冲突.
+        unsafe {
+            let node = self.node(index);
+            if let Some(child) = child {
+                (*self.node(child)).parent = Some(index);
+            }
+            if is_left {
+                mem::replace(&mut (*node).left, child)
+            } else {
+                mem::replace(&mut (*node).right, child)
+            }
+        }
+    }
+
+    /// 返回结点内容的引用.
+    fn elem(&self, index: Option<usize>) -> Option<&T> {
+        // Safety: 树的只读引用保证了生命期内不会有对本树结点的写入.
+        index.and_then(|index| unsafe { (*self.node(index)).elem.as_ref() })
+    }
+
+    /// 返回结点内容的可变引用.
+    /// # Safety
+    /// 生命期`'a`内不存在对该结点内容的其它引用.
+    unsafe fn elem_mut<'a>(&self, index: Option<usize>) -> Option<&'a mut T> {
+        index.and_then(|index| (*self.node(index)).elem.as_mut())
+    }
+
+    /// 释放以`index`为根的子树中的所有结点. 调用者须保证子树已与树脱离.
+    fn free_subtree(&self, index: Option<usize>) {
+        let mut stack = index.into_iter().collect::<Vec<_>>();
+        while let Some(index) = stack.pop() {
+            stack.extend(self.child(index, true));
+            stack.extend(self.child(index, false));
+            self.arena.dealloc(index);
+        }
+    }
+
+    /// 把`other`的全部结点接纳进本树的结点池，返回其根在本结点池中的下标(父母域尚未设置).
+    ///
+    /// 若两者共享结点池则只需摘下根，否则逐个迁移结点.
+    fn adopt(&self, other: Self) -> Option<usize> {
+        let root = other.replace_child(other.root, true, None)?;
+        if Rc::ptr_eq(&self.arena, &other.arena) {
+            return Some(root);
+        }
+        // 释放结点会改写其孩子域，因此须先取出孩子.
+        let (left, right) = (other.child(root, true), other.child(root, false));
+        let new_root = self.arena.alloc(other.arena.dealloc(root));
+        // (原结点池中的结点, 它在本结点池中的父母, 是否为左孩子)
+        let mut stack = vec![(left, new_root, true), (right, new_root, false)];
+        while let Some((index, parent, is_left)) = stack.pop() {
+            if let Some(index) = index {
+                let (left, right) = (other.child(index, true), other.child(index, false));
+                let node = self.arena.alloc(other.arena.dealloc(index));
+                self.replace_child(parent, is_left, Some(node));
+                stack.push((left, node, true));
+                stack.push((right, node, false));
+            }
+        }
+        Some(new_root)
+    }
+}
+
+impl<T> Drop for ArenaBinaryTree<T> {
+    fn drop(&mut self) {
+        self.free_subtree(self.child(self.root, true));
+        self.arena.dealloc(self.root);
+    }
+}
+
+impl<T> BinTree for ArenaBinaryTree<T> {
+    type Elem = T;
+    type Cursor<'a, E: 'a> = cursor::Cursor<'a, E>;
+
+    fn cursor(&self) -> Self::Cursor<'_, Self::Elem> {
+        cursor::Cursor::new(self)
+    }
+}
+
+impl<T: 'static> BinTreeMut for ArenaBinaryTree<T> {
+    type CursorMut<'a> = cursor::CursorMut<'a, T>;
+
+    fn cursor_mut(&mut self) -> Self::CursorMut<'_> {
+        cursor::CursorMut::new(self)
+    }
+}
+
+impl<T: 'static> MoveParentBinTreeMut for ArenaBinaryTree<T> {
+    type MoveParentCursorMut<'a> = cursor::CursorMut<'a, T>;
+
+    fn move_parent_cursor_mut(&mut self) -> Self::MoveParentCursorMut<'_> {
+        cursor::CursorMut::new(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ch4::avlt::AVLTreeMap;
+    use crate::ch4::rbt::RBTreeMap;
+    use crate::ch4::st::SplayTreeMap;
+    use crate::ch4::{
+        BinTreeCursorExt, BinTreeCursorMut, MoveParentCursor, MoveParentCursorMut, UnorderedMap,
+    };
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+    use std::ops::Bound::{Excluded, Unbounded};
+
+    #[test]
+    fn test_locate() {
+        assert_eq!(locate(0), (0, 0));
+        assert_eq!(locate(FIRST_CHUNK - 1), (0, FIRST_CHUNK - 1));
+        assert_eq!(locate(FIRST_CHUNK), (1, 0));
+        assert_eq!(locate(3 * FIRST_CHUNK - 1), (1, 2 * FIRST_CHUNK - 1));
+        assert_eq!(locate(3 * FIRST_CHUNK), (2, 0));
+        assert_eq!(locate(7 * FIRST_CHUNK), (3, 0));
+    }
+
+    #[test]
+    fn test_drop() {
+        let counter = Rc::new(());
+        let mut tree = ArenaBinaryTree::new();
+        let mut cursor = tree.cursor_mut();
+        cursor.insert_as_root(Rc::clone(&counter));
+        cursor.insert_as_left(Rc::clone(&counter));
+        cursor.insert_as_right(Rc::clone(&counter));
+        cursor.move_left();
+        cursor.insert_as_left(Rc::clone(&counter));
+        cursor.insert_as_right(Rc::clone(&counter));
+        let left = cursor.take();
+        assert_eq!(Rc::strong_count(&counter), 6);
+        drop(tree);
+        assert_eq!(Rc::strong_count(&counter), 4);
+        drop(left);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn test_arena_binary_tree_basic() {
+        let mut tree = ArenaBinaryTree::new();
+        let mut cursor = tree.cursor_mut();
+        cursor.insert_as_root(0);
+        cursor.insert_as_left(1);
+        cursor.insert_as_right(2);
+        cursor.move_right();
+        cursor.insert_as_left(3);
+        cursor.move_left();
+        cursor.insert_as_left(4);
+        cursor.insert_as_right(5);
+        assert_eq!(cursor.parent(), Some(&2));
+        *cursor.parent_mut().unwrap() = 6;
+        cursor.move_parent();
+        assert_eq!(cursor.as_ref(), Some(&6));
+        assert!(!cursor.is_left_child());
+        cursor.move_parent();
+        assert_eq!(cursor.parent(), None);
+        let right = cursor.take_right().unwrap();
+        assert_eq!(
+            tree.cursor().pre_order_iter().copied().collect::<Vec<_>>(),
+            [0, 1]
+        );
+        assert_eq!(
+            right.cursor().pre_order_iter().copied().collect::<Vec<_>>(),
+            [6, 3, 4, 5]
+        );
+        let mut cursor = tree.cursor_mut();
+        cursor.move_left();
+        cursor.append_left(right);
+        cursor.move_left();
+        assert_eq!(cursor.parent(), Some(&1));
+        assert_eq!(
+            tree.cursor().pre_order_iter().copied().collect::<Vec<_>>(),
+            [0, 1, 6, 3, 4, 5]
+        );
+        let mut cursor = tree.cursor_mut();
+        cursor.move_left();
+        cursor.move_left();
+        cursor.zig();
+        assert_eq!(cursor.as_ref(), Some(&3));
+        assert_eq!(cursor.parent(), Some(&1));
+        assert_eq!(
+            tree.cursor().mid_order_iter().copied().collect::<Vec<_>>(),
+            [4, 3, 5, 6, 1, 0]
+        );
+        let mut cursor = tree.cursor_mut();
+        cursor.move_left();
+        cursor.move_left();
+        cursor.zag();
+        assert_eq!(cursor.as_ref(), Some(&6));
+        assert_eq!(
+            tree.cursor().mid_order_iter().copied().collect::<Vec<_>>(),
+            [4, 3, 5, 6, 1, 0]
+        );
+        assert_eq!(
+            tree.cursor().pre_order_iter().copied().collect::<Vec<_>>(),
+            [0, 1, 6, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn test_append_across_arenas() {
+        let mut other = ArenaBinaryTree::new();
+        let mut cursor = other.cursor_mut();
+        cursor.insert_as_root(1);
+        cursor.insert_as_left(2);
+        cursor.insert_as_right(3);
+        cursor.move_right();
+        cursor.insert_as_left(4);
+        let mut tree = ArenaBinaryTree::new();
+        let mut cursor = tree.cursor_mut();
+        cursor.insert_as_root(0);
+        cursor.append_right(other);
+        cursor.move_right();
+        cursor.move_right();
+        cursor.move_left();
+        assert_eq!(cursor.as_ref(), Some(&4));
+        cursor.move_parent();
+        cursor.move_parent();
+        assert_eq!(cursor.parent(), Some(&0));
+        assert_eq!(cursor.into_inner(), Some(1));
+        assert_eq!(
+            tree.cursor().pre_order_iter().copied().collect::<Vec<_>>(),
+            [0]
+        );
+        // 释放的结点被复用.
+        let mut cursor = tree.cursor_mut();
+        cursor.insert_as_left(5);
+        assert_eq!(tree.arena.used.get(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "右子树不为空!")]
+    fn test_append_nonempty() {
+        let mut tree = ArenaBinaryTree::new();
+        let mut cursor = tree.cursor_mut();
+        cursor.insert_as_root(0);
+        cursor.insert_as_right(1);
+        let mut other = ArenaBinaryTree::new();
+        other.cursor_mut().insert_as_root(2);
+        tree.cursor_mut().append_right(other);
+    }
+
+    proptest! {
+        #[test]
+        fn test_maps(entrys: Vec<(u8, u32)>, removes: Vec<u8>) {
+            let mut avl = AVLTreeMap::<ArenaBinaryTree<_>, _, _>::default();
+            let mut rb = RBTreeMap::<ArenaBinaryTree<_>, _, _>::default();
+            let mut splay = SplayTreeMap::<ArenaBinaryTree<_>, _, _>::default();
+            let mut expected = BTreeMap::new();
+            for (key, value) in entrys {
+                let old = expected.insert(key, value);
+                prop_assert_eq!(avl.insert(key, value), old);
+                prop_assert_eq!(rb.insert(key, value), old);
+                prop_assert_eq!(splay.insert(key, value), old);
+            }
+            for key in removes {
+                let old = expected.remove(&key);
+                prop_assert_eq!(avl.remove(&key), old);
+                prop_assert_eq!(rb.remove(&key), old);
+                prop_assert_eq!(splay.remove(&key), old);
+            }
+            prop_assert_eq!(avl.validate(), Ok(()));
+            prop_assert_eq!(rb.validate(), Ok(()));
+            prop_assert!(avl.iter().eq(expected.iter()));
+            prop_assert!(rb.iter().eq(expected.iter()));
+            prop_assert!(splay.iter().eq(expected.iter()));
+        }
+
+        #[test]
+        fn test_split_join(left: Vec<(u8, u32)>, right: Vec<(u8, u32)>, key: u8) {
+            // 两棵树各自使用独立的结点池，合并时需要迁移结点.
+            let mut a = AVLTreeMap::<ArenaBinaryTree<_>, _, _>::default();
+            let mut b = AVLTreeMap::<ArenaBinaryTree<_>, _, _>::default();
+            let mut expected = BTreeMap::new();
+            for &(key, value) in &right {
+                b.insert(key, value);
+                expected.insert(key, value);
+            }
+            // 合并时保留`a`中的值.
+            for &(key, value) in &left {
+                a.insert(key, value);
+                expected.insert(key, value);
+            }
+            let union = a.union(b, |_, value, _| value);
+            prop_assert_eq!(union.validate(), Ok(()));
+            prop_assert!(union.iter().eq(expected.iter()));
+            let (less, found, greater) = union.split(&key);
+            prop_assert_eq!(found.as_ref(), expected.get(&key));
+            prop_assert_eq!(less.validate(), Ok(()));
+            prop_assert_eq!(greater.validate(), Ok(()));
+            prop_assert!(less.iter().eq(expected.range(..key)));
+            prop_assert!(greater.iter().eq(expected.range((Excluded(key), Unbounded))));
+        }
+    }
+}
@@ -1,12 +1,12 @@
 use super::super::PriorityQueue;
 use super::*;
-use crate::ch4::Map;
+use crate::ch4::UnorderedMap;
 use crate::vec::MyVec;
 use bitstream_io::{BigEndian, BitReader, BitWriter};
 use std::cmp::{Ordering, PartialOrd};
 use std::io;
 
-pub fn char_count<M: Map<char, usize>>(text: &str) -> M {
+pub fn char_count<M: UnorderedMap<char, usize>>(text: &str) -> M {
     let mut map = M::default();
     for c in text.chars() {
         *map.get_mut_or_insert(c, 0) += 1;
@@ -33,14 +33,7 @@ impl PartialEq for HuffmanChar {
 
 impl PartialOrd for HuffmanChar {
     fn partial_cmp(&self, other: &HuffmanChar) -> Option<Ordering> {
-        self.count
-            .partial_cmp(&other.count)
-            .map(|ordering| match ordering {
-                // 较小者优先级较大.
-                Ordering::Greater => Ordering::Less,
-                Ordering::Less => Ordering::Greater,
-                Ordering::Equal => Ordering::Equal,
-            })
+        self.count.partial_cmp(&other.count)
     }
 }
 
@@ -54,7 +47,7 @@ impl<Tree: Default + BinTreeMut<Elem = HuffmanChar> + PartialOrd> HuffmanCodingT
     /// 从编码树建立编码表.
     /// # Panics
     /// 要求树至少包含2个结点，叶子结点非空，且每个叶子存储的字符不同.
-    fn generate_encoding_map<M: Map<char, Vec<bool>>>(tree: &Tree) -> M {
+    fn generate_encoding_map<M: UnorderedMap<char, Vec<bool>>>(tree: &Tree) -> M {
         let mut code = Vec::new();
         let mut stack = Vec::new(); // 保存着已经左转、但还未右转的结点.
         let mut map = M::default();
@@ -99,9 +92,14 @@ impl<Tree: Default + BinTreeMut<Elem = HuffmanChar> + PartialOrd> HuffmanCodingT
     }
 
     /// 创建Huffman编码树，并对`text`进行编码.
+    /// `Pq`须是以字符计数较小者优先的优先队列，例如`CompleteMinHeap`.
     /// # Panics
     /// `text`中不同的字符数必须大于`1`.
-    pub fn new<Pq: PriorityQueue<Tree>, M1: Map<char, Vec<bool>>, M2: Map<char, usize>>(
+    pub fn new<
+        Pq: PriorityQueue<Tree> + From<MyVec<Tree>>,
+        M1: UnorderedMap<char, Vec<bool>>,
+        M2: UnorderedMap<char, usize>,
+    >(
         text: &str,
     ) -> Option<Self> {
         println!("Counting...");
@@ -125,7 +123,7 @@ impl<Tree: Default + BinTreeMut<Elem = HuffmanChar> + PartialOrd> HuffmanCodingT
 
             // 自底向上建树
             while forest.len() > 1 {
-                let (lhs, rhs) = (forest.delete_max().unwrap(), forest.delete_max().unwrap());
+                let (lhs, rhs) = (forest.pop().unwrap(), forest.pop().unwrap());
                 let mut tree = Tree::default();
                 let mut cursor = tree.cursor_mut();
                 let count =
@@ -136,7 +134,7 @@ impl<Tree: Default + BinTreeMut<Elem = HuffmanChar> + PartialOrd> HuffmanCodingT
                 drop(cursor);
                 forest.insert(tree);
             }
-            let tree = forest.delete_max().unwrap();
+            let tree = forest.pop().unwrap();
 
             println!("Code generating...");
             // 建立编码表
@@ -176,7 +174,10 @@ impl<Tree: Default + BinTreeMut<Elem = HuffmanChar> + PartialOrd> HuffmanCodingT
     /// 编码字符串.
     /// # Panics
     /// 要求`text`中的所有字符均已被编码(存储在`encoding_map`中)，否则报错.
-    fn encode<M: Map<char, Vec<bool>>>(text: &str, encoding_map: &mut M) -> (Vec<u8>, usize) {
+    fn encode<M: UnorderedMap<char, Vec<bool>>>(
+        text: &str,
+        encoding_map: &mut M,
+    ) -> (Vec<u8>, usize) {
         let mut writer = BitWriter::endian(Vec::new(), BigEndian);
         let mut len = 0;
         for ch in text.chars() {
@@ -193,8 +194,9 @@ impl<Tree: Default + BinTreeMut<Elem = HuffmanChar> + PartialOrd> HuffmanCodingT
 
 #[cfg(test)]
 mod test {
-    use super::super::super::priority_queue::complete_heap::CompleteMaxHeap;
+    use super::super::super::priority_queue::complete_heap::CompleteMinHeap;
     use super::super::super::priority_queue::left_heap::LeftHeap;
+    use super::super::super::priority_queue::MinFirst;
     use super::super::linked_binary_tree::LinkedBinaryTree;
     use super::super::vec_binary_tree::VecBinaryTree;
     use super::*;
@@ -223,7 +225,7 @@ mod test {
     fn test_encoding() {
         let s = String::from("0j 1ￚ\u{a81}A_2¡𐝀®a𝒻kB￼lCVD");
         let encoding_tree = HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<
-            CompleteMaxHeap<_>,
+            CompleteMinHeap<_>,
             RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
             RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,
         >(&s)
@@ -237,7 +239,7 @@ mod test {
         fn test_encoding_with_lbt_ch(s: String) {
             if at_least_two_disctint_chars(&s) {
                 let encoding_tree =
-                    HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<CompleteMaxHeap<_>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,>(&s).unwrap();
+                    HuffmanCodingTree::<LinkedBinaryTree<_>>::new::<CompleteMinHeap<_>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,>(&s).unwrap();
                 assert_eq!(s, encoding_tree.decode());
             }
         }
@@ -246,7 +248,7 @@ mod test {
         fn test_encoding_with_vbt_lh(s: String) {
             if at_least_two_disctint_chars(&s) {
                 let encoding_tree =
-                    HuffmanCodingTree::<VecBinaryTree<_>>::new::<LeftHeap<_>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,>(&s).unwrap();
+                    HuffmanCodingTree::<VecBinaryTree<_>>::new::<LeftHeap<_, MinFirst>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,>(&s).unwrap();
                 assert_eq!(s, encoding_tree.decode());
             }
         }
@@ -255,7 +257,7 @@ mod test {
         fn test_encoding_with_dlbt_lh(s: String) {
             if at_least_two_disctint_chars(&s) {
                 let encoding_tree =
-                    HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<LeftHeap<_>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,>(&s).unwrap();
+                    HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<LeftHeap<_, MinFirst>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>, AVLTreeMap<DoublyLinkedBinaryTree<_>, _, _>,>(&s).unwrap();
                 assert_eq!(s, encoding_tree.decode());
             }
         }
@@ -264,7 +266,7 @@ mod test {
         fn test_encoding_with_btm_lh(s: String) {
             if at_least_two_disctint_chars(&s) {
                 let encoding_tree =
-                    HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<LeftHeap<_>, BTreeMap<_, _, 4>, BTreeMap<_, _, 4>,>(&s).unwrap();
+                    HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<LeftHeap<_, MinFirst>, BTreeMap<_, _, 4>, BTreeMap<_, _, 4>,>(&s).unwrap();
                 assert_eq!(s, encoding_tree.decode());
             }
         }
@@ -273,7 +275,7 @@ mod test {
         fn test_encoding_with_rbtm_lh(s: String) {
             if at_least_two_disctint_chars(&s) {
                 let encoding_tree =
-                    HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<LeftHeap<_>, RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>, RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,>(&s).unwrap();
+                    HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<LeftHeap<_, MinFirst>, RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>, RBTreeMap<DoublyLinkedBinaryTree<_>, _, _>,>(&s).unwrap();
                 assert_eq!(s, encoding_tree.decode());
             }
         }
@@ -282,7 +284,7 @@ mod test {
         fn test_encoding_with_llrbtm_lh(s: String) {
             if at_least_two_disctint_chars(&s) {
                 let encoding_tree =
-                    HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<LeftHeap<_>, llrbt::RBTreeMap<_, _>, llrbt::RBTreeMap<_, _>,>(&s).unwrap();
+                    HuffmanCodingTree::<DoublyLinkedBinaryTree<_>>::new::<LeftHeap<_, MinFirst>, llrbt::RBTreeMap<_, _>, llrbt::RBTreeMap<_, _>,>(&s).unwrap();
                 assert_eq!(s, encoding_tree.decode());
             }
         }
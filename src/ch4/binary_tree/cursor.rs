@@ -210,6 +210,17 @@ pub trait BinTreeCursorMut<'a>: BinTreeCursor<'a> {
     }
 }
 
+/// 可以拆分为左右子树的可变游标.
+pub trait SplitCursorMut<'a>: BinTreeCursorMut<'a> + Sized {
+    /// 把游标拆分为`(左子树的游标, 当前结点内容的可变引用, 右子树的游标)`，空子树对应`None`.
+    /// 若为空树，则返回`None`.
+    /// # Safety
+    /// 拆分所得的三者指向树中互不相交的部分，但游标仍共享整棵树.
+    /// 调用者只能通过它们移动、读写结点内容或继续拆分，而不能插入、删除、`take`、`append`或旋转，
+    /// 否则其它部分可能失效.
+    unsafe fn split_mut(self) -> Option<(Option<Self>, &'a mut Self::Elem, Option<Self>)>;
+}
+
 pub trait MoveParentCursor<'a>: BinTreeCursor<'a> + MoveParentBinTree {
     fn move_parent(&mut self);
     fn parent(&self) -> Option<&Self::Elem>;
@@ -1,7 +1,7 @@
 use super::{DoublyLinkedBinaryTree, Link, Node, NodePosi};
 use crate::ch4::{
     BinTree, BinTreeCursor, BinTreeCursorMut, MoveParentBinTree, MoveParentCursor,
-    MoveParentCursorMut,
+    MoveParentCursorMut, SplitCursorMut,
 };
 
 /// 不可变游标.
@@ -630,6 +630,27 @@ impl<'a, T> BinTreeCursorMut<'a> for CursorMut<'a, T> {
     }
 }
 
+impl<'a, T> SplitCursorMut<'a> for CursorMut<'a, T> {
+    unsafe fn split_mut(self) -> Option<(Option<Self>, &'a mut T, Option<Self>)> {
+        // 这里暂时打破了互斥性[6]: 拆分所得的游标共享`tree`，但调用者保证它们只在各自的子树中移动与读写内容.
+        let current = self.current_link()?;
+        let tree: *mut DoublyLinkedBinaryTree<T> = self.tree;
+        let child = |is_left: bool, link: Link<T>| {
+            link.map(|_| Self {
+                parent: current,
+                is_left,
+                tree: &mut *tree,
+            })
+        };
+        let (left_link, right_link) = (left(Some(current)), right(Some(current)));
+        Some((
+            child(true, left_link),
+            elem_mut(Some(current)).unwrap(),
+            child(false, right_link),
+        ))
+    }
+}
+
 impl<'a, T> MoveParentCursorMut<'a> for CursorMut<'a, T> {
     fn parent_mut(&mut self) -> Option<&mut Self::Elem> {
         if !self.is_root() {
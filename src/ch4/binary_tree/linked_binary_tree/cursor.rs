@@ -1,4 +1,4 @@
-use super::super::{BinTree, BinTreeCursor, BinTreeCursorMut, BinTreeMut};
+use super::super::{BinTree, BinTreeCursor, BinTreeCursorMut, BinTreeMut, SplitCursorMut};
 use super::{LinkedBinaryTree, Node};
 
 /// 链式二叉树只读游标.
@@ -447,6 +447,31 @@ impl<'a, T: 'static> BinTreeCursorMut<'a> for CursorMut<'a, T> {
     }
 }
 
+impl<'a, T: 'static> SplitCursorMut<'a> for CursorMut<'a, T> {
+    unsafe fn split_mut(self) -> Option<(Option<Self>, &'a mut T, Option<Self>)> {
+        let parent = self.parent.unwrap();
+        let current: *mut Node<T> = if self.is_left_child {
+            parent.left.as_deref_mut()?
+        } else {
+            parent.right.as_deref_mut()?
+        };
+        // 左右游标都以当前结点为父母，但各自只经由`left`或`right`域访问自己的子树.
+        let child = |is_left_child: bool| {
+            let link = if is_left_child {
+                &(*current).left
+            } else {
+                &(*current).right
+            };
+            link.as_ref().map(|_| Self {
+                parent: Some(&mut *current),
+                is_left_child,
+            })
+        };
+        let (left, right) = (child(true), child(false));
+        Some((left, (*current).elem.as_mut().unwrap(), right))
+    }
+}
+
 impl<'a, T> BinTree for CursorMut<'a, T> {
     type Elem = T;
     type Cursor<'b, E: 'b> = Cursor<'b, E>;
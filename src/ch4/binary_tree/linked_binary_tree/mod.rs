@@ -1,6 +1,7 @@
 pub mod cursor;
+pub mod zipper;
 
-use super::{BinTree, BinTreeCursor, BinTreeMut};
+use super::{BinTree, BinTreeCursor, BinTreeMut, MoveParentBinTreeMut};
 
 type Link<T> = Option<Box<Node<T>>>;
 
@@ -12,6 +13,41 @@ struct Node<T> {
     elem: Option<T>,
 }
 
+impl<T> Node<T> {
+    fn new(elem: T) -> Self {
+        Self {
+            left: None,
+            right: None,
+            elem: Some(elem),
+        }
+    }
+
+    /// 非哨兵结点的内容.
+    fn elem(&self) -> &T {
+        self.elem.as_ref().unwrap()
+    }
+
+    fn elem_mut(&mut self) -> &mut T {
+        self.elem.as_mut().unwrap()
+    }
+
+    fn child(&self, is_left: bool) -> &Link<T> {
+        if is_left {
+            &self.left
+        } else {
+            &self.right
+        }
+    }
+
+    fn child_mut(&mut self, is_left: bool) -> &mut Link<T> {
+        if is_left {
+            &mut self.left
+        } else {
+            &mut self.right
+        }
+    }
+}
+
 /// 链式二叉树.
 /// 带哨兵根结点，根结点是它的左孩子，根结点的后代都是非哨兵结点.
 pub struct LinkedBinaryTree<T> {
@@ -74,10 +110,27 @@ impl<T: 'static> BinTreeMut for LinkedBinaryTree<T> {
     }
 }
 
+impl<T: 'static> MoveParentBinTreeMut for LinkedBinaryTree<T> {
+    type MoveParentCursorMut<'a> = zipper::ZipperCursorMut<'a, T>;
+
+    fn move_parent_cursor_mut(&mut self) -> Self::MoveParentCursorMut<'_> {
+        zipper::ZipperCursorMut::new(self)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::super::{BinTree, BinTreeCursor, BinTreeCursorExt, BinTreeCursorMut};
+    use super::super::{
+        BinTree, BinTreeCursor, BinTreeCursorExt, BinTreeCursorMut, MoveParentBinTree,
+        MoveParentCursor, MoveParentCursorMut,
+    };
     use super::*;
+    use crate::ch4::avlt::AVLTreeMap;
+    use crate::ch4::rbt::RBTreeMap;
+    use crate::ch4::st::SplayTreeMap;
+    use crate::ch4::UnorderedMap;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_linked_binary_tree_basic() {
@@ -167,4 +220,102 @@ mod test {
             [4, 5, 2, 7, 8, 6, 3, 1]
         );
     }
+
+    #[test]
+    fn test_zipper_cursor() {
+        let mut tree = LinkedBinaryTree::new();
+        let mut cursor = tree.move_parent_cursor_mut();
+        cursor.insert_as_root(0);
+        cursor.insert_as_left(1);
+        cursor.insert_as_right(2);
+        cursor.move_right();
+        cursor.insert_as_left(3);
+        cursor.insert_as_right(4);
+        cursor.move_left();
+        assert_eq!(cursor.parent(), Some(&2));
+        assert!(cursor.is_left_child());
+        *cursor.parent_mut().unwrap() = 5;
+        // 只读视图可以越过拉链留下的空缺.
+        let mut view = cursor.move_parent_cursor();
+        view.move_parent();
+        assert_eq!(view.as_ref(), Some(&5));
+        assert_eq!((view.left(), view.right()), (Some(&3), Some(&4)));
+        view.move_parent();
+        assert_eq!(view.parent(), None);
+        assert_eq!((view.left(), view.right()), (Some(&1), Some(&5)));
+        view.move_right();
+        view.move_right();
+        assert_eq!(view.as_ref(), Some(&4));
+        assert!(!view.is_left_child());
+        view.move_parent();
+        view.move_left();
+        assert_eq!(view.as_ref(), Some(&3));
+        assert_eq!(
+            view.clone().post_order_iter().copied().collect::<Vec<_>>(),
+            [3]
+        );
+        cursor.move_parent();
+        cursor.zig();
+        assert_eq!(cursor.as_ref(), Some(&3));
+        assert_eq!(cursor.parent(), Some(&0));
+        cursor.move_right();
+        cursor.move_right();
+        assert_eq!(cursor.into_mut(), Some(&mut 4));
+        assert_eq!(
+            tree.cursor().pre_order_iter().copied().collect::<Vec<_>>(),
+            [0, 1, 3, 5, 4]
+        );
+        let mut cursor = tree.move_parent_cursor_mut();
+        cursor.move_right();
+        assert_eq!(
+            cursor.move_succ_and_split_mut(),
+            (Some(&mut 3), Some(&mut 5))
+        );
+        cursor.move_parent();
+        let right = cursor.take();
+        assert_eq!(
+            right.cursor().pre_order_iter().copied().collect::<Vec<_>>(),
+            [3, 5, 4]
+        );
+        drop(cursor);
+        assert_eq!(
+            tree.cursor().pre_order_iter().copied().collect::<Vec<_>>(),
+            [0, 1]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_maps_with_zipper(entrys: Vec<(u8, u32)>, removes: Vec<u8>) {
+            let mut avl = AVLTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+            let mut rb = RBTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+            let mut splay = SplayTreeMap::<LinkedBinaryTree<_>, _, _>::default();
+            let mut expected = BTreeMap::new();
+            for (key, value) in entrys {
+                let old = expected.insert(key, value);
+                prop_assert_eq!(avl.insert(key, value), old);
+                prop_assert_eq!(rb.insert(key, value), old);
+                prop_assert_eq!(splay.insert(key, value), old);
+            }
+            for key in removes {
+                let old = expected.remove(&key);
+                prop_assert_eq!(avl.remove(&key), old);
+                prop_assert_eq!(rb.remove(&key), old);
+                prop_assert_eq!(splay.remove(&key), old);
+                if let Some(value) = avl.get_mut(&key.wrapping_add(1)) {
+                    *value = value.wrapping_add(1);
+                    *rb.get_mut(&key.wrapping_add(1)).unwrap() = *value;
+                    *splay.get_mut(&key.wrapping_add(1)).unwrap() = *value;
+                    *expected.get_mut(&key.wrapping_add(1)).unwrap() = *value;
+                }
+            }
+            prop_assert_eq!(avl.validate(), Ok(()));
+            prop_assert_eq!(rb.validate(), Ok(()));
+            prop_assert!(avl.iter().eq(expected.iter()));
+            prop_assert!(rb.iter().eq(expected.iter()));
+            for (key, value) in &expected {
+                prop_assert_eq!(avl.get(key), Some(value));
+            }
+        }
+    }
 }
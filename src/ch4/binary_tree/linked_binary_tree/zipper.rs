@@ -0,0 +1,438 @@
+use super::super::{
+    BinTree, BinTreeCursor, BinTreeCursorMut, MoveParentBinTree, MoveParentCursor,
+    MoveParentCursorMut,
+};
+use super::{Link, LinkedBinaryTree, Node};
+use std::ptr;
+
+/// 可向父母移动的拉链式可变游标.
+///
+/// 向下移动时把经过的结点从树上摘下，连同前进的方向一起压入路径，其在该方向上的孩子留空，由路径上的下一个结点(或当前子树)填补；
+/// 向父母移动时弹出路径并把当前子树接回. 因此两个方向的移动都是`O(1)`的，且无需父母指针.
+/// 游标被销毁时沿路径把树重新接好.
+pub struct ZipperCursorMut<'a, T> {
+    /// 哨兵结点的左孩子. 游标存活期间它被摘下，成为路径的首个结点或当前子树.
+    /// 这里用`Option`是为了便于在`into_mut`中取出其所有权.
+    root: Option<&'a mut Link<T>>,
+    /// 从根到当前位置的父母的路径，以及从每个结点前进的方向(`true`为左).
+    path: Vec<(Box<Node<T>>, bool)>,
+    /// 当前子树.
+    focus: Link<T>,
+}
+
+impl<'a, T> ZipperCursorMut<'a, T> {
+    /// 提供一个树的可变引用，返回一个指向根的可变游标.
+    pub fn new(tree: &'a mut LinkedBinaryTree<T>) -> Self {
+        let focus = tree.root.left.take();
+        Self {
+            root: Some(&mut tree.root.left),
+            path: Vec::new(),
+            focus,
+        }
+    }
+
+    /// 把当前子树沿路径接回，游标回到根.
+    fn zip(&mut self) {
+        while let Some((mut node, is_left)) = self.path.pop() {
+            *node.child_mut(is_left) = self.focus.take();
+            self.focus = Some(node);
+        }
+        if let Some(root) = self.root.as_mut() {
+            **root = self.focus.take();
+        }
+    }
+
+    /// 接回整棵树，然后按照路径重新下降，返回指向当前子树的链接.
+    fn into_link(mut self) -> &'a mut Link<T> {
+        let directions = self
+            .path
+            .iter()
+            .map(|&(_, is_left)| is_left)
+            .collect::<Vec<_>>();
+        self.zip();
+        let mut link = self.root.take().unwrap();
+        for is_left in directions {
+            link = link.as_mut().unwrap().child_mut(is_left);
+        }
+        link
+    }
+
+    fn move_child(&mut self, is_left: bool) {
+        if let Some(mut node) = self.focus.take() {
+            self.focus = node.child_mut(is_left).take();
+            self.path.push((node, is_left));
+        }
+    }
+
+    fn insert_child(&mut self, is_left: bool, elem: T) -> Option<T> {
+        match self.focus.as_mut().map(|node| node.child_mut(is_left)) {
+            Some(child) if child.is_none() => {
+                *child = Some(Box::new(Node::new(elem)));
+                None
+            }
+            _ => Some(elem),
+        }
+    }
+
+    fn take_child(&mut self, is_left: bool) -> Option<LinkedBinaryTree<T>> {
+        let node = self.focus.as_mut()?.child_mut(is_left).take();
+        let mut tree = LinkedBinaryTree::default();
+        tree.replace_root_node(node);
+        Some(tree)
+    }
+
+    /// # Panics
+    /// 当前子树为空或对应的孩子不为空时panic.
+    fn append_child(&mut self, is_left: bool, mut other: LinkedBinaryTree<T>) {
+        let child = self.focus.as_mut().expect("子树为空!").child_mut(is_left);
+        match (child.is_some(), is_left) {
+            (true, true) => panic!("左子树不为空!"),
+            (true, false) => panic!("右子树不为空!"),
+            _ => {}
+        }
+        *child = other.replace_root_node(None);
+    }
+}
+
+impl<'a, T> Drop for ZipperCursorMut<'a, T> {
+    fn drop(&mut self) {
+        self.zip()
+    }
+}
+
+impl<'a, T> BinTree for ZipperCursorMut<'a, T> {
+    type Elem = T;
+    type Cursor<'b, E: 'b> = ZipperCursor<'b, E>;
+
+    fn cursor(&self) -> Self::Cursor<'_, Self::Elem> {
+        self.move_parent_cursor()
+    }
+}
+
+impl<'a, T> BinTreeCursor<'a> for ZipperCursorMut<'a, T> {
+    fn is_parent(&self, other: &Self) -> bool {
+        match (self.path.last(), other.focus.as_deref()) {
+            (Some((parent, _)), Some(node)) => ptr::eq(&**parent, node),
+            _ => false,
+        }
+    }
+
+    fn as_ref(&self) -> Option<&Self::Elem> {
+        self.focus.as_deref().map(Node::elem)
+    }
+
+    fn left(&self) -> Option<&Self::Elem> {
+        self.focus
+            .as_ref()
+            .and_then(|node| node.left.as_deref().map(Node::elem))
+    }
+
+    fn right(&self) -> Option<&Self::Elem> {
+        self.focus
+            .as_ref()
+            .and_then(|node| node.right.as_deref().map(Node::elem))
+    }
+
+    fn move_left(&mut self) {
+        self.move_child(true)
+    }
+
+    fn move_right(&mut self) {
+        self.move_child(false)
+    }
+
+    fn into_ref(self) -> Option<&'a Self::Elem>
+    where
+        Self: Sized,
+    {
+        let link: &'a Link<T> = self.into_link();
+        link.as_deref().map(Node::elem)
+    }
+}
+
+impl<'a, T: 'static> BinTreeCursorMut<'a> for ZipperCursorMut<'a, T> {
+    type SubTree = LinkedBinaryTree<T>;
+
+    fn as_mut(&mut self) -> Option<&mut Self::Elem> {
+        self.focus.as_deref_mut().map(Node::elem_mut)
+    }
+
+    fn left_mut(&mut self) -> Option<&mut Self::Elem> {
+        self.focus
+            .as_mut()
+            .and_then(|node| node.left.as_deref_mut().map(Node::elem_mut))
+    }
+
+    fn right_mut(&mut self) -> Option<&mut Self::Elem> {
+        self.focus
+            .as_mut()
+            .and_then(|node| node.right.as_deref_mut().map(Node::elem_mut))
+    }
+
+    fn move_succ_and_split_mut(&mut self) -> (Option<&mut Self::Elem>, Option<&mut Self::Elem>) {
+        if self.right().is_none() {
+            return (self.as_mut(), None);
+        }
+        // 原结点将被压入路径的这个位置，与当前子树互不重叠.
+        let index = self.path.len();
+        self.move_right();
+        while self.left().is_some() {
+            self.move_left();
+        }
+        (
+            Some(self.path[index].0.elem_mut()),
+            self.focus.as_deref_mut().map(Node::elem_mut),
+        )
+    }
+
+    fn insert_as_root(&mut self, elem: Self::Elem) -> Option<Self::Elem> {
+        if self.focus.is_none() {
+            self.focus = Some(Box::new(Node::new(elem)));
+            None
+        } else {
+            Some(elem)
+        }
+    }
+
+    fn insert_as_left(&mut self, elem: Self::Elem) -> Option<Self::Elem> {
+        self.insert_child(true, elem)
+    }
+
+    fn insert_as_right(&mut self, elem: Self::Elem) -> Option<Self::Elem> {
+        self.insert_child(false, elem)
+    }
+
+    fn into_inner(mut self) -> Option<Self::Elem> {
+        self.focus.take().and_then(|node| node.elem)
+    }
+
+    fn append(&mut self, mut other: Self::SubTree) {
+        if self.focus.is_some() {
+            panic!("子树不为空!");
+        }
+        self.focus = other.replace_root_node(None);
+    }
+
+    fn append_left(&mut self, other: Self::SubTree) {
+        self.append_child(true, other)
+    }
+
+    fn append_right(&mut self, other: Self::SubTree) {
+        self.append_child(false, other)
+    }
+
+    fn take(&mut self) -> Self::SubTree {
+        let mut tree = LinkedBinaryTree::default();
+        tree.replace_root_node(self.focus.take());
+        tree
+    }
+
+    fn take_left(&mut self) -> Option<Self::SubTree>
+    where
+        Self::SubTree: Sized,
+    {
+        self.take_child(true)
+    }
+
+    fn take_right(&mut self) -> Option<Self::SubTree>
+    where
+        Self::SubTree: Sized,
+    {
+        self.take_child(false)
+    }
+
+    fn into_mut(self) -> Option<&'a mut Self::Elem>
+    where
+        Self: Sized,
+    {
+        self.into_link().as_deref_mut().map(Node::elem_mut)
+    }
+}
+
+impl<'a, T> MoveParentBinTree for ZipperCursorMut<'a, T> {
+    type MoveParentCursor<'b, E: 'b> = ZipperCursor<'b, E>;
+
+    fn move_parent_cursor(&self) -> Self::MoveParentCursor<'_, Self::Elem> {
+        ZipperCursor {
+            path: &self.path,
+            focus: self.focus.as_deref(),
+            depth: self.path.len(),
+            below: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T> MoveParentCursor<'a> for ZipperCursorMut<'a, T> {
+    fn move_parent(&mut self) {
+        if let Some((mut node, is_left)) = self.path.pop() {
+            *node.child_mut(is_left) = self.focus.take();
+            self.focus = Some(node);
+        }
+    }
+
+    fn parent(&self) -> Option<&Self::Elem> {
+        self.path.last().map(|(node, _)| node.elem())
+    }
+
+    fn is_left_child(&self) -> bool {
+        match self.path.last() {
+            Some(&(_, is_left)) => is_left,
+            None => true,
+        }
+    }
+}
+
+impl<'a, T> MoveParentCursorMut<'a> for ZipperCursorMut<'a, T> {
+    fn parent_mut(&mut self) -> Option<&mut Self::Elem> {
+        self.path.last_mut().map(|(node, _)| node.elem_mut())
+    }
+}
+
+/// 拉链式游标的只读视图，同样可以向父母移动.
+///
+/// 可变游标的路径上的结点缺少一个孩子，它由路径上的下一个结点填补.
+/// 只读游标的祖先由两部分组成: 可变游标路径的前`depth`个结点，以及之后离开该路径向下移动时经过的结点`below`.
+pub struct ZipperCursor<'a, T> {
+    path: &'a [(Box<Node<T>>, bool)],
+    focus: Option<&'a Node<T>>,
+    depth: usize,
+    below: Vec<(&'a Node<T>, bool)>,
+}
+
+impl<'a, T> Clone for ZipperCursor<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path,
+            focus: self.focus,
+            depth: self.depth,
+            below: self.below.clone(),
+        }
+    }
+}
+
+impl<'a, T> ZipperCursor<'a, T> {
+    /// 当前位置是否在可变游标的路径上(包括可变游标的当前子树).
+    fn on_path(&self) -> bool {
+        self.below.is_empty()
+    }
+
+    /// 路径上第`depth`个结点，若已到路径末尾，则为可变游标的当前子树.
+    fn path_node(&self, depth: usize) -> Option<&'a Node<T>> {
+        match self.path.get(depth) {
+            Some((node, _)) => Some(node),
+            None => self.focus,
+        }
+    }
+
+    fn current(&self) -> Option<&'a Node<T>> {
+        match self.below.last() {
+            Some(&(node, is_left)) => node.child(is_left).as_deref(),
+            None => self.path_node(self.depth),
+        }
+    }
+
+    fn parent_node(&self) -> Option<&'a Node<T>> {
+        match self.below.last() {
+            Some(&(node, _)) => Some(node),
+            None if self.depth > 0 => Some(&self.path[self.depth - 1].0),
+            None => None,
+        }
+    }
+
+    /// 当前结点是否沿可变游标的路径继续向`is_left`方向前进.
+    fn follows_path(&self, is_left: bool) -> bool {
+        self.on_path() && self.path.get(self.depth).map(|&(_, dir)| dir) == Some(is_left)
+    }
+
+    fn child(&self, is_left: bool) -> Option<&'a Node<T>> {
+        if self.follows_path(is_left) {
+            self.path_node(self.depth + 1)
+        } else {
+            self.current()
+                .and_then(|node| node.child(is_left).as_deref())
+        }
+    }
+
+    fn move_child(&mut self, is_left: bool) {
+        if let Some(node) = self.current() {
+            if self.follows_path(is_left) {
+                self.depth += 1;
+            } else {
+                self.below.push((node, is_left));
+            }
+        }
+    }
+}
+
+impl<'a, T> BinTree for ZipperCursor<'a, T> {
+    type Elem = T;
+    type Cursor<'b, E: 'b> = ZipperCursor<'b, E>;
+
+    fn cursor(&self) -> Self::Cursor<'_, Self::Elem> {
+        self.clone()
+    }
+}
+
+impl<'a, T> BinTreeCursor<'a> for ZipperCursor<'a, T> {
+    fn is_parent(&self, other: &Self) -> bool {
+        match (self.parent_node(), other.current()) {
+            (Some(parent), Some(node)) => ptr::eq(parent, node),
+            _ => false,
+        }
+    }
+
+    fn as_ref(&self) -> Option<&Self::Elem> {
+        self.current().map(Node::elem)
+    }
+
+    fn left(&self) -> Option<&Self::Elem> {
+        self.child(true).map(Node::elem)
+    }
+
+    fn right(&self) -> Option<&Self::Elem> {
+        self.child(false).map(Node::elem)
+    }
+
+    fn move_left(&mut self) {
+        self.move_child(true)
+    }
+
+    fn move_right(&mut self) {
+        self.move_child(false)
+    }
+
+    fn into_ref(self) -> Option<&'a Self::Elem>
+    where
+        Self: Sized,
+    {
+        self.current().map(Node::elem)
+    }
+}
+
+impl<'a, T> MoveParentBinTree for ZipperCursor<'a, T> {
+    type MoveParentCursor<'b, E: 'b> = ZipperCursor<'b, E>;
+
+    fn move_parent_cursor(&self) -> Self::MoveParentCursor<'_, Self::Elem> {
+        self.clone()
+    }
+}
+
+impl<'a, T> MoveParentCursor<'a> for ZipperCursor<'a, T> {
+    fn move_parent(&mut self) {
+        if self.below.pop().is_none() && self.depth > 0 {
+            self.depth -= 1;
+        }
+    }
+
+    fn parent(&self) -> Option<&Self::Elem> {
+        self.parent_node().map(Node::elem)
+    }
+
+    fn is_left_child(&self) -> bool {
+        match self.below.last() {
+            Some(&(_, is_left)) => is_left,
+            None if self.depth > 0 => self.path[self.depth - 1].1,
+            None => true,
+        }
+    }
+}
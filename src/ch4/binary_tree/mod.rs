@@ -1,3 +1,4 @@
+pub mod arena_binary_tree;
 pub mod coding_tree;
 pub mod cursor;
 pub mod cursor_ext;
@@ -40,7 +41,7 @@ pub trait MoveParentBinTree: BinTree {
 /// 可变二叉树特质.
 pub trait BinTreeMut: BinTree {
     /// 可变游标类型.
-    type CursorMut<'a>: BinTreeCursorMut<'a, Elem = Self::Elem, SubTree = Self>;
+    type CursorMut<'a>: BinTreeCursorMut<'a, Elem = Self::Elem, SubTree = Self> + SplitCursorMut<'a>;
 
     /// 创建一个可变结点游标.
     fn cursor_mut(&mut self) -> Self::CursorMut<'_>;
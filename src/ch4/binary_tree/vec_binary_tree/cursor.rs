@@ -4,7 +4,7 @@ use super::{
 };
 use crate::ch4::binary_tree::{
     BinTree, BinTreeCursor, BinTreeCursorMut, BinTreeMut, MoveParentBinTree, MoveParentCursor,
-    MoveParentCursorMut,
+    MoveParentCursorMut, SplitCursorMut,
 };
 
 pub(super) const fn left_index(index: usize) -> usize {
@@ -373,6 +373,22 @@ impl<'a, T: 'static> BinTreeCursorMut<'a> for CursorMut<'a, T> {
     }
 }
 
+impl<'a, T: 'static> SplitCursorMut<'a> for CursorMut<'a, T> {
+    unsafe fn split_mut(self) -> Option<(Option<Self>, &'a mut T, Option<Self>)> {
+        // 拆分所得的各部分只访问各自的下标，而调用者保证不会调整向量的大小.
+        let tree: *mut VecBinaryTree<T> = self.tree;
+        let current = self.current;
+        let child = |index: usize| {
+            (*tree).get(index).map(|_| Self {
+                current: index,
+                tree: &mut *tree,
+            })
+        };
+        let (left, right) = (child(left_index(current)), child(right_index(current)));
+        Some((left, (*tree).get_mut(current)?, right))
+    }
+}
+
 impl<'a, T> MoveParentBinTree for CursorMut<'a, T> {
     type MoveParentCursor<'b, E: 'b> = Cursor<'b, E>;
 
@@ -1,7 +1,11 @@
 pub mod binary_search_tree;
 pub mod binary_tree;
 pub mod priority_queue;
+pub mod stats;
+pub mod validate;
 
 pub use binary_search_tree::*;
 pub use binary_tree::*;
 pub use priority_queue::*;
+pub use stats::{Counter, Stats};
+pub use validate::*;
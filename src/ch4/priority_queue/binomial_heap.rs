@@ -0,0 +1,488 @@
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
+use super::{Handle, IndexedPriorityQueue, MaxFirst, Order, PriorityQueue};
+use crate::vec::MyVec;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    handle: Handle,
+    elem: T,
+    /// 孩子数. 度数为`k`的二项树有`2^k`个结点.
+    degree: usize,
+    parent: Link<T>,
+    /// 度数最大的孩子.
+    child: Link<T>,
+    /// 度数小一的兄弟.
+    sibling: Link<T>,
+}
+
+/// 二项堆. 元素的先后由顺序策略`O`决定，默认为大顶堆.
+///
+/// 堆是一组度数互不相同的二项树，`roots[k]`为度数为`k`的树(若有). 合并两个堆如同二进制加法，
+/// 度数相同的两棵树连接为度数加一的树，因此合并与出队为`O(log n)`，插入摊还为`O(1)`.
+/// 修改优先级时交换结点的内容进行上滤或下滤，并同步更新句柄表；删除时把结点上滤到根再摘下.
+///
+/// 同时实现了`PriorityQueue`与`IndexedPriorityQueue`，两者的方法同名，在同时引入两个特质时需要写明特质.
+///
+/// # Safety
+/// 句柄表中的指针都指向由`Box`分配、且仍在堆中的结点. 结点只在`remove`与`Drop`中释放，释放前先从句柄表中移除.
+pub struct BinomialHeap<T, O = MaxFirst> {
+    roots: Vec<Link<T>>,
+    /// 句柄所指的结点. 已删除元素的句柄为`None`.
+    nodes: Vec<Link<T>>,
+    len: usize,
+    order: O,
+    stats: Counter,
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+impl<T, O: Default> Default for BinomialHeap<T, O> {
+    fn default() -> Self {
+        Self::with_order(O::default())
+    }
+}
+
+impl<T, O> Drop for BinomialHeap<T, O> {
+    fn drop(&mut self) {
+        for node in self.nodes.drain(..).flatten() {
+            // Safety: 句柄表中的结点都是由`Box`分配的，且每个结点只出现一次.
+            unsafe {
+                drop(Box::from_raw(node.as_ptr()));
+            }
+        }
+    }
+}
+
+impl<T, O> BinomialHeap<T, O> {
+    /// 创建一个使用给定顺序策略的空堆.
+    pub fn with_order(order: O) -> Self {
+        Self {
+            roots: Vec::new(),
+            nodes: Vec::new(),
+            len: 0,
+            order,
+            stats: Counter::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn node(&self, handle: Handle) -> Link<T> {
+        self.nodes.get(handle.0).copied().flatten()
+    }
+
+    /// 分配一个孤立结点并登记到句柄表中.
+    ///
+    /// 只借用句柄表，以便在计数守卫存活期间调用.
+    fn alloc(nodes: &mut Vec<Link<T>>, elem: T) -> (Handle, NonNull<Node<T>>) {
+        let handle = Handle(nodes.len());
+        let node = NonNull::from(Box::leak(Box::new(Node {
+            handle,
+            elem,
+            degree: 0,
+            parent: None,
+            child: None,
+            sibling: None,
+        })));
+        stats::record(|stats| stats.allocations += 1);
+        nodes.push(Some(node));
+        (handle, node)
+    }
+
+    /// 交换两个结点的内容(句柄与元素)，并更新句柄表.
+    unsafe fn swap(nodes: &mut [Link<T>], lhs: NonNull<Node<T>>, rhs: NonNull<Node<T>>) {
+        stats::record(|stats| stats.swaps += 1);
+        let (lhs, rhs) = (&mut *lhs.as_ptr(), &mut *rhs.as_ptr());
+        mem::swap(&mut lhs.handle, &mut rhs.handle);
+        mem::swap(&mut lhs.elem, &mut rhs.elem);
+        nodes[lhs.handle.0] = Some(NonNull::from(&mut *lhs));
+        nodes[rhs.handle.0] = Some(NonNull::from(&mut *rhs));
+    }
+
+    /// 把结点的内容一路交换到根，返回该根. 用于删除任意结点.
+    unsafe fn float_to_root(nodes: &mut [Link<T>], mut node: NonNull<Node<T>>) -> NonNull<Node<T>> {
+        while let Some(parent) = (*node.as_ptr()).parent {
+            Self::swap(nodes, node, parent);
+            node = parent;
+        }
+        node
+    }
+}
+
+impl<T, O: Order<T>> BinomialHeap<T, O> {
+    /// 连接两棵度数相同的树，优先级较低者成为另一个度数最大的孩子. 返回新的根.
+    unsafe fn link(lhs: NonNull<Node<T>>, rhs: NonNull<Node<T>>, order: &O) -> NonNull<Node<T>> {
+        stats::record(|stats| stats.comparisons += 1);
+        let (root, child) = if order.is_lower(&(*lhs.as_ptr()).elem, &(*rhs.as_ptr()).elem) {
+            (rhs, lhs)
+        } else {
+            (lhs, rhs)
+        };
+        let (raw, child_raw) = (root.as_ptr(), child.as_ptr());
+        (*child_raw).parent = Some(root);
+        (*child_raw).sibling = (*raw).child;
+        (*raw).child = Some(child);
+        (*raw).degree += 1;
+        root
+    }
+
+    /// 把一棵树加入森林，与已有的同度数的树逐级连接，如同二进制加法的进位.
+    unsafe fn add_tree(roots: &mut Vec<Link<T>>, mut tree: NonNull<Node<T>>, order: &O) {
+        loop {
+            let degree = (*tree.as_ptr()).degree;
+            if roots.len() <= degree {
+                roots.resize(degree + 1, None);
+            }
+            match roots[degree].take() {
+                None => {
+                    roots[degree] = Some(tree);
+                    return;
+                }
+                Some(other) => tree = Self::link(tree, other, order),
+            }
+        }
+    }
+
+    /// 优先级最高的根.
+    unsafe fn top(roots: &[Link<T>], order: &O) -> Link<T> {
+        roots.iter().flatten().fold(None, |top, &root| match top {
+            Some(top) => {
+                stats::record(|stats| stats.comparisons += 1);
+                if order.is_lower(&(*top.as_ptr()).elem, &(*root.as_ptr()).elem) {
+                    Some(root)
+                } else {
+                    Some(top)
+                }
+            }
+            None => Some(root),
+        })
+    }
+
+    /// 摘下一个根，它的孩子作为独立的树加回森林. 根成为孤立结点.
+    unsafe fn remove_root(roots: &mut Vec<Link<T>>, root: NonNull<Node<T>>, order: &O) {
+        let raw = root.as_ptr();
+        roots[(*raw).degree] = None;
+        let mut child = (*raw).child.take();
+        while let Some(node) = child {
+            let node_raw = node.as_ptr();
+            child = (*node_raw).sibling.take();
+            (*node_raw).parent = None;
+            Self::add_tree(roots, node, order);
+        }
+        (*raw).degree = 0;
+        while let Some(None) = roots.last() {
+            roots.pop();
+        }
+    }
+
+    /// 上滤. 若父母的优先级低于结点则交换内容.
+    unsafe fn percolate_up(nodes: &mut [Link<T>], mut node: NonNull<Node<T>>, order: &O) {
+        while let Some(parent) = (*node.as_ptr()).parent {
+            stats::record(|stats| stats.comparisons += 1);
+            if !order.is_lower(&(*parent.as_ptr()).elem, &(*node.as_ptr()).elem) {
+                break;
+            }
+            Self::swap(nodes, node, parent);
+            node = parent;
+        }
+    }
+
+    /// 下滤. 若结点的优先级低于优先级最高的孩子则交换内容.
+    unsafe fn percolate_down(nodes: &mut [Link<T>], mut node: NonNull<Node<T>>, order: &O) {
+        loop {
+            let mut top = node;
+            let mut child = (*node.as_ptr()).child;
+            while let Some(current) = child {
+                stats::record(|stats| stats.comparisons += 1);
+                if order.is_lower(&(*top.as_ptr()).elem, &(*current.as_ptr()).elem) {
+                    top = current;
+                }
+                child = (*current.as_ptr()).sibling;
+            }
+            if top == node {
+                return;
+            }
+            Self::swap(nodes, node, top);
+            node = top;
+        }
+    }
+
+    /// 检查长度、堆序、度数、父母指针以及句柄表. `position`为结点在各树(按度数递增)中的先序次序.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let mut position = 0;
+        // Safety: 只读访问堆中的结点.
+        unsafe {
+            for (degree, &root) in self.roots.iter().enumerate() {
+                let root = match root {
+                    Some(root) => root,
+                    None => continue,
+                };
+                if (*root.as_ptr()).degree != degree {
+                    violations.push(Violation::Degree {
+                        position,
+                        degree: (*root.as_ptr()).degree,
+                    });
+                }
+                let mut stack = vec![(root, None::<NonNull<Node<T>>>)];
+                while let Some((node, parent)) = stack.pop() {
+                    let raw = node.as_ptr();
+                    let unordered = match parent {
+                        Some(parent) => self.order.is_lower(&(*parent.as_ptr()).elem, &(*raw).elem),
+                        None => false,
+                    };
+                    if unordered {
+                        violations.push(Violation::HeapOrder { position });
+                    }
+                    if (*raw).parent != parent {
+                        violations.push(Violation::ParentLink { position });
+                    }
+                    if self.node((*raw).handle) != Some(node) {
+                        violations.push(Violation::HandleLink { position });
+                    }
+                    // 度数为`d`的结点的孩子的度数依次为`d - 1, ..., 0`.
+                    let mut children = Vec::new();
+                    let mut child = (*raw).child;
+                    while let Some(current) = child {
+                        children.push(current);
+                        child = (*current.as_ptr()).sibling;
+                    }
+                    let degree = (*raw).degree;
+                    if children.len() != degree
+                        || children
+                            .iter()
+                            .enumerate()
+                            .any(|(index, child)| (*child.as_ptr()).degree + index + 1 != degree)
+                    {
+                        violations.push(Violation::Degree { position, degree });
+                    }
+                    position += 1;
+                    stack.extend(children.into_iter().rev().map(|child| (child, Some(node))));
+                }
+            }
+        }
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        validate::into_validation(violations)
+    }
+}
+
+impl<T, O: Order<T>> IndexedPriorityQueue<T> for BinomialHeap<T, O> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn insert(&mut self, elem: T) -> Handle {
+        let _stats = self.stats.enter();
+        let (handle, node) = Self::alloc(&mut self.nodes, elem);
+        // Safety: 新结点是孤立的.
+        unsafe { Self::add_tree(&mut self.roots, node, &self.order) };
+        self.len += 1;
+        handle
+    }
+
+    fn peek(&self) -> Option<(Handle, &T)> {
+        // Safety: 根在堆中，借用期间堆不会被修改.
+        unsafe {
+            Self::top(&self.roots, &self.order).map(|root| {
+                let root = &*root.as_ptr();
+                (root.handle, &root.elem)
+            })
+        }
+    }
+
+    fn pop(&mut self) -> Option<(Handle, T)> {
+        let handle = {
+            let _stats = self.stats.enter();
+            // Safety: 根在堆中.
+            unsafe { (*Self::top(&self.roots, &self.order)?.as_ptr()).handle }
+        };
+        IndexedPriorityQueue::remove(self, handle).map(|elem| (handle, elem))
+    }
+
+    fn get(&self, handle: Handle) -> Option<&T> {
+        // Safety: 句柄表中的结点在堆中，借用期间堆不会被修改.
+        self.node(handle)
+            .map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    fn change_priority(&mut self, handle: Handle, elem: T) -> Option<T> {
+        let _stats = self.stats.enter();
+        let node = self.node(handle)?;
+        // Safety: 结点在堆中，上滤与下滤只交换结点的内容.
+        unsafe {
+            stats::record(|stats| stats.comparisons += 1);
+            let lower = self.order.is_lower(&elem, &(*node.as_ptr()).elem);
+            let old = mem::replace(&mut (*node.as_ptr()).elem, elem);
+            if lower {
+                Self::percolate_down(&mut self.nodes, node, &self.order);
+            } else {
+                Self::percolate_up(&mut self.nodes, node, &self.order);
+            }
+            Some(old)
+        }
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<T> {
+        let _stats = self.stats.enter();
+        let node = self.node(handle)?;
+        self.len -= 1;
+        // Safety: 结点在堆中. 它的内容被交换到根后摘下该根，从句柄表中移除后不再有指向它的指针.
+        unsafe {
+            let root = Self::float_to_root(&mut self.nodes, node);
+            Self::remove_root(&mut self.roots, root, &self.order);
+            self.nodes[handle.0] = None;
+            Some(Box::from_raw(root.as_ptr()).elem)
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<T, O: Order<T> + Default> From<MyVec<T>> for BinomialHeap<T, O> {
+    fn from(mut vec: MyVec<T>) -> Self {
+        let mut heap = Self::default();
+        while let Some(elem) = vec.pop() {
+            PriorityQueue::insert(&mut heap, elem);
+        }
+        heap
+    }
+}
+
+impl<T, O: Order<T>> PriorityQueue<T> for BinomialHeap<T, O> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        IndexedPriorityQueue::pop(self).map(|(_, elem)| elem)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        IndexedPriorityQueue::peek(self).map(|(_, elem)| elem)
+    }
+
+    fn insert(&mut self, elem: T) {
+        IndexedPriorityQueue::insert(self, elem);
+    }
+
+    /// 合并两个二项堆，逐级连接同度数的树，为`O(log n)`. 但`other`的句柄失效:
+    /// 它的元素按原来的编号顺序接在`self`的句柄之后，因此句柄表的拼接为`O(m)`.
+    /// `other`的顺序策略被丢弃，合并后沿用`self`的.
+    fn merge(&mut self, mut other: Self) {
+        let _stats = self.stats.enter();
+        let offset = self.nodes.len();
+        for (index, node) in other.nodes.drain(..).enumerate() {
+            if let Some(node) = node {
+                // Safety: 结点在`other`中，此后归`self`所有.
+                unsafe { (*node.as_ptr()).handle = Handle(offset + index) };
+            }
+            self.nodes.push(node);
+        }
+        for root in other.roots.drain(..).flatten() {
+            // Safety: 根分属不同的堆.
+            unsafe { Self::add_tree(&mut self.roots, root, &self.order) };
+        }
+        self.len += mem::take(&mut other.len);
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{check_indexed, check_queue, op};
+    use super::super::MinFirst;
+    use super::*;
+    use proptest::prelude::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_shape() {
+        let heap = BinomialHeap::<_>::from(MyVec::from((0..11).collect::<Vec<_>>()));
+        // 11 = 0b1011.
+        let degrees = heap
+            .roots
+            .iter()
+            .map(|root| root.map(|root| unsafe { (*root.as_ptr()).degree }))
+            .collect::<Vec<_>>();
+        assert_eq!(degrees, [Some(0), Some(1), None, Some(3)]);
+        assert_eq!(heap.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_drop() {
+        let counter = Rc::new(());
+        let mut heap = BinomialHeap::<_>::default();
+        let handles = (0..8)
+            .map(|elem| IndexedPriorityQueue::insert(&mut heap, (elem, Rc::clone(&counter))))
+            .collect::<Vec<_>>();
+        IndexedPriorityQueue::pop(&mut heap);
+        IndexedPriorityQueue::remove(&mut heap, handles[3]);
+        heap.change_priority(handles[5], (-1, Rc::clone(&counter)));
+        assert_eq!(Rc::strong_count(&counter), 7);
+        drop(heap);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn test_validate() {
+        let heap = BinomialHeap::<_>::from(MyVec::from(vec![1, 2, 3, 4]));
+        assert_eq!(heap.validate(), Ok(()));
+        unsafe { (*heap.roots[2].unwrap().as_ptr()).degree = 1 };
+        assert_eq!(
+            heap.validate(),
+            Err(vec![
+                Violation::Degree {
+                    position: 0,
+                    degree: 1
+                },
+                Violation::Degree {
+                    position: 0,
+                    degree: 1
+                }
+            ])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_binomial_heap(data1: Vec<i64>, data2: Vec<i64>) {
+            check_queue::<BinomialHeap<_>, _>(data1.clone(), data2.clone(), MaxFirst, BinomialHeap::validate)?;
+            check_queue::<BinomialHeap<_, MinFirst>, _>(data1, data2, MinFirst, BinomialHeap::validate)?;
+        }
+
+        #[test]
+        fn test_indexed_binomial_heap(ops in prop::collection::vec(op(), 0..200)) {
+            check_indexed(BinomialHeap::<_>::default(), ops.clone(), MaxFirst, BinomialHeap::validate)?;
+            check_indexed(BinomialHeap::<_, MinFirst>::default(), ops, MinFirst, BinomialHeap::validate)?;
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = BinomialHeap::<_>::default();
+        for elem in 0..8 {
+            PriorityQueue::insert(&mut heap, elem);
+        }
+        // 8次插入共产生7次连接.
+        assert_eq!(PriorityQueue::stats(&heap).comparisons, 7);
+        heap.change_priority(Handle(0), 10);
+        let stats = PriorityQueue::stats(&heap);
+        assert_eq!(stats.allocations, 8);
+        // 元素0位于最深处，上滤3层.
+        assert_eq!(stats.swaps, 3);
+    }
+}
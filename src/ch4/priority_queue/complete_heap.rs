@@ -1,20 +1,38 @@
-use super::PriorityQueue;
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
+use super::{MaxFirst, MinFirst, Order, PriorityQueue};
 use crate::vec::MyVec;
 use std::convert::From;
 
-pub struct CompleteMaxHeap<T> {
+/// 完全二叉堆. 元素的先后由顺序策略`O`决定，默认为大顶堆.
+pub struct CompleteHeap<T, O = MaxFirst> {
     vec: MyVec<T>,
+    order: O,
+    stats: Counter,
 }
 
-impl<T> Default for CompleteMaxHeap<T> {
+/// 大顶完全二叉堆.
+pub type CompleteMaxHeap<T> = CompleteHeap<T, MaxFirst>;
+
+/// 小顶完全二叉堆.
+pub type CompleteMinHeap<T> = CompleteHeap<T, MinFirst>;
+
+impl<T, O: Default> Default for CompleteHeap<T, O> {
     fn default() -> Self {
+        Self::with_order(O::default())
+    }
+}
+
+impl<T, O> CompleteHeap<T, O> {
+    /// 创建一个使用给定顺序策略的空堆.
+    pub fn with_order(order: O) -> Self {
         Self {
             vec: MyVec::default(),
+            order,
+            stats: Counter::default(),
         }
     }
-}
 
-impl<T> CompleteMaxHeap<T> {
     const fn left(n: usize) -> usize {
         2 * n + 1
     }
@@ -26,15 +44,35 @@ impl<T> CompleteMaxHeap<T> {
     }
 }
 
-impl<T: PartialOrd> CompleteMaxHeap<T> {
+impl<T, O: Order<T>> CompleteHeap<T, O> {
+    /// 以给定的顺序策略从列表建堆.
+    pub fn from_vec_with_order(vec: MyVec<T>, order: O) -> Self {
+        let mut heap = Self {
+            vec,
+            order,
+            stats: Counter::default(),
+        };
+        heap.rebuild();
+        heap
+    }
+
+    /// `i`处元素的优先级是否低于`j`处.
+    fn is_lower(&self, i: usize, j: usize) -> bool {
+        self.order
+            .is_lower(self.vec.get(i).unwrap(), self.vec.get(j).unwrap())
+    }
+
     /// 上滤.
-    /// 若父母小于孩子则交换，并继续向上比较.
+    /// 若父母的优先级低于孩子则交换，并继续向上比较.
     /// # Panics
     /// `n`溢出时报错.
     fn percolate_up(&mut self, mut n: usize) {
+        let _stats = self.stats.enter();
         while n != 0 {
             let parent = Self::parent(n);
-            if self.vec.get(parent).unwrap() < self.vec.get(n).unwrap() {
+            stats::record(|stats| stats.comparisons += 1);
+            if self.is_lower(parent, n) {
+                stats::record(|stats| stats.swaps += 1);
                 self.vec.swap(n, parent);
                 n = parent;
             } else {
@@ -44,19 +82,24 @@ impl<T: PartialOrd> CompleteMaxHeap<T> {
     }
 
     /// 下滤(直到`limit`).
-    /// 若父母小于孩子则交换，并继续向下比较，直到越界.
+    /// 若父母的优先级低于孩子则交换，并继续向下比较，直到越界.
     fn percolate_down_with_limit(&mut self, mut n: usize, limit: usize) {
+        let _stats = self.stats.enter();
         while n < limit {
             let mut max = n;
             let left = Self::left(n);
-            if left < limit && self.vec.get(max).unwrap() < self.vec.get(left).unwrap() {
+            let right = Self::right(n);
+            stats::record(|stats| {
+                stats.comparisons += (left < limit) as usize + (right < limit) as usize
+            });
+            if left < limit && self.is_lower(max, left) {
                 max = left;
             }
-            let right = Self::right(n);
-            if right < limit && self.vec.get(max).unwrap() < self.vec.get(right).unwrap() {
+            if right < limit && self.is_lower(max, right) {
                 max = right;
             }
             if max != n {
+                stats::record(|stats| stats.swaps += 1);
                 self.vec.swap(max, n);
                 n = max;
             } else {
@@ -66,14 +109,17 @@ impl<T: PartialOrd> CompleteMaxHeap<T> {
     }
 
     /// 下滤.
-    /// 若父母小于孩子则交换，并继续向下比较，直到越界.
+    /// 若父母的优先级低于孩子则交换，并继续向下比较，直到越界.
     fn percolate_down(&mut self, n: usize) {
         self.percolate_down_with_limit(n, self.vec.len())
     }
 
     /// 堆排序.
-    /// 消耗一个列表，并返回一个排序好的列表.
-    pub fn sort(vec: MyVec<T>) -> MyVec<T> {
+    /// 消耗一个列表，并返回按优先级从低到高排列的列表(大顶堆为升序).
+    pub fn sort(vec: MyVec<T>) -> MyVec<T>
+    where
+        O: Default,
+    {
         let mut heap = Self::from(vec);
         for idx in (0..(heap.vec.len())).rev() {
             heap.vec.swap(0, idx);
@@ -82,6 +128,20 @@ impl<T: PartialOrd> CompleteMaxHeap<T> {
         heap.vec
     }
 
+    /// 检查堆序.
+    pub fn validate(&self) -> Validation {
+        let len = self.vec.len();
+        let violations = (0..len)
+            .filter(|&n| {
+                [Self::left(n), Self::right(n)]
+                    .iter()
+                    .any(|&child| child < len && self.is_lower(n, child))
+            })
+            .map(|position| Violation::HeapOrder { position })
+            .collect();
+        validate::into_validation(violations)
+    }
+
     /// 重新建堆.
     fn rebuild(&mut self) {
         if self.vec.len() > 1 {
@@ -93,16 +153,16 @@ impl<T: PartialOrd> CompleteMaxHeap<T> {
     }
 }
 
-impl<T: PartialOrd> PriorityQueue<T> for CompleteMaxHeap<T> {
+impl<T, O: Order<T>> PriorityQueue<T> for CompleteHeap<T, O> {
     /// 插入一个新的元素.
     fn insert(&mut self, elem: T) {
         self.vec.push(elem);
         self.percolate_up(self.vec.len() - 1);
     }
 
-    /// 删除最大元素.
+    /// 删除堆顶元素.
     /// 若堆空则返回`None`.
-    fn delete_max(&mut self) -> Option<T> {
+    fn pop(&mut self) -> Option<T> {
         if self.vec.is_empty() {
             None
         } else {
@@ -118,7 +178,7 @@ impl<T: PartialOrd> PriorityQueue<T> for CompleteMaxHeap<T> {
         self.vec.len()
     }
 
-    fn get_max(&self) -> Option<&T> {
+    fn peek(&self) -> Option<&T> {
         self.vec.get(0)
     }
 
@@ -128,20 +188,34 @@ impl<T: PartialOrd> PriorityQueue<T> for CompleteMaxHeap<T> {
         }
         self.rebuild();
     }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
 }
 
-impl<T: PartialOrd> From<MyVec<T>> for CompleteMaxHeap<T> {
+impl<T, O: Order<T> + Default> From<MyVec<T>> for CompleteHeap<T, O> {
     fn from(vec: MyVec<T>) -> Self {
-        let mut heap = Self { vec };
-        heap.rebuild();
-        heap
+        Self::from_vec_with_order(vec, O::default())
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::super::{ByCmp, ByKey};
     use super::*;
     use proptest::prelude::*;
+    use std::cmp::Reverse;
+
+    /// 依次出队所有元素.
+    fn drain<T, O: Order<T>>(mut heap: CompleteHeap<T, O>) -> Vec<T> {
+        let mut popped = Vec::new();
+        while let Some(elem) = heap.pop() {
+            assert_eq!(heap.validate(), Ok(()));
+            popped.push(elem);
+        }
+        popped
+    }
 
     proptest! {
         #[test]
@@ -158,9 +232,37 @@ mod test {
             let mut heap = CompleteMaxHeap::from(MyVec::from(data1));
             for &elem in data2.iter() {
                 heap.insert(elem);
+                prop_assert_eq!(heap.validate(), Ok(()));
                 let max = heap.vec.iter().max().copied();
-                prop_assert_eq!(heap.delete_max(), max);
+                prop_assert_eq!(heap.pop(), max);
+                prop_assert_eq!(heap.validate(), Ok(()));
+            }
+        }
+
+        #[test]
+        fn test_orders(data: Vec<(i32, i32)>) {
+            let mut sorted = data.clone();
+            sorted.sort_unstable();
+            let heap = CompleteMinHeap::from(MyVec::from(data.clone()));
+            prop_assert_eq!(heap.peek(), sorted.first());
+            prop_assert_eq!(drain(heap), sorted.clone());
+
+            let heap = CompleteHeap::from_vec_with_order(
+                MyVec::from(data.clone()),
+                ByKey(|&(_, key): &(i32, i32)| Reverse(key)),
+            );
+            let keys: Vec<_> = drain(heap).into_iter().map(|(_, key)| key).collect();
+            let mut expected: Vec<_> = data.iter().map(|&(_, key)| key).collect();
+            expected.sort_unstable();
+            prop_assert_eq!(keys, expected);
+
+            let mut heap = CompleteHeap::with_order(ByCmp(|lhs: &(i32, i32), rhs: &(i32, i32)| {
+                rhs.cmp(lhs)
+            }));
+            for &elem in data.iter() {
+                heap.insert(elem);
             }
+            prop_assert_eq!(drain(heap), sorted);
         }
 
         #[test]
@@ -168,10 +270,25 @@ mod test {
             let mut heap1 = CompleteMaxHeap::from(MyVec::from(data1));
             let heap2 = CompleteMaxHeap::from(MyVec::from(data2));
             heap1.merge(heap2);
+            prop_assert_eq!(heap1.validate(), Ok(()));
             while !heap1.is_empty() {
                 let max = heap1.vec.iter().max().copied();
-                assert_eq!(heap1.delete_max(), max);
+                assert_eq!(heap1.pop(), max);
+                prop_assert_eq!(heap1.validate(), Ok(()));
             }
         }
     }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = CompleteMaxHeap::default();
+        for elem in 1..=3 {
+            heap.insert(elem);
+        }
+        heap.pop();
+        let stats = heap.stats();
+        assert_eq!(stats.comparisons, 3);
+        assert_eq!(stats.swaps, 2);
+    }
 }
@@ -0,0 +1,539 @@
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
+use super::{Handle, IndexedPriorityQueue, MaxFirst, Order, PriorityQueue};
+use crate::vec::MyVec;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    handle: Handle,
+    elem: T,
+    /// 孩子数.
+    degree: usize,
+    /// 成为非根结点后是否失去过孩子.
+    marked: bool,
+    parent: Link<T>,
+    /// 孩子链表中的任一结点.
+    child: Link<T>,
+    /// 所在的循环双向链表(根链表或孩子链表)中的左右兄弟. 独自成表时指向自身.
+    left: NonNull<Node<T>>,
+    right: NonNull<Node<T>>,
+}
+
+/// 斐波那契堆. 元素的先后由顺序策略`O`决定，默认为大顶堆.
+///
+/// 堆是一组以循环双向链表连接的树，`top`指向优先级最高的根. 插入、合并与提升优先级都只做`O(1)`的工作:
+/// 新结点直接加入根链表，提升优先级时把违反堆序的结点剪下成为根，
+/// 并级联地剪下已经失去过一个孩子(被标记)的祖先. 出队时才把度数相同的树两两连接，摊还为`O(log n)`.
+/// 降低优先级与删除则先把结点剪下成为根，再像出队一样摘下它.
+///
+/// 同时实现了`PriorityQueue`与`IndexedPriorityQueue`，两者的方法同名，在同时引入两个特质时需要写明特质.
+///
+/// # Safety
+/// 句柄表中的指针都指向由`Box`分配、且仍在堆中的结点. 结点只在`remove`与`Drop`中释放，释放前先从句柄表中移除.
+pub struct FibonacciHeap<T, O = MaxFirst> {
+    top: Link<T>,
+    /// 句柄所指的结点. 已删除元素的句柄为`None`.
+    nodes: Vec<Link<T>>,
+    len: usize,
+    order: O,
+    stats: Counter,
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+impl<T, O: Default> Default for FibonacciHeap<T, O> {
+    fn default() -> Self {
+        Self::with_order(O::default())
+    }
+}
+
+impl<T, O> Drop for FibonacciHeap<T, O> {
+    fn drop(&mut self) {
+        for node in self.nodes.drain(..).flatten() {
+            // Safety: 句柄表中的结点都是由`Box`分配的，且每个结点只出现一次.
+            unsafe {
+                drop(Box::from_raw(node.as_ptr()));
+            }
+        }
+    }
+}
+
+/// 链表中从`first`开始的所有结点.
+unsafe fn siblings<T>(first: Link<T>) -> Vec<NonNull<Node<T>>> {
+    let mut nodes = Vec::new();
+    if let Some(first) = first {
+        let mut current = first;
+        loop {
+            nodes.push(current);
+            current = (*current.as_ptr()).right;
+            if current == first {
+                break;
+            }
+        }
+    }
+    nodes
+}
+
+/// 连接分别包含`lhs`与`rhs`的两个链表.
+unsafe fn splice<T>(lhs: NonNull<Node<T>>, rhs: NonNull<Node<T>>) {
+    let (lhs_right, rhs_left) = ((*lhs.as_ptr()).right, (*rhs.as_ptr()).left);
+    (*lhs.as_ptr()).right = rhs;
+    (*rhs.as_ptr()).left = lhs;
+    (*rhs_left.as_ptr()).right = lhs_right;
+    (*lhs_right.as_ptr()).left = rhs_left;
+}
+
+/// 把结点从所在的链表中移除，使它独自成表.
+unsafe fn unlink<T>(node: NonNull<Node<T>>) {
+    let raw = node.as_ptr();
+    (*(*raw).left.as_ptr()).right = (*raw).right;
+    (*(*raw).right.as_ptr()).left = (*raw).left;
+    (*raw).left = node;
+    (*raw).right = node;
+}
+
+impl<T, O> FibonacciHeap<T, O> {
+    /// 创建一个使用给定顺序策略的空堆.
+    pub fn with_order(order: O) -> Self {
+        Self {
+            top: None,
+            nodes: Vec::new(),
+            len: 0,
+            order,
+            stats: Counter::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn node(&self, handle: Handle) -> Link<T> {
+        self.nodes.get(handle.0).copied().flatten()
+    }
+
+    /// 分配一个孤立结点并登记到句柄表中.
+    ///
+    /// 只借用句柄表，以便在计数守卫存活期间调用.
+    fn alloc(nodes: &mut Vec<Link<T>>, elem: T) -> (Handle, NonNull<Node<T>>) {
+        let handle = Handle(nodes.len());
+        let node = NonNull::from(Box::leak(Box::new(Node {
+            handle,
+            elem,
+            degree: 0,
+            marked: false,
+            parent: None,
+            child: None,
+            left: NonNull::dangling(),
+            right: NonNull::dangling(),
+        })));
+        // Safety: 结点刚刚分配.
+        unsafe {
+            (*node.as_ptr()).left = node;
+            (*node.as_ptr()).right = node;
+        }
+        stats::record(|stats| stats.allocations += 1);
+        nodes.push(Some(node));
+        (handle, node)
+    }
+}
+
+impl<T, O: Order<T>> FibonacciHeap<T, O> {
+    /// 把独自成表的结点加入根链表，必要时更新`top`.
+    unsafe fn add_root(top: &mut Link<T>, node: NonNull<Node<T>>, order: &O) {
+        (*node.as_ptr()).parent = None;
+        (*node.as_ptr()).marked = false;
+        match *top {
+            None => *top = Some(node),
+            Some(current) => {
+                splice(current, node);
+                stats::record(|stats| stats.comparisons += 1);
+                if order.is_lower(&(*current.as_ptr()).elem, &(*node.as_ptr()).elem) {
+                    *top = Some(node);
+                }
+            }
+        }
+    }
+
+    /// 连接两个独自成表的根，优先级较低者成为另一个的孩子. 返回新的根.
+    unsafe fn link(lhs: NonNull<Node<T>>, rhs: NonNull<Node<T>>, order: &O) -> NonNull<Node<T>> {
+        stats::record(|stats| stats.comparisons += 1);
+        let (root, child) = if order.is_lower(&(*lhs.as_ptr()).elem, &(*rhs.as_ptr()).elem) {
+            (rhs, lhs)
+        } else {
+            (lhs, rhs)
+        };
+        let (raw, child_raw) = (root.as_ptr(), child.as_ptr());
+        (*child_raw).parent = Some(root);
+        (*child_raw).marked = false;
+        match (*raw).child {
+            Some(first) => splice(first, child),
+            None => (*raw).child = Some(child),
+        }
+        (*raw).degree += 1;
+        root
+    }
+
+    /// 把度数相同的根两两连接，直到根的度数互不相同，并重新找出`top`.
+    unsafe fn consolidate(top: &mut Link<T>, order: &O) {
+        let mut table: Vec<Link<T>> = Vec::new();
+        for mut root in siblings(top.take()) {
+            (*root.as_ptr()).left = root;
+            (*root.as_ptr()).right = root;
+            loop {
+                let degree = (*root.as_ptr()).degree;
+                if table.len() <= degree {
+                    table.resize(degree + 1, None);
+                }
+                match table[degree].take() {
+                    None => {
+                        table[degree] = Some(root);
+                        break;
+                    }
+                    Some(other) => root = Self::link(root, other, order),
+                }
+            }
+        }
+        for root in table.into_iter().flatten() {
+            Self::add_root(top, root, order);
+        }
+    }
+
+    /// 把非根结点剪下，加入根链表.
+    unsafe fn cut(top: &mut Link<T>, node: NonNull<Node<T>>, order: &O) {
+        let raw = node.as_ptr();
+        let parent = (*raw).parent.unwrap().as_ptr();
+        if (*parent).child == Some(node) {
+            (*parent).child = if (*raw).right == node {
+                None
+            } else {
+                Some((*raw).right)
+            };
+        }
+        (*parent).degree -= 1;
+        unlink(node);
+        Self::add_root(top, node, order);
+    }
+
+    /// 级联剪切: 未标记的非根结点被标记，已标记的则被剪下，并继续处理它的父母.
+    unsafe fn cascading_cut(top: &mut Link<T>, mut node: NonNull<Node<T>>, order: &O) {
+        while let Some(parent) = (*node.as_ptr()).parent {
+            if !(*node.as_ptr()).marked {
+                (*node.as_ptr()).marked = true;
+                return;
+            }
+            Self::cut(top, node, order);
+            node = parent;
+        }
+    }
+
+    /// 若结点不是根，则把它剪下成为根.
+    unsafe fn cut_to_root(top: &mut Link<T>, node: NonNull<Node<T>>, order: &O) {
+        if let Some(parent) = (*node.as_ptr()).parent {
+            Self::cut(top, node, order);
+            Self::cascading_cut(top, parent, order);
+        }
+    }
+
+    /// 把根从根链表中摘下，它的孩子成为根，再合并度数相同的根. 根成为孤立结点.
+    unsafe fn extract(top: &mut Link<T>, root: NonNull<Node<T>>, order: &O) {
+        let raw = root.as_ptr();
+        if let Some(child) = (*raw).child.take() {
+            for node in siblings(Some(child)) {
+                (*node.as_ptr()).parent = None;
+                (*node.as_ptr()).marked = false;
+            }
+            splice(root, child);
+        }
+        (*raw).degree = 0;
+        *top = if (*raw).right == root {
+            None
+        } else {
+            Some((*raw).right)
+        };
+        unlink(root);
+        Self::consolidate(top, order);
+    }
+
+    /// 检查长度、堆序(包括`top`是优先级最高的根)、度数、父母指针以及句柄表.
+    /// `position`为结点在各树(从`top`开始)中的先序次序.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let mut position = 0;
+        // Safety: 只读访问堆中的结点.
+        unsafe {
+            let mut stack = siblings(self.top)
+                .into_iter()
+                .rev()
+                .map(|root| (root, None))
+                .collect::<Vec<_>>();
+            while let Some((node, parent)) = stack.pop() {
+                let raw = node.as_ptr();
+                let above = parent.or(self.top.filter(|&top| top != node));
+                let unordered = match above {
+                    Some(above) => self.order.is_lower(&(*above.as_ptr()).elem, &(*raw).elem),
+                    None => false,
+                };
+                if unordered {
+                    violations.push(Violation::HeapOrder { position });
+                }
+                if (*raw).parent != parent {
+                    violations.push(Violation::ParentLink { position });
+                }
+                if self.node((*raw).handle) != Some(node) {
+                    violations.push(Violation::HandleLink { position });
+                }
+                let children = siblings((*raw).child);
+                if children.len() != (*raw).degree {
+                    violations.push(Violation::Degree {
+                        position,
+                        degree: (*raw).degree,
+                    });
+                }
+                position += 1;
+                stack.extend(children.into_iter().rev().map(|child| (child, Some(node))));
+            }
+        }
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        validate::into_validation(violations)
+    }
+}
+
+impl<T, O: Order<T>> IndexedPriorityQueue<T> for FibonacciHeap<T, O> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn insert(&mut self, elem: T) -> Handle {
+        let _stats = self.stats.enter();
+        let (handle, node) = Self::alloc(&mut self.nodes, elem);
+        // Safety: 新结点独自成表.
+        unsafe { Self::add_root(&mut self.top, node, &self.order) };
+        self.len += 1;
+        handle
+    }
+
+    fn peek(&self) -> Option<(Handle, &T)> {
+        // Safety: 根在堆中，借用期间堆不会被修改.
+        self.top.map(|top| unsafe {
+            let top = &*top.as_ptr();
+            (top.handle, &top.elem)
+        })
+    }
+
+    fn pop(&mut self) -> Option<(Handle, T)> {
+        // Safety: 根在堆中.
+        let handle = unsafe { (*self.top?.as_ptr()).handle };
+        IndexedPriorityQueue::remove(self, handle).map(|elem| (handle, elem))
+    }
+
+    fn get(&self, handle: Handle) -> Option<&T> {
+        // Safety: 句柄表中的结点在堆中，借用期间堆不会被修改.
+        self.node(handle)
+            .map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    fn change_priority(&mut self, handle: Handle, elem: T) -> Option<T> {
+        let _stats = self.stats.enter();
+        let node = self.node(handle)?;
+        let raw = node.as_ptr();
+        // Safety: 结点在堆中.
+        unsafe {
+            stats::record(|stats| stats.comparisons += 1);
+            let lower = self.order.is_lower(&elem, &(*raw).elem);
+            let old = mem::replace(&mut (*raw).elem, elem);
+            if lower {
+                // 摘下后作为新的根重新加入.
+                Self::cut_to_root(&mut self.top, node, &self.order);
+                Self::extract(&mut self.top, node, &self.order);
+                Self::add_root(&mut self.top, node, &self.order);
+            } else {
+                stats::record(|stats| stats.comparisons += 1);
+                match (*raw).parent {
+                    Some(parent) => {
+                        if self.order.is_lower(&(*parent.as_ptr()).elem, &(*raw).elem) {
+                            Self::cut(&mut self.top, node, &self.order);
+                            Self::cascading_cut(&mut self.top, parent, &self.order);
+                        }
+                    }
+                    None => {
+                        let top = self.top.unwrap();
+                        if self.order.is_lower(&(*top.as_ptr()).elem, &(*raw).elem) {
+                            self.top = Some(node);
+                        }
+                    }
+                }
+            }
+            Some(old)
+        }
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<T> {
+        let _stats = self.stats.enter();
+        let node = self.nodes.get_mut(handle.0)?.take()?;
+        self.len -= 1;
+        // Safety: 结点在堆中，摘下并从句柄表中移除后不再有指向它的指针.
+        unsafe {
+            Self::cut_to_root(&mut self.top, node, &self.order);
+            Self::extract(&mut self.top, node, &self.order);
+            Some(Box::from_raw(node.as_ptr()).elem)
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<T, O: Order<T> + Default> From<MyVec<T>> for FibonacciHeap<T, O> {
+    fn from(mut vec: MyVec<T>) -> Self {
+        let mut heap = Self::default();
+        while let Some(elem) = vec.pop() {
+            PriorityQueue::insert(&mut heap, elem);
+        }
+        heap
+    }
+}
+
+impl<T, O: Order<T>> PriorityQueue<T> for FibonacciHeap<T, O> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        IndexedPriorityQueue::pop(self).map(|(_, elem)| elem)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        IndexedPriorityQueue::peek(self).map(|(_, elem)| elem)
+    }
+
+    fn insert(&mut self, elem: T) {
+        IndexedPriorityQueue::insert(self, elem);
+    }
+
+    /// 合并两个斐波那契堆，只需连接根链表，为`O(1)`. 但`other`的句柄失效:
+    /// 它的元素按原来的编号顺序接在`self`的句柄之后，因此句柄表的拼接为`O(m)`.
+    /// `other`的顺序策略被丢弃，合并后沿用`self`的.
+    fn merge(&mut self, mut other: Self) {
+        let _stats = self.stats.enter();
+        let offset = self.nodes.len();
+        for (index, node) in other.nodes.drain(..).enumerate() {
+            if let Some(node) = node {
+                // Safety: 结点在`other`中，此后归`self`所有.
+                unsafe { (*node.as_ptr()).handle = Handle(offset + index) };
+            }
+            self.nodes.push(node);
+        }
+        match (self.top, other.top.take()) {
+            (_, None) => {}
+            (None, top) => self.top = top,
+            // Safety: 两个根链表分属不同的堆.
+            (Some(lhs), Some(rhs)) => unsafe {
+                splice(lhs, rhs);
+                stats::record(|stats| stats.comparisons += 1);
+                if self
+                    .order
+                    .is_lower(&(*lhs.as_ptr()).elem, &(*rhs.as_ptr()).elem)
+                {
+                    self.top = Some(rhs);
+                }
+            },
+        }
+        self.len += mem::take(&mut other.len);
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{check_indexed, check_queue, op};
+    use super::super::MinFirst;
+    use super::*;
+    use proptest::prelude::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_cascading_cut() {
+        let mut heap = FibonacciHeap::<_>::default();
+        for elem in 0..9 {
+            IndexedPriorityQueue::insert(&mut heap, elem);
+        }
+        // 出队后剩余8个结点连接为一棵度数为3的树.
+        IndexedPriorityQueue::pop(&mut heap);
+        let roots = unsafe { siblings(heap.top) };
+        assert_eq!(roots.len(), 1);
+        // 取根的度数为2的孩子，依次提升它的两个孩子: 第一次它被标记，第二次它也被剪下.
+        let (middle, children) = unsafe {
+            let middle = siblings((*roots[0].as_ptr()).child)
+                .into_iter()
+                .find(|child| (*child.as_ptr()).degree == 2)
+                .unwrap();
+            let children = siblings((*middle.as_ptr()).child)
+                .into_iter()
+                .map(|child| (*child.as_ptr()).handle)
+                .collect::<Vec<_>>();
+            (middle, children)
+        };
+        heap.change_priority(children[0], 10);
+        assert!(unsafe { (*middle.as_ptr()).marked });
+        heap.change_priority(children[1], 11);
+        assert!(unsafe { !(*middle.as_ptr()).marked && (*middle.as_ptr()).parent.is_none() });
+        assert_eq!(heap.validate(), Ok(()));
+        assert_eq!(IndexedPriorityQueue::peek(&heap), Some((children[1], &11)));
+        assert_eq!(unsafe { siblings(heap.top) }.len(), 4);
+    }
+
+    #[test]
+    fn test_drop() {
+        let counter = Rc::new(());
+        let mut heap = FibonacciHeap::<_>::default();
+        let handles = (0..8)
+            .map(|elem| IndexedPriorityQueue::insert(&mut heap, (elem, Rc::clone(&counter))))
+            .collect::<Vec<_>>();
+        IndexedPriorityQueue::pop(&mut heap);
+        IndexedPriorityQueue::remove(&mut heap, handles[3]);
+        heap.change_priority(handles[5], (-1, Rc::clone(&counter)));
+        assert_eq!(Rc::strong_count(&counter), 7);
+        drop(heap);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    proptest! {
+        #[test]
+        fn test_fibonacci_heap(data1: Vec<i64>, data2: Vec<i64>) {
+            check_queue::<FibonacciHeap<_>, _>(data1.clone(), data2.clone(), MaxFirst, FibonacciHeap::validate)?;
+            check_queue::<FibonacciHeap<_, MinFirst>, _>(data1, data2, MinFirst, FibonacciHeap::validate)?;
+        }
+
+        #[test]
+        fn test_indexed_fibonacci_heap(ops in prop::collection::vec(op(), 0..200)) {
+            check_indexed(FibonacciHeap::<_>::default(), ops.clone(), MaxFirst, FibonacciHeap::validate)?;
+            check_indexed(FibonacciHeap::<_, MinFirst>::default(), ops, MinFirst, FibonacciHeap::validate)?;
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = FibonacciHeap::<_>::default();
+        for elem in 0..4 {
+            PriorityQueue::insert(&mut heap, elem);
+        }
+        // 插入只与`top`比较.
+        assert_eq!(PriorityQueue::stats(&heap).comparisons, 3);
+        // 剩余3个根连接一次，再把2个根加回根链表.
+        PriorityQueue::pop(&mut heap);
+        let stats = PriorityQueue::stats(&heap);
+        assert_eq!(stats.allocations, 4);
+        assert_eq!(stats.comparisons, 5);
+    }
+}
@@ -0,0 +1,276 @@
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
+use super::{Handle, IndexedPriorityQueue, MaxFirst, MinFirst, Order};
+use crate::vec::MyVec;
+use std::mem;
+
+/// 可修改优先级的完全二叉堆. 元素的先后由顺序策略`O`决定，默认为大顶堆.
+///
+/// 在`CompleteHeap`的基础上，每个元素带有句柄，并以句柄为下标记录元素在堆中的位置，
+/// 上滤与下滤交换元素时同步更新. 因此修改优先级与删除任意元素都是`O(log n)`的.
+pub struct IndexedHeap<T, O = MaxFirst> {
+    vec: MyVec<(Handle, T)>,
+    /// 句柄所指元素在`vec`中的下标. 已删除元素的句柄为`None`.
+    positions: Vec<Option<usize>>,
+    order: O,
+    stats: Counter,
+}
+
+/// 可修改优先级的大顶完全二叉堆.
+pub type IndexedMaxHeap<T> = IndexedHeap<T, MaxFirst>;
+
+/// 可修改优先级的小顶完全二叉堆.
+pub type IndexedMinHeap<T> = IndexedHeap<T, MinFirst>;
+
+impl<T, O: Default> Default for IndexedHeap<T, O> {
+    fn default() -> Self {
+        Self::with_order(O::default())
+    }
+}
+
+impl<T> IndexedHeap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, O> IndexedHeap<T, O> {
+    /// 创建一个使用给定顺序策略的空堆.
+    pub fn with_order(order: O) -> Self {
+        Self {
+            vec: MyVec::default(),
+            positions: Vec::new(),
+            order,
+            stats: Counter::default(),
+        }
+    }
+
+    const fn left(n: usize) -> usize {
+        2 * n + 1
+    }
+    const fn right(n: usize) -> usize {
+        2 * n + 2
+    }
+    const fn parent(n: usize) -> usize {
+        (n - 1) >> 1
+    }
+
+    /// 交换两个元素，并更新它们的位置.
+    ///
+    /// 只借用这两个域，以便在计数守卫存活期间调用.
+    fn swap(vec: &mut [(Handle, T)], positions: &mut [Option<usize>], i: usize, j: usize) {
+        vec.swap(i, j);
+        positions[vec[i].0 .0] = Some(i);
+        positions[vec[j].0 .0] = Some(j);
+    }
+
+    fn position(&self, handle: Handle) -> Option<usize> {
+        self.positions.get(handle.0).copied().flatten()
+    }
+}
+
+impl<T, O: Order<T>> IndexedHeap<T, O> {
+    /// 上滤，返回元素最终的位置.
+    /// 若父母应排在孩子之后则交换，并继续向上比较.
+    fn percolate_up(&mut self, mut n: usize) -> usize {
+        let _stats = self.stats.enter();
+        while n != 0 {
+            let parent = Self::parent(n);
+            stats::record(|stats| stats.comparisons += 1);
+            if self.order.is_lower(&self.vec[parent].1, &self.vec[n].1) {
+                stats::record(|stats| stats.swaps += 1);
+                Self::swap(&mut self.vec, &mut self.positions, n, parent);
+                n = parent;
+            } else {
+                break;
+            }
+        }
+        n
+    }
+
+    /// 下滤.
+    /// 若父母应排在孩子之后则与优先的孩子交换，并继续向下比较，直到越界.
+    fn percolate_down(&mut self, mut n: usize) {
+        let _stats = self.stats.enter();
+        let limit = self.vec.len();
+        while n < limit {
+            let mut first = n;
+            let left = Self::left(n);
+            let right = Self::right(n);
+            stats::record(|stats| {
+                stats.comparisons += (left < limit) as usize + (right < limit) as usize
+            });
+            if left < limit && self.order.is_lower(&self.vec[first].1, &self.vec[left].1) {
+                first = left;
+            }
+            if right < limit && self.order.is_lower(&self.vec[first].1, &self.vec[right].1) {
+                first = right;
+            }
+            if first != n {
+                stats::record(|stats| stats.swaps += 1);
+                Self::swap(&mut self.vec, &mut self.positions, first, n);
+                n = first;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// 位置`n`的元素被替换后恢复堆序.
+    fn restore(&mut self, n: usize) {
+        let n = self.percolate_up(n);
+        self.percolate_down(n);
+    }
+
+    /// 检查堆序以及句柄表.
+    pub fn validate(&self) -> Validation {
+        let len = self.vec.len();
+        let mut violations = Vec::new();
+        for n in 0..len {
+            if [Self::left(n), Self::right(n)].iter().any(|&child| {
+                child < len && self.order.is_lower(&self.vec[n].1, &self.vec[child].1)
+            }) {
+                violations.push(Violation::HeapOrder { position: n });
+            }
+            if self.position(self.vec[n].0) != Some(n) {
+                violations.push(Violation::HandleLink { position: n });
+            }
+        }
+        let actual = self.positions.iter().flatten().count();
+        if actual != len {
+            violations.push(Violation::Length {
+                recorded: len,
+                actual,
+            });
+        }
+        validate::into_validation(violations)
+    }
+}
+
+impl<T, O: Order<T>> IndexedPriorityQueue<T> for IndexedHeap<T, O> {
+    fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    fn insert(&mut self, elem: T) -> Handle {
+        let handle = Handle(self.positions.len());
+        self.positions.push(Some(self.vec.len()));
+        self.vec.push((handle, elem));
+        self.percolate_up(self.vec.len() - 1);
+        handle
+    }
+
+    fn peek(&self) -> Option<(Handle, &T)> {
+        self.vec.first().map(|(handle, elem)| (*handle, elem))
+    }
+
+    fn pop(&mut self) -> Option<(Handle, T)> {
+        let handle = self.vec.first()?.0;
+        self.remove(handle).map(|elem| (handle, elem))
+    }
+
+    fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle).map(|n| &self.vec[n].1)
+    }
+
+    fn change_priority(&mut self, handle: Handle, elem: T) -> Option<T> {
+        let n = self.position(handle)?;
+        let old = mem::replace(&mut self.vec[n].1, elem);
+        self.restore(n);
+        Some(old)
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<T> {
+        let n = self.position(handle)?;
+        let last = self.vec.len() - 1;
+        Self::swap(&mut self.vec, &mut self.positions, n, last);
+        let (_, elem) = self.vec.pop().unwrap();
+        self.positions[handle.0] = None;
+        if n < last {
+            self.restore(n);
+        }
+        Some(elem)
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{check_indexed, op};
+    use super::*;
+    use proptest::prelude::*;
+
+    /// 在有向图上运行Dijkstra算法. 队列为小顶堆，未到达的顶点以`i64::MAX`为距离.
+    fn dijkstra(edges: &[Vec<(usize, i64)>], source: usize) -> Vec<Option<i64>> {
+        let mut heap = IndexedMinHeap::default();
+        let handles = (0..edges.len())
+            .map(|v| heap.insert((if v == source { 0 } else { i64::MAX }, v)))
+            .collect::<Vec<_>>();
+        let mut dists = vec![None; edges.len()];
+        while let Some((_, (dist, u))) = heap.pop() {
+            if dist == i64::MAX {
+                break;
+            }
+            dists[u] = Some(dist);
+            for &(v, weight) in &edges[u] {
+                if let Some(&(old, _)) = heap.get(handles[v]) {
+                    if dist + weight < old {
+                        heap.change_priority(handles[v], (dist + weight, v));
+                    }
+                }
+            }
+        }
+        dists
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let edges = vec![
+            vec![(1, 4), (2, 1)],
+            vec![(3, 1)],
+            vec![(1, 2), (3, 5)],
+            vec![],
+            vec![(0, 1)],
+        ];
+        assert_eq!(
+            dijkstra(&edges, 0),
+            [Some(0), Some(3), Some(1), Some(4), None]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut heap = IndexedMaxHeap::new();
+        for elem in 0..4 {
+            heap.insert(elem);
+        }
+        assert_eq!(heap.validate(), Ok(()));
+        heap.positions[0] = Some(0);
+        assert_eq!(
+            heap.validate(),
+            Err(vec![Violation::HandleLink { position: 3 }])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_indexed_heap(ops in prop::collection::vec(op(), 0..200)) {
+            check_indexed(IndexedHeap::<_>::default(), ops.clone(), MaxFirst, IndexedHeap::validate)?;
+            check_indexed(IndexedHeap::<_, MinFirst>::default(), ops, MinFirst, IndexedHeap::validate)?;
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = IndexedMaxHeap::new();
+        let handles = (1..=3).map(|elem| heap.insert(elem)).collect::<Vec<_>>();
+        heap.change_priority(handles[0], 4);
+        let stats = heap.stats();
+        assert_eq!(stats.comparisons, 5);
+        assert_eq!(stats.swaps, 3);
+    }
+}
@@ -0,0 +1,372 @@
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
+use super::{Handle, IndexedPriorityQueue, MaxFirst, Order};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    handle: Handle,
+    elem: T,
+    npl: usize,
+    parent: Link<T>,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+/// 可修改优先级的左式堆. 元素的先后由顺序策略`O`决定，默认为大顶堆.
+///
+/// 与`LeftHeap`不同，结点带有父母指针，并以句柄为下标记录每个结点的指针，从而可以在`O(log n)`时间内删除任意结点:
+/// 把它的左右子树合并后接在原处，再自下而上地修正NPL. 修改优先级即删除该结点后以新的内容重新合并进堆.
+///
+/// # Safety
+/// 句柄表中的指针都指向由`Box`分配、且仍在堆中的结点. 结点只在`remove`与`Drop`中释放，释放前先从句柄表中移除.
+pub struct IndexedLeftHeap<T, O = MaxFirst> {
+    root: Link<T>,
+    /// 句柄所指的结点. 已删除元素的句柄为`None`.
+    nodes: Vec<Link<T>>,
+    len: usize,
+    order: O,
+    stats: Counter,
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+impl<T, O: Default> Default for IndexedLeftHeap<T, O> {
+    fn default() -> Self {
+        Self::with_order(O::default())
+    }
+}
+
+impl<T, O> Drop for IndexedLeftHeap<T, O> {
+    fn drop(&mut self) {
+        for node in self.nodes.drain(..).flatten() {
+            // Safety: 句柄表中的结点都是由`Box`分配的，且每个结点只出现一次.
+            unsafe {
+                drop(Box::from_raw(node.as_ptr()));
+            }
+        }
+    }
+}
+
+/// 子树的NPL. 空树的NPL为0.
+unsafe fn npl<T>(link: Link<T>) -> usize {
+    link.map_or(0, |node| (*node.as_ptr()).npl)
+}
+
+/// 保持左式性质并更新NPL，返回NPL是否改变.
+unsafe fn update<T>(node: NonNull<Node<T>>) -> bool {
+    let node = node.as_ptr();
+    if npl((*node).left) < npl((*node).right) {
+        stats::record(|stats| stats.swaps += 1);
+        mem::swap(&mut (*node).left, &mut (*node).right);
+    }
+    let npl = 1 + npl((*node).right);
+    mem::replace(&mut (*node).npl, npl) != npl
+}
+
+impl<T> IndexedLeftHeap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, O> IndexedLeftHeap<T, O> {
+    /// 创建一个使用给定顺序策略的空堆.
+    pub fn with_order(order: O) -> Self {
+        Self {
+            root: None,
+            nodes: Vec::new(),
+            len: 0,
+            order,
+            stats: Counter::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn node(&self, handle: Handle) -> Link<T> {
+        self.nodes.get(handle.0).copied().flatten()
+    }
+}
+
+impl<T, O: Order<T>> IndexedLeftHeap<T, O> {
+    /// 合并两棵子树，返回新的根. 新根的`parent`域由调用者设置.
+    ///
+    /// 只沿右侧链递归，因此递归深度为`O(log n)`.
+    unsafe fn merge(lhs: Link<T>, rhs: Link<T>, order: &O) -> Link<T> {
+        match (lhs, rhs) {
+            (None, link) | (link, None) => link,
+            (Some(lhs), Some(rhs)) => {
+                stats::record(|stats| stats.comparisons += 1);
+                let (root, other) = if order.is_lower(&(*lhs.as_ptr()).elem, &(*rhs.as_ptr()).elem)
+                {
+                    (rhs, lhs)
+                } else {
+                    (lhs, rhs)
+                };
+                let right = Self::merge((*root.as_ptr()).right, Some(other), order);
+                if let Some(right) = right {
+                    (*right.as_ptr()).parent = Some(root);
+                }
+                (*root.as_ptr()).right = right;
+                update(root);
+                Some(root)
+            }
+        }
+    }
+
+    /// 把子树合并进以`root`为根的堆.
+    unsafe fn merge_into_root(root: &mut Link<T>, link: Link<T>, order: &O) {
+        *root = Self::merge(*root, link, order);
+        if let Some(root) = *root {
+            (*root.as_ptr()).parent = None;
+        }
+    }
+
+    /// 把结点从堆中摘下，它的左右子树合并后接在原处. 结点成为孤立结点.
+    unsafe fn detach(root: &mut Link<T>, node: NonNull<Node<T>>, order: &O) {
+        let raw = node.as_ptr();
+        let parent = (*raw).parent;
+        let children = Self::merge((*raw).left, (*raw).right, order);
+        if let Some(child) = children {
+            (*child.as_ptr()).parent = parent;
+        }
+        match parent {
+            None => *root = children,
+            Some(parent) => {
+                let parent = parent.as_ptr();
+                if (*parent).left == Some(node) {
+                    (*parent).left = children;
+                } else {
+                    (*parent).right = children;
+                }
+            }
+        }
+        // 自下而上修正NPL，直到某个祖先的NPL不变.
+        let mut current = parent;
+        while let Some(node) = current {
+            if !update(node) {
+                break;
+            }
+            current = (*node.as_ptr()).parent;
+        }
+        (*raw).parent = None;
+        (*raw).left = None;
+        (*raw).right = None;
+        (*raw).npl = 1;
+    }
+
+    /// 检查长度、堆序、父母指针、句柄表、每个结点记录的NPL以及左式性质.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let mut position = 0;
+        // Safety: 只读访问堆中的结点.
+        unsafe {
+            self.validate_at(self.root, None, &mut position, &mut violations);
+        }
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        validate::into_validation(violations)
+    }
+
+    /// 中序遍历，返回子树的NPL.
+    unsafe fn validate_at(
+        &self,
+        link: Link<T>,
+        parent: Link<T>,
+        position: &mut usize,
+        violations: &mut Vec<Violation>,
+    ) -> usize {
+        let node = match link {
+            Some(node) => node,
+            None => return 0,
+        };
+        let raw = node.as_ptr();
+        let lnpl = self.validate_at((*raw).left, link, position, violations);
+        let current = *position;
+        *position += 1;
+        let rnpl = self.validate_at((*raw).right, link, position, violations);
+        if [(*raw).left, (*raw).right]
+            .iter()
+            .flatten()
+            .any(|child| self.order.is_lower(&(*raw).elem, &(*child.as_ptr()).elem))
+        {
+            violations.push(Violation::HeapOrder { position: current });
+        }
+        if (*raw).parent != parent {
+            violations.push(Violation::ParentLink { position: current });
+        }
+        if self.node((*raw).handle) != link {
+            violations.push(Violation::HandleLink { position: current });
+        }
+        let npl = 1 + lnpl.min(rnpl);
+        if (*raw).npl != npl {
+            violations.push(Violation::Npl {
+                position: current,
+                recorded: (*raw).npl,
+                actual: npl,
+            });
+        }
+        if lnpl < rnpl {
+            violations.push(Violation::Leftist { position: current });
+        }
+        npl
+    }
+}
+
+impl<T, O: Order<T>> IndexedPriorityQueue<T> for IndexedLeftHeap<T, O> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn insert(&mut self, elem: T) -> Handle {
+        let _stats = self.stats.enter();
+        let handle = Handle(self.nodes.len());
+        let node = NonNull::from(Box::leak(Box::new(Node {
+            handle,
+            elem,
+            npl: 1,
+            parent: None,
+            left: None,
+            right: None,
+        })));
+        stats::record(|stats| stats.allocations += 1);
+        self.nodes.push(Some(node));
+        // Safety: 新结点是孤立的.
+        unsafe { Self::merge_into_root(&mut self.root, Some(node), &self.order) };
+        self.len += 1;
+        handle
+    }
+
+    fn peek(&self) -> Option<(Handle, &T)> {
+        // Safety: 根在堆中，借用期间堆不会被修改.
+        self.root.map(|root| unsafe {
+            let root = &*root.as_ptr();
+            (root.handle, &root.elem)
+        })
+    }
+
+    fn pop(&mut self) -> Option<(Handle, T)> {
+        // Safety: 根在堆中.
+        let handle = unsafe { (*self.root?.as_ptr()).handle };
+        self.remove(handle).map(|elem| (handle, elem))
+    }
+
+    fn get(&self, handle: Handle) -> Option<&T> {
+        // Safety: 句柄表中的结点在堆中，借用期间堆不会被修改.
+        self.node(handle)
+            .map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    fn change_priority(&mut self, handle: Handle, elem: T) -> Option<T> {
+        let _stats = self.stats.enter();
+        let node = self.node(handle)?;
+        // Safety: 结点在堆中. 摘下后它是孤立的，再合并回堆中.
+        unsafe {
+            Self::detach(&mut self.root, node, &self.order);
+            let old = mem::replace(&mut (*node.as_ptr()).elem, elem);
+            Self::merge_into_root(&mut self.root, Some(node), &self.order);
+            Some(old)
+        }
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<T> {
+        let _stats = self.stats.enter();
+        let node = self.nodes.get_mut(handle.0)?.take()?;
+        self.len -= 1;
+        // Safety: 结点在堆中，摘下并从句柄表中移除后不再有指向它的指针.
+        unsafe {
+            Self::detach(&mut self.root, node, &self.order);
+            Some(Box::from_raw(node.as_ptr()).elem)
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{check_indexed, op};
+    use super::super::MinFirst;
+    use super::*;
+    use proptest::prelude::*;
+    use std::rc::Rc;
+
+    /// 在连通的无向图上运行Prim算法，返回最小生成树的权重. 队列为小顶堆，以连接代价为优先级.
+    fn prim(n: usize, edges: &[(usize, usize, i64)]) -> i64 {
+        let mut adjacent = vec![Vec::new(); n];
+        for &(u, v, weight) in edges {
+            adjacent[u].push((v, weight));
+            adjacent[v].push((u, weight));
+        }
+        let mut heap = IndexedLeftHeap::<_, MinFirst>::default();
+        let handles = (0..n)
+            .map(|v| heap.insert((if v == 0 { 0 } else { i64::MAX }, v)))
+            .collect::<Vec<_>>();
+        let mut total = 0;
+        while let Some((_, (cost, u))) = heap.pop() {
+            total += cost;
+            for &(v, weight) in &adjacent[u] {
+                if let Some(&(old, _)) = heap.get(handles[v]) {
+                    if weight < old {
+                        heap.change_priority(handles[v], (weight, v));
+                    }
+                }
+            }
+        }
+        total
+    }
+
+    #[test]
+    fn test_prim() {
+        let edges = [
+            (0, 1, 4),
+            (0, 2, 3),
+            (1, 2, 1),
+            (1, 3, 2),
+            (2, 3, 4),
+            (3, 4, 2),
+        ];
+        assert_eq!(prim(5, &edges), 8);
+    }
+
+    #[test]
+    fn test_drop() {
+        let counter = Rc::new(());
+        let mut heap = IndexedLeftHeap::new();
+        let handles = (0..8)
+            .map(|elem| heap.insert((elem, Rc::clone(&counter))))
+            .collect::<Vec<_>>();
+        heap.remove(handles[3]);
+        heap.change_priority(handles[5], (10, Rc::clone(&counter)));
+        assert_eq!(Rc::strong_count(&counter), 8);
+        drop(heap);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    proptest! {
+        #[test]
+        fn test_indexed_left_heap(ops in prop::collection::vec(op(), 0..200)) {
+            check_indexed(IndexedLeftHeap::<_>::default(), ops.clone(), MaxFirst, IndexedLeftHeap::validate)?;
+            check_indexed(IndexedLeftHeap::<_, MinFirst>::default(), ops, MinFirst, IndexedLeftHeap::validate)?;
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = IndexedLeftHeap::new();
+        let handles = (0..4).map(|elem| heap.insert(elem)).collect::<Vec<_>>();
+        heap.change_priority(handles[0], 5);
+        let stats = heap.stats();
+        assert_eq!(stats.allocations, 4);
+        assert_eq!(stats.comparisons, 4);
+    }
+}
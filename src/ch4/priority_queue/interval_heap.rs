@@ -0,0 +1,299 @@
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
+use super::DoubleEndedPriorityQueue;
+use crate::vec::MyVec;
+use std::convert::From;
+
+/// 区间堆.
+///
+/// 完全二叉树的每个结点存放两个元素，构成区间`[vec[2k], vec[2k + 1]]`(最后一个结点可能只有一个元素)，
+/// 孩子的区间包含于父母的区间之中. 于是各左端点构成小顶堆，各右端点构成大顶堆，
+/// 两端分别在左端点与右端点上滤或下滤，并随时保持结点内的两个元素有序.
+pub struct IntervalHeap<T> {
+    vec: MyVec<T>,
+    stats: Counter,
+}
+
+impl<T> Default for IntervalHeap<T> {
+    fn default() -> Self {
+        Self {
+            vec: MyVec::default(),
+            stats: Counter::default(),
+        }
+    }
+}
+
+impl<T> IntervalHeap<T> {
+    const fn parent(node: usize) -> usize {
+        (node - 1) >> 1
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 交换两个元素.
+    ///
+    /// 只借用向量，以便在计数守卫存活期间调用.
+    fn swap(vec: &mut [T], i: usize, j: usize) {
+        stats::record(|stats| stats.swaps += 1);
+        vec.swap(i, j);
+    }
+
+    /// 结点的右端点. 只有一个元素的结点以该元素为右端点.
+    fn right(&self, node: usize) -> usize {
+        (2 * node + 1).min(self.vec.len() - 1)
+    }
+}
+
+impl<T: PartialOrd> IntervalHeap<T> {
+    fn less(&self, i: usize, j: usize) -> bool {
+        stats::record(|stats| stats.comparisons += 1);
+        self.vec[i] < self.vec[j]
+    }
+
+    /// 结点内的两个元素逆序时交换它们.
+    ///
+    /// 只借用向量，以便在计数守卫存活期间调用.
+    fn fix_node(vec: &mut [T], node: usize) {
+        let (left, right) = (2 * node, 2 * node + 1);
+        if right < vec.len() {
+            stats::record(|stats| stats.comparisons += 1);
+            if vec[right] < vec[left] {
+                Self::swap(vec, left, right);
+            }
+        }
+    }
+
+    /// 上滤最后一个元素.
+    fn percolate_up(&mut self) {
+        let _stats = self.stats.enter();
+        let last = self.vec.len() - 1;
+        let mut node = last / 2;
+        Self::fix_node(&mut self.vec, node);
+        if node == 0 {
+            return;
+        }
+        // 新元素要么不在父母的区间之内(至多超出一侧)，要么不必移动.
+        let parent = Self::parent(node);
+        let (mut i, is_min) = if self.less(2 * node, 2 * parent) {
+            (2 * node, true)
+        } else if self.less(2 * parent + 1, self.right(node)) {
+            (self.right(node), false)
+        } else {
+            return;
+        };
+        loop {
+            let parent = Self::parent(node);
+            let j = if is_min { 2 * parent } else { 2 * parent + 1 };
+            Self::swap(&mut self.vec, i, j);
+            i = j;
+            node = parent;
+            if node == 0 {
+                return;
+            }
+            let parent = Self::parent(node);
+            let j = if is_min { 2 * parent } else { 2 * parent + 1 };
+            if !(if is_min {
+                self.less(i, j)
+            } else {
+                self.less(j, i)
+            }) {
+                return;
+            }
+        }
+    }
+
+    /// 从`node`开始下滤左端点.
+    fn percolate_down_min(&mut self, mut node: usize) {
+        let _stats = self.stats.enter();
+        let len = self.vec.len();
+        loop {
+            Self::fix_node(&mut self.vec, node);
+            let first = 2 * node + 1;
+            if 2 * first >= len {
+                return;
+            }
+            let child = if 2 * first + 2 < len && self.less(2 * first + 2, 2 * first) {
+                first + 1
+            } else {
+                first
+            };
+            if !self.less(2 * child, 2 * node) {
+                return;
+            }
+            Self::swap(&mut self.vec, 2 * child, 2 * node);
+            node = child;
+        }
+    }
+
+    /// 从`node`开始下滤右端点.
+    fn percolate_down_max(&mut self, mut node: usize) {
+        let _stats = self.stats.enter();
+        let len = self.vec.len();
+        loop {
+            Self::fix_node(&mut self.vec, node);
+            let first = 2 * node + 1;
+            if 2 * first >= len {
+                return;
+            }
+            let child =
+                if 2 * first + 2 < len && self.less(self.right(first), self.right(first + 1)) {
+                    first + 1
+                } else {
+                    first
+                };
+            let (i, j) = (self.right(node), self.right(child));
+            if !self.less(i, j) {
+                return;
+            }
+            Self::swap(&mut self.vec, i, j);
+            node = child;
+        }
+    }
+
+    /// 删除`i`处的端点: 与末尾元素交换后删除，再从根开始下滤.
+    fn remove(&mut self, i: usize, is_min: bool) -> T {
+        let last = self.vec.len() - 1;
+        self.vec.swap(i, last);
+        let elem = self.vec.pop().unwrap();
+        if i < last {
+            if is_min {
+                self.percolate_down_min(0);
+            } else {
+                self.percolate_down_max(0);
+            }
+        }
+        elem
+    }
+
+    /// 检查结点内的顺序，以及孩子的区间是否包含于父母的区间. `position`为元素的下标.
+    pub fn validate(&self) -> Validation {
+        let len = self.vec.len();
+        let mut violations = Vec::new();
+        for i in 0..len {
+            let node = i / 2;
+            let out_of_node = i % 2 == 1 && self.vec[i] < self.vec[i - 1];
+            let out_of_parent = node > 0 && {
+                let parent = Self::parent(node);
+                self.vec[i] < self.vec[2 * parent] || self.vec[i] > self.vec[2 * parent + 1]
+            };
+            if out_of_node || out_of_parent {
+                violations.push(Violation::HeapOrder { position: i });
+            }
+        }
+        validate::into_validation(violations)
+    }
+}
+
+impl<T: PartialOrd> DoubleEndedPriorityQueue<T> for IntervalHeap<T> {
+    fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    fn insert(&mut self, elem: T) {
+        self.vec.push(elem);
+        self.percolate_up();
+    }
+
+    fn get_min(&self) -> Option<&T> {
+        self.vec.first()
+    }
+
+    fn get_max(&self) -> Option<&T> {
+        self.vec.get(1).or_else(|| self.vec.first())
+    }
+
+    fn delete_min(&mut self) -> Option<T> {
+        if self.vec.is_empty() {
+            None
+        } else {
+            Some(self.remove(0, true))
+        }
+    }
+
+    fn delete_max(&mut self) -> Option<T> {
+        match self.vec.len() {
+            0 => None,
+            1 => self.vec.pop(),
+            _ => Some(self.remove(1, false)),
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<T: PartialOrd> From<MyVec<T>> for IntervalHeap<T> {
+    /// 自下而上地对每个结点下滤左右端点建堆，为`O(n)`.
+    fn from(vec: MyVec<T>) -> Self {
+        let mut heap = Self {
+            vec,
+            stats: Counter::default(),
+        };
+        let len = heap.vec.len();
+        if len > 0 {
+            for node in (0..=(len - 1) / 2).rev() {
+                heap.percolate_down_min(node);
+                heap.percolate_down_max(node);
+            }
+        }
+        heap
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{check_double_ended, double_ended_op, top_k};
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_validate() {
+        let mut heap = IntervalHeap::from(MyVec::from((0..7).collect::<Vec<_>>()));
+        assert_eq!(heap.validate(), Ok(()));
+        assert_eq!((heap.vec[0], heap.vec[1]), (0, 6));
+        heap.vec.swap(0, 1);
+        assert_eq!(
+            heap.validate(),
+            Err(vec![
+                Violation::HeapOrder { position: 1 },
+                Violation::HeapOrder { position: 2 },
+                Violation::HeapOrder { position: 3 },
+                Violation::HeapOrder { position: 4 },
+                Violation::HeapOrder { position: 5 },
+            ])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_interval_heap(data: Vec<i64>, ops in prop::collection::vec(double_ended_op(), 0..200)) {
+            check_double_ended::<IntervalHeap<_>>(data, ops, IntervalHeap::validate)?;
+        }
+
+        #[test]
+        fn test_top_k(data: Vec<i64>, k in 0..20usize) {
+            let mut sorted = data.clone();
+            sorted.sort_unstable_by(|lhs, rhs| rhs.cmp(lhs));
+            sorted.truncate(k);
+            prop_assert_eq!(top_k::<IntervalHeap<_>>(&data, k), sorted);
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = IntervalHeap::new();
+        for elem in [2, 1, 3].iter().copied() {
+            heap.insert(elem);
+        }
+        // 1与2在同一结点内交换；3与根的区间比较两次后与右端点交换.
+        assert_eq!(heap.stats().comparisons, 3);
+        assert_eq!(heap.stats().swaps, 2);
+        assert_eq!(heap.delete_max(), Some(3));
+        assert_eq!(heap.delete_min(), Some(1));
+        assert_eq!(heap.get_max(), Some(&2));
+    }
+}
@@ -1,6 +1,8 @@
 use super::super::linked_binary_tree::{cursor::CursorMut, LinkedBinaryTree};
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
 use super::super::{BinTree, BinTreeCursor, BinTreeCursorMut, BinTreeMut};
-use super::PriorityQueue;
+use super::{MaxFirst, Order, PriorityQueue};
 use crate::vec::MyVec;
 use std::mem::swap;
 
@@ -10,51 +12,149 @@ pub struct LeftNode<T> {
     npl: usize,
 }
 
-/// (大顶)左式堆.
-pub struct LeftHeap<T> {
+/// 左式堆. 元素的先后由顺序策略`O`决定，默认为大顶堆.
+pub struct LeftHeap<T, O = MaxFirst> {
     len: usize,
     tree: LinkedBinaryTree<LeftNode<T>>,
+    order: O,
+    stats: Counter,
 }
 
-impl<T> Default for LeftHeap<T> {
+impl<T, O: Default> Default for LeftHeap<T, O> {
     fn default() -> Self {
+        Self::with_order(O::default())
+    }
+}
+
+impl<T, O> LeftHeap<T, O> {
+    /// 创建一个使用给定顺序策略的空堆.
+    pub fn with_order(order: O) -> Self {
         Self {
             len: 0,
             tree: LinkedBinaryTree::default(),
+            order,
+            stats: Counter::default(),
         }
     }
 }
 
-impl<T: PartialOrd + 'static> LeftHeap<T> {
-    fn merge_inner<'a>(lhs: &mut CursorMut<'a, LeftNode<T>>, rhs: LinkedBinaryTree<LeftNode<T>>) {
+impl<T: 'static, O: Order<T>> LeftHeap<T, O> {
+    /// 将`rhs`合并到`lhs`所指的子树中. 要求`lhs`的根的优先级不低于`rhs`的根.
+    ///
+    /// 沿右侧链合并后需要自下而上地更新NPL，而游标无法回到父母，
+    /// 因此将右子树摘下合并后再接回.
+    fn merge_inner<'a>(
+        lhs: &mut CursorMut<'a, LeftNode<T>>,
+        mut rhs: LinkedBinaryTree<LeftNode<T>>,
+        order: &O,
+    ) {
         if !rhs.is_empty() {
-            if lhs.right().is_some() {
-                if lhs.right().unwrap() < rhs.cursor().as_ref().unwrap() {
-                    let subtree = lhs.take_right().unwrap();
-                    lhs.append_right(rhs);
-                    lhs.move_right();
-                    Self::merge_inner(lhs, subtree);
-                } else {
-                    lhs.move_right();
-                    Self::merge_inner(lhs, rhs);
-                }
-            } else {
-                lhs.append_right(rhs);
+            let mut right = lhs.take_right().unwrap();
+            stats::record(|stats| stats.comparisons += !right.is_empty() as usize);
+            if right.is_empty()
+                || order.is_lower(
+                    &right.cursor().as_ref().unwrap().elem,
+                    &rhs.cursor().as_ref().unwrap().elem,
+                )
+            {
+                swap(&mut right, &mut rhs);
             }
+            Self::merge_inner(&mut right.cursor_mut(), rhs, order);
+            lhs.append_right(right);
             let (lc, rc) = (lhs.left(), lhs.right());
             let lnpl = lc.map_or(0, |node| node.npl);
             let rnpl = rc.map_or(0, |node| node.npl);
             lhs.as_mut().unwrap().npl = 1 + lnpl.min(rnpl);
             if lnpl < rnpl {
+                stats::record(|stats| stats.swaps += 1);
                 let (left, right) = (lhs.take_left().unwrap(), lhs.take_right().unwrap());
                 lhs.append_left(right);
                 lhs.append_right(left);
             }
         }
     }
+
+    /// 把另一棵左式树合并进来. 长度由调用者维护.
+    fn merge_tree(&mut self, mut other: LinkedBinaryTree<LeftNode<T>>) {
+        let _stats = self.stats.enter();
+        if !self.tree.is_empty() {
+            if let Some(rhs) = other.cursor().as_ref() {
+                let lhs = self.tree.cursor().into_ref().unwrap();
+                stats::record(|stats| stats.comparisons += 1);
+                if self.order.is_lower(&lhs.elem, &rhs.elem) {
+                    swap(&mut self.tree, &mut other);
+                }
+            }
+            Self::merge_inner(&mut self.tree.cursor_mut(), other, &self.order);
+        } else if !other.is_empty() {
+            self.tree = other;
+        }
+    }
+}
+
+impl<T, O: Order<T>> LeftHeap<T, O> {
+    /// 检查长度、堆序、每个结点记录的NPL以及左式性质.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let mut position = 0;
+        Self::validate_at(
+            self.tree.cursor(),
+            &self.order,
+            &mut position,
+            &mut violations,
+        );
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        validate::into_validation(violations)
+    }
+
+    /// 中序遍历，返回子树的NPL. 空树的NPL为0.
+    fn validate_at<'a>(
+        cursor: <LinkedBinaryTree<LeftNode<T>> as BinTree>::Cursor<'a, LeftNode<T>>,
+        order: &O,
+        position: &mut usize,
+        violations: &mut Vec<Violation>,
+    ) -> usize {
+        let heap_order = match cursor.as_ref() {
+            Some(node) => [cursor.left(), cursor.right()]
+                .iter()
+                .flatten()
+                .any(|child| order.is_lower(&node.elem, &child.elem)),
+            None => return 0,
+        };
+        let (left, right) = cursor.split();
+        let node = cursor.into_ref().unwrap();
+        let lnpl = left.map_or(0, |left| {
+            Self::validate_at(left, order, position, violations)
+        });
+        let current = *position;
+        *position += 1;
+        let rnpl = right.map_or(0, |right| {
+            Self::validate_at(right, order, position, violations)
+        });
+        if heap_order {
+            violations.push(Violation::HeapOrder { position: current });
+        }
+        let npl = 1 + lnpl.min(rnpl);
+        if node.npl != npl {
+            violations.push(Violation::Npl {
+                position: current,
+                recorded: node.npl,
+                actual: npl,
+            });
+        }
+        if lnpl < rnpl {
+            violations.push(Violation::Leftist { position: current });
+        }
+        npl
+    }
 }
 
-impl<T: PartialOrd + 'static> From<MyVec<T>> for LeftHeap<T> {
+impl<T: 'static, O: Order<T> + Default> From<MyVec<T>> for LeftHeap<T, O> {
     fn from(mut vec: MyVec<T>) -> Self {
         let mut heap = Self::default();
         while let Some(elem) = vec.pop() {
@@ -64,55 +164,43 @@ impl<T: PartialOrd + 'static> From<MyVec<T>> for LeftHeap<T> {
     }
 }
 
-impl<T: PartialOrd + 'static> PriorityQueue<T> for LeftHeap<T> {
+impl<T: 'static, O: Order<T>> PriorityQueue<T> for LeftHeap<T, O> {
     fn insert(&mut self, elem: T) {
-        let mut heap = Self::default();
-        heap.tree
-            .cursor_mut()
-            .insert_as_root(LeftNode { elem, npl: 1 });
-        heap.len = 1;
-        self.merge(heap);
+        let mut tree = LinkedBinaryTree::default();
+        tree.cursor_mut().insert_as_root(LeftNode { elem, npl: 1 });
+        self.stats.record(|stats| stats.allocations += 1);
+        self.merge_tree(tree);
+        self.len += 1;
     }
 
-    fn merge(&mut self, mut other: Self) {
-        let len = self.len + other.len;
-        if !self.tree.is_empty() {
-            if let Some(rhs) = other.tree.cursor().as_ref() {
-                let lhs = self.tree.cursor().into_ref().unwrap();
-                if lhs < rhs {
-                    swap(&mut self.tree, &mut other.tree);
-                }
-            }
-            Self::merge_inner(&mut self.tree.cursor_mut(), other.tree);
-        } else if !other.tree.is_empty() {
-            self.tree = other.tree;
-        }
-        self.len = len;
+    /// 合并两个左式堆. `other`的顺序策略被丢弃，合并后沿用`self`的.
+    fn merge(&mut self, other: Self) {
+        self.merge_tree(other.tree);
+        self.len += other.len;
     }
 
     fn len(&self) -> usize {
         self.len
     }
 
-    fn get_max(&self) -> Option<&T> {
+    fn peek(&self) -> Option<&T> {
         self.tree.cursor().into_ref().map(|node| &node.elem)
     }
 
-    fn delete_max(&mut self) -> Option<T> {
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+
+    fn pop(&mut self) -> Option<T> {
         if self.is_empty() {
             None
         } else {
-            let len = self.len - 1;
             let mut cursor = self.tree.cursor_mut();
             let (left, right) = (cursor.take_left().unwrap(), cursor.take_right().unwrap());
             let elem = cursor.into_inner().map(|node| node.elem);
-            let mut lhs = Self { len, tree: left };
-            let rhs = Self {
-                len: 0,
-                tree: right,
-            };
-            lhs.merge(rhs);
-            *self = lhs;
+            self.tree = left;
+            self.len -= 1;
+            self.merge_tree(right);
             elem
         }
     }
@@ -120,10 +208,29 @@ impl<T: PartialOrd + 'static> PriorityQueue<T> for LeftHeap<T> {
 
 #[cfg(test)]
 mod test {
+    use super::super::MinFirst;
     use super::*;
     use crate::ch4::BinTreeCursorExt;
     use proptest::prelude::*;
 
+    #[test]
+    fn test_validate() {
+        let mut heap = LeftHeap::<_>::from(MyVec::from(vec![1, 2, 3]));
+        assert_eq!(heap.validate(), Ok(()));
+        heap.tree.cursor_mut().as_mut().unwrap().npl = 5;
+        heap.len = 4;
+        assert!(matches!(
+            heap.validate().unwrap_err()[..],
+            [
+                Violation::Npl { recorded: 5, .. },
+                Violation::Length {
+                    recorded: 4,
+                    actual: 3
+                }
+            ]
+        ));
+    }
+
     proptest! {
         // #[test]
         // fn test_basic(mut data: Vec<i64>) {
@@ -136,27 +243,58 @@ mod test {
 
         #[test]
         fn test_priority(data1: Vec<i64>, data2: Vec<i64>) {
-            let mut heap = LeftHeap::from(MyVec::from(data1));
+            let mut heap = LeftHeap::<_>::from(MyVec::from(data1));
             for &elem in data2.iter() {
                 heap.insert(elem);
+                prop_assert_eq!(heap.validate(), Ok(()));
                 let max = heap.tree.cursor().in_order_iter().map(|node| node.elem).max();
                 let len = heap.tree.cursor().in_order_iter().count();
                 assert_eq!(heap.len(), len);
-                prop_assert_eq!(heap.delete_max(), max);
+                prop_assert_eq!(heap.pop(), max);
+                prop_assert_eq!(heap.validate(), Ok(()));
+            }
+        }
+
+        #[test]
+        fn test_min_first(data1: Vec<i64>, data2: Vec<i64>) {
+            let mut heap = LeftHeap::<_, MinFirst>::from(MyVec::from(data1.clone()));
+            heap.merge(LeftHeap::from(MyVec::from(data2.clone())));
+            let mut sorted = [data1, data2].concat();
+            sorted.sort_unstable();
+            prop_assert_eq!(heap.peek(), sorted.first());
+            for elem in sorted {
+                prop_assert_eq!(heap.pop(), Some(elem));
+                prop_assert_eq!(heap.validate(), Ok(()));
             }
+            prop_assert!(heap.is_empty());
         }
 
         #[test]
         fn test_merge(data1: Vec<i64>, data2: Vec<i64>) {
-            let mut heap1 = LeftHeap::from(MyVec::from(data1));
-            let heap2 = LeftHeap::from(MyVec::from(data2));
+            let mut heap1 = LeftHeap::<_>::from(MyVec::from(data1));
+            let heap2 = LeftHeap::<_>::from(MyVec::from(data2));
             heap1.merge(heap2);
+            prop_assert_eq!(heap1.validate(), Ok(()));
             while !heap1.is_empty() {
                 let max = heap1.tree.cursor().in_order_iter().map(|node| node.elem).max();
                 let len = heap1.tree.cursor().in_order_iter().count();
                 assert_eq!(heap1.len(), len);
-                assert_eq!(heap1.delete_max(), max);
+                assert_eq!(heap1.pop(), max);
+                prop_assert_eq!(heap1.validate(), Ok(()));
             }
         }
     }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = LeftHeap::<_>::default();
+        for elem in 0..10 {
+            heap.insert(elem);
+        }
+        while heap.pop().is_some() {}
+        let stats = heap.stats();
+        assert_eq!(stats.allocations, 10);
+        assert!(stats.comparisons > 0);
+    }
 }
@@ -0,0 +1,289 @@
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
+use super::DoubleEndedPriorityQueue;
+use crate::vec::MyVec;
+use std::convert::From;
+
+/// 最小-最大堆.
+///
+/// 与`CompleteMaxHeap`一样是存放在向量中的完全二叉树，但偶数层(根为第0层)为最小层，奇数层为最大层:
+/// 最小层的结点不大于它的所有后代，最大层的结点不小于它的所有后代.
+/// 因此最小元素为根，最大元素为根的某个孩子. 上滤与下滤都隔层进行，读取两端为`O(1)`，删除两端为`O(log n)`.
+pub struct MinMaxHeap<T> {
+    vec: MyVec<T>,
+    stats: Counter,
+}
+
+impl<T> Default for MinMaxHeap<T> {
+    fn default() -> Self {
+        Self {
+            vec: MyVec::default(),
+            stats: Counter::default(),
+        }
+    }
+}
+
+impl<T> MinMaxHeap<T> {
+    const fn parent(n: usize) -> usize {
+        (n - 1) >> 1
+    }
+
+    /// 结点`n`的孩子与孙子(可能越界).
+    const fn descendants(n: usize) -> [usize; 6] {
+        let first = 2 * n + 1;
+        [
+            first,
+            first + 1,
+            2 * first + 1,
+            2 * first + 2,
+            2 * first + 3,
+            2 * first + 4,
+        ]
+    }
+
+    /// 结点`n`是否位于最小层.
+    fn is_min_level(n: usize) -> bool {
+        // 第`k`层的下标为`[2^k - 1, 2^(k+1) - 1)`，即`n + 1`的二进制有`k + 1`位.
+        (0usize.leading_zeros() - (n + 1).leading_zeros()) % 2 == 1
+    }
+
+    /// 交换两个元素.
+    ///
+    /// 只借用向量，以便在计数守卫存活期间调用.
+    fn swap(vec: &mut [T], i: usize, j: usize) {
+        stats::record(|stats| stats.swaps += 1);
+        vec.swap(i, j);
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: PartialOrd> MinMaxHeap<T> {
+    /// 按`is_min`所指的方向，`i`处的元素是否应位于`j`处的元素之上.
+    fn precedes(&self, i: usize, j: usize, is_min: bool) -> bool {
+        stats::record(|stats| stats.comparisons += 1);
+        if is_min {
+            self.vec[i] < self.vec[j]
+        } else {
+            self.vec[i] > self.vec[j]
+        }
+    }
+
+    /// 上滤. 先与父母比较决定元素属于最小层还是最大层，再在同类的层中隔层向上比较.
+    fn percolate_up(&mut self, mut n: usize) {
+        let _stats = self.stats.enter();
+        if n == 0 {
+            return;
+        }
+        let mut is_min = Self::is_min_level(n);
+        let parent = Self::parent(n);
+        if self.precedes(parent, n, is_min) {
+            Self::swap(&mut self.vec, parent, n);
+            n = parent;
+            is_min = !is_min;
+        }
+        while n > 2 {
+            let grandparent = Self::parent(Self::parent(n));
+            if self.precedes(n, grandparent, is_min) {
+                Self::swap(&mut self.vec, n, grandparent);
+                n = grandparent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// 下滤. 在孩子与孙子中找出最应位于上方的元素，若它是孙子则交换后继续，并与中间层的父母比较.
+    fn percolate_down(&mut self, mut n: usize) {
+        let _stats = self.stats.enter();
+        let is_min = Self::is_min_level(n);
+        let len = self.vec.len();
+        loop {
+            let top = Self::descendants(n)
+                .iter()
+                .copied()
+                .filter(|&i| i < len)
+                .fold(None, |top, i| match top {
+                    Some(top) if !self.precedes(i, top, is_min) => Some(top),
+                    _ => Some(i),
+                });
+            let top = match top {
+                Some(top) => top,
+                None => return,
+            };
+            if !self.precedes(top, n, is_min) {
+                return;
+            }
+            Self::swap(&mut self.vec, top, n);
+            if top <= 2 * n + 2 {
+                // 孩子之下没有同类的层需要比较.
+                return;
+            }
+            let parent = Self::parent(top);
+            if self.precedes(parent, top, is_min) {
+                Self::swap(&mut self.vec, parent, top);
+            }
+            n = top;
+        }
+    }
+
+    /// 最大元素的下标.
+    fn max_index(&self) -> Option<usize> {
+        match self.vec.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => Some(if self.precedes(2, 1, false) { 2 } else { 1 }),
+        }
+    }
+
+    /// 删除`n`处的元素: 与末尾元素交换后删除，再下滤.
+    fn remove(&mut self, n: usize) -> T {
+        let last = self.vec.len() - 1;
+        self.vec.swap(n, last);
+        let elem = self.vec.pop().unwrap();
+        if n < last {
+            self.percolate_down(n);
+        }
+        elem
+    }
+
+    /// 检查最小层与最大层的堆序. 只需比较孩子与孙子.
+    pub fn validate(&self) -> Validation {
+        let len = self.vec.len();
+        let violations = (0..len)
+            .filter(|&n| {
+                let is_min = Self::is_min_level(n);
+                Self::descendants(n).iter().any(|&i| {
+                    i < len
+                        && if is_min {
+                            self.vec[i] < self.vec[n]
+                        } else {
+                            self.vec[i] > self.vec[n]
+                        }
+                })
+            })
+            .map(|position| Violation::HeapOrder { position })
+            .collect();
+        validate::into_validation(violations)
+    }
+}
+
+impl<T: PartialOrd> DoubleEndedPriorityQueue<T> for MinMaxHeap<T> {
+    fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    fn insert(&mut self, elem: T) {
+        self.vec.push(elem);
+        self.percolate_up(self.vec.len() - 1);
+    }
+
+    fn get_min(&self) -> Option<&T> {
+        self.vec.first()
+    }
+
+    fn get_max(&self) -> Option<&T> {
+        self.max_index().map(|n| &self.vec[n])
+    }
+
+    fn delete_min(&mut self) -> Option<T> {
+        if self.vec.is_empty() {
+            None
+        } else {
+            Some(self.remove(0))
+        }
+    }
+
+    fn delete_max(&mut self) -> Option<T> {
+        let n = {
+            let _stats = self.stats.enter();
+            self.max_index()?
+        };
+        Some(self.remove(n))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<T: PartialOrd> From<MyVec<T>> for MinMaxHeap<T> {
+    /// 自下而上地下滤建堆，为`O(n)`.
+    fn from(vec: MyVec<T>) -> Self {
+        let mut heap = Self {
+            vec,
+            stats: Counter::default(),
+        };
+        if heap.vec.len() > 1 {
+            for n in (0..=Self::parent(heap.vec.len() - 1)).rev() {
+                heap.percolate_down(n);
+            }
+        }
+        heap
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{check_double_ended, double_ended_op, top_k};
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_levels() {
+        let levels = (0..15)
+            .map(MinMaxHeap::<i32>::is_min_level)
+            .collect::<Vec<_>>();
+        let mut expected = vec![true];
+        expected.extend(vec![false; 2]);
+        expected.extend(vec![true; 4]);
+        expected.extend(vec![false; 8]);
+        assert_eq!(levels, expected);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut heap = MinMaxHeap::from(MyVec::from((0..7).collect::<Vec<_>>()));
+        assert_eq!(heap.validate(), Ok(()));
+        assert_eq!(heap.vec[0], 0);
+        assert!(heap.vec[1] == 6 || heap.vec[2] == 6);
+        heap.vec.swap(0, 3);
+        assert_eq!(
+            heap.validate(),
+            Err(vec![Violation::HeapOrder { position: 0 }])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_min_max_heap(data: Vec<i64>, ops in prop::collection::vec(double_ended_op(), 0..200)) {
+            check_double_ended::<MinMaxHeap<_>>(data, ops, MinMaxHeap::validate)?;
+        }
+
+        #[test]
+        fn test_top_k(data: Vec<i64>, k in 0..20usize) {
+            let mut sorted = data.clone();
+            sorted.sort_unstable_by(|lhs, rhs| rhs.cmp(lhs));
+            sorted.truncate(k);
+            prop_assert_eq!(top_k::<MinMaxHeap<_>>(&data, k), sorted);
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = MinMaxHeap::new();
+        for elem in (0..4).rev() {
+            heap.insert(elem);
+        }
+        // 2与1插入时都小于根，各与根交换一次；0在最小层，先与最大层的父母3比较，再与祖父母1比较并交换.
+        assert_eq!(heap.stats().comparisons, 4);
+        assert_eq!(heap.stats().swaps, 3);
+        assert_eq!(heap.delete_max(), Some(3));
+        assert_eq!(heap.delete_min(), Some(0));
+        assert_eq!(heap.get_max(), Some(&2));
+    }
+}
@@ -1,10 +1,24 @@
+pub mod binomial_heap;
 pub mod complete_heap;
+pub mod fibonacci_heap;
+pub mod indexed_heap;
+pub mod indexed_left_heap;
+pub mod interval_heap;
 pub mod left_heap;
+pub mod min_max_heap;
+pub mod pairing_heap;
+pub mod skew_heap;
 
-use crate::vec::MyVec;
+mod order;
 
-/// 最大优先队列.
-pub trait PriorityQueue<T: PartialOrd>: From<MyVec<T>> {
+pub use order::*;
+
+use super::stats::Stats;
+
+/// 优先队列.
+///
+/// 元素出队的先后由实现所采用的顺序策略(`Order`)决定，例如`MaxFirst`时为最大优先队列.
+pub trait PriorityQueue<T> {
     /// 获取队列长度.
     fn len(&self) -> usize;
 
@@ -13,17 +27,441 @@ pub trait PriorityQueue<T: PartialOrd>: From<MyVec<T>> {
         self.len() == 0
     }
 
-    /// 删除最大元素.
+    /// 删除并返回优先级最高的元素.
     /// 若队列为空则返回`None`.
-    fn delete_max(&mut self) -> Option<T>;
+    fn pop(&mut self) -> Option<T>;
 
-    /// 读取最大元素.
+    /// 读取优先级最高的元素.
     /// 若队列为空则返回`None`.
-    fn get_max(&self) -> Option<&T>;
+    fn peek(&self) -> Option<&T>;
 
     /// 向队列插入一个元素.
     fn insert(&mut self, elem: T);
 
     /// 合并两个优先队列.
     fn merge(&mut self, other: Self);
+
+    /// 返回累计的操作次数. 只有启用`stats`特性时才会计数，不计数的实现总是返回全零.
+    fn stats(&self) -> Stats {
+        Stats::default()
+    }
+}
+
+/// 双端优先队列.
+///
+/// 可以同时读取与删除最小元素和最大元素，例如只保留最大的`k`个元素时，超出容量便删除最小元素.
+pub trait DoubleEndedPriorityQueue<T: PartialOrd> {
+    /// 获取队列长度.
+    fn len(&self) -> usize;
+
+    /// 队列是否为空.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 向队列插入一个元素.
+    fn insert(&mut self, elem: T);
+
+    /// 读取最小元素.
+    /// 若队列为空则返回`None`.
+    fn get_min(&self) -> Option<&T>;
+
+    /// 读取最大元素.
+    /// 若队列为空则返回`None`.
+    fn get_max(&self) -> Option<&T>;
+
+    /// 删除并返回最小元素.
+    /// 若队列为空则返回`None`.
+    fn delete_min(&mut self) -> Option<T>;
+
+    /// 删除并返回最大元素.
+    /// 若队列为空则返回`None`.
+    fn delete_max(&mut self) -> Option<T>;
+
+    /// 返回累计的操作次数. 只有启用`stats`特性时才会计数，不计数的实现总是返回全零.
+    fn stats(&self) -> Stats {
+        Stats::default()
+    }
+}
+
+/// 可修改优先级的优先队列中元素的句柄.
+///
+/// 由`IndexedPriorityQueue::insert`返回，在元素被删除前始终指向它. 句柄按插入顺序编号，不会被复用.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Handle(usize);
+
+impl Handle {
+    /// 句柄的编号，即它是第几个插入的元素(从0开始). 可用作外部数组的下标.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// 可修改优先级的优先队列.
+///
+/// 插入时返回句柄，之后可经由句柄读取、修改或删除该元素，适用于Dijkstra、Prim等算法.
+/// 与`PriorityQueue`一样，元素出队的先后由实现所采用的顺序策略决定.
+pub trait IndexedPriorityQueue<T> {
+    /// 获取队列长度.
+    fn len(&self) -> usize;
+
+    /// 队列是否为空.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 向队列插入一个元素，并返回它的句柄.
+    fn insert(&mut self, elem: T) -> Handle;
+
+    /// 读取优先级最高的元素及其句柄.
+    /// 若队列为空则返回`None`.
+    fn peek(&self) -> Option<(Handle, &T)>;
+
+    /// 删除优先级最高的元素，并返回它及其句柄. 此后该句柄失效.
+    /// 若队列为空则返回`None`.
+    fn pop(&mut self) -> Option<(Handle, T)>;
+
+    /// 读取句柄所指的元素. 若句柄已失效则返回`None`.
+    fn get(&self, handle: Handle) -> Option<&T>;
+
+    /// 句柄所指的元素是否仍在队列中.
+    fn contains(&self, handle: Handle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// 把句柄所指的元素替换为`elem`(优先级可升可降)，并返回原来的元素. 句柄依然有效.
+    /// 若句柄已失效则不修改队列并返回`None`.
+    fn change_priority(&mut self, handle: Handle, elem: T) -> Option<T>;
+
+    /// 删除句柄所指的元素并返回. 此后该句柄失效.
+    /// 若句柄已失效则返回`None`.
+    fn remove(&mut self, handle: Handle) -> Option<T>;
+
+    /// 返回累计的操作次数. 只有启用`stats`特性时才会计数，不计数的实现总是返回全零.
+    fn stats(&self) -> Stats {
+        Stats::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::validate::Validation;
+    use super::binomial_heap::BinomialHeap;
+    use super::fibonacci_heap::FibonacciHeap;
+    use super::indexed_heap::IndexedMinHeap;
+    use super::indexed_left_heap::IndexedLeftHeap;
+    use super::pairing_heap::PairingHeap;
+    use super::*;
+    use crate::vec::MyVec;
+    use ::test::Bencher;
+    use proptest::prelude::*;
+    use proptest::test_runner::TestCaseError;
+    use random::Source;
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+    use std::mem;
+
+    /// 按顺序策略，`model`中优先级最高的元素的下标.
+    fn top_of<T, O: Order<T>>(model: &[T], order: &O) -> Option<usize> {
+        (0..model.len()).fold(None, |top, index| match top {
+            Some(top) if !order.is_lower(&model[top], &model[index]) => Some(top),
+            _ => Some(index),
+        })
+    }
+
+    /// 以`Vec`为模型检查优先队列: 交替地插入与出队，再合并另一个队列并逐个出队.
+    /// 每次修改后都调用`validate`检查不变式.
+    pub(super) fn check_queue<Q, O>(
+        data1: Vec<i64>,
+        data2: Vec<i64>,
+        order: O,
+        validate: fn(&Q) -> Validation,
+    ) -> Result<(), TestCaseError>
+    where
+        Q: PriorityQueue<i64> + From<MyVec<i64>>,
+        O: Order<i64>,
+    {
+        let mut heap = Q::from(MyVec::from(data1.clone()));
+        let mut model = data1;
+        prop_assert_eq!(validate(&heap), Ok(()));
+        for &elem in data2.iter() {
+            heap.insert(elem);
+            model.push(elem);
+            prop_assert_eq!(validate(&heap), Ok(()));
+            let top = top_of(&model, &order).map(|index| model.swap_remove(index));
+            prop_assert_eq!(heap.peek(), top.as_ref());
+            prop_assert_eq!(heap.pop(), top);
+            prop_assert_eq!(validate(&heap), Ok(()));
+        }
+        heap.merge(Q::from(MyVec::from(data2.clone())));
+        model.extend(data2);
+        prop_assert_eq!(validate(&heap), Ok(()));
+        prop_assert_eq!(heap.len(), model.len());
+        while let Some(index) = top_of(&model, &order) {
+            prop_assert_eq!(heap.pop(), Some(model.swap_remove(index)));
+            prop_assert_eq!(validate(&heap), Ok(()));
+        }
+        prop_assert!(heap.is_empty());
+        prop_assert_eq!(heap.pop(), None);
+        Ok(())
+    }
+
+    /// 可修改优先级的优先队列的操作: 插入、修改优先级、删除、出队.
+    #[derive(Debug, Clone)]
+    pub(super) enum Op {
+        Insert(i32),
+        Change(usize, i32),
+        Remove(usize),
+        Pop,
+    }
+
+    pub(super) fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            3 => any::<i32>().prop_map(Op::Insert),
+            2 => (any::<usize>(), any::<i32>()).prop_map(|(index, elem)| Op::Change(index, elem)),
+            1 => any::<usize>().prop_map(Op::Remove),
+            1 => Just(Op::Pop),
+        ]
+    }
+
+    /// 以句柄编号为下标的数组为模型，按顺序策略检查可修改优先级的优先队列.
+    pub(super) fn check_indexed<Q: IndexedPriorityQueue<i32>, O: Order<i32>>(
+        mut heap: Q,
+        ops: Vec<Op>,
+        order: O,
+        validate: fn(&Q) -> Validation,
+    ) -> Result<(), TestCaseError> {
+        // 仍在队列中的元素.
+        let mut expected = Vec::<Option<i32>>::new();
+        for op in ops {
+            match op {
+                Op::Insert(elem) => {
+                    prop_assert_eq!(heap.insert(elem).index(), expected.len());
+                    expected.push(Some(elem));
+                }
+                Op::Change(index, elem) if !expected.is_empty() => {
+                    let handle = Handle(index % expected.len());
+                    let old = expected[handle.0]
+                        .as_mut()
+                        .map(|old| mem::replace(old, elem));
+                    prop_assert_eq!(heap.change_priority(handle, elem), old);
+                }
+                Op::Remove(index) if !expected.is_empty() => {
+                    let handle = Handle(index % expected.len());
+                    prop_assert_eq!(heap.remove(handle), expected[handle.0].take());
+                    prop_assert!(!heap.contains(handle));
+                }
+                Op::Pop => {
+                    let model = expected.iter().flatten().copied().collect::<Vec<_>>();
+                    let top = top_of(&model, &order).map(|index| model[index]);
+                    prop_assert_eq!(heap.peek().map(|(_, &elem)| elem), top);
+                    let popped = heap.pop();
+                    prop_assert_eq!(popped.map(|(_, elem)| elem), top);
+                    if let Some((handle, _)) = popped {
+                        prop_assert_eq!(expected[handle.0].take(), top);
+                    }
+                }
+                _ => {}
+            }
+            prop_assert_eq!(validate(&heap), Ok(()));
+            prop_assert_eq!(heap.len(), expected.iter().flatten().count());
+        }
+        for (index, elem) in expected.iter().enumerate() {
+            prop_assert_eq!(heap.get(Handle(index)), elem.as_ref());
+        }
+        Ok(())
+    }
+
+    /// 双端优先队列的操作: 插入、删除最小元素、删除最大元素.
+    #[derive(Debug, Clone)]
+    pub(super) enum DoubleEndedOp {
+        Insert(i64),
+        DeleteMin,
+        DeleteMax,
+    }
+
+    pub(super) fn double_ended_op() -> impl Strategy<Value = DoubleEndedOp> {
+        prop_oneof![
+            3 => any::<i64>().prop_map(DoubleEndedOp::Insert),
+            1 => Just(DoubleEndedOp::DeleteMin),
+            1 => Just(DoubleEndedOp::DeleteMax),
+        ]
+    }
+
+    /// 以有序的`Vec`为模型检查双端优先队列. 每次修改后都调用`validate`检查不变式.
+    pub(super) fn check_double_ended<Q>(
+        data: Vec<i64>,
+        ops: Vec<DoubleEndedOp>,
+        validate: fn(&Q) -> Validation,
+    ) -> Result<(), TestCaseError>
+    where
+        Q: DoubleEndedPriorityQueue<i64> + From<MyVec<i64>>,
+    {
+        let mut heap = Q::from(MyVec::from(data.clone()));
+        let mut model = data;
+        model.sort_unstable();
+        prop_assert_eq!(validate(&heap), Ok(()));
+        for op in ops {
+            match op {
+                DoubleEndedOp::Insert(elem) => {
+                    heap.insert(elem);
+                    let index = model.binary_search(&elem).unwrap_or_else(|index| index);
+                    model.insert(index, elem);
+                }
+                DoubleEndedOp::DeleteMin => {
+                    let min = if model.is_empty() {
+                        None
+                    } else {
+                        Some(model.remove(0))
+                    };
+                    prop_assert_eq!(heap.delete_min(), min);
+                }
+                DoubleEndedOp::DeleteMax => prop_assert_eq!(heap.delete_max(), model.pop()),
+            }
+            prop_assert_eq!(validate(&heap), Ok(()));
+            prop_assert_eq!(heap.len(), model.len());
+            prop_assert_eq!(heap.get_min(), model.first());
+            prop_assert_eq!(heap.get_max(), model.last());
+        }
+        Ok(())
+    }
+
+    /// 用双端优先队列保留最大的`k`个元素，返回它们(降序).
+    pub(super) fn top_k<Q>(data: &[i64], k: usize) -> Vec<i64>
+    where
+        Q: DoubleEndedPriorityQueue<i64> + Default,
+    {
+        let mut heap = Q::default();
+        for &elem in data {
+            heap.insert(elem);
+            if heap.len() > k {
+                heap.delete_min();
+            }
+        }
+        let mut top = Vec::new();
+        while let Some(elem) = heap.delete_max() {
+            top.push(elem);
+        }
+        top
+    }
+
+    /// 在有向图上运行Dijkstra算法. 队列为小顶堆，以距离为优先级.
+    /// 顶点在第一次被松弛时入队，此后以`change_priority`缩短距离.
+    fn dijkstra<Q: IndexedPriorityQueue<(i64, usize)> + Default>(
+        edges: &[Vec<(usize, i64)>],
+        source: usize,
+    ) -> Vec<Option<i64>> {
+        let mut heap = Q::default();
+        let mut handles = vec![None; edges.len()];
+        let mut dists = vec![None; edges.len()];
+        handles[source] = Some(heap.insert((0, source)));
+        while let Some((_, (dist, u))) = heap.pop() {
+            dists[u] = Some(dist);
+            for &(v, weight) in &edges[u] {
+                match handles[v] {
+                    None => handles[v] = Some(heap.insert((dist + weight, v))),
+                    Some(handle) => {
+                        if let Some(&(old, _)) = heap.get(handle) {
+                            if dist + weight < old {
+                                heap.change_priority(handle, (dist + weight, v));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        dists
+    }
+
+    /// 以`std::collections::BinaryHeap`实现的Dijkstra算法，过时的条目在出队时跳过.
+    fn dijkstra_std(edges: &[Vec<(usize, i64)>], source: usize) -> Vec<Option<i64>> {
+        let mut heap = BinaryHeap::new();
+        let mut dists = vec![None; edges.len()];
+        heap.push(Reverse((0, source)));
+        while let Some(Reverse((dist, u))) = heap.pop() {
+            if dists[u].is_some() {
+                continue;
+            }
+            dists[u] = Some(dist);
+            for &(v, weight) in &edges[u] {
+                if dists[v].is_none() {
+                    heap.push(Reverse((dist + weight, v)));
+                }
+            }
+        }
+        dists
+    }
+
+    fn graph() -> impl Strategy<Value = Vec<Vec<(usize, i64)>>> {
+        (1..30usize).prop_flat_map(|n| {
+            prop::collection::vec(prop::collection::vec((0..n, 0..100i64), 0..6), n)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_dijkstra(edges in graph()) {
+            let expected = dijkstra_std(&edges, 0);
+            prop_assert_eq!(dijkstra::<IndexedMinHeap<_>>(&edges, 0), expected.clone());
+            prop_assert_eq!(dijkstra::<IndexedLeftHeap<_, MinFirst>>(&edges, 0), expected.clone());
+            prop_assert_eq!(dijkstra::<BinomialHeap<_, MinFirst>>(&edges, 0), expected.clone());
+            prop_assert_eq!(dijkstra::<PairingHeap<_, MinFirst>>(&edges, 0), expected.clone());
+            prop_assert_eq!(dijkstra::<FibonacciHeap<_, MinFirst>>(&edges, 0), expected);
+        }
+    }
+
+    const N: usize = 10000;
+    const M: usize = 8;
+    const SEEDS: [u64; 2] = [42, 69];
+
+    /// `N`个顶点、每个顶点`M`条出边的随机图.
+    fn random_graph() -> Vec<Vec<(usize, i64)>> {
+        let mut source = random::default().seed(SEEDS);
+        (0..N)
+            .map(|_| {
+                (0..M)
+                    .map(|_| {
+                        let v = source.read::<u64>() as usize % N;
+                        (v, (source.read::<u64>() % 1000) as i64)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn bench_dijkstra<Q: IndexedPriorityQueue<(i64, usize)> + Default>(b: &mut Bencher) {
+        let edges = random_graph();
+        b.iter(|| dijkstra::<Q>(&edges, 0))
+    }
+
+    #[bench]
+    fn bench_std_dijkstra(b: &mut Bencher) {
+        let edges = random_graph();
+        b.iter(|| dijkstra_std(&edges, 0))
+    }
+
+    #[bench]
+    fn bench_indexed_heap_dijkstra(b: &mut Bencher) {
+        bench_dijkstra::<IndexedMinHeap<_>>(b)
+    }
+
+    #[bench]
+    fn bench_indexed_left_heap_dijkstra(b: &mut Bencher) {
+        bench_dijkstra::<IndexedLeftHeap<_, MinFirst>>(b)
+    }
+
+    #[bench]
+    fn bench_binomial_heap_dijkstra(b: &mut Bencher) {
+        bench_dijkstra::<BinomialHeap<_, MinFirst>>(b)
+    }
+
+    #[bench]
+    fn bench_pairing_heap_dijkstra(b: &mut Bencher) {
+        bench_dijkstra::<PairingHeap<_, MinFirst>>(b)
+    }
+
+    #[bench]
+    fn bench_fibonacci_heap_dijkstra(b: &mut Bencher) {
+        bench_dijkstra::<FibonacciHeap<_, MinFirst>>(b)
+    }
 }
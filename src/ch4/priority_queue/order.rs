@@ -0,0 +1,47 @@
+use std::cmp::Ordering;
+
+/// 优先队列的顺序策略，决定元素出队的先后.
+pub trait Order<T> {
+    /// `lhs`的优先级是否严格低于`rhs`，即`rhs`应先于`lhs`出队.
+    fn is_lower(&self, lhs: &T, rhs: &T) -> bool;
+}
+
+/// 较大者优先(大顶堆).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxFirst;
+
+impl<T: PartialOrd> Order<T> for MaxFirst {
+    fn is_lower(&self, lhs: &T, rhs: &T) -> bool {
+        lhs < rhs
+    }
+}
+
+/// 较小者优先(小顶堆).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinFirst;
+
+impl<T: PartialOrd> Order<T> for MinFirst {
+    fn is_lower(&self, lhs: &T, rhs: &T) -> bool {
+        lhs > rhs
+    }
+}
+
+/// 按键函数的结果比较，键较大者优先. 需要键较小者优先时可以返回`std::cmp::Reverse`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByKey<F>(pub F);
+
+impl<T, K: PartialOrd, F: Fn(&T) -> K> Order<T> for ByKey<F> {
+    fn is_lower(&self, lhs: &T, rhs: &T) -> bool {
+        (self.0)(lhs) < (self.0)(rhs)
+    }
+}
+
+/// 按比较函数比较，比较结果为`Greater`者优先.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByCmp<F>(pub F);
+
+impl<T, F: Fn(&T, &T) -> Ordering> Order<T> for ByCmp<F> {
+    fn is_lower(&self, lhs: &T, rhs: &T) -> bool {
+        (self.0)(lhs, rhs) == Ordering::Less
+    }
+}
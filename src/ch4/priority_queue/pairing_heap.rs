@@ -0,0 +1,402 @@
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
+use super::{Handle, IndexedPriorityQueue, MaxFirst, Order, PriorityQueue};
+use crate::vec::MyVec;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    handle: Handle,
+    elem: T,
+    /// 最左的孩子.
+    child: Link<T>,
+    /// 右侧的兄弟.
+    sibling: Link<T>,
+    /// 左侧的兄弟. 最左的孩子则为父母，根为`None`.
+    prev: Link<T>,
+}
+
+/// 配对堆. 元素的先后由顺序策略`O`决定，默认为大顶堆.
+///
+/// 堆是一棵多叉树，以"最左孩子-右兄弟"表示. 插入与合并只比较两个根，为`O(1)`；
+/// 出队时把根的孩子两两配对合并，再自右向左合并为一棵树，摊还为`O(log n)`.
+/// 提升优先级时把子树剪下与根合并，降低优先级与删除则先把结点摘下.
+///
+/// 同时实现了`PriorityQueue`与`IndexedPriorityQueue`，两者的方法同名，在同时引入两个特质时需要写明特质.
+///
+/// # Safety
+/// 句柄表中的指针都指向由`Box`分配、且仍在堆中的结点. 结点只在`remove`与`Drop`中释放，释放前先从句柄表中移除.
+pub struct PairingHeap<T, O = MaxFirst> {
+    root: Link<T>,
+    /// 句柄所指的结点. 已删除元素的句柄为`None`.
+    nodes: Vec<Link<T>>,
+    len: usize,
+    order: O,
+    stats: Counter,
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+impl<T, O: Default> Default for PairingHeap<T, O> {
+    fn default() -> Self {
+        Self::with_order(O::default())
+    }
+}
+
+impl<T, O> Drop for PairingHeap<T, O> {
+    fn drop(&mut self) {
+        for node in self.nodes.drain(..).flatten() {
+            // Safety: 句柄表中的结点都是由`Box`分配的，且每个结点只出现一次.
+            unsafe {
+                drop(Box::from_raw(node.as_ptr()));
+            }
+        }
+    }
+}
+
+impl<T, O> PairingHeap<T, O> {
+    /// 创建一个使用给定顺序策略的空堆.
+    pub fn with_order(order: O) -> Self {
+        Self {
+            root: None,
+            nodes: Vec::new(),
+            len: 0,
+            order,
+            stats: Counter::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn node(&self, handle: Handle) -> Link<T> {
+        self.nodes.get(handle.0).copied().flatten()
+    }
+
+    /// 分配一个孤立结点并登记到句柄表中.
+    ///
+    /// 只借用句柄表，以便在计数守卫存活期间调用.
+    fn alloc(nodes: &mut Vec<Link<T>>, elem: T) -> (Handle, NonNull<Node<T>>) {
+        let handle = Handle(nodes.len());
+        let node = NonNull::from(Box::leak(Box::new(Node {
+            handle,
+            elem,
+            child: None,
+            sibling: None,
+            prev: None,
+        })));
+        stats::record(|stats| stats.allocations += 1);
+        nodes.push(Some(node));
+        (handle, node)
+    }
+
+    /// 把子树从它的父母与兄弟中剪下. 若它是根则堆变为空.
+    unsafe fn cut(root: &mut Link<T>, node: NonNull<Node<T>>) {
+        let raw = node.as_ptr();
+        match (*raw).prev {
+            None => *root = None,
+            Some(prev) => {
+                let prev = prev.as_ptr();
+                if (*prev).child == Some(node) {
+                    (*prev).child = (*raw).sibling;
+                } else {
+                    (*prev).sibling = (*raw).sibling;
+                }
+                if let Some(sibling) = (*raw).sibling {
+                    (*sibling.as_ptr()).prev = (*raw).prev;
+                }
+            }
+        }
+        (*raw).prev = None;
+        (*raw).sibling = None;
+    }
+}
+
+impl<T, O: Order<T>> PairingHeap<T, O> {
+    /// 合并两个根，优先级较低者成为另一个的最左孩子. 返回新的根.
+    unsafe fn link(lhs: NonNull<Node<T>>, rhs: NonNull<Node<T>>, order: &O) -> NonNull<Node<T>> {
+        stats::record(|stats| stats.comparisons += 1);
+        let (root, child) = if order.is_lower(&(*lhs.as_ptr()).elem, &(*rhs.as_ptr()).elem) {
+            (rhs, lhs)
+        } else {
+            (lhs, rhs)
+        };
+        let (raw, child_raw) = (root.as_ptr(), child.as_ptr());
+        if let Some(first) = (*raw).child {
+            (*first.as_ptr()).prev = Some(child);
+        }
+        (*child_raw).sibling = (*raw).child;
+        (*child_raw).prev = Some(root);
+        (*raw).child = Some(child);
+        root
+    }
+
+    unsafe fn meld(lhs: Link<T>, rhs: Link<T>, order: &O) -> Link<T> {
+        match (lhs, rhs) {
+            (None, link) | (link, None) => link,
+            (Some(lhs), Some(rhs)) => Some(Self::link(lhs, rhs, order)),
+        }
+    }
+
+    /// 两趟合并一串兄弟: 先自左向右两两配对，再自右向左逐个合并. 返回新的根.
+    unsafe fn combine_siblings(first: Link<T>, order: &O) -> Link<T> {
+        let mut pairs = Vec::new();
+        let mut current = first;
+        while let Some(lhs) = current {
+            let rhs = (*lhs.as_ptr()).sibling;
+            current = rhs.and_then(|rhs| (*rhs.as_ptr()).sibling);
+            (*lhs.as_ptr()).prev = None;
+            (*lhs.as_ptr()).sibling = None;
+            if let Some(rhs) = rhs {
+                (*rhs.as_ptr()).prev = None;
+                (*rhs.as_ptr()).sibling = None;
+            }
+            pairs.push(Self::meld(Some(lhs), rhs, order));
+        }
+        pairs
+            .into_iter()
+            .rev()
+            .fold(None, |root, pair| Self::meld(root, pair, order))
+    }
+
+    /// 把结点从堆中摘下，它的孩子合并后并入堆中. 结点成为孤立结点.
+    unsafe fn detach(root: &mut Link<T>, node: NonNull<Node<T>>, order: &O) {
+        Self::cut(root, node);
+        let children = Self::combine_siblings((*node.as_ptr()).child.take(), order);
+        *root = Self::meld(*root, children, order);
+    }
+
+    /// 检查长度、堆序、兄弟与父母指针以及句柄表. `position`为结点的先序次序.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let mut position = 0;
+        // (结点, 期望的`prev`, 父母)
+        let mut stack = self
+            .root
+            .map(|root| (root, None, None::<NonNull<Node<T>>>))
+            .into_iter()
+            .collect::<Vec<_>>();
+        // Safety: 只读访问堆中的结点.
+        unsafe {
+            while let Some((node, prev, parent)) = stack.pop() {
+                let raw = node.as_ptr();
+                let unordered = match parent {
+                    Some(parent) => self.order.is_lower(&(*parent.as_ptr()).elem, &(*raw).elem),
+                    None => false,
+                };
+                if unordered {
+                    violations.push(Violation::HeapOrder { position });
+                }
+                if (*raw).prev != prev {
+                    violations.push(Violation::ParentLink { position });
+                }
+                if self.node((*raw).handle) != Some(node) {
+                    violations.push(Violation::HandleLink { position });
+                }
+                position += 1;
+                if let Some(sibling) = (*raw).sibling {
+                    stack.push((sibling, Some(node), parent));
+                }
+                if let Some(child) = (*raw).child {
+                    stack.push((child, Some(node), Some(node)));
+                }
+            }
+        }
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        validate::into_validation(violations)
+    }
+}
+
+impl<T, O: Order<T>> IndexedPriorityQueue<T> for PairingHeap<T, O> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn insert(&mut self, elem: T) -> Handle {
+        let _stats = self.stats.enter();
+        let (handle, node) = Self::alloc(&mut self.nodes, elem);
+        // Safety: 新结点是孤立的.
+        self.root = unsafe { Self::meld(self.root, Some(node), &self.order) };
+        self.len += 1;
+        handle
+    }
+
+    fn peek(&self) -> Option<(Handle, &T)> {
+        // Safety: 根在堆中，借用期间堆不会被修改.
+        self.root.map(|root| unsafe {
+            let root = &*root.as_ptr();
+            (root.handle, &root.elem)
+        })
+    }
+
+    fn pop(&mut self) -> Option<(Handle, T)> {
+        // Safety: 根在堆中.
+        let handle = unsafe { (*self.root?.as_ptr()).handle };
+        IndexedPriorityQueue::remove(self, handle).map(|elem| (handle, elem))
+    }
+
+    fn get(&self, handle: Handle) -> Option<&T> {
+        // Safety: 句柄表中的结点在堆中，借用期间堆不会被修改.
+        self.node(handle)
+            .map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    fn change_priority(&mut self, handle: Handle, elem: T) -> Option<T> {
+        let _stats = self.stats.enter();
+        let node = self.node(handle)?;
+        let raw = node.as_ptr();
+        // Safety: 结点在堆中. 剪下或摘下后再合并回堆中.
+        unsafe {
+            stats::record(|stats| stats.comparisons += 1);
+            if self.order.is_lower(&elem, &(*raw).elem) {
+                Self::detach(&mut self.root, node, &self.order);
+            } else {
+                // 优先级不降，子树的堆序依然成立.
+                Self::cut(&mut self.root, node);
+            }
+            let old = mem::replace(&mut (*raw).elem, elem);
+            self.root = Self::meld(self.root, Some(node), &self.order);
+            Some(old)
+        }
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<T> {
+        let _stats = self.stats.enter();
+        let node = self.nodes.get_mut(handle.0)?.take()?;
+        self.len -= 1;
+        // Safety: 结点在堆中，摘下并从句柄表中移除后不再有指向它的指针.
+        unsafe {
+            Self::detach(&mut self.root, node, &self.order);
+            Some(Box::from_raw(node.as_ptr()).elem)
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+impl<T, O: Order<T> + Default> From<MyVec<T>> for PairingHeap<T, O> {
+    fn from(mut vec: MyVec<T>) -> Self {
+        let mut heap = Self::default();
+        while let Some(elem) = vec.pop() {
+            PriorityQueue::insert(&mut heap, elem);
+        }
+        heap
+    }
+}
+
+impl<T, O: Order<T>> PriorityQueue<T> for PairingHeap<T, O> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        IndexedPriorityQueue::pop(self).map(|(_, elem)| elem)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        IndexedPriorityQueue::peek(self).map(|(_, elem)| elem)
+    }
+
+    fn insert(&mut self, elem: T) {
+        IndexedPriorityQueue::insert(self, elem);
+    }
+
+    /// 合并两个配对堆. 比较一次两个根即可，但`other`的句柄失效:
+    /// 它的元素按原来的编号顺序接在`self`的句柄之后，因此句柄表的拼接为`O(m)`.
+    /// `other`的顺序策略被丢弃，合并后沿用`self`的.
+    fn merge(&mut self, mut other: Self) {
+        let _stats = self.stats.enter();
+        let offset = self.nodes.len();
+        for (index, node) in other.nodes.drain(..).enumerate() {
+            if let Some(node) = node {
+                // Safety: 结点在`other`中，此后归`self`所有.
+                unsafe { (*node.as_ptr()).handle = Handle(offset + index) };
+            }
+            self.nodes.push(node);
+        }
+        // Safety: 两个根分属不同的堆.
+        self.root = unsafe { Self::meld(self.root, other.root.take(), &self.order) };
+        self.len += mem::take(&mut other.len);
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{check_indexed, check_queue, op};
+    use super::super::MinFirst;
+    use super::*;
+    use proptest::prelude::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_drop() {
+        let counter = Rc::new(());
+        let mut heap = PairingHeap::<_>::default();
+        let handles = (0..8)
+            .map(|elem| IndexedPriorityQueue::insert(&mut heap, (elem, Rc::clone(&counter))))
+            .collect::<Vec<_>>();
+        IndexedPriorityQueue::pop(&mut heap);
+        IndexedPriorityQueue::remove(&mut heap, handles[3]);
+        heap.change_priority(handles[5], (-1, Rc::clone(&counter)));
+        assert_eq!(Rc::strong_count(&counter), 7);
+        drop(heap);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn test_merge_handles() {
+        let mut heap = PairingHeap::<_>::default();
+        IndexedPriorityQueue::insert(&mut heap, 1);
+        let mut other = PairingHeap::default();
+        let handles = (2..5)
+            .map(|elem| IndexedPriorityQueue::insert(&mut other, elem))
+            .collect::<Vec<_>>();
+        IndexedPriorityQueue::remove(&mut other, handles[0]);
+        PriorityQueue::merge(&mut heap, other);
+        assert_eq!(heap.validate(), Ok(()));
+        assert_eq!(heap.get(Handle(1)), None);
+        assert_eq!(heap.get(Handle(2)), Some(&3));
+        assert_eq!(IndexedPriorityQueue::pop(&mut heap), Some((Handle(3), 4)));
+    }
+
+    proptest! {
+        #[test]
+        fn test_pairing_heap(data1: Vec<i64>, data2: Vec<i64>) {
+            check_queue::<PairingHeap<_>, _>(data1.clone(), data2.clone(), MaxFirst, PairingHeap::validate)?;
+            check_queue::<PairingHeap<_, MinFirst>, _>(data1, data2, MinFirst, PairingHeap::validate)?;
+        }
+
+        #[test]
+        fn test_indexed_pairing_heap(ops in prop::collection::vec(op(), 0..200)) {
+            check_indexed(PairingHeap::<_>::default(), ops.clone(), MaxFirst, PairingHeap::validate)?;
+            check_indexed(PairingHeap::<_, MinFirst>::default(), ops, MinFirst, PairingHeap::validate)?;
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = PairingHeap::<_>::default();
+        for elem in (0..4).rev() {
+            PriorityQueue::insert(&mut heap, elem);
+        }
+        // 插入只与根比较，根有3个孩子.
+        assert_eq!(PriorityQueue::stats(&heap).comparisons, 3);
+        // 根的3个孩子两两配对后再合并.
+        PriorityQueue::pop(&mut heap);
+        let stats = PriorityQueue::stats(&heap);
+        assert_eq!(stats.allocations, 4);
+        assert_eq!(stats.comparisons, 5);
+    }
+}
@@ -0,0 +1,231 @@
+use super::super::stats::{self, Counter, Stats};
+use super::super::validate::{self, Validation, Violation};
+use super::{MaxFirst, Order, PriorityQueue};
+use crate::vec::MyVec;
+use std::mem::swap;
+
+type Tree<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    left: Tree<T>,
+    right: Tree<T>,
+}
+
+/// 斜堆. 元素的先后由顺序策略`O`决定，默认为大顶堆.
+///
+/// 左式堆的自调整版本: 不记录NPL，合并时沿右侧链下行，并无条件地交换途经结点的左右子树.
+/// 单次操作最坏为`O(n)`，摊还为`O(log n)`. 由于树高可能达到`O(n)`，合并与析构都不使用递归.
+pub struct SkewHeap<T, O = MaxFirst> {
+    root: Tree<T>,
+    len: usize,
+    order: O,
+    stats: Counter,
+}
+
+impl<T, O: Default> Default for SkewHeap<T, O> {
+    fn default() -> Self {
+        Self::with_order(O::default())
+    }
+}
+
+impl<T, O> Drop for SkewHeap<T, O> {
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<T, O> SkewHeap<T, O> {
+    /// 创建一个使用给定顺序策略的空堆.
+    pub fn with_order(order: O) -> Self {
+        Self {
+            root: None,
+            len: 0,
+            order,
+            stats: Counter::default(),
+        }
+    }
+}
+
+impl<T, O: Order<T>> SkewHeap<T, O> {
+    /// 自顶向下地合并两棵树，返回新的根.
+    ///
+    /// 每一步取两根中优先级较高者作为当前结点，它原来的左子树换到右侧，
+    /// 右子树则与另一棵树继续合并，结果接为左子树.
+    fn merge_tree(mut lhs: Tree<T>, mut rhs: Tree<T>, order: &O) -> Tree<T> {
+        let mut root = None;
+        let mut tail = &mut root;
+        loop {
+            match (lhs, rhs) {
+                (None, tree) | (tree, None) => {
+                    *tail = tree;
+                    return root;
+                }
+                (Some(mut top), Some(mut other)) => {
+                    stats::record(|stats| stats.comparisons += 1);
+                    if order.is_lower(&top.elem, &other.elem) {
+                        swap(&mut top, &mut other);
+                    }
+                    stats::record(|stats| stats.swaps += 1);
+                    lhs = top.right.take();
+                    rhs = Some(other);
+                    top.right = top.left.take();
+                    tail = &mut tail.insert(top).left;
+                }
+            }
+        }
+    }
+
+    /// 把另一棵树合并进来. 长度由调用者维护.
+    fn merge_root(&mut self, other: Tree<T>) {
+        let _stats = self.stats.enter();
+        self.root = Self::merge_tree(self.root.take(), other, &self.order);
+    }
+
+    /// 检查长度与堆序.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        // 中序遍历.
+        let mut stack = Vec::new();
+        let mut current = self.root.as_deref();
+        let mut position = 0;
+        loop {
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_deref();
+            }
+            let node = match stack.pop() {
+                Some(node) => node,
+                None => break,
+            };
+            if [&node.left, &node.right]
+                .iter()
+                .filter_map(|child| child.as_deref())
+                .any(|child| self.order.is_lower(&node.elem, &child.elem))
+            {
+                violations.push(Violation::HeapOrder { position });
+            }
+            position += 1;
+            current = node.right.as_deref();
+        }
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        validate::into_validation(violations)
+    }
+}
+
+impl<T, O: Order<T> + Default> From<MyVec<T>> for SkewHeap<T, O> {
+    fn from(mut vec: MyVec<T>) -> Self {
+        let mut heap = Self::default();
+        while let Some(elem) = vec.pop() {
+            heap.insert(elem);
+        }
+        heap
+    }
+}
+
+impl<T, O: Order<T>> PriorityQueue<T> for SkewHeap<T, O> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let mut root = self.root.take()?;
+        self.len -= 1;
+        let (left, right) = (root.left.take(), root.right.take());
+        self.root = left;
+        self.merge_root(right);
+        Some(root.elem)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|root| &root.elem)
+    }
+
+    fn insert(&mut self, elem: T) {
+        self.stats.record(|stats| stats.allocations += 1);
+        self.merge_root(Some(Box::new(Node {
+            elem,
+            left: None,
+            right: None,
+        })));
+        self.len += 1;
+    }
+
+    /// 合并两个斜堆. `other`的顺序策略被丢弃，合并后沿用`self`的.
+    fn merge(&mut self, mut other: Self) {
+        self.merge_root(other.root.take());
+        self.len += other.len;
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::check_queue;
+    use super::super::MinFirst;
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_validate() {
+        let mut heap = SkewHeap::<_>::from(MyVec::from(vec![1, 2, 3]));
+        assert_eq!(heap.validate(), Ok(()));
+        heap.root.as_mut().unwrap().elem = 0;
+        heap.len = 4;
+        assert!(matches!(
+            heap.validate().unwrap_err()[..],
+            [
+                Violation::HeapOrder { .. },
+                Violation::Length {
+                    recorded: 4,
+                    actual: 3
+                }
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_deep() {
+        // 依次插入递增的元素，每个新元素都成为根，原堆成为它的左子树，树高为`n`.
+        let mut heap = SkewHeap::<_>::default();
+        for elem in 0..100_000 {
+            heap.insert(elem);
+        }
+        assert_eq!(heap.peek(), Some(&99_999));
+        heap.merge(SkewHeap::from(MyVec::from(vec![-1])));
+        assert_eq!(heap.len(), 100_001);
+    }
+
+    proptest! {
+        #[test]
+        fn test_skew_heap(data1: Vec<i64>, data2: Vec<i64>) {
+            check_queue::<SkewHeap<_>, _>(data1.clone(), data2.clone(), MaxFirst, SkewHeap::validate)?;
+            check_queue::<SkewHeap<_, MinFirst>, _>(data1, data2, MinFirst, SkewHeap::validate)?;
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut heap = SkewHeap::<_>::default();
+        for elem in 0..4 {
+            heap.insert(elem);
+        }
+        let stats = heap.stats();
+        assert_eq!(stats.allocations, 4);
+        assert_eq!(stats.comparisons, 3);
+        assert_eq!(stats.swaps, 3);
+    }
+}
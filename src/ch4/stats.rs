@@ -0,0 +1,257 @@
+//! 操作计数.
+//!
+//! 启用`stats`特性后，各字典与优先队列会统计比较、旋转、结点分配等操作的次数，
+//! 并可通过`Map::stats`与`PriorityQueue::stats`读取. 未启用时计数器不占空间，计数均为空操作.
+//!
+//! 计数发生在结构内部的辅助函数中，它们大多不持有结构本身.
+//! 因此每次操作开始时由`Counter::enter`登记当前的计数器，其间的`record`都记在该计数器上.
+//! 计数器由原子整数构成，启用特性不会改变各结构的`Send`与`Sync`.
+
+#[cfg(feature = "stats")]
+use std::cell::Cell;
+#[cfg(not(feature = "stats"))]
+use std::marker::PhantomData;
+use std::ops::AddAssign;
+#[cfg(feature = "stats")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 操作次数.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    /// 键(或元素)的比较次数.
+    pub comparisons: usize,
+    /// 旋转次数. AVL树的3 + 4重构按单旋计一次、双旋计两次.
+    pub rotations: usize,
+    /// 伸展次数，`zig`、`zig-zig`与`zig-zag`各计一次.
+    pub splay_steps: usize,
+    /// 分配的结点数.
+    pub allocations: usize,
+    /// B树结点的分裂次数.
+    pub splits: usize,
+    /// B树结点的合并次数.
+    pub merges: usize,
+    /// 堆中上滤与下滤的交换次数(二项堆为交换结点内容)，左式堆与斜堆交换左右子树的次数，
+    /// 以及Robin Hood散列与布谷鸟散列中词条被挤出原位的次数.
+    pub swaps: usize,
+}
+
+impl AddAssign for Stats {
+    fn add_assign(&mut self, other: Self) {
+        self.comparisons += other.comparisons;
+        self.rotations += other.rotations;
+        self.splay_steps += other.splay_steps;
+        self.allocations += other.allocations;
+        self.splits += other.splits;
+        self.merges += other.merges;
+        self.swaps += other.swaps;
+    }
+}
+
+#[cfg(feature = "stats")]
+thread_local! {
+    /// 当前操作累计的次数. 不在任何操作中时为`None`.
+    static CURRENT: Cell<Option<Stats>> = Cell::new(None);
+}
+
+/// `Stats`的各项次数，每项都是一个原子整数.
+///
+/// 计数只需保证各项自身不丢失，因此都使用`Relaxed`序.
+#[cfg(feature = "stats")]
+#[derive(Debug, Default)]
+struct AtomicStats {
+    comparisons: AtomicUsize,
+    rotations: AtomicUsize,
+    splay_steps: AtomicUsize,
+    allocations: AtomicUsize,
+    splits: AtomicUsize,
+    merges: AtomicUsize,
+    swaps: AtomicUsize,
+}
+
+#[cfg(feature = "stats")]
+impl AtomicStats {
+    fn fields(&self) -> [&AtomicUsize; 7] {
+        [
+            &self.comparisons,
+            &self.rotations,
+            &self.splay_steps,
+            &self.allocations,
+            &self.splits,
+            &self.merges,
+            &self.swaps,
+        ]
+    }
+
+    fn load(&self) -> Stats {
+        let [comparisons, rotations, splay_steps, allocations, splits, merges, swaps] =
+            self.fields();
+        Stats {
+            comparisons: comparisons.load(Ordering::Relaxed),
+            rotations: rotations.load(Ordering::Relaxed),
+            splay_steps: splay_steps.load(Ordering::Relaxed),
+            allocations: allocations.load(Ordering::Relaxed),
+            splits: splits.load(Ordering::Relaxed),
+            merges: merges.load(Ordering::Relaxed),
+            swaps: swaps.load(Ordering::Relaxed),
+        }
+    }
+
+    fn add(&self, stats: Stats) {
+        let values = [
+            stats.comparisons,
+            stats.rotations,
+            stats.splay_steps,
+            stats.allocations,
+            stats.splits,
+            stats.merges,
+            stats.swaps,
+        ];
+        for (field, value) in self.fields().iter().zip(values.iter()) {
+            if *value > 0 {
+                field.fetch_add(*value, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn clear(&self) {
+        for field in self.fields().iter() {
+            field.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 操作计数器.
+#[derive(Debug, Default)]
+pub struct Counter {
+    #[cfg(feature = "stats")]
+    stats: AtomicStats,
+}
+
+impl Counter {
+    /// 返回累计的操作次数. 未启用`stats`特性时总是返回全零.
+    pub fn get(&self) -> Stats {
+        #[cfg(feature = "stats")]
+        {
+            self.stats.load()
+        }
+        #[cfg(not(feature = "stats"))]
+        {
+            Stats::default()
+        }
+    }
+
+    /// 清零.
+    pub fn reset(&self) {
+        #[cfg(feature = "stats")]
+        self.stats.clear();
+    }
+
+    /// 直接在该计数器上计数，用于不在操作之中的计数. `f`只能累加次数.
+    pub(crate) fn record<F: FnOnce(&mut Stats)>(&self, f: F) {
+        #[cfg(feature = "stats")]
+        {
+            let mut stats = Stats::default();
+            f(&mut stats);
+            self.stats.add(stats);
+        }
+        #[cfg(not(feature = "stats"))]
+        let _ = f;
+    }
+
+    /// 开始一次操作. 守卫存活期间`record`的计数都记在该计数器上，可以嵌套.
+    pub(crate) fn enter(&self) -> Guard<'_> {
+        #[cfg(feature = "stats")]
+        {
+            Guard {
+                counter: self,
+                outer: CURRENT.with(|current| current.replace(Some(Stats::default()))),
+            }
+        }
+        #[cfg(not(feature = "stats"))]
+        {
+            Guard {
+                marker: PhantomData,
+            }
+        }
+    }
+}
+
+/// 见`Counter::enter`.
+pub(crate) struct Guard<'a> {
+    #[cfg(feature = "stats")]
+    counter: &'a Counter,
+    #[cfg(feature = "stats")]
+    outer: Option<Stats>,
+    #[cfg(not(feature = "stats"))]
+    marker: PhantomData<&'a Counter>,
+}
+
+#[cfg(feature = "stats")]
+impl<'a> Drop for Guard<'a> {
+    fn drop(&mut self) {
+        let current = CURRENT.with(|current| current.replace(self.outer));
+        self.counter.stats.add(current.unwrap_or_default());
+    }
+}
+
+/// 在当前操作上计数. 不在任何操作中时为空操作.
+#[inline]
+pub(crate) fn record<F: FnOnce(&mut Stats)>(f: F) {
+    #[cfg(feature = "stats")]
+    CURRENT.with(|current| {
+        if let Some(mut stats) = current.get() {
+            f(&mut stats);
+            current.set(Some(stats));
+        }
+    });
+    #[cfg(not(feature = "stats"))]
+    let _ = f;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_nested() {
+        let (outer, inner) = (Counter::default(), Counter::default());
+        {
+            let _outer = outer.enter();
+            record(|stats| stats.comparisons += 1);
+            {
+                let _inner = inner.enter();
+                record(|stats| stats.rotations += 2);
+            }
+            record(|stats| stats.comparisons += 1);
+        }
+        record(|stats| stats.swaps += 1);
+        assert_eq!(outer.get().comparisons, 2);
+        assert_eq!(outer.get().rotations, 0);
+        assert_eq!(inner.get().rotations, 2);
+        outer.reset();
+        assert_eq!(outer.get(), Stats::default());
+    }
+
+    #[test]
+    fn test_auto_traits() {
+        use crate::ch4::binary_search_tree::bst::TreeMap;
+        use crate::ch4::vec_binary_tree::VecBinaryTree;
+
+        fn check<T: Send + Sync>() {}
+        check::<Counter>();
+        check::<TreeMap<VecBinaryTree<_>, i32, i32>>();
+    }
+
+    #[cfg(not(feature = "stats"))]
+    #[test]
+    fn test_disabled() {
+        let counter = Counter::default();
+        {
+            let _guard = counter.enter();
+            record(|stats| stats.comparisons += 1);
+        }
+        counter.record(|stats| stats.allocations += 1);
+        assert_eq!(counter.get(), Stats::default());
+    }
+}
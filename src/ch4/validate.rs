@@ -0,0 +1,183 @@
+use thiserror::Error;
+
+/// 数据结构的不变式被违反之处.
+///
+/// `position`为结点在中序遍历中的次序(完全堆中为数组下标)，
+/// B树结点的`position`为其第一个词条的次序，字典树结点的`position`为先序遍历中位于它之前的词条数，
+/// 散列表中为词条(或墓碑)之前按迭代顺序的词条数.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// 记录的长度与实际的词条数不符.
+    #[error("length is recorded as {recorded} but {actual} entries are found.")]
+    Length { recorded: usize, actual: usize },
+
+    /// 键没有严格大于它的中序前驱(二叉查找树中为没有严格位于左右子树的键之间).
+    #[error("entry {position} is out of order.")]
+    Unordered { position: usize },
+
+    /// 记录的子树规模与实际不符.
+    #[error("node {position} records size {recorded} but its subtree has {actual} nodes.")]
+    Size {
+        position: usize,
+        recorded: usize,
+        actual: usize,
+    },
+
+    /// 记录的高度与实际不符.
+    #[error("node {position} records height {recorded} but its height is {actual}.")]
+    Height {
+        position: usize,
+        recorded: isize,
+        actual: isize,
+    },
+
+    /// AVL树结点的平衡因子不在`[-1, 1]`内.
+    #[error("node {position} has balance factor {factor}.")]
+    BalanceFactor { position: usize, factor: isize },
+
+    /// 红黑树的根为红色.
+    #[error("the root is red.")]
+    RedRoot,
+
+    /// 红结点有红孩子.
+    #[error("red node {position} has a red child.")]
+    DoubleRed { position: usize },
+
+    /// 左右子树的黑高度不等.
+    #[error("subtrees of node {position} have black heights {left} and {right}.")]
+    BlackHeight {
+        position: usize,
+        left: usize,
+        right: usize,
+    },
+
+    /// 权平衡树结点的左右子树规模失衡.
+    #[error("subtrees of node {position} have unbalanced sizes {left} and {right}.")]
+    WeightBalance {
+        position: usize,
+        left: usize,
+        right: usize,
+    },
+
+    /// 替罪羊树最深结点的深度超过了上限.
+    #[error("the deepest node is at depth {depth}, expected at most {max}.")]
+    Depth { depth: usize, max: usize },
+
+    /// 左倾红黑树中出现了红右链.
+    #[error("node {position} has a red right child.")]
+    RightLeaning { position: usize },
+
+    /// B树结点的词条数(B+树中为叶子的词条数或内部结点的孩子数)超出了`[min, max]`.
+    #[error("node {position} holds {entrys} entries, expected {min} to {max}.")]
+    Occupancy {
+        position: usize,
+        entrys: usize,
+        min: usize,
+        max: usize,
+    },
+
+    /// B树内部结点的孩子数不等于词条数加一.
+    #[error("node {position} has {childrens} children for {entrys} entries.")]
+    Childrens {
+        position: usize,
+        entrys: usize,
+        childrens: usize,
+    },
+
+    /// B树的叶子不在同一深度.
+    #[error("leaf {position} is at depth {depth}, expected {expected}.")]
+    LeafDepth {
+        position: usize,
+        depth: usize,
+        expected: usize,
+    },
+
+    /// 结点的父母指针没有指向其父母.
+    #[error("node {position} has a wrong parent link.")]
+    ParentLink { position: usize },
+
+    /// B+树的叶子链或跳表的链接没有指向相邻的结点. 跳表中某一层的链接出错时，`position`为链接起点之后的词条.
+    #[error("node {position} is not linked to its neighbours.")]
+    SiblingLink { position: usize },
+
+    /// 跳表中第`level`层上、从第`position`个词条之前出发的链接记录的跨度与实际不符.
+    #[error("link before node {position} on level {level} records span {recorded} but spans {actual} nodes.")]
+    Span {
+        position: usize,
+        level: usize,
+        recorded: usize,
+        actual: usize,
+    },
+
+    /// 跳表记录的层数与最高结点的层数不符.
+    #[error("level is recorded as {recorded} but the highest node has {actual} levels.")]
+    Level { recorded: usize, actual: usize },
+
+    /// 记录的NPL与实际不符.
+    #[error("node {position} records npl {recorded} but its npl is {actual}.")]
+    Npl {
+        position: usize,
+        recorded: usize,
+        actual: usize,
+    },
+
+    /// 左式堆结点的左孩子的NPL小于右孩子.
+    #[error("node {position} has a left child with a smaller npl than its right child.")]
+    Leftist { position: usize },
+
+    /// (大顶)堆的结点小于它的孩子，或树堆结点的优先级小于它的孩子.
+    #[error("node {position} is less than one of its children.")]
+    HeapOrder { position: usize },
+
+    /// 二项堆或斐波那契堆的结点记录的度数与它的孩子不符，或二项堆的根不在与其度数对应的位置.
+    #[error("node {position} has a wrong degree {degree}.")]
+    Degree { position: usize, degree: usize },
+
+    /// 句柄表中记录的位置与结点实际所在之处不符.
+    #[error("node {position} is not where its handle points to.")]
+    HandleLink { position: usize },
+
+    /// 页式B树的结点所在的页面越界、在空闲链表中，或被多个结点共用.
+    #[error("node {position} is stored in invalid page {page}.")]
+    Page { position: usize, page: u64 },
+
+    /// 字典树或基数树中词条的键与从根到它所在结点的路径不符.
+    #[error("entry {position} is not stored under its key.")]
+    Path { position: usize },
+
+    /// 字典树中不含任何键的非根结点，或基数树中标签为空的边、可以与唯一的孩子合并的空结点.
+    #[error("node {position} should have been pruned or merged.")]
+    Redundant { position: usize },
+
+    /// 散列表的词条记录的散列值与重新计算的结果不符.
+    #[error("entry {position} records a stale hash.")]
+    Hash { position: usize },
+
+    /// 散列表的词条不在按散列值能找到它的位置: 独立链法中不在散列值对应的链上，布谷鸟散列中不在两个候选槽之一，
+    /// Robin Hood散列中试探距离比前一个槽多出`1`以上.
+    #[error("entry {position} is not where its hash leads.")]
+    Misplaced { position: usize },
+
+    /// 散列表的装填因子超出了上限. 开放定址时`len`包括墓碑.
+    #[error("{len} entries in {slots} slots exceed the load factor.")]
+    LoadFactor { len: usize, slots: usize },
+
+    /// 记录的墓碑数与实际不符.
+    #[error("tombstones are recorded as {recorded} but {actual} are found.")]
+    Tombstones { recorded: usize, actual: usize },
+
+    /// 结点记录的附加信息(如区间树的最大终点)与由子树重新计算的结果不符.
+    #[error("node {position} records a stale augment.")]
+    Augment { position: usize },
+}
+
+/// 检查结果. 若不变式都成立则为`Ok(())`，否则返回所有违反之处.
+pub type Validation = Result<(), Vec<Violation>>;
+
+pub(crate) fn into_validation(violations: Vec<Violation>) -> Validation {
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
@@ -0,0 +1,308 @@
+use super::{make_hash, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+/// 初始桶数.
+const MIN_BUCKETS: usize = 8;
+
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+type Chain<K, V> = Vec<Bucket<K, V>>;
+
+/// 独立链法(Separate Chaining)散列表.
+///
+/// 每个桶是一条链，散列到同一个桶的键都放在这条链上. 装填因子超过`1`时桶数加倍，并重新散列.
+pub struct ChainedHashMap<K: Hash + Eq, V, S: BuildHasher = RandomState> {
+    chains: Vec<Chain<K, V>>,
+    len: usize,
+    hasher: S,
+    stats: Counter,
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> Default for ChainedHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V> ChainedHashMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> ChainedHashMap<K, V, S> {
+    /// 用给定的散列函数创建.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    /// 创建至少能容纳`capacity`个键而无需重新散列的散列表.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let mut chains = Vec::new();
+        chains.resize_with(capacity.next_power_of_two().max(MIN_BUCKETS), Vec::new);
+        Self {
+            chains,
+            len: 0,
+            hasher,
+            stats: Counter::default(),
+        }
+    }
+
+    /// 桶数.
+    pub fn buckets(&self) -> usize {
+        self.chains.len()
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    fn chain_of(&self, hash: u64) -> usize {
+        hash as usize & (self.chains.len() - 1)
+    }
+
+    /// 查找键，返回`(桶, 链中的下标)`.
+    fn find(&self, key: &K) -> Option<(usize, usize)> {
+        let hash = make_hash(&self.hasher, key);
+        let chain = self.chain_of(hash);
+        self.chains[chain]
+            .iter()
+            .position(|bucket| {
+                stats::record(|stats| stats.comparisons += 1);
+                bucket.hash == hash && bucket.key == *key
+            })
+            .map(|idx| (chain, idx))
+    }
+
+    /// 重新散列到`buckets`个桶中.
+    fn resize(&mut self, buckets: usize) {
+        let mut chains = Vec::new();
+        chains.resize_with(buckets, Vec::new);
+        for bucket in mem::replace(&mut self.chains, chains).into_iter().flatten() {
+            let chain = self.chain_of(bucket.hash);
+            self.chains[chain].push(bucket);
+        }
+    }
+
+    /// 插入一个新键，返回它的位置.
+    fn insert_new(&mut self, hash: u64, key: K, value: V) -> (usize, usize) {
+        if self.len >= self.chains.len() {
+            self.resize(self.chains.len() * 2);
+        }
+        let chain = self.chain_of(hash);
+        self.chains[chain].push(Bucket { hash, key, value });
+        self.len += 1;
+        (chain, self.chains[chain].len() - 1)
+    }
+
+    /// 检查每个词条记录的散列值与所在的链、长度，以及装填因子不超过`1`.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let mut position = 0;
+        for (chain, buckets) in self.chains.iter().enumerate() {
+            for bucket in buckets.iter() {
+                if make_hash(&self.hasher, &bucket.key) != bucket.hash {
+                    violations.push(Violation::Hash { position });
+                }
+                if self.chain_of(bucket.hash) != chain {
+                    violations.push(Violation::Misplaced { position });
+                }
+                position += 1;
+            }
+        }
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        if !self.chains.len().is_power_of_two() || position > self.chains.len() {
+            violations.push(Violation::LoadFactor {
+                len: position,
+                slots: self.chains.len(),
+            });
+        }
+        validate::into_validation(violations)
+    }
+
+    /// 以任意顺序迭代所有键值对.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            chains: self.chains.iter(),
+            chain: [].iter(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> UnorderedMap<K, V> for ChainedHashMap<K, V, S> {
+    fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
+        let (chain, idx) = self.find(key)?;
+        Some(&self.chains[chain][idx].value)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let _stats = self.stats.enter();
+        let (chain, idx) = self.find(key)?;
+        Some(&mut self.chains[chain][idx].value)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let found = {
+            let _stats = self.stats.enter();
+            self.find(&key)
+        };
+        match found {
+            Some((chain, idx)) => Some(mem::replace(&mut self.chains[chain][idx].value, value)),
+            None => {
+                let hash = make_hash(&self.hasher, &key);
+                self.insert_new(hash, key, value);
+                None
+            }
+        }
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let found = {
+            let _stats = self.stats.enter();
+            self.find(&key)
+        };
+        let (chain, idx) = match found {
+            Some(posi) => posi,
+            None => {
+                let hash = make_hash(&self.hasher, &key);
+                self.insert_new(hash, key, default)
+            }
+        };
+        &mut self.chains[chain][idx].value
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.stats.enter();
+        let (chain, idx) = self.find(key)?;
+        self.len -= 1;
+        Some(self.chains[chain].swap_remove(idx).value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(ChainedHashMap::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+/// 独立链法散列表的迭代器.
+pub struct Iter<'a, K, V> {
+    chains: std::slice::Iter<'a, Chain<K, V>>,
+    chain: std::slice::Iter<'a, Bucket<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(bucket) = self.chain.next() {
+                self.len -= 1;
+                return Some((&bucket.key, &bucket.value));
+            }
+            self.chain = self.chains.next()?.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a ChainedHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::WeakHasher;
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+    use std::hash::BuildHasherDefault;
+
+    #[test]
+    fn test_resize() {
+        let mut map = ChainedHashMap::new();
+        for key in 0..1000 {
+            map.insert(key, key);
+        }
+        assert_eq!(map.validate(), Ok(()));
+        assert_eq!(map.buckets(), 1024);
+        for key in 0..1000 {
+            assert_eq!(map.remove(&key), Some(key));
+        }
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut map = ChainedHashMap::new();
+        for key in 0..4 {
+            map.insert(key, key);
+        }
+        assert_eq!(map.validate(), Ok(()));
+        let bucket = map.chains.iter_mut().flatten().next().unwrap();
+        bucket.hash ^= 1;
+        assert_eq!(
+            map.validate(),
+            Err(vec![
+                Violation::Hash { position: 0 },
+                Violation::Misplaced { position: 0 }
+            ])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_chained_hash_map(entrys: Vec<(u8, u32)>, removes: Vec<u8>) {
+            let mut map = ChainedHashMap::<_, _, BuildHasherDefault<WeakHasher>>::default();
+            let mut expected = HashMap::new();
+            for (key, value) in entrys.iter() {
+                prop_assert_eq!(map.insert(*key, *value), expected.insert(*key, *value));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for key in removes.iter() {
+                prop_assert_eq!(map.remove(key), expected.remove(key));
+                prop_assert_eq!(map.validate(), Ok(()));
+                *map.get_mut_or_insert(key.wrapping_add(1), 0) += 1;
+                *expected.entry(key.wrapping_add(1)).or_insert(0) += 1;
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            prop_assert_eq!(map.len(), expected.len());
+            prop_assert_eq!(map.iter().map(|(key, value)| (*key, *value)).collect::<HashMap<_, _>>(), expected.clone());
+            for (key, value) in expected.iter() {
+                prop_assert_eq!(map.get(key), Some(value));
+            }
+        }
+    }
+}
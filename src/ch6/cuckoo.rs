@@ -0,0 +1,409 @@
+use super::UnorderedMap;
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::mem;
+
+/// 每张表的初始槽数.
+const MIN_SLOTS: usize = 4;
+
+/// 同一容量下重新散列的次数达到该值时，改为扩容.
+const MAX_REHASHES: usize = 4;
+
+struct Bucket<K, V> {
+    key: K,
+    value: V,
+}
+
+/// 槽的位置: `(表, 下标)`.
+type Posi = (usize, usize);
+
+/// 插入失败时无处安放的词条，以及新词条所在的位置(若新词条不是无处安放的那个).
+type Homeless<K, V> = (Bucket<K, V>, Option<Posi>);
+
+/// 布谷鸟散列表(Cuckoo Hashing).
+///
+/// 使用两张表与两个散列函数，每个键只可能位于两张表中各自的一个槽里，因此查找与删除在最坏情况下也是`O(1)`的.
+/// 插入时若两个槽都被占用，则把其中的词条"踢"到它在另一张表中的槽里，如此往复.
+/// 踢出次数超过上限时，更换散列函数重新散列. 两个散列函数是通过在键之前写入不同的种子，由同一个`BuildHasher`得到的，
+/// 因此要求散列函数的结果依赖于写入的所有字节.
+pub struct CuckooHashMap<K: Hash + Eq, V, S: BuildHasher = RandomState> {
+    tables: [Vec<Option<Bucket<K, V>>>; 2],
+    seeds: [u64; 2],
+    len: usize,
+    /// 当前容量下重新散列的次数.
+    rehashes: usize,
+    hasher: S,
+    stats: Counter,
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> Default for CuckooHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V> CuckooHashMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn empty_table<K, V>(slots: usize) -> Vec<Option<Bucket<K, V>>> {
+    let mut table = Vec::new();
+    table.resize_with(slots, || None);
+    table
+}
+
+/// 由旧种子生成新种子(splitmix64).
+fn next_seed(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> CuckooHashMap<K, V, S> {
+    /// 用给定的散列函数创建.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    /// 创建至少能容纳`capacity`个键而无需扩容的散列表.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let slots = capacity.next_power_of_two().max(MIN_SLOTS);
+        Self {
+            tables: [empty_table(slots), empty_table(slots)],
+            seeds: [next_seed(0), next_seed(1)],
+            len: 0,
+            rehashes: 0,
+            hasher,
+            stats: Counter::default(),
+        }
+    }
+
+    /// 每张表的槽数. 词条数不会超过它，即装填因子不超过`1/2`.
+    pub fn slots(&self) -> usize {
+        self.tables[0].len()
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    /// 键在第`table`张表中的位置.
+    fn index(&self, table: usize, key: &K) -> usize {
+        let mut state = self.hasher.build_hasher();
+        state.write_u64(self.seeds[table]);
+        key.hash(&mut state);
+        state.finish() as usize & (self.slots() - 1)
+    }
+
+    fn find(&self, key: &K) -> Option<Posi> {
+        (0..2)
+            .map(|table| (table, self.index(table, key)))
+            .find(|&(table, idx)| match &self.tables[table][idx] {
+                Some(bucket) => {
+                    stats::record(|stats| stats.comparisons += 1);
+                    bucket.key == *key
+                }
+                None => false,
+            })
+    }
+
+    fn bucket_mut(&mut self, (table, idx): Posi) -> &mut Bucket<K, V> {
+        self.tables[table][idx].as_mut().unwrap()
+    }
+
+    /// 踢出次数的上限.
+    fn max_kicks(&self) -> usize {
+        8 + 2 * self.slots().trailing_zeros() as usize
+    }
+
+    /// 尝试放置一个不在表中的词条. 成功时返回它的位置，失败时返回无处安放的词条.
+    fn try_place(&mut self, bucket: Bucket<K, V>) -> Result<Posi, Homeless<K, V>> {
+        let mut carry = bucket;
+        // 新词条当前所在的位置. 为`None`时表示正拿在手上.
+        let mut placed = None;
+        for kick in 0..self.max_kicks() {
+            let table = kick % 2;
+            let posi = (table, self.index(table, &carry.key));
+            let kicked = self.tables[table][posi.1].replace(carry);
+            let kicked_new = placed == Some(posi);
+            if placed.is_none() {
+                placed = Some(posi);
+            }
+            if kicked_new {
+                placed = None;
+            }
+            match kicked {
+                None => return Ok(placed.unwrap()),
+                Some(bucket) => {
+                    self.stats.record(|stats| stats.swaps += 1);
+                    carry = bucket;
+                }
+            }
+        }
+        Err((carry, placed))
+    }
+
+    /// 放置一个不在表中的词条，必要时重新散列.
+    fn place(&mut self, bucket: Bucket<K, V>) {
+        let mut carry = bucket;
+        while let Err((homeless, _)) = self.try_place(carry) {
+            self.rehash();
+            carry = homeless;
+        }
+    }
+
+    /// 更换散列函数重新散列. 若当前容量下已多次失败，则同时扩容.
+    fn rehash(&mut self) {
+        self.rehashes += 1;
+        let slots = if self.rehashes >= MAX_REHASHES {
+            self.slots() * 2
+        } else {
+            self.slots()
+        };
+        self.resize(slots);
+    }
+
+    fn resize(&mut self, slots: usize) {
+        if slots != self.slots() {
+            self.rehashes = 0;
+        }
+        self.seeds = [next_seed(self.seeds[0]), next_seed(self.seeds[1])];
+        let [first, second] =
+            mem::replace(&mut self.tables, [empty_table(slots), empty_table(slots)]);
+        for bucket in first.into_iter().chain(second).flatten() {
+            self.place(bucket);
+        }
+    }
+
+    /// 插入一个新键，返回它的位置.
+    fn insert_new(&mut self, key: K, value: V) -> Posi {
+        if self.len >= self.slots() {
+            self.resize(self.slots() * 2);
+        }
+        self.len += 1;
+        let mut bucket = Bucket { key, value };
+        loop {
+            match self.try_place(bucket) {
+                Ok(posi) => return posi,
+                Err((homeless, placed)) => {
+                    // 先取出新词条，待其它词条重新散列后再放置它，以便追踪它的位置.
+                    bucket = match placed {
+                        Some((table, idx)) => {
+                            let new = self.tables[table][idx].take().unwrap();
+                            self.rehash();
+                            self.place(homeless);
+                            new
+                        }
+                        None => {
+                            self.rehash();
+                            homeless
+                        }
+                    };
+                }
+            }
+        }
+    }
+
+    /// 检查每个词条都位于它在所在表中的候选槽、长度，以及装填因子不超过`1/2`.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let mut position = 0;
+        for (table, slots) in self.tables.iter().enumerate() {
+            for (idx, slot) in slots.iter().enumerate() {
+                if let Some(bucket) = slot {
+                    // 键在另一张表中的候选槽里不能再有一份.
+                    if self.index(table, &bucket.key) != idx
+                        || self.find(&bucket.key) != Some((table, idx))
+                    {
+                        violations.push(Violation::Misplaced { position });
+                    }
+                    position += 1;
+                }
+            }
+        }
+        if position != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: position,
+            });
+        }
+        let slots = self.tables[0].len();
+        if self.tables[1].len() != slots || !slots.is_power_of_two() || position > slots {
+            violations.push(Violation::LoadFactor {
+                len: position,
+                slots: self.tables[0].len() + self.tables[1].len(),
+            });
+        }
+        validate::into_validation(violations)
+    }
+
+    /// 以任意顺序迭代所有键值对.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.tables[0].iter().chain(self.tables[1].iter()),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> UnorderedMap<K, V> for CuckooHashMap<K, V, S> {
+    fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
+        let (table, idx) = self.find(key)?;
+        self.tables[table][idx].as_ref().map(|bucket| &bucket.value)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let posi = {
+            let _stats = self.stats.enter();
+            self.find(key)?
+        };
+        Some(&mut self.bucket_mut(posi).value)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let found = {
+            let _stats = self.stats.enter();
+            self.find(&key)
+        };
+        match found {
+            Some(posi) => Some(mem::replace(&mut self.bucket_mut(posi).value, value)),
+            None => {
+                self.insert_new(key, value);
+                None
+            }
+        }
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let found = {
+            let _stats = self.stats.enter();
+            self.find(&key)
+        };
+        let posi = match found {
+            Some(posi) => posi,
+            None => self.insert_new(key, default),
+        };
+        &mut self.bucket_mut(posi).value
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let _stats = self.stats.enter();
+        let (table, idx) = self.find(key)?;
+        self.len -= 1;
+        self.tables[table][idx].take().map(|bucket| bucket.value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(CuckooHashMap::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+type Slots<'a, K, V> = std::iter::Chain<
+    std::slice::Iter<'a, Option<Bucket<K, V>>>,
+    std::slice::Iter<'a, Option<Bucket<K, V>>>,
+>;
+
+/// 布谷鸟散列表的迭代器.
+pub struct Iter<'a, K, V> {
+    slots: Slots<'a, K, V>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.slots.by_ref().flatten().next()?;
+        self.len -= 1;
+        Some((&bucket.key, &bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a CuckooHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_grow() {
+        let mut map = CuckooHashMap::new();
+        for key in 0..10000 {
+            assert_eq!(map.insert(key, key), None);
+        }
+        assert_eq!(map.validate(), Ok(()));
+        for key in 0..10000 {
+            assert_eq!(map.get(&key), Some(&key));
+        }
+        for key in (0..10000).step_by(2) {
+            assert_eq!(map.remove(&key), Some(key));
+        }
+        assert_eq!(map.validate(), Ok(()));
+        assert_eq!(map.len(), 5000);
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = CuckooHashMap::new();
+        for key in 0..1000 {
+            map.insert(key, key);
+        }
+        assert!(map.stats().swaps > 0);
+        assert_eq!(map.remove(&0), Some(0));
+        assert!(map.stats().comparisons > 0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_cuckoo_hash_map(entrys: Vec<(u16, u32)>, removes: Vec<u16>) {
+            let mut map = CuckooHashMap::new();
+            let mut expected = HashMap::new();
+            for (key, value) in entrys.iter() {
+                prop_assert_eq!(map.insert(*key, *value), expected.insert(*key, *value));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for key in removes.iter() {
+                prop_assert_eq!(map.remove(key), expected.remove(key));
+                prop_assert_eq!(map.validate(), Ok(()));
+                *map.get_mut_or_insert(key.wrapping_add(1), 0) += 1;
+                *expected.entry(key.wrapping_add(1)).or_insert(0) += 1;
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            prop_assert_eq!(map.len(), expected.len());
+            prop_assert_eq!(map.iter().map(|(key, value)| (*key, *value)).collect::<HashMap<_, _>>(), expected.clone());
+            for (key, value) in expected.iter() {
+                prop_assert_eq!(map.get(key), Some(value));
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! 实现了散列表相关算法.
+//!
+//! - 散列表实现了不要求键有序的字典特质`UnorderedMap`(定义在`ch4`中，与`Map`共用，在此重新导出).
+//! - 独立链法散列表: `chaining::ChainedHashMap`.
+//! - 线性试探(Robin Hood)散列表: `probing::RobinHoodHashMap`.
+//! - 布谷鸟散列表: `cuckoo::CuckooHashMap`.
+
+pub mod chaining;
+pub mod cuckoo;
+pub mod probing;
+
+pub use crate::ch4::UnorderedMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// 用`hasher`计算键的散列值.
+pub(crate) fn make_hash<K: Hash, S: BuildHasher>(hasher: &S, key: &K) -> u64 {
+    let mut state = hasher.build_hasher();
+    key.hash(&mut state);
+    state.finish()
+}
+
+/// 只取低几位的弱散列函数，用来在测试中制造大量冲突.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct WeakHasher(u64);
+
+#[cfg(test)]
+impl Hasher for WeakHasher {
+    fn finish(&self) -> u64 {
+        self.0 % 7
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = self.0.wrapping_mul(31).wrapping_add(*byte as u64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::chaining::ChainedHashMap;
+    use super::cuckoo::CuckooHashMap;
+    use super::probing::RobinHoodHashMap;
+    use super::*;
+    use ::test::Bencher;
+    use random::Source;
+    use std::collections::HashMap;
+
+    const N: usize = 10000;
+    const M: usize = 100;
+    const SEEDS: [u64; 2] = [42, 69];
+
+    #[bench]
+    fn bench_std_hm_random_insert(b: &mut Bencher) {
+        let mut map = HashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.insert(source.read(), source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_chm_random_insert(b: &mut Bencher) {
+        let mut map = ChainedHashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.insert(source.read(), source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_rhm_random_insert(b: &mut Bencher) {
+        let mut map = RobinHoodHashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.insert(source.read(), source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_cuckoo_random_insert(b: &mut Bencher) {
+        let mut map = CuckooHashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.insert(source.read(), source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_std_hm_random_get(b: &mut Bencher) {
+        let mut map = HashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.get(&source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_chm_random_get(b: &mut Bencher) {
+        let mut map = ChainedHashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.get(&source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_rhm_random_get(b: &mut Bencher) {
+        let mut map = RobinHoodHashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.get(&source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_cuckoo_random_get(b: &mut Bencher) {
+        let mut map = CuckooHashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        b.iter(|| {
+            map.get(&source.read());
+        })
+    }
+
+    #[bench]
+    fn bench_std_hm_sequential_get(b: &mut Bencher) {
+        let mut map = HashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        let mut gets = (0..M).map(|n| n as i64).cycle();
+        b.iter(|| {
+            map.get(&gets.next().unwrap());
+        })
+    }
+
+    #[bench]
+    fn bench_chm_sequential_get(b: &mut Bencher) {
+        let mut map = ChainedHashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        let mut gets = (0..M).map(|n| n as i64).cycle();
+        b.iter(|| {
+            map.get(&gets.next().unwrap());
+        })
+    }
+
+    #[bench]
+    fn bench_rhm_sequential_get(b: &mut Bencher) {
+        let mut map = RobinHoodHashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        let mut gets = (0..M).map(|n| n as i64).cycle();
+        b.iter(|| {
+            map.get(&gets.next().unwrap());
+        })
+    }
+
+    #[bench]
+    fn bench_cuckoo_sequential_get(b: &mut Bencher) {
+        let mut map = CuckooHashMap::<_, _>::default();
+        let mut source = random::default().seed(SEEDS);
+        for idx in source.iter::<i64>().take(N) {
+            map.insert(idx, idx);
+        }
+        let mut gets = (0..M).map(|n| n as i64).cycle();
+        b.iter(|| {
+            map.get(&gets.next().unwrap());
+        })
+    }
+}
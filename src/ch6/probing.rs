@@ -0,0 +1,432 @@
+use super::{make_hash, UnorderedMap};
+use crate::ch4::stats::{self, Counter, Stats};
+use crate::ch4::validate::{self, Validation, Violation};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+/// 初始槽数.
+const MIN_SLOTS: usize = 8;
+
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+enum Slot<K, V> {
+    Empty,
+    /// 已删除的槽，保留原词条的散列值，以便计算它的试探距离.
+    Tombstone(u64),
+    Full(Bucket<K, V>),
+}
+
+impl<K, V> Slot<K, V> {
+    fn hash(&self) -> Option<u64> {
+        match self {
+            Slot::Empty => None,
+            Slot::Tombstone(hash) => Some(*hash),
+            Slot::Full(bucket) => Some(bucket.hash),
+        }
+    }
+}
+
+/// 基于线性试探与Robin Hood策略的开放定址散列表.
+///
+/// 插入时，若当前槽中词条的试探距离(到其散列位置的距离)小于待插入词条的，则二者交换("劫富济贫")，
+/// 从而使试探距离的方差很小，并且查找可以在遇到试探距离更小的槽时提前终止.
+///
+/// 删除时留下墓碑(Tombstone)，墓碑保留原词条的试探距离，因此不会破坏上述性质. 插入时墓碑可以被复用，
+/// 而在重新散列时被清除. 有效词条与墓碑的总数超过槽数的`7/8`时重新散列，若有效词条超过一半则槽数加倍.
+pub struct RobinHoodHashMap<K: Hash + Eq, V, S: BuildHasher = RandomState> {
+    slots: Vec<Slot<K, V>>,
+    len: usize,
+    tombstones: usize,
+    hasher: S,
+    stats: Counter,
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> Default for RobinHoodHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V> RobinHoodHashMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> RobinHoodHashMap<K, V, S> {
+    /// 用给定的散列函数创建.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    /// 创建至少能容纳`capacity`个键而无需重新散列的散列表.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let mut slots = Vec::new();
+        slots.resize_with((capacity * 2).next_power_of_two().max(MIN_SLOTS), || {
+            Slot::Empty
+        });
+        Self {
+            slots,
+            len: 0,
+            tombstones: 0,
+            hasher,
+            stats: Counter::default(),
+        }
+    }
+
+    /// 槽数.
+    pub fn slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// 墓碑数.
+    pub fn tombstones(&self) -> usize {
+        self.tombstones
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    fn mask(&self) -> usize {
+        self.slots.len() - 1
+    }
+
+    /// 散列值为`hash`的词条位于`posi`时的试探距离.
+    fn distance(&self, hash: u64, posi: usize) -> usize {
+        posi.wrapping_sub(hash as usize) & self.mask()
+    }
+
+    fn find(&self, key: &K) -> Option<usize> {
+        let hash = make_hash(&self.hasher, key);
+        let mut posi = hash as usize & self.mask();
+        for dist in 0.. {
+            let slot = &self.slots[posi];
+            match slot.hash() {
+                None => return None,
+                Some(slot_hash) if self.distance(slot_hash, posi) < dist => return None,
+                _ => {}
+            }
+            if let Slot::Full(bucket) = slot {
+                stats::record(|stats| stats.comparisons += 1);
+                if bucket.hash == hash && bucket.key == *key {
+                    return Some(posi);
+                }
+            }
+            posi = (posi + 1) & self.mask();
+        }
+        unreachable!()
+    }
+
+    /// 重新散列到`slots`个槽中，同时清除所有墓碑.
+    fn resize(&mut self, slots: usize) {
+        let mut new_slots = Vec::new();
+        new_slots.resize_with(slots, || Slot::Empty);
+        let olds = mem::replace(&mut self.slots, new_slots);
+        self.tombstones = 0;
+        for slot in olds {
+            if let Slot::Full(bucket) = slot {
+                self.place(bucket);
+            }
+        }
+    }
+
+    /// 放置一个不在表中的词条，返回它所在的槽.
+    fn place(&mut self, bucket: Bucket<K, V>) -> usize {
+        let mask = self.mask();
+        let mut carry = bucket;
+        let mut posi = carry.hash as usize & mask;
+        let mut dist = 0;
+        let mut placed = None;
+        loop {
+            let rich = match self.slots[posi].hash() {
+                None => true,
+                Some(hash) => self.distance(hash, posi) < dist,
+            };
+            if rich {
+                // 第一次放下的位置就是新词条的最终位置，之后移动的都是被替换出来的词条.
+                placed = placed.or(Some(posi));
+                match mem::replace(&mut self.slots[posi], Slot::Full(carry)) {
+                    Slot::Full(bucket) => {
+                        self.stats.record(|stats| stats.swaps += 1);
+                        carry = bucket;
+                        dist = self.distance(carry.hash, posi);
+                    }
+                    Slot::Tombstone(_) => {
+                        self.tombstones -= 1;
+                        return placed.unwrap();
+                    }
+                    Slot::Empty => return placed.unwrap(),
+                }
+            }
+            posi = (posi + 1) & mask;
+            dist += 1;
+        }
+    }
+
+    /// 插入一个新键，返回它所在的槽.
+    fn insert_new(&mut self, hash: u64, key: K, value: V) -> usize {
+        if (self.len + self.tombstones + 1) * 8 > self.slots.len() * 7 {
+            let slots = if (self.len + 1) * 2 > self.slots.len() {
+                self.slots.len() * 2
+            } else {
+                self.slots.len()
+            };
+            self.resize(slots);
+        }
+        self.len += 1;
+        self.place(Bucket { hash, key, value })
+    }
+
+    fn bucket_mut(&mut self, posi: usize) -> &mut Bucket<K, V> {
+        match &mut self.slots[posi] {
+            Slot::Full(bucket) => bucket,
+            _ => unreachable!(),
+        }
+    }
+
+    /// 检查每个词条记录的散列值、词条数与墓碑数、装填因子，以及Robin Hood性质:
+    /// 同一段连续的非空槽中，试探距离每次至多增加`1`.
+    pub fn validate(&self) -> Validation {
+        let mut violations = Vec::new();
+        let mut len = 0;
+        let mut tombstones = 0;
+        for (posi, slot) in self.slots.iter().enumerate() {
+            match slot {
+                Slot::Empty => {}
+                Slot::Tombstone(_) => tombstones += 1,
+                Slot::Full(bucket) => {
+                    if make_hash(&self.hasher, &bucket.key) != bucket.hash {
+                        violations.push(Violation::Hash { position: len });
+                    }
+                }
+            }
+            if let Some(hash) = slot.hash() {
+                let dist = self.distance(hash, posi);
+                let prev = posi.wrapping_sub(1) & self.mask();
+                let prev_dist = self.slots[prev]
+                    .hash()
+                    .map(|hash| self.distance(hash, prev));
+                let misplaced = dist > 0
+                    && match prev_dist {
+                        Some(prev_dist) => prev_dist + 1 < dist,
+                        None => true,
+                    };
+                if misplaced {
+                    violations.push(Violation::Misplaced { position: len });
+                }
+            }
+            if let Slot::Full(_) = slot {
+                len += 1;
+            }
+        }
+        if len != self.len {
+            violations.push(Violation::Length {
+                recorded: self.len,
+                actual: len,
+            });
+        }
+        if tombstones != self.tombstones {
+            violations.push(Violation::Tombstones {
+                recorded: self.tombstones,
+                actual: tombstones,
+            });
+        }
+        if !self.slots.len().is_power_of_two() || (len + tombstones) * 8 > self.slots.len() * 7 {
+            violations.push(Violation::LoadFactor {
+                len: len + tombstones,
+                slots: self.slots.len(),
+            });
+        }
+        validate::into_validation(violations)
+    }
+
+    /// 以任意顺序迭代所有键值对.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.slots.iter(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> UnorderedMap<K, V> for RobinHoodHashMap<K, V, S> {
+    fn get(&self, key: &K) -> Option<&V> {
+        let _stats = self.stats.enter();
+        match &self.slots[self.find(key)?] {
+            Slot::Full(bucket) => Some(&bucket.value),
+            _ => unreachable!(),
+        }
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let posi = {
+            let _stats = self.stats.enter();
+            self.find(key)?
+        };
+        Some(&mut self.bucket_mut(posi).value)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let found = {
+            let _stats = self.stats.enter();
+            self.find(&key)
+        };
+        match found {
+            Some(posi) => Some(mem::replace(&mut self.bucket_mut(posi).value, value)),
+            None => {
+                let hash = make_hash(&self.hasher, &key);
+                self.insert_new(hash, key, value);
+                None
+            }
+        }
+    }
+
+    fn get_mut_or_insert(&mut self, key: K, default: V) -> &mut V {
+        let found = {
+            let _stats = self.stats.enter();
+            self.find(&key)
+        };
+        let posi = match found {
+            Some(posi) => posi,
+            None => {
+                let hash = make_hash(&self.hasher, &key);
+                self.insert_new(hash, key, default)
+            }
+        };
+        &mut self.bucket_mut(posi).value
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let posi = {
+            let _stats = self.stats.enter();
+            self.find(key)?
+        };
+        let hash = self.bucket_mut(posi).hash;
+        match mem::replace(&mut self.slots[posi], Slot::Tombstone(hash)) {
+            Slot::Full(bucket) => {
+                self.len -= 1;
+                self.tombstones += 1;
+                Some(bucket.value)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&K, &V)>> {
+        Box::new(RobinHoodHashMap::iter(self))
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.get()
+    }
+}
+
+/// Robin Hood散列表的迭代器.
+pub struct Iter<'a, K, V> {
+    slots: std::slice::Iter<'a, Slot<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for slot in self.slots.by_ref() {
+            if let Slot::Full(bucket) = slot {
+                self.len -= 1;
+                return Some((&bucket.key, &bucket.value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a RobinHoodHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::WeakHasher;
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+    use std::hash::BuildHasherDefault;
+
+    #[test]
+    fn test_tombstones() {
+        let mut map = RobinHoodHashMap::new();
+        for key in 0..100 {
+            map.insert(key, key);
+        }
+        let slots = map.slots();
+        // 反复插入删除不会使表无限增长.
+        for key in 100..10000 {
+            map.insert(key, key);
+            assert_eq!(map.remove(&(key - 100)), Some(key - 100));
+        }
+        assert_eq!(map.validate(), Ok(()));
+        assert!(map.slots() <= slots * 2);
+        assert!(map.iter().map(|(key, _)| *key).all(|key| key >= 9900));
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        let mut map = RobinHoodHashMap::<_, _, BuildHasherDefault<WeakHasher>>::default();
+        for key in 0..100u32 {
+            map.insert(key, key);
+        }
+        let stats = map.stats();
+        assert!(stats.swaps > 0);
+        for key in 0..100 {
+            assert_eq!(map.get(&key), Some(&key));
+        }
+        assert!(map.stats().comparisons >= stats.comparisons + 100);
+    }
+
+    proptest! {
+        #[test]
+        fn test_robin_hood_hash_map(entrys: Vec<(u8, u32)>, removes: Vec<u8>) {
+            let mut map = RobinHoodHashMap::<_, _, BuildHasherDefault<WeakHasher>>::default();
+            let mut expected = HashMap::new();
+            for (key, value) in entrys.iter() {
+                prop_assert_eq!(map.insert(*key, *value), expected.insert(*key, *value));
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            for key in removes.iter() {
+                prop_assert_eq!(map.remove(key), expected.remove(key));
+                prop_assert_eq!(map.validate(), Ok(()));
+                *map.get_mut_or_insert(key.wrapping_add(1), 0) += 1;
+                *expected.entry(key.wrapping_add(1)).or_insert(0) += 1;
+                prop_assert_eq!(map.validate(), Ok(()));
+            }
+            prop_assert_eq!(map.len(), expected.len());
+            prop_assert_eq!(map.iter().map(|(key, value)| (*key, *value)).collect::<HashMap<_, _>>(), expected.clone());
+            for (key, value) in expected.iter() {
+                prop_assert_eq!(map.get(key), Some(value));
+            }
+        }
+    }
+}
@@ -12,6 +12,7 @@ pub mod ch2;
 pub mod ch3;
 pub mod ch4;
 pub mod ch5;
+pub mod ch6;
 pub mod vec;
 
 pub use ch1::*;
@@ -19,4 +20,5 @@ pub use ch2::*;
 pub use ch3::*;
 pub use ch4::*;
 pub use ch5::*;
+pub use ch6::*;
 pub use vec::*;